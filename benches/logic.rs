@@ -0,0 +1,83 @@
+//! Benchmarks for compiling and stepping generated circuits.
+//!
+//! Run with `cargo bench --features bench`, and add `-- step_logic/chain` for a single group.
+
+use bevy::prelude::*;
+use bevy_logic::{ logic::bench_util::{ self, GeneratedCircuit }, prelude::* };
+use criterion::{ criterion_group, criterion_main, BenchmarkId, Criterion, Throughput };
+
+const SIZES: [usize; 3] = [100, 1_000, 10_000];
+const SEED: u64 = 7;
+
+/// A generator that spawns a circuit of about `size` gates.
+type Generator = fn(&mut World, usize) -> GeneratedCircuit;
+
+const GENERATORS: [(&str, Generator); 4] = [
+    ("chain", bench_util::chain),
+    ("fanout", bench_util::fanout),
+    ("random_dag", |world, size| bench_util::random_dag(world, size, 16, SEED)),
+    ("scc_heavy", |world, size| bench_util::scc_heavy(world, size / 10, 10, SEED)),
+];
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins).add_plugins(LogicSimulationPlugin::default());
+    app
+}
+
+fn compile(c: &mut Criterion) {
+    let mut group = c.benchmark_group("compile");
+    for (name, generate) in GENERATORS {
+        for size in SIZES {
+            let mut app = app();
+            generate(app.world_mut(), size);
+            let mut graph = app.world_mut().resource_mut::<LogicGraph>();
+
+            group.throughput(Throughput::Elements(size as u64));
+            group.bench_with_input(BenchmarkId::new(name, size), &size, |b, _| {
+                b.iter(|| graph.compile());
+            });
+        }
+    }
+    group.finish();
+}
+
+fn step_logic(c: &mut Criterion) {
+    let mut group = c.benchmark_group("step_logic");
+    for (name, generate) in GENERATORS {
+        for size in SIZES {
+            let mut app = app();
+            let world = app.world_mut();
+            generate(world, size);
+            world.resource_mut::<LogicGraph>().compile();
+
+            group.throughput(Throughput::Elements(size as u64));
+            group.bench_with_input(BenchmarkId::new(name, size), &size, |b, _| {
+                b.iter(|| world.step_logic_once());
+            });
+        }
+    }
+    group.finish();
+}
+
+fn step_compiled(c: &mut Criterion) {
+    let mut group = c.benchmark_group("step_compiled");
+    for (name, generate) in GENERATORS {
+        for size in SIZES {
+            let mut app = app();
+            let world = app.world_mut();
+            generate(world, size);
+            world.resource_mut::<LogicGraph>().compile();
+            let mut circuit = CompiledCircuit::compile(world).expect("basic gates compile");
+
+            group.throughput(Throughput::Elements(size as u64));
+            group.bench_with_input(BenchmarkId::new(name, size), &size, |b, _| {
+                b.iter(|| circuit.step());
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, compile, step_logic, step_compiled);
+criterion_main!(benches);
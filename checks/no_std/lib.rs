@@ -0,0 +1,6 @@
+#![no_std]
+
+extern crate alloc;
+
+#[path = "../../src/sim.rs"]
+pub mod sim;
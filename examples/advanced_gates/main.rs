@@ -1,17 +1,17 @@
-//! This example showcases how one might design "advanced" gates with
-//! functionality beyond what is typically permitted in real life.
+//! This example showcases "advanced" gates with functionality beyond what is typically
+//! permitted in real life.
 //!
-//! In this example, we design a `Selector` and a `Counter`, and spawn a demo circuit
+//! In this example, we use the crate's `Selector` and `Counter` gates to spawn a demo circuit
 //! that works as a permutational keypad. For simplicity, we will ignore incorrect key presses and only
 //! require that the correct keys are pressed in order; however, reset functionality could easily
-//! be implemented with a handful of OR gates.
+//! be implemented with a handful of OR gates. A single `SequenceDetector` gate does all of this,
+//! including the reset.
 //!
 //! The valid code will be `2, 1, 1, 8`, in order.
 
 use bevy::{ ecs::system::EntityCommands, prelude::* };
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 use bevy_logic::{ logic::builder::{ GateData, GateFanEntityMut, Known }, prelude::* };
-use itertools::Itertools;
 
 mod camera_rig;
 mod visual;
@@ -20,200 +20,17 @@ use visual::*;
 
 use crate::camera_rig::CameraRigPlugin;
 
-mod custom_gates {
-    use derive_new::new;
-
-    use super::*;
-
-    /// A selector acts like a state machine. It has an even number of standard inputs and outputs, and 1 "cycle" input.
-    ///
-    /// Selectors should *always* have *exactly* 1 more input
-    /// than outputs.
-    ///
-    /// Higher inputs take priority over lower ones.
-    ///
-    /// The cycle input is ignored if it was previously evaluated and the signal is still truthy.
-    #[derive(Component, Clone, Debug, Default, Reflect)]
-    pub struct Selector {
-        stale_cycle_input: bool,
-    }
-
-    impl LogicGate for Selector {
-        fn evaluate(&mut self, inputs: &[Signal], outputs: &mut [Signal]) {
-            #[cfg(debug_assertions)]
-            {
-                if inputs.len() != outputs.len() + 1 {
-                    panic!(
-                        "Selector does not have the correct number of child fans. There must be exactly 1 more input than outputs."
-                    );
-                }
-            }
-
-            if
-                let Some((index, signal)) = inputs
-                    .iter()
-                    .rev()
-                    .find_position(|input| { input.is_truthy() })
-            {
-                // flip the index
-                let index = inputs.len() - 1 - index;
-
-                if index == 0 {
-                    if self.stale_cycle_input {
-                        return;
-                    } else {
-                        self.stale_cycle_input = true;
-                    }
-
-                    let mut iter = outputs.iter_mut().skip_while(|o| o.is_falsy());
-                    if let Some(active) = iter.next() {
-                        active.turn_off();
-                    }
-                    if let Some(next) = iter.next() {
-                        next.replace(*signal);
-                    } else {
-                        outputs
-                            .first_mut()
-                            .expect("Selector does not have any outputs")
-                            .replace(*signal);
-                    }
-                } else {
-                    outputs.set_all(Signal::OFF);
-                    outputs
-                        .get_mut(index - 1)
-                        .unwrap()
-                        .replace(*signal);
-                }
-                return;
-            } else {
-                self.stale_cycle_input = false;
-            }
-
-            if outputs.iter().all(|s| s.is_falsy()) {
-                if let Some(first) = outputs.first_mut() {
-                    first.turn_on();
-                }
-            }
-        }
-    }
-    /// A counter counts input signal pulses up to the target value, and
-    /// only emits a signal once the target is met, unless the `signal_strength` is true,
-    /// in which case the output signal is a value between `0.0..1.0` representing the
-    /// completion of the counter.
-    ///
-    /// Counters have 1 standard input, 1 reset input, and 1 output.
-    ///
-    /// The first input is used as the "reset" input.
-    #[derive(new, Component, Clone, Debug, Reflect)]
-    pub struct Counter {
-        /// The current count.
-        pub current: i8,
-        /// The max/target count.
-        pub target: i8,
-        /// If true, output a value between `0.0..1.0` representing the completion state of the counter.
-        pub signal_strength: bool,
-        /// Stores the previously evaluated inputs so that solid signals do not continuously trigger their input.
-        #[new(default)]
-        #[reflect(ignore)]
-        stale_inputs: [bool; 2],
-    }
-
-    impl Default for Counter {
-        fn default() -> Self {
-            Self {
-                current: 0,
-                target: 10,
-                signal_strength: false,
-                stale_inputs: [false; 2],
-            }
-        }
-    }
-
-    impl Counter {
-        /// Increments the counter if [`Self::current`] is less than [`Self::target`].
-        #[inline]
-        pub fn increment(&mut self) {
-            if self.current < self.target {
-                self.current += 1;
-            }
-        }
-
-        /// Decrement the counter if [`Self::curent`] is greater than `0`.
-        #[inline]
-        pub fn decrement(&mut self) {
-            if self.current > 0 {
-                self.current -= 1;
-            }
-        }
-
-        /// Reset the counter to `0`.
-        #[inline]
-        pub fn reset(&mut self) {
-            self.current = 0;
-        }
-
-        /// Returns `Self::current / Self::target` as a [`Signal`].
-        pub fn signal_strength(&self) -> Signal {
-            ((self.current as f32) / (self.target as f32)).into()
-        }
-
-        /// Returns a [`Signal::Digital`] that is true if [`Self::current`] is equal to [`Self::target`]
-        pub fn signal_on_off(&self) -> Signal {
-            (self.current == self.target).into()
-        }
-    }
-
-    impl LogicGate for Counter {
-        fn evaluate(&mut self, inputs: &[Signal], outputs: &mut [Signal]) {
-            if let Some((index, signal)) = inputs.iter().find_position(|s| s.is_truthy()) {
-                if self.stale_inputs[index] {
-                    return;
-                } else {
-                    self.stale_inputs[index] = true;
-                }
-
-                match index {
-                    0 => self.reset(),
-                    1 => {
-                        if signal.is_sign_negative() {
-                            self.decrement();
-                        } else {
-                            self.increment();
-                        }
-                    }
-                    _ => panic!("Counter has too many inputs!"),
-                }
-            } else {
-                self.stale_inputs.iter_mut().for_each(|b| {
-                    *b = false;
-                });
-            }
-
-            let output = outputs.get_mut(0).expect("Counter should have one output!");
-            if self.signal_strength {
-                output.replace(self.signal_strength());
-            } else {
-                let signal = self.signal_on_off();
-                output.replace(signal);
-            }
-        }
-    }
-}
-
 fn main() {
     const TICKS_PER_SECOND: f64 = 30.0;
 
     let mut app = App::new();
 
-    // Register the [`Selector`] as a `LogicGate`.
-    app.register_logic_gate::<custom_gates::Selector>().register_logic_gate::<custom_gates::Counter>();
-
     // Add the `LogicSimulationPlugin`.
     app.add_plugins((
         DefaultPlugins,
         CameraRigPlugin,
         WorldInspectorPlugin::new(),
-        LogicSimulationPlugin,
+        LogicSimulationPlugin::default(),
     ))
         .insert_resource(ClearColor(Color::linear_rgba(0.22, 0.402, 0.598, 1.0)))
         .insert_resource(Time::<LogicStep>::from_hz(TICKS_PER_SECOND))
@@ -518,7 +335,7 @@ mod helpers {
         commands
             .spawn_gate((
                 Name::new("Selector"),
-                custom_gates::Selector::default(),
+                Selector::new(states),
                 pbr(position.extend(0.0), meshes.add(build_mesh(states, states, 1)), materials),
             ))
             .build_inputs(states + 1, selector_input_entity_mut(states + 1))
@@ -531,14 +348,14 @@ mod helpers {
         commands: &mut Commands,
         meshes: &mut ResMut<Assets<Mesh>>,
         materials: &mut ResMut<Assets<StandardMaterial>>,
-        max_count: i8,
+        max_count: u32,
         signal_strength: bool,
         position: Vec2
     ) -> GateData<Known, Known> {
         commands
             .spawn_gate((
                 Name::new("Counter"),
-                custom_gates::Counter::new(0, max_count, signal_strength),
+                Counter::new(max_count).with_signal_strength(signal_strength),
                 pbr(position.extend(0.0), meshes.add(build_mesh(1, 1, 1)), materials),
             ))
             .build_inputs(2, selector_input_entity_mut(2))
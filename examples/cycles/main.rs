@@ -5,8 +5,7 @@ mod camera_rig;
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 use bevy::prelude::*;
 use bevy_logic::{
-    components::GateFan,
-    logic::{ builder::{ GateFanWorldMut, LogicExt }, gates::{ AndGate, Battery, NotGate, OrGate } },
+    logic::{ builder::LogicExt, gates::{ AndGate, Battery, NotGate, OrGate } },
     prelude::*,
 };
 use camera_rig::CameraRigPlugin;
@@ -17,7 +16,7 @@ fn main() {
 
     app.add_plugins((DefaultPlugins, WorldInspectorPlugin::new(), CameraRigPlugin, VisualPlugin))
         .insert_resource(ClearColor(Color::linear_rgba(0.22, 0.402, 0.598, 1.0)))
-        .add_plugins(LogicSimulationPlugin::default())
+        .add_plugins((LogicSimulationPlugin::default(), FanLayoutPlugin))
         .insert_resource(Time::<Fixed>::from_seconds(1.0)) // Just to demonstrate Fixed steps independent of LogicStep.
         .add_systems(Startup, setup)
         .run();
@@ -36,52 +35,52 @@ fn setup(world: &mut World) {
     let not_bundle_d = pbr_bundle(world, GateIcon::Not, Vec2::new(6.0, -2.0));
 
     let or_gate = world
-        .spawn_gate((Name::new("OR"), OrGate::default()))
-        .build_inputs(3, gate_fan(GateFan::Input, 3, 1.0))
-        .build_outputs(1, gate_fan(GateFan::Output, 1, 1.0))
+        .spawn_gate((Name::new("OR"), OrGate::default(), FanLayout::LeftRight))
+        .with_inputs(3)
+        .with_outputs(1)
         .insert_bundle(or_bundle)
         .build();
     let not_gate_a = world
-        .spawn_gate((Name::new("NOT"), NotGate))
-        .build_inputs(1, gate_fan(GateFan::Input, 1, 1.0))
-        .build_outputs(1, gate_fan(GateFan::Output, 1, 1.0))
+        .spawn_gate((Name::new("NOT"), NotGate, FanLayout::LeftRight))
+        .with_inputs(1)
+        .with_outputs(1)
         .insert_bundle(not_bundle_a.clone())
         .build();
     let not_gate_b = world
-        .spawn_gate((Name::new("NOT"), NotGate))
-        .build_inputs(1, gate_fan(GateFan::Input, 1, 1.0))
-        .build_outputs(1, gate_fan(GateFan::Output, 1, 1.0))
+        .spawn_gate((Name::new("NOT"), NotGate, FanLayout::LeftRight))
+        .with_inputs(1)
+        .with_outputs(1)
         .insert_bundle(not_bundle_b)
         .build();
     let and_gate_a = world
-        .spawn_gate((Name::new("AND"), AndGate::default()))
-        .build_inputs(2, gate_fan(GateFan::Input, 2, 1.0))
-        .build_outputs(1, gate_fan(GateFan::Output, 1, 1.0))
+        .spawn_gate((Name::new("AND"), AndGate::default(), FanLayout::LeftRight))
+        .with_inputs(2)
+        .with_outputs(1)
         .insert_bundle(and_bundle_a.clone())
         .build();
     let and_gate_b = world
-        .spawn_gate((Name::new("AND"), AndGate::default()))
-        .build_inputs(2, gate_fan(GateFan::Input, 2, 1.0))
-        .build_outputs(1, gate_fan(GateFan::Output, 1, 1.0))
+        .spawn_gate((Name::new("AND"), AndGate::default(), FanLayout::LeftRight))
+        .with_inputs(2)
+        .with_outputs(1)
         .insert_bundle(and_bundle_b)
         .build();
 
     let not_gate_c = world
-        .spawn_gate((Name::new("NOT"), NotGate))
+        .spawn_gate((Name::new("NOT"), NotGate, FanLayout::LeftRight))
         .insert_bundle(not_bundle_c)
-        .build_inputs(1, gate_fan(GateFan::Input, 1, 1.0))
-        .build_outputs(1, gate_fan(GateFan::Output, 1, 1.0))
+        .with_inputs(1)
+        .with_outputs(1)
         .build();
     let not_gate_d = world
-        .spawn_gate((Name::new("NOT"), NotGate))
+        .spawn_gate((Name::new("NOT"), NotGate, FanLayout::LeftRight))
         .insert_bundle(not_bundle_d)
-        .build_inputs(1, gate_fan(GateFan::Input, 1, 1.0))
-        .build_outputs(1, gate_fan(GateFan::Output, 1, 1.0))
+        .with_inputs(1)
+        .with_outputs(1)
         .build();
 
     let battery = world
-        .spawn_gate((Name::new("BAT"), Battery::ON))
-        .build_outputs(1, gate_fan(GateFan::Output, 1, 1.0))
+        .spawn_gate((Name::new("BAT"), Battery::ON, FanLayout::LeftRight))
+        .with_outputs(1)
         .insert_bundle(battery_bundle)
         .build();
 
@@ -120,33 +119,6 @@ fn setup(world: &mut World) {
         .compile();
 }
 
-/// Returns a function that inserts a [`SpatialBundle`] into the [`GateFan`] entity.
-///
-/// The `kind` parameter determines the side of the gate the fan is on.
-/// The `len` parameter describes the total number of fans on the side.
-/// The `height` parameter is used to distribute the fans vertically.
-fn gate_fan(kind: GateFan, len: usize, height: f32) -> impl GateFanWorldMut {
-    #[cfg(debug_assertions)]
-    if len == 0 {
-        panic!("Fan length must be greater than 0.");
-    }
-    let x: f32 =
-        (match kind {
-            GateFan::Input => -1.0,
-            GateFan::Output => 1.0,
-        }) * 0.5;
-    let section_height: f32 = height / ((len + 1) as f32);
-    let half_height = height / 2.0;
-    move |cmd: &mut EntityWorldMut, index: usize| {
-        let position = Vec3::new(
-            x,
-            -1.0 * (section_height * ((1 + index) as f32) - half_height),
-            0.0
-        );
-        cmd.insert(SpatialBundle::from_transform(Transform::from_translation(position)));
-    }
-}
-
 fn pbr_bundle(
     world: &mut World,
     gate_icon: GateIcon,
@@ -1,14 +1,16 @@
-use bevy::{ color::palettes, prelude::*, utils::HashMap };
-use bevy_logic::{ components::{ GateFan, GateOutput, LogicGateFans, Wire }, logic::signal::Signal };
+use bevy::{ color::{ palettes, Mix }, prelude::*, utils::HashMap };
+use bevy_logic::{
+    components::{ GateFan, GateOutput, LogicGateFans, Wire },
+    logic::signal::Signal,
+    prelude::*,
+};
 
 pub struct VisualPlugin;
 
 impl Plugin for VisualPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PreStartup, init_logic_gate_icons).add_systems(Update, (
-            colorize_logic_gates,
-            gizmo_wires,
-        ));
+        app.add_systems(PreStartup, init_logic_gate_icons)
+            .add_systems(Update, (colorize_logic_gates, insert_prev_signals, gizmo_wires));
     }
 }
 
@@ -74,12 +76,20 @@ fn colorize_logic_gates(
     }
 }
 
+/// Track the previous signal of each wire, so its color can fade between steps.
+fn insert_prev_signals(mut commands: Commands, query: Query<(Entity, &Signal), Added<Wire>>) {
+    for (entity, signal) in query.iter() {
+        commands.entity(entity).insert(PrevSignal(*signal));
+    }
+}
+
 fn gizmo_wires(
     mut gizmos: Gizmos,
-    query_wires: Query<(&Wire, &Signal)>,
+    interpolation: Res<LogicTickInterpolation>,
+    query_wires: Query<(&Wire, &Signal, &PrevSignal)>,
     query_fans: Query<&GlobalTransform, With<GateFan>>
 ) {
-    for (wire, signal) in query_wires.iter() {
+    for (wire, signal, prev_signal) in query_wires.iter() {
         let Ok(from) = query_fans.get(wire.from).map(|t| t.translation()) else {
             continue;
         };
@@ -87,7 +97,9 @@ fn gizmo_wires(
             continue;
         };
 
-        let color = if signal.is_truthy() { palettes::basic::GREEN.into() } else { Color::BLACK };
+        // Fade between the previous and current step, so the low tick rate does not look choppy.
+        let intensity = interpolation.lerp_signal(prev_signal.0, *signal).abs().min(1.0);
+        let color: Color = Srgba::BLACK.mix(&palettes::basic::GREEN, intensity).into();
 
         gizmos.line(from, to, color);
         gizmos.circle(from, Dir3::Z, 0.1, color);
@@ -0,0 +1,238 @@
+//! Sink gates that move entities in the game world.
+
+use bevy::{
+    ecs::{ entity::{ EntityMapper, MapEntities }, reflect::ReflectMapEntities },
+    prelude::*,
+};
+
+use crate::logic::{ signal::Signal, AppLogicGateExt, LogicGate };
+
+pub mod prelude {
+    pub use super::{ ActuatorPlugin, MotorGate, PistonGate };
+}
+
+/// A plugin that registers the [`MotorGate`] and [`PistonGate`], and moves their targets
+/// every frame in [`Update`].
+pub struct ActuatorPlugin;
+
+impl Plugin for ActuatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_logic_gate::<MotorGate>()
+            .register_logic_gate::<PistonGate>()
+            .register_type::<MotorGate>()
+            .register_type::<PistonGate>()
+            .add_systems(Update, (drive_motors, drive_pistons));
+    }
+}
+
+/// A sink gate that spins a target at an angular velocity proportional to its input.
+///
+/// The gate reads the absolute maximum of its inputs, which it also emits from every
+/// output. Negative signals spin the other way, and falsy signals stop the motor.
+///
+/// The [`ActuatorPlugin`] rotates the [`Transform`] of `target`, or of the gate itself if
+/// `target` is `None`. To drive a physics body instead, set `rotate_transform` to `false`
+/// and write [`MotorGate::angular_velocity`] to the body in your own system.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, MapEntities)]
+pub struct MotorGate {
+    /// The entity to rotate, or `None` for the gate.
+    pub target: Option<Entity>,
+    /// The axis of rotation, in the target's local space.
+    pub axis: Dir3,
+    /// The angular speed in radians per second, at a signal of `1.0`.
+    pub speed: f32,
+    /// If false, the plugin does not rotate the target's [`Transform`].
+    pub rotate_transform: bool,
+    /// The input signal of the last step.
+    pub signal: Signal,
+}
+
+impl MapEntities for MotorGate {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        self.target = self.target.map(|target| entity_mapper.map_entity(target));
+    }
+}
+
+impl Default for MotorGate {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl MotorGate {
+    /// Create a new motor that spins `target` around the Z axis at one radian per second.
+    pub fn new(target: Option<Entity>) -> Self {
+        Self {
+            target,
+            axis: Dir3::Z,
+            speed: 1.0,
+            rotate_transform: true,
+            signal: Signal::Undefined,
+        }
+    }
+
+    pub fn with_axis(mut self, axis: Dir3) -> Self {
+        self.axis = axis;
+        self
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Returns the angular velocity for the current signal, in radians per second.
+    pub fn angular_velocity(&self) -> Vec3 {
+        if self.signal.is_falsy() {
+            return Vec3::ZERO;
+        }
+        *self.axis * self.speed * self.signal.as_f32()
+    }
+}
+
+impl LogicGate for MotorGate {
+    fn evaluate(&mut self, inputs: &[Signal], outputs: &mut [Signal]) {
+        let signal = inputs.iter().fold(Signal::Undefined, |acc, s| acc.max_abs(*s));
+        self.signal = signal;
+        outputs.fill(signal);
+    }
+}
+
+/// A sink gate that extends a target along an axis while its input is truthy, and retracts
+/// it otherwise.
+///
+/// Analog inputs extend the target partway, clamped to `0.0..=1.0` of the `stroke`. The
+/// gate emits its current extension from every output, so circuits can wait for the piston
+/// to finish moving.
+///
+/// The [`ActuatorPlugin`] moves the [`Transform`] of `target`, usually a child of the gate,
+/// to `retracted + axis * stroke * extension`. Nothing moves while `target` is `None`.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, MapEntities)]
+pub struct PistonGate {
+    /// The entity to move.
+    pub target: Option<Entity>,
+    /// The direction the target extends in, in its parent's space.
+    pub axis: Dir3,
+    /// The distance between the retracted and extended translation.
+    pub stroke: f32,
+    /// How fast the target moves, in strokes per second. `0.0` moves it instantly.
+    pub speed: f32,
+    /// The translation of the target when it is retracted.
+    pub retracted: Vec3,
+    /// How far the target is extended, from `0.0` to `1.0`.
+    pub extension: f32,
+    /// The input signal of the last step.
+    pub signal: Signal,
+}
+
+impl MapEntities for PistonGate {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        self.target = self.target.map(|target| entity_mapper.map_entity(target));
+    }
+}
+
+impl Default for PistonGate {
+    fn default() -> Self {
+        Self::new(None, Dir3::Y, 1.0)
+    }
+}
+
+impl PistonGate {
+    /// Create a new retracted piston that extends at two strokes per second.
+    pub fn new(target: Option<Entity>, axis: Dir3, stroke: f32) -> Self {
+        Self {
+            target,
+            axis,
+            stroke,
+            speed: 2.0,
+            retracted: Vec3::ZERO,
+            extension: 0.0,
+            signal: Signal::Undefined,
+        }
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn with_retracted(mut self, retracted: Vec3) -> Self {
+        self.retracted = retracted;
+        self
+    }
+
+    /// Returns the extension the piston is moving towards, from `0.0` to `1.0`.
+    pub fn target_extension(&self) -> f32 {
+        if self.signal.is_falsy() {
+            return 0.0;
+        }
+        self.signal.as_f32().clamp(0.0, 1.0)
+    }
+
+    /// Returns the translation of the target at the current extension.
+    pub fn translation(&self) -> Vec3 {
+        self.retracted + *self.axis * self.stroke * self.extension
+    }
+}
+
+impl LogicGate for PistonGate {
+    fn evaluate(&mut self, inputs: &[Signal], outputs: &mut [Signal]) {
+        self.signal = inputs.iter().fold(Signal::Undefined, |acc, s| acc.max_abs(*s));
+        outputs.fill(Signal::Analog(self.extension));
+    }
+}
+
+/// Rotate the target of each [`MotorGate`] by its angular velocity.
+pub fn drive_motors(
+    time: Res<Time>,
+    query_motors: Query<(Entity, &MotorGate)>,
+    mut query_transforms: Query<&mut Transform>
+) {
+    for (entity, motor) in query_motors.iter() {
+        if !motor.rotate_transform {
+            continue;
+        }
+        let angle = motor.speed * motor.signal.as_f32() * time.delta_seconds();
+        if angle == 0.0 || motor.signal.is_falsy() {
+            continue;
+        }
+        if let Ok(mut transform) = query_transforms.get_mut(motor.target.unwrap_or(entity)) {
+            transform.rotate_local_axis(motor.axis, angle);
+        }
+    }
+}
+
+/// Move the extension of each [`PistonGate`] towards its target extension, and its target
+/// to the matching translation.
+pub fn drive_pistons(
+    time: Res<Time>,
+    mut query_pistons: Query<&mut PistonGate>,
+    mut query_transforms: Query<&mut Transform, Without<PistonGate>>
+) {
+    for mut piston in query_pistons.iter_mut() {
+        let goal = piston.target_extension();
+        if piston.extension != goal {
+            let extension = if piston.speed > 0.0 {
+                let max_delta = piston.speed * time.delta_seconds();
+                piston.extension + (goal - piston.extension).clamp(-max_delta, max_delta)
+            } else {
+                goal
+            };
+            piston.extension = extension;
+        }
+
+        let Some(target) = piston.target else {
+            continue;
+        };
+        if let Ok(mut transform) = query_transforms.get_mut(target) {
+            let translation = piston.translation();
+            if transform.translation != translation {
+                transform.translation = translation;
+            }
+        }
+    }
+}
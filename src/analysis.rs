@@ -0,0 +1,697 @@
+//! Headless analysis of circuits, such as truth tables and equivalence checks.
+
+use bevy::{ ecs::{ entity::EntityHashSet, world::CommandQueue }, prelude::*, utils::HashMap };
+use petgraph::{ algo::kosaraju_scc, Direction };
+
+use crate::{
+    blueprint::{ extract_blueprint, spawn_blueprint },
+    components::{ ConnectedWires, GateOutput, LogicGateFans, NoEvalOutput, Wire },
+    logic::{
+        dynamic::DynamicGateRegistry,
+        gates::Battery,
+        schedule::LogicWorldExt,
+        signal::Signal,
+        LogicGateTypes,
+    },
+    resources::LogicGraph,
+    LogicSimulationPlugin,
+};
+
+pub mod prelude {
+    pub use super::{
+        are_equivalent,
+        check_invariants,
+        AnalysisError,
+        InvariantViolation,
+        HeadlessCircuit,
+        TruthTable,
+        MAX_TRUTH_TABLE_INPUTS,
+    };
+}
+
+/// The most inputs a [`TruthTable`] can be extracted for.
+pub const MAX_TRUTH_TABLE_INPUTS: usize = 16;
+
+/// An error produced while analyzing a circuit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AnalysisError {
+    /// The entity is not a gate in the [`LogicGraph`].
+    NotInGraph(Entity),
+    /// An input is not a [`Battery`] gate.
+    NotAnInput(Entity),
+    /// The circuit contains a cycle, so it may not be combinational.
+    Cyclic,
+    /// The circuit has more than [`MAX_TRUTH_TABLE_INPUTS`] inputs.
+    TooManyInputs(usize),
+    /// A tick of a [`TestVector`] has the wrong number of inputs or expected outputs.
+    ///
+    /// [`TestVector`]: crate::logic::verify::TestVector
+    InvalidVector {
+        name: String,
+        tick: usize,
+    },
+}
+
+impl std::fmt::Display for AnalysisError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnalysisError::NotInGraph(entity) => write!(f, "{entity} is not in the logic graph"),
+            AnalysisError::NotAnInput(entity) => write!(f, "{entity} is not a battery"),
+            AnalysisError::Cyclic => write!(f, "the circuit contains a cycle"),
+            AnalysisError::TooManyInputs(inputs) => {
+                write!(f, "{inputs} inputs is more than {MAX_TRUTH_TABLE_INPUTS}")
+            }
+            AnalysisError::InvalidVector { name, tick } => {
+                write!(f, "tick {tick} of `{name}` does not match the circuit's inputs and outputs")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AnalysisError {}
+
+/// A copy of a circuit in its own headless [`App`], so it can be simulated without
+/// affecting the world it was copied from.
+///
+/// The copy contains the gates the outputs depend on, up to and including the inputs.
+/// Gates are copied through reflection with [`extract_blueprint`], so their components
+/// must be registered with `#[reflect(Component)]`. Gate types registered in the source
+/// world's [`LogicGateTypes`] and [`DynamicGateRegistry`] are registered in the copy too.
+pub struct HeadlessCircuit {
+    app: App,
+    inputs: Vec<Entity>,
+    outputs: Vec<Entity>,
+}
+
+impl HeadlessCircuit {
+    /// Copy the circuit between `inputs` and `outputs` out of `world`. The circuit may
+    /// contain cycles, such as latches.
+    ///
+    /// Inputs must be [`Battery`] gates. Every output fan of each output gate is read,
+    /// in order. The copy starts with the same signals and gate state as the original.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `world` does not have an [`AppTypeRegistry`] or a [`LogicGraph`].
+    pub fn extract(
+        world: &World,
+        inputs: &[Entity],
+        outputs: &[Entity]
+    ) -> Result<Self, AnalysisError> {
+        let graph = &world.resource::<LogicGraph>().graph;
+
+        for &gate in inputs.iter().chain(outputs.iter()) {
+            if !graph.contains_node(gate) {
+                return Err(AnalysisError::NotInGraph(gate));
+            }
+        }
+        if let Some(&input) = inputs.iter().find(|&&input| world.get::<Battery>(input).is_none()) {
+            return Err(AnalysisError::NotAnInput(input));
+        }
+
+        // Walk upstream from the outputs, stopping at the inputs.
+        let input_set = inputs.iter().copied().collect::<EntityHashSet>();
+        let mut gates = Vec::new();
+        let mut visited = EntityHashSet::default();
+        let mut stack = outputs.to_vec();
+        while let Some(gate) = stack.pop() {
+            if !visited.insert(gate) {
+                continue;
+            }
+            gates.push(gate);
+            if !input_set.contains(&gate) {
+                stack.extend(graph.neighbors_directed(gate, Direction::Incoming));
+            }
+        }
+        for &input in inputs.iter() {
+            if visited.insert(input) {
+                gates.push(input);
+            }
+        }
+
+        let mut app = App::new();
+        app.insert_resource(world.resource::<AppTypeRegistry>().clone());
+        app.add_plugins(LogicSimulationPlugin::default());
+        if let Some(types) = world.get_resource::<LogicGateTypes>() {
+            types.register_all(&mut app);
+        }
+        if let Some(registry) = world.get_resource::<DynamicGateRegistry>() {
+            app.insert_resource(registry.clone());
+        }
+        app.finish();
+        app.cleanup();
+
+        let blueprint = extract_blueprint(world, &gates);
+        let copies = {
+            let mut queue = CommandQueue::default();
+            let mut commands = Commands::new(&mut queue, app.world());
+            let copies = spawn_blueprint(&mut commands, &blueprint, Vec3::ZERO);
+            queue.apply(app.world_mut());
+            copies
+        };
+        let copy_of = gates.iter().copied().zip(copies).collect::<HashMap<_, _>>();
+
+        Ok(Self {
+            app,
+            inputs: inputs.iter().map(|input| copy_of[input]).collect(),
+            outputs: outputs.iter().map(|output| copy_of[output]).collect(),
+        })
+    }
+
+    /// Returns the world the circuit was copied into.
+    pub fn world(&self) -> &World {
+        self.app.world()
+    }
+
+    /// Returns the world the circuit was copied into, mutably.
+    pub fn world_mut(&mut self) -> &mut World {
+        self.app.world_mut()
+    }
+
+    /// Returns the number of input gates.
+    pub fn input_count(&self) -> usize {
+        self.inputs.len()
+    }
+
+    /// Set the signal of each input gate, in order.
+    pub fn set_inputs(&mut self, signals: &[Signal]) {
+        for (&input, &signal) in self.inputs.iter().zip(signals) {
+            if let Some(mut battery) = self.app.world_mut().get_mut::<Battery>(input) {
+                battery.signal = signal;
+            }
+        }
+    }
+
+    /// Run a single logic step. Gates are evaluated in order, so a combinational
+    /// circuit settles in one step.
+    pub fn step(&mut self) {
+        self.app.world_mut().step_logic_once();
+    }
+
+    /// Returns the signals of every output fan of each output gate, in order.
+    pub fn outputs(&self) -> Vec<Signal> {
+        let world = self.app.world();
+        self.outputs
+            .iter()
+            .filter_map(|&gate| world.get::<LogicGateFans>(gate))
+            .flat_map(|fans| fans.outputs.iter())
+            .map(|output| {
+                output.and_then(|output| world.get::<Signal>(output)).copied().unwrap_or_default()
+            })
+            .collect()
+    }
+
+    /// Returns `true` if the copied circuit does not contain any cycles.
+    pub fn is_acyclic(&self) -> bool {
+        let graph = &self.app.world().resource::<LogicGraph>().graph;
+        kosaraju_scc(graph)
+            .iter()
+            .all(|component| {
+                component.len() == 1 && !graph.contains_edge(component[0], component[0])
+            })
+    }
+
+    /// Exhaustively simulate the circuit and return its [`TruthTable`].
+    ///
+    /// Returns [`AnalysisError::Cyclic`] if the circuit contains a cycle.
+    pub fn truth_table(&mut self) -> Result<TruthTable, AnalysisError> {
+        let inputs = self.inputs.len();
+        if inputs > MAX_TRUTH_TABLE_INPUTS {
+            return Err(AnalysisError::TooManyInputs(inputs));
+        }
+        if !self.is_acyclic() {
+            return Err(AnalysisError::Cyclic);
+        }
+
+        let rows = (0..1usize << inputs)
+            .map(|row| {
+                let signals = (0..inputs)
+                    .map(|bit| Signal::from((row >> bit) & 1 == 1))
+                    .collect::<Vec<_>>();
+                self.set_inputs(&signals);
+                self.step();
+                self.outputs()
+            })
+            .collect();
+
+        Ok(TruthTable { inputs, rows })
+    }
+}
+
+/// The output signals of a combinational circuit for every combination of its inputs.
+///
+/// Row `i` holds the outputs when input `n` is on if bit `n` of `i` is set, so the first
+/// input is the least significant bit.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TruthTable {
+    pub inputs: usize,
+    pub rows: Vec<Vec<Signal>>,
+}
+
+impl TruthTable {
+    /// Simulate the circuit between `inputs` and `outputs` in a [`HeadlessCircuit`]
+    /// and return its truth table.
+    pub fn extract(
+        world: &World,
+        inputs: &[Entity],
+        outputs: &[Entity]
+    ) -> Result<Self, AnalysisError> {
+        if inputs.len() > MAX_TRUTH_TABLE_INPUTS {
+            return Err(AnalysisError::TooManyInputs(inputs.len()));
+        }
+        HeadlessCircuit::extract(world, inputs, outputs)?.truth_table()
+    }
+
+    /// Returns the outputs for the given input values, if there is one value per input.
+    pub fn row(&self, inputs: &[bool]) -> Option<&[Signal]> {
+        if inputs.len() != self.inputs {
+            return None;
+        }
+        let index = inputs
+            .iter()
+            .enumerate()
+            .fold(0, |index, (bit, &on)| index | ((on as usize) << bit));
+        self.rows.get(index).map(Vec::as_slice)
+    }
+}
+
+/// Returns `true` if two truth tables have the same number of inputs and the same
+/// outputs for every row.
+///
+/// Signals are compared exactly, so an [`Undefined`] output only matches another
+/// [`Undefined`] output.
+///
+/// [`Undefined`]: Signal::Undefined
+pub fn are_equivalent(a: &TruthTable, b: &TruthTable) -> bool {
+    a.inputs == b.inputs && a.rows == b.rows
+}
+
+/// An inconsistency between the [`LogicGraph`], the wire sets of fans, and [`Wire`]
+/// components, found by [`check_invariants`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InvariantViolation {
+    /// A gate in the graph does not exist, or does not have [`LogicGateFans`].
+    MissingGate(Entity),
+    /// The wire of a graph edge does not exist, or does not have a [`Wire`].
+    MissingEdgeWire {
+        from_gate: Entity,
+        to_gate: Entity,
+        wire: Entity,
+    },
+    /// The wire of a graph edge does not connect a fan of `from_gate` to a fan of `to_gate`.
+    MisplacedEdgeWire {
+        from_gate: Entity,
+        to_gate: Entity,
+        wire: Entity,
+    },
+    /// A [`GateOutput::wires`] or [`ConnectedWires::wires`] set contains an entity that does
+    /// not exist, or does not have a [`Wire`].
+    ///
+    /// [`GateOutput::wires`]: crate::components::GateOutput::wires
+    MissingWire {
+        fan: Entity,
+        wire: Entity,
+    },
+    /// A [`GateOutput::wires`] or [`ConnectedWires::wires`] set contains a wire that does not
+    /// start or end at the fan.
+    ///
+    /// [`GateOutput::wires`]: crate::components::GateOutput::wires
+    MisplacedWire {
+        fan: Entity,
+        wire: Entity,
+    },
+    /// A wire is used by the simulation, but is missing from the wire set of `fan`.
+    UnlistedWire {
+        fan: Entity,
+        wire: Entity,
+    },
+    /// A wire connects two gates in the graph, but the graph has no edge between them.
+    MissingEdge {
+        from_gate: Entity,
+        to_gate: Entity,
+        wire: Entity,
+    },
+    /// A [`GateOutput::wires`] or [`ConnectedWires::wires`] set contains a wire that ends at
+    /// a gate that is not in the graph.
+    ///
+    /// [`GateOutput::wires`]: crate::components::GateOutput::wires
+    DanglingWire {
+        fan: Entity,
+        wire: Entity,
+        to_gate: Entity,
+    },
+}
+
+impl std::fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvariantViolation::MissingGate(gate) => {
+                write!(f, "gate {gate} is in the logic graph, but not in the world")
+            }
+            InvariantViolation::MissingEdgeWire { from_gate, to_gate, wire } => {
+                write!(f, "the edge {from_gate} -> {to_gate} refers to {wire}, which is not a wire")
+            }
+            InvariantViolation::MisplacedEdgeWire { from_gate, to_gate, wire } => {
+                write!(f, "the edge {from_gate} -> {to_gate} refers to {wire}, between other gates")
+            }
+            InvariantViolation::MissingWire { fan, wire } => {
+                write!(f, "fan {fan} lists {wire}, which is not a wire")
+            }
+            InvariantViolation::MisplacedWire { fan, wire } => {
+                write!(f, "fan {fan} lists wire {wire}, which is not connected to it")
+            }
+            InvariantViolation::UnlistedWire { fan, wire } => {
+                write!(f, "wire {wire} is missing from the wire set of fan {fan}")
+            }
+            InvariantViolation::MissingEdge { from_gate, to_gate, wire } => {
+                write!(f, "wire {wire} connects {from_gate} -> {to_gate}, but the graph does not")
+            }
+            InvariantViolation::DanglingWire { fan, wire, to_gate } => {
+                write!(f, "fan {fan} lists wire {wire} to {to_gate}, which is not in the graph")
+            }
+        }
+    }
+}
+
+impl std::error::Error for InvariantViolation {}
+
+/// Check that `graph`, the [`GateOutput::wires`] and [`ConnectedWires::wires`] sets of every
+/// fan, and the [`Wire`] components of `world` agree with each other.
+///
+/// - Every gate in the graph exists, and every edge refers to a wire between the two gates.
+/// - Every wire in a fan's wire set exists, and starts or ends at that fan.
+/// - A wire listed by its output fan is listed by its input fan too, and is a graph edge
+///   between their gates if both gates are in the graph.
+/// - Every wire in a fan's wire set ends at a gate in the graph.
+///
+/// Wires from a [`NoEvalOutput`] are not part of the graph, and wires that are not listed by
+/// any fan are ignored, since the simulation never reads them. The gate and wire commands
+/// keep these invariants, so a violation means a gate, fan, or wire was changed or despawned
+/// directly.
+///
+/// [`GateOutput::wires`]: crate::components::GateOutput::wires
+pub fn check_invariants(world: &World, graph: &LogicGraph) -> Result<(), Vec<InvariantViolation>> {
+    let mut violations = Vec::new();
+    let parent_of = |fan: Entity| world.get::<Parent>(fan).map(Parent::get);
+
+    for gate in graph.graph.nodes() {
+        if world.get::<LogicGateFans>(gate).is_none() {
+            violations.push(InvariantViolation::MissingGate(gate));
+        }
+    }
+
+    for (from_gate, to_gate, &wire) in graph.graph.all_edges() {
+        let Some(&connection) = world.get::<Wire>(wire) else {
+            violations.push(InvariantViolation::MissingEdgeWire { from_gate, to_gate, wire });
+            continue;
+        };
+        if
+            parent_of(connection.from) != Some(from_gate) ||
+            parent_of(connection.to) != Some(to_gate)
+        {
+            violations.push(InvariantViolation::MisplacedEdgeWire { from_gate, to_gate, wire });
+        }
+    }
+
+    for entity in world.iter_entities() {
+        let fan = entity.id();
+        if let Some(connected) = entity.get::<ConnectedWires>() {
+            check_input_wires(world, graph, fan, connected, &mut violations);
+        }
+        let Some(output) = entity.get::<GateOutput>() else {
+            continue;
+        };
+        let no_eval = entity.contains::<NoEvalOutput>();
+        for &wire in output.wires.iter() {
+            let Some(&connection) = world.get::<Wire>(wire) else {
+                violations.push(InvariantViolation::MissingWire { fan, wire });
+                continue;
+            };
+            if connection.from != fan {
+                violations.push(InvariantViolation::MisplacedWire { fan, wire });
+                continue;
+            }
+            check_target_gate(world, graph, fan, wire, connection, &mut violations);
+            if
+                world
+                    .get::<ConnectedWires>(connection.to)
+                    .is_some_and(|connected| !connected.wires.contains(&wire))
+            {
+                violations.push(InvariantViolation::UnlistedWire { fan: connection.to, wire });
+            }
+
+            if no_eval {
+                continue;
+            }
+            let (Some(from_gate), Some(to_gate)) = (parent_of(fan), parent_of(connection.to)) else {
+                continue;
+            };
+            if
+                graph.graph.contains_node(from_gate) &&
+                graph.graph.contains_node(to_gate) &&
+                !graph.graph.contains_edge(from_gate, to_gate)
+            {
+                violations.push(InvariantViolation::MissingEdge { from_gate, to_gate, wire });
+            }
+        }
+    }
+
+    if violations.is_empty() { Ok(()) } else { Err(violations) }
+}
+
+/// Check that every wire in the [`ConnectedWires`] set of `fan` ends at `fan`.
+fn check_input_wires(
+    world: &World,
+    graph: &LogicGraph,
+    fan: Entity,
+    connected: &ConnectedWires,
+    violations: &mut Vec<InvariantViolation>
+) {
+    for &wire in connected.wires.iter() {
+        match world.get::<Wire>(wire) {
+            None => violations.push(InvariantViolation::MissingWire { fan, wire }),
+            Some(connection) if connection.to != fan => {
+                violations.push(InvariantViolation::MisplacedWire { fan, wire });
+            }
+            Some(connection) => {
+                check_target_gate(world, graph, fan, wire, *connection, violations);
+            }
+        }
+    }
+}
+
+/// Check that the gate `connection` ends at is in the graph, if it has one.
+fn check_target_gate(
+    world: &World,
+    graph: &LogicGraph,
+    fan: Entity,
+    wire: Entity,
+    connection: Wire,
+    violations: &mut Vec<InvariantViolation>
+) {
+    let Some(to_gate) = world.get::<Parent>(connection.to).map(Parent::get) else {
+        return;
+    };
+    if !graph.graph.contains_node(to_gate) {
+        violations.push(InvariantViolation::DanglingWire { fan, wire, to_gate });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::world::Command;
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::{
+        commands::{
+            AddGateToLogicGraph,
+            CloneGateCommand,
+            DespawnGateRecursive,
+            DespawnWire,
+            RebuildLogicGraph,
+            RemoveGateFromLogicGraph,
+            RemoveWireFromLogicGraph,
+        },
+        logic::{ builder::LogicExt, gates::OrGate },
+    };
+
+    /// A graph sync command, with indices into the gates and wires that exist when it is
+    /// applied.
+    #[derive(Clone, Debug)]
+    enum Op {
+        AddGate {
+            inputs: usize,
+            outputs: usize,
+        },
+        AddWire {
+            from: usize,
+            output: usize,
+            to: usize,
+            input: usize,
+        },
+        AddToGraph(usize),
+        RemoveFromGraph(usize),
+        RemoveWire(usize),
+        DespawnWire(usize),
+        DespawnGate(usize),
+        CloneGate {
+            gate: usize,
+            with_incoming_wires: bool,
+        },
+        Rebuild,
+        Step,
+    }
+
+    fn op() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            3 => (0..3usize, 0..3usize).prop_map(|(inputs, outputs)| {
+                Op::AddGate { inputs, outputs }
+            }),
+            5 => any::<(usize, usize, usize, usize)>().prop_map(|(from, output, to, input)| {
+                Op::AddWire { from, output, to, input }
+            }),
+            1 => any::<usize>().prop_map(Op::AddToGraph),
+            1 => any::<usize>().prop_map(Op::RemoveFromGraph),
+            1 => any::<usize>().prop_map(Op::RemoveWire),
+            1 => any::<usize>().prop_map(Op::DespawnWire),
+            1 => any::<usize>().prop_map(Op::DespawnGate),
+            1 => any::<(usize, bool)>().prop_map(|(gate, with_incoming_wires)| {
+                Op::CloneGate { gate, with_incoming_wires }
+            }),
+            1 => Just(Op::Rebuild),
+            1 => Just(Op::Step),
+        ]
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_plugins(LogicSimulationPlugin::default());
+        app
+    }
+
+    /// Returns the entities with `T`, in a stable order.
+    fn entities_with<T: Component>(world: &mut World) -> Vec<Entity> {
+        let mut query = world.query_filtered::<Entity, With<T>>();
+        let mut entities = query.iter(world).collect::<Vec<_>>();
+        entities.sort_unstable();
+        entities
+    }
+
+    fn pick<T: Component>(world: &mut World, index: usize) -> Option<Entity> {
+        let entities = entities_with::<T>(world);
+        (!entities.is_empty()).then(|| entities[index % entities.len()])
+    }
+
+    fn apply(world: &mut World, op: Op) {
+        match op {
+            Op::AddGate { inputs, outputs } => {
+                let gate = world
+                    .spawn_gate(OrGate::default())
+                    .with_inputs(inputs)
+                    .with_outputs(outputs)
+                    .build();
+                world.resource_mut::<LogicGraph>().add_data(gate).compile();
+            }
+            Op::AddWire { from, output, to, input } => {
+                let (Some(from), Some(to)) = (
+                    pick::<LogicGateFans>(world, from),
+                    pick::<LogicGateFans>(world, to),
+                ) else {
+                    return;
+                };
+                let outputs = world.get::<LogicGateFans>(from).unwrap().some_outputs();
+                let inputs = world.get::<LogicGateFans>(to).unwrap().some_inputs();
+                if !outputs.is_empty() && !inputs.is_empty() {
+                    let output = outputs[output % outputs.len()];
+                    world.spawn_wire_between(output, inputs[input % inputs.len()]);
+                }
+            }
+            Op::AddToGraph(gate) => {
+                if let Some(gate) = pick::<LogicGateFans>(world, gate) {
+                    AddGateToLogicGraph(gate).apply(world);
+                }
+            }
+            Op::RemoveFromGraph(gate) => {
+                if let Some(gate) = pick::<LogicGateFans>(world, gate) {
+                    RemoveGateFromLogicGraph(gate).apply(world);
+                }
+            }
+            Op::RemoveWire(wire) => {
+                if let Some(wire) = pick::<Wire>(world, wire) {
+                    RemoveWireFromLogicGraph(wire).apply(world);
+                    world.entity_mut(wire).despawn_recursive();
+                }
+            }
+            Op::DespawnWire(wire) => {
+                if let Some(wire) = pick::<Wire>(world, wire) {
+                    DespawnWire(wire).apply(world);
+                }
+            }
+            Op::DespawnGate(gate) => {
+                if let Some(gate) = pick::<LogicGateFans>(world, gate) {
+                    DespawnGateRecursive(gate).apply(world);
+                }
+            }
+            Op::CloneGate { gate, with_incoming_wires } => {
+                if let Some(source) = pick::<LogicGateFans>(world, gate) {
+                    let target = world.spawn_empty().id();
+                    CloneGateCommand { source, target, with_incoming_wires }.apply(world);
+                }
+            }
+            Op::Rebuild => RebuildLogicGraph.apply(world),
+            Op::Step => world.step_logic_once(),
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn graph_sync_keeps_invariants(ops in prop::collection::vec(op(), 1..40)) {
+            let mut app = app();
+            let world = app.world_mut();
+            for op in ops {
+                apply(world, op.clone());
+                let result = check_invariants(world, world.resource::<LogicGraph>());
+                prop_assert_eq!(result, Ok(()), "after {:?}", op);
+            }
+        }
+    }
+
+    #[test]
+    fn despawned_wire_is_a_violation() {
+        let mut app = app();
+        let world = app.world_mut();
+        let a = world.spawn_gate(OrGate::default()).with_inputs(1).with_outputs(1).build();
+        let b = world.spawn_gate(OrGate::default()).with_inputs(1).with_outputs(1).build();
+        world.resource_mut::<LogicGraph>().add_data(vec![a.clone(), b.clone()]);
+        let wire = world.spawn_wire_between(a.output(0), b.input(0));
+        assert_eq!(check_invariants(world, world.resource::<LogicGraph>()), Ok(()));
+
+        world.despawn(wire);
+        let violations = check_invariants(world, world.resource::<LogicGraph>()).unwrap_err();
+        assert!(violations.contains(&InvariantViolation::MissingWire { fan: a.output(0), wire }));
+        assert!(violations.contains(&InvariantViolation::MissingWire { fan: b.input(0), wire }));
+        let from_gate = a.id();
+        let to_gate = b.id();
+        let missing_edge_wire = InvariantViolation::MissingEdgeWire { from_gate, to_gate, wire };
+        assert!(violations.contains(&missing_edge_wire));
+    }
+
+    #[test]
+    fn wire_to_a_removed_gate_is_a_violation() {
+        let mut app = app();
+        let world = app.world_mut();
+        let a = world.spawn_gate(OrGate::default()).with_inputs(1).with_outputs(1).build();
+        let b = world.spawn_gate(OrGate::default()).with_inputs(1).with_outputs(1).build();
+        world.resource_mut::<LogicGraph>().add_data(vec![a.clone(), b.clone()]);
+        let wire = world.spawn_wire_between(a.output(0), b.input(0));
+
+        world.resource_mut::<LogicGraph>().remove_gate(b.id());
+        let violations = check_invariants(world, world.resource::<LogicGraph>()).unwrap_err();
+        let to_gate = b.id();
+        let dangling = InvariantViolation::DanglingWire { fan: a.output(0), wire, to_gate };
+        assert!(violations.contains(&dangling));
+    }
+}
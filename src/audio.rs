@@ -0,0 +1,144 @@
+//! Speakers driven by signals. Requires the `audio` feature.
+
+use std::time::Duration;
+
+use bevy::{
+    audio::{ AudioSink, AudioSinkPlayback, Pitch, PitchBundle, PlaybackSettings },
+    prelude::*,
+};
+
+use crate::logic::{ signal::Signal, AppLogicGateExt, LogicGate };
+
+pub mod prelude {
+    pub use super::{ SpeakerGate, SpeakerMode, SpeakerPlugin };
+}
+
+/// A plugin that registers the [`SpeakerGate`] and plays a tone for each one through
+/// `bevy_audio`.
+///
+/// Add bevy's `AudioPlugin` alongside this plugin.
+pub struct SpeakerPlugin;
+
+impl Plugin for SpeakerPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_logic_gate::<SpeakerGate>()
+            .register_type::<SpeakerGate>()
+            .add_systems(PostUpdate, (insert_speaker_tones, update_speaker_sinks).chain());
+    }
+}
+
+/// How the signal of a [`SpeakerGate`] controls its tone.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SpeakerMode {
+    /// The signal scales the frequency of the tone, at a constant volume.
+    #[default]
+    Pitch,
+    /// The signal scales the volume of the tone, at a constant frequency.
+    Gain,
+}
+
+/// A sink gate that plays a tone while its input is truthy, like a buzzer or an alarm.
+///
+/// The gate reads the absolute maximum of its inputs, which it also emits from every
+/// output. With [`SpeakerMode::Pitch`] a signal of `2.0` plays the tone an octave higher,
+/// and with [`SpeakerMode::Gain`] it plays the tone twice as loud. Digital signals play the
+/// tone at `frequency` and `volume`.
+///
+/// The tone is a [`Pitch`] of `frequency`, created when the gate is added by the
+/// [`SpeakerPlugin`]. The speaker follows the signal once the audio has loaded.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
+pub struct SpeakerGate {
+    pub mode: SpeakerMode,
+    /// The frequency of the tone in hertz, at a signal of `1.0`.
+    pub frequency: f32,
+    /// The volume of the tone, at a signal of `1.0`.
+    pub volume: f32,
+    /// The input signal of the last step.
+    pub signal: Signal,
+}
+
+impl Default for SpeakerGate {
+    fn default() -> Self {
+        Self::new(SpeakerMode::Pitch, 440.0)
+    }
+}
+
+impl SpeakerGate {
+    /// Create a new speaker with a volume of `1.0`.
+    pub fn new(mode: SpeakerMode, frequency: f32) -> Self {
+        Self {
+            mode,
+            frequency,
+            volume: 1.0,
+            signal: Signal::Undefined,
+        }
+    }
+
+    pub fn with_volume(mut self, volume: f32) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    /// Returns the `(speed, volume)` of the tone for the current signal, or `None` if the
+    /// speaker is silent.
+    pub fn playback(&self) -> Option<(f32, f32)> {
+        if !self.signal.is_truthy() {
+            return None;
+        }
+        let scale = self.signal.as_f32().abs();
+        match self.mode {
+            SpeakerMode::Pitch => Some((scale, self.volume)),
+            SpeakerMode::Gain => Some((1.0, self.volume * scale)),
+        }
+    }
+}
+
+impl LogicGate for SpeakerGate {
+    fn evaluate(&mut self, inputs: &[Signal], outputs: &mut [Signal]) {
+        let signal = inputs.iter().fold(Signal::Undefined, |acc, s| acc.max_abs(*s));
+        self.signal = signal;
+        outputs.fill(signal);
+    }
+}
+
+/// Give each new [`SpeakerGate`] a paused, looping tone.
+pub fn insert_speaker_tones(
+    mut commands: Commands,
+    mut pitches: ResMut<Assets<Pitch>>,
+    query: Query<(Entity, &SpeakerGate), Added<SpeakerGate>>
+) {
+    for (entity, speaker) in query.iter() {
+        commands.entity(entity).insert(PitchBundle {
+            source: pitches.add(Pitch::new(speaker.frequency, Duration::from_secs(1))),
+            settings: PlaybackSettings {
+                paused: true,
+                ..PlaybackSettings::LOOP
+            },
+        });
+    }
+}
+
+/// Play, pause, and adjust the [`AudioSink`] of each [`SpeakerGate`] whose signal changed.
+pub fn update_speaker_sinks(
+    query: Query<(&SpeakerGate, &AudioSink), Or<(Changed<SpeakerGate>, Added<AudioSink>)>>
+) {
+    for (speaker, sink) in query.iter() {
+        match speaker.playback() {
+            Some((speed, volume)) => {
+                sink.set_speed(speed);
+                sink.set_volume(volume);
+                if sink.is_paused() {
+                    sink.play();
+                }
+            }
+            None => {
+                if !sink.is_paused() {
+                    sink.pause();
+                }
+            }
+        }
+    }
+}
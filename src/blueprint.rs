@@ -0,0 +1,255 @@
+use std::any::TypeId;
+
+use bevy::{ ecs::reflect::ReflectCommandExt, prelude::*, reflect::TypeRegistry };
+
+use crate::{
+    board::BoardCell,
+    commands::AddGateToLogicGraph,
+    components::{ ConnectedWires, GateOutput, LogicGateFans, LogicUuid, Wire },
+    logic::builder::{ GateData, Known, LogicExt },
+};
+
+pub mod prelude {
+    pub use super::{ CircuitBlueprint, extract_blueprint, spawn_blueprint };
+}
+
+/// A copy of a selection of logic gates and the wires between them.
+///
+/// Components are captured through reflection, so only components registered with
+/// `#[reflect(Component)]` are copied. Entity references (fans, wires, and the hierarchy)
+/// are stored as indices and rebuilt when the blueprint is spawned.
+///
+/// See [`extract_blueprint`] and [`spawn_blueprint`].
+#[derive(Default)]
+pub struct CircuitBlueprint {
+    pub gates: Vec<GateBlueprint>,
+    /// Wires where both the source and destination gate are part of the blueprint.
+    pub wires: Vec<WireBlueprint>,
+}
+
+/// A reflected copy of a gate entity and its fans.
+pub struct GateBlueprint {
+    /// The gate's transform, relative to the center of the selection.
+    pub transform: Transform,
+    pub components: Vec<Box<dyn Reflect>>,
+    /// The reflected components of each input fan, in order.
+    pub inputs: Vec<Vec<Box<dyn Reflect>>>,
+    /// The reflected components of each output fan, in order.
+    pub outputs: Vec<Vec<Box<dyn Reflect>>>,
+}
+
+/// A wire between two gates in a [`CircuitBlueprint`], stored by index.
+pub struct WireBlueprint {
+    /// The index of the source gate in [`CircuitBlueprint::gates`].
+    pub from_gate: usize,
+    pub from_output: usize,
+    /// The index of the destination gate in [`CircuitBlueprint::gates`].
+    pub to_gate: usize,
+    pub to_input: usize,
+    pub components: Vec<Box<dyn Reflect>>,
+}
+
+/// Capture `gates`, their fans, and all wires between them into a [`CircuitBlueprint`].
+///
+/// Gate transforms are stored relative to the center of the selection. Wires that
+/// leave or enter the selection are not captured.
+///
+/// # Panics
+///
+/// Panics if the world does not have an [`AppTypeRegistry`], or if an entity in `gates`
+/// does not have a [`LogicGateFans`] component.
+pub fn extract_blueprint(world: &World, gates: &[Entity]) -> CircuitBlueprint {
+    let registry = world.resource::<AppTypeRegistry>().read();
+
+    let translations = gates
+        .iter()
+        .filter_map(|&gate| world.get::<Transform>(gate))
+        .map(|transform| transform.translation)
+        .collect::<Vec<_>>();
+    let center = if translations.is_empty() {
+        Vec3::ZERO
+    } else {
+        translations.iter().sum::<Vec3>() / (translations.len() as f32)
+    };
+
+    let excluded = excluded_components();
+    let gate_excluded = [excluded.as_slice(), &[TypeId::of::<Transform>()]].concat();
+
+    let mut blueprint = CircuitBlueprint::default();
+
+    for &gate in gates.iter() {
+        let fans = world
+            .get::<LogicGateFans>(gate)
+            .expect("Cannot extract an entity without `LogicGateFans` into a blueprint.");
+        let reflect_fans = |fans: &[Option<Entity>]| {
+            fans.iter()
+                .map(|fan| {
+                    fan.map(|fan| reflect_components(world, &registry, fan, &excluded)).unwrap_or_default()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut transform = world.get::<Transform>(gate).copied().unwrap_or_default();
+        transform.translation -= center;
+
+        blueprint.gates.push(GateBlueprint {
+            transform,
+            components: reflect_components(world, &registry, gate, &gate_excluded),
+            inputs: reflect_fans(&fans.inputs),
+            outputs: reflect_fans(&fans.outputs),
+        });
+    }
+
+    for (from_gate, &gate) in gates.iter().enumerate() {
+        let fans = world.get::<LogicGateFans>(gate).unwrap();
+
+        for (from_output, output) in fans.outputs.iter().enumerate() {
+            let Some(gate_output) = output.and_then(|output| world.get::<GateOutput>(output)) else {
+                continue;
+            };
+
+            for &wire_entity in gate_output.wires.iter() {
+                let Some(wire) = world.get::<Wire>(wire_entity) else {
+                    continue;
+                };
+                let Some(to_gate) = world.get::<Parent>(wire.to).map(|parent| parent.get()) else {
+                    continue;
+                };
+                let Some(to_index) = gates.iter().position(|&g| g == to_gate) else {
+                    continue;
+                };
+                let Some(to_input) = world
+                    .get::<LogicGateFans>(to_gate)
+                    .and_then(|fans| fans.inputs.iter().position(|&i| i == Some(wire.to))) else {
+                    continue;
+                };
+
+                blueprint.wires.push(WireBlueprint {
+                    from_gate,
+                    from_output,
+                    to_gate: to_index,
+                    to_input,
+                    components: reflect_components(world, &registry, wire_entity, &excluded),
+                });
+            }
+        }
+    }
+
+    blueprint
+}
+
+/// Spawn a copy of `blueprint` with fresh entities, offset by `offset`.
+///
+/// Wires are rebuilt between the new fans and every gate is added to the [`LogicGraph`]
+/// resource through [`AddGateToLogicGraph`]. Returns the new gate entities in the same
+/// order as [`CircuitBlueprint::gates`].
+///
+/// [`LogicGraph`]: crate::resources::LogicGraph
+pub fn spawn_blueprint(
+    commands: &mut Commands,
+    blueprint: &CircuitBlueprint,
+    offset: Vec3
+) -> Vec<Entity> {
+    let gates: Vec<GateData<Known, Known>> = blueprint.gates
+        .iter()
+        .map(|gate| {
+            let mut transform = gate.transform;
+            transform.translation += offset;
+
+            let data = commands
+                .spawn_gate(transform)
+                .with_inputs(gate.inputs.len())
+                .with_outputs(gate.outputs.len())
+                .build();
+
+            insert_components(commands, data.id(), &gate.components);
+            for (fan, components) in data.inputs().iter().zip(gate.inputs.iter()) {
+                insert_components(commands, fan.unwrap(), components);
+            }
+            for (fan, components) in data.outputs().iter().zip(gate.outputs.iter()) {
+                insert_components(commands, fan.unwrap(), components);
+            }
+
+            data
+        })
+        .collect();
+
+    for wire in blueprint.wires.iter() {
+        let wire_entity = commands
+            .spawn_wire(
+                &gates[wire.from_gate],
+                wire.from_output,
+                &gates[wire.to_gate],
+                wire.to_input
+            )
+            .id();
+        insert_components(commands, wire_entity, &wire.components);
+    }
+
+    for gate in gates.iter() {
+        commands.add(AddGateToLogicGraph(gate.id()));
+    }
+
+    gates
+        .iter()
+        .map(|gate| gate.id())
+        .collect()
+}
+
+/// Components that store entity IDs, or must be unique to one entity, and are rebuilt instead
+/// of copied.
+pub(crate) fn excluded_components() -> Vec<TypeId> {
+    vec![
+        TypeId::of::<Parent>(),
+        TypeId::of::<Children>(),
+        TypeId::of::<LogicGateFans>(),
+        TypeId::of::<Wire>(),
+        TypeId::of::<GateOutput>(),
+        TypeId::of::<ConnectedWires>(),
+        TypeId::of::<LogicUuid>(),
+        TypeId::of::<BoardCell>()
+    ]
+}
+
+/// Clone all reflectable components of `entity`, skipping `excluded` types.
+pub(crate) fn reflect_components(
+    world: &World,
+    registry: &TypeRegistry,
+    entity: Entity,
+    excluded: &[TypeId]
+) -> Vec<Box<dyn Reflect>> {
+    world
+        .inspect_entity(entity)
+        .into_iter()
+        .filter_map(|info| info.type_id())
+        .filter(|type_id| !excluded.contains(type_id))
+        .filter_map(|type_id| registry.get_type_data::<ReflectComponent>(type_id))
+        .filter_map(|reflect_component| reflect_component.reflect(world.entity(entity)))
+        .map(|component| component.clone_value())
+        .collect()
+}
+
+fn insert_components(commands: &mut Commands, entity: Entity, components: &[Box<dyn Reflect>]) {
+    let mut entity_commands = commands.entity(entity);
+    for component in components.iter() {
+        entity_commands.insert_reflect(component.clone_value());
+    }
+}
+
+/// Insert reflected components into `entity` directly through the world.
+pub(crate) fn insert_reflected_components(
+    world: &mut World,
+    registry: &TypeRegistry,
+    entity: Entity,
+    components: &[Box<dyn Reflect>]
+) {
+    let mut entity_mut = world.entity_mut(entity);
+    for component in components.iter() {
+        let Some(reflect_component) = component
+            .get_represented_type_info()
+            .and_then(|info| registry.get_type_data::<ReflectComponent>(info.type_id())) else {
+            continue;
+        };
+        reflect_component.insert(&mut entity_mut, component.as_ref(), registry);
+    }
+}
@@ -0,0 +1,622 @@
+//! Grid placement for gates.
+//!
+//! Add the [`BoardPlugin`] to place gates on the cells of a [`LogicBoard`], like blocks in a
+//! redstone circuit. A gate with a [`BoardCell`] occupies that cell and is snapped to its
+//! center, and wires with a [`BoardRoute`] are routed around occupied cells.
+//!
+//! A [`GateOrientation`] turns a gate to face a [`BoardDirection`], and tells which of its fans
+//! faces each neighboring cell, so gates placed next to each other can be wired together.
+//! Set an [`AutoWirePolicy`] to wire them automatically.
+
+use std::{ collections::VecDeque, f32::consts::FRAC_PI_2 };
+
+use bevy::{
+    ecs::{ component::{ ComponentHooks, StorageType }, entity::EntityHashMap },
+    prelude::*,
+    transform::TransformSystem,
+    utils::{ HashMap, HashSet },
+};
+
+use crate::{
+    commands::DespawnWire,
+    components::{ GateFan, LogicGateFans, Wire },
+    logic::builder::LogicExt,
+    routing::{ update_wire_paths, WirePath, WireRoutingPlugin },
+};
+
+pub mod prelude {
+    pub use super::{
+        AutoWire,
+        AutoWirePolicy,
+        BoardCell,
+        BoardDirection,
+        BoardPlugin,
+        BoardRoute,
+        GateOrientation,
+        LogicBoard,
+    };
+}
+
+/// A plugin that inserts a [`LogicBoard`], snaps gates with a [`BoardCell`] to the center of
+/// their cell, turns gates to their [`GateOrientation`], and keeps the [`WirePath`] of every
+/// wire with a [`BoardRoute`] up to date.
+pub struct BoardPlugin {
+    /// The width and height of each cell.
+    pub cell_size: Vec2,
+    /// Whether gates placed next to each other are wired together. See [`AutoWirePolicy`].
+    pub auto_wire: AutoWirePolicy,
+}
+
+impl Default for BoardPlugin {
+    fn default() -> Self {
+        Self {
+            cell_size: Vec2::ONE,
+            auto_wire: AutoWirePolicy::default(),
+        }
+    }
+}
+
+impl Plugin for BoardPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<WireRoutingPlugin>() {
+            app.add_plugins(WireRoutingPlugin);
+        }
+
+        app.insert_resource(LogicBoard::new(self.cell_size))
+            .insert_resource(self.auto_wire)
+            .register_type::<AutoWirePolicy>()
+            .register_type::<AutoWire>()
+            .register_type::<BoardCell>()
+            .register_type::<BoardRoute>()
+            .register_type::<BoardDirection>()
+            .register_type::<GateOrientation>()
+            .add_systems(
+                PostUpdate,
+                (
+                    (snap_to_board, orient_gates).before(TransformSystem::TransformPropagate),
+                    auto_wire_board,
+                    route_board_wires
+                        .after(TransformSystem::TransformPropagate)
+                        .after(update_wire_paths),
+                )
+            );
+    }
+}
+
+/// A grid of cells, each occupied by at most one gate.
+///
+/// Cell `(0, 0)` is centered on the `origin`. The board is kept in sync with the [`BoardCell`]
+/// of each entity, so it should be changed by inserting and removing those, or with the
+/// [`PlaceOnBoard`] and [`RemoveFromBoard`] commands.
+///
+/// [`PlaceOnBoard`]: crate::commands::PlaceOnBoard
+/// [`RemoveFromBoard`]: crate::commands::RemoveFromBoard
+#[derive(Resource, Clone, Debug, Default)]
+pub struct LogicBoard {
+    pub cell_size: Vec2,
+    pub origin: Vec2,
+    cells: HashMap<IVec2, Entity>,
+    positions: EntityHashMap<IVec2>,
+}
+
+impl LogicBoard {
+    pub fn new(cell_size: Vec2) -> Self {
+        Self {
+            cell_size,
+            ..default()
+        }
+    }
+
+    pub fn with_origin(mut self, origin: Vec2) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Returns the cell that contains `position`.
+    pub fn cell_at(&self, position: Vec2) -> IVec2 {
+        ((position - self.origin) / self.cell_size).round().as_ivec2()
+    }
+
+    /// Returns the center of `cell`.
+    pub fn cell_center(&self, cell: IVec2) -> Vec2 {
+        self.origin + cell.as_vec2() * self.cell_size
+    }
+
+    /// Returns the center of the cell that contains `position`.
+    pub fn snap(&self, position: Vec2) -> Vec2 {
+        self.cell_center(self.cell_at(position))
+    }
+
+    /// Returns the entity that occupies `cell`.
+    pub fn get(&self, cell: IVec2) -> Option<Entity> {
+        self.cells.get(&cell).copied()
+    }
+
+    /// Returns the entity that occupies the cell next to `cell` in `direction`.
+    pub fn neighbor(&self, cell: IVec2, direction: BoardDirection) -> Option<Entity> {
+        self.get(cell + direction.offset())
+    }
+
+    /// Returns the cell that `entity` occupies.
+    pub fn cell_of(&self, entity: Entity) -> Option<IVec2> {
+        self.positions.get(&entity).copied()
+    }
+
+    pub fn is_occupied(&self, cell: IVec2) -> bool {
+        self.cells.contains_key(&cell)
+    }
+
+    /// Returns `true` if `entity` can be placed on `cell`, because it is empty or already
+    /// occupied by `entity`.
+    pub fn can_place(&self, cell: IVec2, entity: Entity) -> bool {
+        !matches!(self.get(cell), Some(occupant) if occupant != entity)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Returns an iterator over each occupied cell and its entity.
+    pub fn iter(&self) -> impl Iterator<Item = (IVec2, Entity)> + '_ {
+        self.cells.iter().map(|(&cell, &entity)| (cell, entity))
+    }
+
+    /// Place `entity` on `cell`, moving it from its previous cell. Returns the entity that
+    /// occupied the cell before, if it was a different one.
+    pub(crate) fn insert(&mut self, cell: IVec2, entity: Entity) -> Option<Entity> {
+        self.remove(entity);
+        let previous = self.cells.insert(cell, entity);
+        if let Some(previous) = previous {
+            self.positions.remove(&previous);
+        }
+        self.positions.insert(entity, cell);
+        previous
+    }
+
+    /// Remove `entity` from the board, returning the cell it occupied.
+    pub(crate) fn remove(&mut self, entity: Entity) -> Option<IVec2> {
+        let cell = self.positions.remove(&entity)?;
+        self.cells.remove(&cell);
+        Some(cell)
+    }
+
+    /// Returns the shortest orthogonal path of cells from `from` to `to`, including both,
+    /// that only passes through empty cells. The end cells may be occupied.
+    ///
+    /// Among paths of the same length, the one that turns least often is preferred. Returns
+    /// `None` if every path is blocked.
+    pub fn route(&self, from: IVec2, to: IVec2) -> Option<Vec<IVec2>> {
+        const DIRECTIONS: [IVec2; 4] = [IVec2::X, IVec2::Y, IVec2::NEG_X, IVec2::NEG_Y];
+
+        // Keep the search finite: a path never needs to leave the bounds of the occupied
+        // cells and both ends, plus a margin of one cell to go around them.
+        let (min, max) = self.cells
+            .keys()
+            .fold((from.min(to), from.max(to)), |(min, max), &cell| {
+                (min.min(cell), max.max(cell))
+            });
+        let (min, max) = (min - IVec2::ONE, max + IVec2::ONE);
+        let passable = |cell: IVec2| {
+            cell.cmpge(min).all() &&
+                cell.cmple(max).all() &&
+                (cell == from || cell == to || !self.is_occupied(cell))
+        };
+
+        // Search backwards from `to`, so every cell knows its distance to the end.
+        let mut distances = HashMap::<IVec2, usize>::new();
+        let mut queue = VecDeque::from([to]);
+        distances.insert(to, 0);
+        while let Some(cell) = queue.pop_front() {
+            if cell == from {
+                break;
+            }
+            let distance = distances[&cell];
+            for direction in DIRECTIONS {
+                let next = cell + direction;
+                if passable(next) && !distances.contains_key(&next) {
+                    distances.insert(next, distance + 1);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        // Walk forwards from `from`, keeping the same direction whenever it is a shortest way.
+        let mut distance = *distances.get(&from)?;
+        let mut path = vec![from];
+        let mut cell = from;
+        let mut heading = None;
+        while distance > 0 {
+            let next_step = heading
+                .into_iter()
+                .chain(DIRECTIONS)
+                .find(|&direction| distances.get(&(cell + direction)) == Some(&(distance - 1)))?;
+            cell += next_step;
+            heading = Some(next_step);
+            distance -= 1;
+            path.push(cell);
+        }
+        Some(path)
+    }
+
+    /// Returns a [`WirePath`] between two fan positions that follows the cells from
+    /// [`route`](Self::route), turning at the centers of its corner cells.
+    ///
+    /// Like [`WirePath::manhattan`], the path leaves and enters the fans horizontally, and it
+    /// is used instead when the route does not turn. Returns `None` if every path is blocked.
+    pub fn wire_path(&self, from: Vec3, to: Vec3) -> Option<WirePath> {
+        let cells = self.route(self.cell_at(from.xy()), self.cell_at(to.xy()))?;
+        let corners = cells
+            .windows(3)
+            .filter(|cells| cells[1] - cells[0] != cells[2] - cells[1])
+            .map(|cells| cells[1])
+            .collect::<Vec<_>>();
+        let (Some(&first), Some(&last)) = (corners.first(), corners.last()) else {
+            return Some(WirePath::manhattan(from, to));
+        };
+
+        let z = from.lerp(to, 0.5).z;
+        let (first, last) = (self.cell_center(first), self.cell_center(last));
+        let mut points = vec![from, Vec3::new(first.x, from.y, z)];
+        points.extend(corners.iter().map(|&cell| self.cell_center(cell).extend(z)));
+        points.extend([Vec3::new(last.x, to.y, z), to]);
+        points.dedup();
+        Some(WirePath(points))
+    }
+}
+
+/// One of the four directions from a cell to its neighbors. North is +Y and east is +X.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+pub enum BoardDirection {
+    North,
+    #[default]
+    East,
+    South,
+    West,
+}
+
+impl BoardDirection {
+    /// Every direction, in clockwise order from north.
+    pub const ALL: [BoardDirection; 4] = [
+        BoardDirection::North,
+        BoardDirection::East,
+        BoardDirection::South,
+        BoardDirection::West,
+    ];
+
+    /// Returns the offset from a cell to its neighbor in this direction.
+    pub fn offset(self) -> IVec2 {
+        match self {
+            BoardDirection::North => IVec2::Y,
+            BoardDirection::East => IVec2::X,
+            BoardDirection::South => IVec2::NEG_Y,
+            BoardDirection::West => IVec2::NEG_X,
+        }
+    }
+
+    /// Returns the direction of a neighboring cell at `offset`, if it is one.
+    pub fn from_offset(offset: IVec2) -> Option<Self> {
+        Self::ALL.into_iter().find(|direction| direction.offset() == offset)
+    }
+
+    pub fn opposite(self) -> Self {
+        self.rotate_cw(2)
+    }
+
+    /// Returns this direction turned clockwise by `quarter_turns`.
+    pub fn rotate_cw(self, quarter_turns: i32) -> Self {
+        Self::ALL[((self as i32) + quarter_turns).rem_euclid(4) as usize]
+    }
+
+    /// Returns the number of clockwise quarter turns from east to this direction.
+    pub fn quarter_turns(self) -> i32 {
+        ((self as i32) - (BoardDirection::East as i32)).rem_euclid(4)
+    }
+}
+
+/// The direction that the front of a gate faces on the board.
+///
+/// Fans are given a side relative to the front, as if the gate faced [`BoardDirection::East`]:
+///
+/// - Input `0` is on the back, input `1` on the left, and input `2` on the right.
+/// - Output `0` is on the front.
+///
+/// Other fans do not face a neighbor. The [`FanLayout::Sides`] layout places fans on these
+/// sides, and the [`BoardPlugin`] turns each gate's [`Transform`] to match its orientation.
+///
+/// [`FanLayout::Sides`]: crate::layout::FanLayout::Sides
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Component, PartialEq, Hash)]
+pub struct GateOrientation(pub BoardDirection);
+
+impl GateOrientation {
+    /// The side of each input, for a gate facing east.
+    pub const INPUT_SIDES: [BoardDirection; 3] = [
+        BoardDirection::West,
+        BoardDirection::North,
+        BoardDirection::South,
+    ];
+    /// The side of each output, for a gate facing east.
+    pub const OUTPUT_SIDES: [BoardDirection; 1] = [BoardDirection::East];
+
+    /// Returns the orientation closest to the rotation of a gate about the Z axis.
+    pub fn from_rotation(rotation: Quat) -> Self {
+        let (axis, angle) = rotation.to_axis_angle();
+        let angle = if axis.z < 0.0 { -angle } else { angle };
+        let quarter_turns = (angle / FRAC_PI_2).round() as i32;
+        Self(BoardDirection::East.rotate_cw(-quarter_turns))
+    }
+
+    /// Returns the rotation about the Z axis that turns a gate facing east to this orientation.
+    pub fn rotation(&self) -> Quat {
+        Quat::from_rotation_z(-(self.0.quarter_turns() as f32) * FRAC_PI_2)
+    }
+
+    /// Returns the side of `direction` relative to the gate, as if it faced east.
+    pub fn to_local(&self, direction: BoardDirection) -> BoardDirection {
+        direction.rotate_cw(-self.0.quarter_turns())
+    }
+
+    /// Returns the board direction of a `local` side of the gate.
+    pub fn to_board(&self, local: BoardDirection) -> BoardDirection {
+        local.rotate_cw(self.0.quarter_turns())
+    }
+
+    /// Returns the board direction that a fan faces, or `None` if it does not face a neighbor.
+    pub fn fan_direction(&self, kind: &GateFan, index: usize) -> Option<BoardDirection> {
+        let local = match kind {
+            GateFan::Input => Self::INPUT_SIDES.get(index),
+            GateFan::Output => Self::OUTPUT_SIDES.get(index),
+        };
+        local.map(|&local| self.to_board(local))
+    }
+
+    /// Returns the kind and index of the fan that faces `direction`, if any.
+    pub fn fan_at(&self, direction: BoardDirection) -> Option<(GateFan, usize)> {
+        let local = self.to_local(direction);
+        if let Some(index) = Self::INPUT_SIDES.iter().position(|&side| side == local) {
+            return Some((GateFan::Input, index));
+        }
+        Self::OUTPUT_SIDES
+            .iter()
+            .position(|&side| side == local)
+            .map(|index| (GateFan::Output, index))
+    }
+
+    /// Returns the output index of a gate at `from` and the input index of a gate at `to` that
+    /// face each other, if the cells are neighbors.
+    ///
+    /// ```ignore
+    /// if let Some((output, input)) = GateOrientation::connection(a_cell, a, b_cell, b) {
+    ///     commands.spawn_wire(&gate_a, output, &gate_b, input);
+    /// }
+    /// ```
+    pub fn connection(
+        from_cell: IVec2,
+        from: &GateOrientation,
+        to_cell: IVec2,
+        to: &GateOrientation
+    ) -> Option<(usize, usize)> {
+        let direction = BoardDirection::from_offset(to_cell - from_cell)?;
+        let (GateFan::Output, output) = from.fan_at(direction)? else {
+            return None;
+        };
+        let (GateFan::Input, input) = to.fan_at(direction.opposite())? else {
+            return None;
+        };
+        Some((output, input))
+    }
+}
+
+/// The cell of a [`LogicBoard`] that an entity occupies.
+///
+/// Inserting one places the entity on the board, taking the cell from any entity that
+/// occupied it before. Use the [`PlaceOnBoard`] command to check that the cell is empty
+/// first.
+///
+/// [`PlaceOnBoard`]: crate::commands::PlaceOnBoard
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Component, PartialEq, Hash)]
+pub struct BoardCell(pub IVec2);
+
+impl Component for BoardCell {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks
+            .on_insert(|mut world, entity, _| {
+                let cell = world.get::<BoardCell>(entity).expect("BoardCell was just inserted").0;
+                let Some(mut board) = world.get_resource_mut::<LogicBoard>() else {
+                    return;
+                };
+                if let Some(previous) = board.insert(cell, entity) {
+                    if previous != entity {
+                        warn!("{entity:?} replaced {previous:?} on board cell {cell}");
+                        world.commands().entity(previous).remove::<BoardCell>();
+                    }
+                }
+            })
+            .on_remove(|mut world, entity, _| {
+                let cell = world.get::<BoardCell>(entity).expect("BoardCell is being removed").0;
+                if let Some(mut board) = world.get_resource_mut::<LogicBoard>() {
+                    if board.get(cell) == Some(entity) {
+                        board.remove(entity);
+                    }
+                }
+            });
+    }
+}
+
+/// Routes a wire's [`WirePath`] through the empty cells of the [`LogicBoard`], with
+/// [`LogicBoard::wire_path`]. Wires that cannot be routed fall back to
+/// [`WirePath::manhattan`].
+///
+/// This takes precedence over a [`WireRoute`](crate::routing::WireRoute) on the same wire.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub struct BoardRoute;
+
+/// A filter for gates that are not at the center of their [`BoardCell`] yet.
+type BoardCellChanged = Or<(Changed<BoardCell>, Added<Transform>)>;
+
+/// Move each gate whose [`BoardCell`] changed to the center of its cell, keeping its Z.
+pub fn snap_to_board(
+    board: Res<LogicBoard>,
+    mut query: Query<(&BoardCell, &mut Transform), BoardCellChanged>
+) {
+    for (cell, mut transform) in query.iter_mut() {
+        let center = board.cell_center(cell.0);
+        if transform.translation.xy() != center {
+            transform.translation = center.extend(transform.translation.z);
+        }
+    }
+}
+
+/// Whether the [`BoardPlugin`] wires neighboring gates together, like redstone dust joining.
+///
+/// Two gates on neighboring cells are connected when an output of one faces an input of the
+/// other, as given by [`GateOrientation::connection`]. Gates without a [`GateOrientation`]
+/// face east. Wires created this way are marked with [`AutoWire`].
+///
+/// This can be changed at any time, and applies the next time the board changes.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+pub enum AutoWirePolicy {
+    /// Wires are only created by hand.
+    #[default]
+    Manual,
+    /// Wire gates together when they are placed facing each other. Wires are kept when the
+    /// gates are moved apart.
+    Connect,
+    /// Like [`AutoWirePolicy::Connect`], and also despawn each [`AutoWire`] whose gates no
+    /// longer face each other.
+    Sync,
+}
+
+/// Marks a wire that was created by the [`AutoWirePolicy`]. Only these wires are despawned
+/// by [`AutoWirePolicy::Sync`].
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub struct AutoWire;
+
+/// A filter for gates on the board whose neighbors may need to be wired again.
+type BoardGateChanged = (With<BoardCell>, Or<(Changed<GateOrientation>, Changed<LogicGateFans>)>);
+
+/// Create and despawn wires between neighboring gates according to the [`AutoWirePolicy`],
+/// whenever the [`LogicBoard`], a [`GateOrientation`], or the fans of a gate changed.
+pub fn auto_wire_board(
+    mut commands: Commands,
+    policy: Res<AutoWirePolicy>,
+    board: Res<LogicBoard>,
+    query_changed: Query<(), BoardGateChanged>,
+    query_gates: Query<(Option<&GateOrientation>, &LogicGateFans)>,
+    query_wires: Query<(Entity, &Wire, Has<AutoWire>)>
+) {
+    if *policy == AutoWirePolicy::Manual {
+        return;
+    }
+    if !board.is_changed() && !policy.is_changed() && query_changed.is_empty() {
+        return;
+    }
+
+    // The output and input fans of every pair of neighbors that face each other.
+    let mut connections = HashSet::new();
+    for (cell, entity) in board.iter() {
+        let Ok((orientation, fans)) = query_gates.get(entity) else {
+            continue;
+        };
+        let orientation = orientation.copied().unwrap_or_default();
+
+        for direction in BoardDirection::ALL {
+            let Some(neighbor) = board.neighbor(cell, direction) else {
+                continue;
+            };
+            let Ok((neighbor_orientation, neighbor_fans)) = query_gates.get(neighbor) else {
+                continue;
+            };
+            let neighbor_orientation = neighbor_orientation.copied().unwrap_or_default();
+
+            let Some((output, input)) = GateOrientation::connection(
+                cell,
+                &orientation,
+                cell + direction.offset(),
+                &neighbor_orientation
+            ) else {
+                continue;
+            };
+            let from = fans.outputs.get(output).copied().flatten();
+            let to = neighbor_fans.inputs.get(input).copied().flatten();
+            if let (Some(from), Some(to)) = (from, to) {
+                connections.insert((from, to));
+            }
+        }
+    }
+
+    for (entity, wire, is_auto) in query_wires.iter() {
+        let connected = connections.remove(&(wire.from, wire.to));
+        if is_auto && !connected && *policy == AutoWirePolicy::Sync {
+            commands.add(DespawnWire(entity));
+        }
+    }
+
+    // Whatever is left is not wired yet.
+    for (from, to) in connections {
+        let wire = commands.spawn_wire_between(from, to);
+        commands.entity(wire).insert(AutoWire);
+    }
+}
+
+/// A filter for gates that do not face their [`GateOrientation`] yet.
+type GateOrientationChanged = Or<(Changed<GateOrientation>, Added<Transform>)>;
+
+/// Turn each gate whose [`GateOrientation`] changed to face its direction.
+pub fn orient_gates(mut query: Query<(&GateOrientation, &mut Transform), GateOrientationChanged>) {
+    for (orientation, mut transform) in query.iter_mut() {
+        let rotation = orientation.rotation();
+        if transform.rotation != rotation {
+            transform.rotation = rotation;
+        }
+    }
+}
+
+/// A wire with a [`BoardRoute`], and its [`WirePath`] if it has one.
+type BoardRoutedWire<'a> = (Entity, Ref<'a, Wire>, Ref<'a, BoardRoute>, Option<&'a mut WirePath>);
+
+/// Recompute the [`WirePath`] of each wire with a [`BoardRoute`] whose fans moved, or of
+/// every such wire when the [`LogicBoard`] changed, since cells may have been filled or
+/// emptied.
+pub fn route_board_wires(
+    mut commands: Commands,
+    board: Res<LogicBoard>,
+    mut query_wires: Query<BoardRoutedWire>,
+    query_fans: Query<Ref<GlobalTransform>, With<GateFan>>
+) {
+    for (entity, wire, route, path) in query_wires.iter_mut() {
+        let (Ok(from), Ok(to)) = (query_fans.get(wire.from), query_fans.get(wire.to)) else {
+            continue;
+        };
+
+        let changed =
+            board.is_changed() ||
+            wire.is_changed() ||
+            route.is_changed() ||
+            from.is_changed() ||
+            to.is_changed();
+        if !changed && path.is_some() {
+            continue;
+        }
+
+        let (from, to) = (from.translation(), to.translation());
+        let new_path = board.wire_path(from, to).unwrap_or_else(|| WirePath::manhattan(from, to));
+        match path {
+            Some(mut path) => {
+                path.set_if_neq(new_path);
+            }
+            None => {
+                commands.entity(entity).insert(new_path);
+            }
+        }
+    }
+}
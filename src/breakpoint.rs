@@ -0,0 +1,120 @@
+//! Pause the simulation when a signal meets a condition.
+
+use bevy::prelude::*;
+
+use crate::logic::{ schedule::{ FixedLogicStepExt, LogicStep, LogicTick }, signal::Signal };
+
+pub mod prelude {
+    pub use super::{ BreakCondition, BreakpointHit, LogicBreakpoint };
+}
+
+/// A condition on the previous and current signal of an entity, checked after every step.
+#[derive(Clone, Copy, Debug)]
+pub enum BreakCondition {
+    /// The signal turned on.
+    RisingEdge,
+    /// The signal turned off.
+    FallingEdge,
+    /// The signal changed.
+    Changed,
+    /// The signal became exactly this value.
+    Equals(Signal),
+    /// The signal rose above this value, compared with [`Signal::as_f32`].
+    Above(f32),
+    /// The signal fell below this value, compared with [`Signal::as_f32`].
+    Below(f32),
+    /// A custom predicate, called with the previous and current signal.
+    Custom(fn(Signal, Signal) -> bool),
+}
+
+impl BreakCondition {
+    /// Returns `true` if the change from `previous` to `current` meets the condition.
+    ///
+    /// Level conditions only match when the signal crosses into them, so a breakpoint is not
+    /// hit again on every step while the condition holds.
+    pub fn is_met(&self, previous: Signal, current: Signal) -> bool {
+        match *self {
+            BreakCondition::RisingEdge => previous.is_falsy() && current.is_truthy(),
+            BreakCondition::FallingEdge => previous.is_truthy() && current.is_falsy(),
+            BreakCondition::Changed => previous != current,
+            BreakCondition::Equals(signal) => previous != signal && current == signal,
+            BreakCondition::Above(value) => {
+                previous.as_f32() <= value && current.as_f32() > value
+            }
+            BreakCondition::Below(value) => {
+                previous.as_f32() >= value && current.as_f32() < value
+            }
+            BreakCondition::Custom(predicate) => predicate(previous, current),
+        }
+    }
+}
+
+/// Pauses [`Time<LogicStep>`] and sends a [`BreakpointHit`] event when the [`Signal`] of this
+/// entity, usually a fan or a wire, meets a [`BreakCondition`].
+///
+/// Breakpoints are checked in [`LogicSystemSet::PostStep`] by [`check_breakpoints`]. Resume
+/// the simulation with [`FixedLogicStepExt::unpause`], or step it manually.
+///
+/// [`LogicSystemSet::PostStep`]: crate::logic::schedule::LogicSystemSet::PostStep
+#[derive(Component, Clone, Debug)]
+pub struct LogicBreakpoint {
+    pub condition: BreakCondition,
+    /// If false, the breakpoint is never hit.
+    pub enabled: bool,
+    /// The signal seen by the last check.
+    previous: Option<Signal>,
+}
+
+impl LogicBreakpoint {
+    pub fn new(condition: BreakCondition) -> Self {
+        Self {
+            condition,
+            enabled: true,
+            previous: None,
+        }
+    }
+}
+
+/// Sent when a [`LogicBreakpoint`] is hit.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct BreakpointHit {
+    /// The [`LogicTick`] of the step that hit the breakpoint.
+    pub tick: u64,
+    /// The entity with the breakpoint.
+    pub entity: Entity,
+    /// The gate the entity belongs to, if it is a fan.
+    pub gate: Option<Entity>,
+    pub condition: BreakCondition,
+    pub previous: Signal,
+    pub signal: Signal,
+}
+
+/// Check every [`LogicBreakpoint`], pausing [`Time<LogicStep>`] and sending a
+/// [`BreakpointHit`] event for each one that is hit.
+pub fn check_breakpoints(
+    tick: Option<Res<LogicTick>>,
+    mut time: Option<ResMut<Time<LogicStep>>>,
+    mut events: EventWriter<BreakpointHit>,
+    mut query: Query<(Entity, &mut LogicBreakpoint, &Signal, Option<&Parent>)>
+) {
+    for (entity, mut breakpoint, &signal, parent) in query.iter_mut() {
+        let Some(previous) = breakpoint.previous.replace(signal) else {
+            continue;
+        };
+        if !breakpoint.enabled || !breakpoint.condition.is_met(previous, signal) {
+            continue;
+        }
+
+        events.send(BreakpointHit {
+            tick: tick.as_ref().map_or(0, |tick| tick.0),
+            entity,
+            gate: parent.map(|parent| parent.get()),
+            condition: breakpoint.condition,
+            previous,
+            signal,
+        });
+        if let Some(time) = time.as_mut() {
+            time.pause();
+        }
+    }
+}
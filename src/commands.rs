@@ -1,8 +1,24 @@
-use bevy::{ ecs::world::Command, prelude::* };
+use bevy::{ ecs::{ entity::EntityHashSet, world::Command }, prelude::* };
+use bevy_trait_query::One;
 use crate::{
-    components::{ GateOutput, Wire },
-    logic::builder::WireData,
+    blueprint::{ excluded_components, insert_reflected_components, reflect_components },
+    board::{ BoardCell, LogicBoard },
+    components::{
+        BidirectionalWire,
+        CircuitId,
+        CircuitMember,
+        ConnectedWires,
+        GateDisabled,
+        GateOutput,
+        InputBundle,
+        NoEvalOutput,
+        OutputBundle,
+        Wire,
+        WireBundle,
+    },
+    logic::{ builder::WireData, signal::Signal, LogicGate },
     prelude::{ LogicGateFans, LogicGraph },
+    snapshot::LogicRollback,
 };
 
 pub mod prelude {
@@ -11,6 +27,18 @@ pub mod prelude {
         RemoveGateFromLogicGraph,
         AddWireToLogicGraph,
         RemoveWireFromLogicGraph,
+        RebuildLogicGraph,
+        CloneGateCommand,
+        DespawnCircuit,
+        DespawnGateRecursive,
+        DespawnWire,
+        DisableGate,
+        EnableGate,
+        ToggleGate,
+        ResetCircuit,
+        RewindLogic,
+        PlaceOnBoard,
+        RemoveFromBoard,
     };
 }
 
@@ -29,13 +57,13 @@ impl Command for AddGateToLogicGraph {
 }
 
 /// A command that removes a logic entity from the [`LogicGraph`] resource and
-/// all wires connected to it. This updates the [`GateOutput::wires`] set
-/// for the targeted gate and any gates incoming via wire.
+/// all wires connected to it. This updates the [`GateOutput::wires`] and [`ConnectedWires`]
+/// sets for the targeted gate and any gates connected to it via wire.
 ///
 /// This command does not despawn any entities.
 ///
-/// Unlike [`AddGateToLogicGraph`], this command collects wire data
-/// using the [`LogicGraph`] resource instead of [`extract_outgoing_wires`].
+/// Unlike [`AddGateToLogicGraph`], this command collects wire data from the wire sets of
+/// the gate's fans instead of [`extract_outgoing_wires`], so incoming wires are found too.
 ///
 /// [`GateOutput::wires`]: crate::components::GateOutput::wires
 /// [`LogicGraph`]: crate::resources::LogicGraph
@@ -43,21 +71,40 @@ pub struct RemoveGateFromLogicGraph(pub Entity);
 
 impl Command for RemoveGateFromLogicGraph {
     fn apply(self, world: &mut World) {
-        let mut sim = world.resource_mut::<LogicGraph>();
-        let incoming_wires: Vec<_> = sim.iter_incoming_wires(self.0).collect();
-        sim.remove_gate(self.0).compile();
+        // The graph has one edge per pair of gates, so find every wire through the fans
+        // instead, including parallel wires between the same gates.
+        let wires = world
+            .get::<LogicGateFans>(self.0)
+            .into_iter()
+            .flat_map(|fans| fans.some_inputs().into_iter().chain(fans.some_outputs()))
+            .flat_map(|fan| {
+                let incoming = world.get::<ConnectedWires>(fan).map(|connected| &connected.wires);
+                let outgoing = world.get::<GateOutput>(fan).map(|output| &output.wires);
+                incoming.into_iter().chain(outgoing).flat_map(|wires| wires.iter().copied())
+            })
+            .collect::<EntityHashSet>();
+        world.resource_mut::<LogicGraph>().remove_gate(self.0).compile();
 
-        for (wire_entity, wire) in incoming_wires {
-            world
-                .get_mut::<GateOutput>(wire.from)
-                .expect("Wire::from Entity does not have GateOutput component")
-                .wires.remove(&wire_entity);
+        for wire_entity in wires {
+            // The graph only knows the gates, so grab the fans from the wire itself.
+            let Some(&wire) = world.get::<Wire>(wire_entity) else {
+                continue;
+            };
+
+            if let Some(mut output) = world.get_mut::<GateOutput>(wire.from) {
+                output.wires.remove(&wire_entity);
+            } else {
+                warn!("Wire {wire_entity} starts at {}, which is not a GateOutput", wire.from);
+            }
+            if let Some(mut connected) = world.get_mut::<ConnectedWires>(wire.to) {
+                connected.wires.remove(&wire_entity);
+            }
         }
     }
 }
 
 /// A command that adds an edge between two logic entities in the [`LogicGraph`] resource and
-/// updates the [`GateOutput::wires`] set for the output fan entity.
+/// updates the [`GateOutput::wires`] and [`ConnectedWires`] sets for the connected fan entities.
 ///
 /// This command does not spawn any entities.
 ///
@@ -70,21 +117,25 @@ impl Command for AddWireToLogicGraph {
         let wire_entity = self.0;
         let &wire = world.get::<Wire>(wire_entity).expect("Entity does not have a Wire component");
 
-        // Update the `wires` set in the output fan.
-        world
-            .get_mut::<GateOutput>(wire.from)
-            .expect("Wire::from Entity does not have GateOutput component")
-            .wires.insert(wire_entity);
-
         // Grab the gates for the graph.
-        let from_gate = world
-            .get::<Parent>(wire.from)
-            .expect("GateOutput does not have a parent gate")
-            .get();
-        let to_gate = world
-            .get::<Parent>(wire.to)
-            .expect("GateInput does not have a parent gate")
-            .get();
+        let from_gate = world.get::<Parent>(wire.from).map(Parent::get);
+        let to_gate = world.get::<Parent>(wire.to).map(Parent::get);
+        let (Some(from_gate), Some(to_gate)) = (from_gate, to_gate) else {
+            warn!("Wire {wire_entity} is not added to the LogicGraph, as a fan has no parent gate");
+            return;
+        };
+
+        // Update the `wires` set in the output fan.
+        if let Some(mut output) = world.get_mut::<GateOutput>(wire.from) {
+            output.wires.insert(wire_entity);
+        } else {
+            warn!("Wire {wire_entity} starts at {}, which is not a GateOutput", wire.from);
+        }
+        if let Some(mut connected) = world.get_mut::<ConnectedWires>(wire.to) {
+            connected.wires.insert(wire_entity);
+        } else {
+            warn!("Wire {wire_entity} ends at {}, which has no ConnectedWires", wire.to);
+        }
 
         // Add the data and recompile
         world.resource_mut::<LogicGraph>().add_wire(from_gate, to_gate, wire_entity).compile();
@@ -92,7 +143,9 @@ impl Command for AddWireToLogicGraph {
 }
 
 /// A command that removes a wire between two logic entities in the [`LogicGraph`] resource and
-/// updates the [`GateOutput::wires`] set for the output fan entity.
+/// updates the [`GateOutput::wires`] and [`ConnectedWires`] sets for the connected fan entities.
+///
+/// If another wire connects the same two gates, the graph edge is kept for that wire.
 ///
 /// This command does not despawn any entities. It is important that the [`Entity`]
 /// already exists with a [`Wire`] component.
@@ -104,33 +157,529 @@ pub struct RemoveWireFromLogicGraph(pub Entity);
 impl Command for RemoveWireFromLogicGraph {
     fn apply(self, world: &mut World) {
         let wire_entity = self.0;
-        let &wire = world.get::<Wire>(wire_entity).expect("Entity does not have a Wire component");
+        let Some(&wire) = world.get::<Wire>(wire_entity) else {
+            warn!("Wire {wire_entity} is not removed from the LogicGraph, as it has no Wire");
+            return;
+        };
 
         // Update the `wires` set in the output fan.
-        world
-            .get_mut::<GateOutput>(wire.from)
-            .expect("Wire::from Entity does not have GateOutput component")
-            .wires.remove(&wire_entity);
+        if let Some(mut output) = world.get_mut::<GateOutput>(wire.from) {
+            output.wires.remove(&wire_entity);
+        } else {
+            warn!("Wire {wire_entity} starts at {}, which is not a GateOutput", wire.from);
+        }
+        if let Some(mut connected) = world.get_mut::<ConnectedWires>(wire.to) {
+            connected.wires.remove(&wire_entity);
+        }
 
         // Grab the gates for the graph.
-        let from_gate = world
-            .get::<Parent>(wire.from)
-            .expect("GateOutput does not have a parent gate")
-            .get();
-        let to_gate = world
-            .get::<Parent>(wire.to)
-            .expect("GateInput does not have a parent gate")
-            .get();
+        let from_gate = world.get::<Parent>(wire.from).map(Parent::get);
+        let to_gate = world.get::<Parent>(wire.to).map(Parent::get);
+        let (Some(from_gate), Some(to_gate)) = (from_gate, to_gate) else {
+            warn!("Wire {wire_entity} is not removed from the LogicGraph, as a fan has no parent");
+            return;
+        };
 
         // Remove the data and recompile
-        world.resource_mut::<LogicGraph>().remove_wire(from_gate, to_gate).compile();
+        remove_wire_edge(world, wire_entity, from_gate, to_gate);
+    }
+}
+
+/// A command that brings the [`LogicGraph`] resource in line with the gates and wires in the
+/// world, for entities that were spawned without the graph commands, such as by a
+/// `DynamicScene`.
+///
+/// Every gate and wire missing from the graph is added, along with the wire to the
+/// [`GateOutput::wires`] and [`ConnectedWires`] sets of its fans. Gates and wires that no
+/// longer exist are removed. The graph is then recompiled.
+///
+/// [`GateOutput::wires`]: crate::components::GateOutput::wires
+/// [`LogicGraph`]: crate::resources::LogicGraph
+pub struct RebuildLogicGraph;
+
+impl Command for RebuildLogicGraph {
+    fn apply(self, world: &mut World) {
+        let gates = world
+            .query_filtered::<Entity, With<LogicGateFans>>()
+            .iter(world)
+            .collect::<EntityHashSet>();
+        let mut query_wires = world.query_filtered::<(Entity, &Wire), Without<BidirectionalWire>>();
+        let wires = query_wires
+            .iter(world)
+            .filter_map(|(wire_entity, &wire)| {
+                let from_gate = world.get::<Parent>(wire.from)?.get();
+                let to_gate = world.get::<Parent>(wire.to)?.get();
+                Some((wire_entity, wire, from_gate, to_gate))
+            })
+            .collect::<Vec<_>>();
+
+        for &(wire_entity, wire, _, _) in wires.iter() {
+            if let Some(mut output) = world.get_mut::<GateOutput>(wire.from) {
+                if !output.wires.contains(&wire_entity) {
+                    output.wires.insert(wire_entity);
+                }
+            }
+            if let Some(mut connected) = world.get_mut::<ConnectedWires>(wire.to) {
+                if !connected.wires.contains(&wire_entity) {
+                    connected.wires.insert(wire_entity);
+                }
+            }
+        }
+
+        let wire_entities = wires
+            .iter()
+            .map(|(wire_entity, ..)| *wire_entity)
+            .collect::<EntityHashSet>();
+        let mut graph = world.resource_mut::<LogicGraph>();
+
+        let stale_gates = graph.graph
+            .nodes()
+            .filter(|gate| !gates.contains(gate))
+            .collect::<Vec<_>>();
+        for gate in stale_gates {
+            graph.remove_gate(gate);
+        }
+        let stale_wires = graph.graph
+            .all_edges()
+            .filter(|(_, _, wire_entity)| !wire_entities.contains(*wire_entity))
+            .map(|(from_gate, to_gate, _)| (from_gate, to_gate))
+            .collect::<Vec<_>>();
+        for (from_gate, to_gate) in stale_wires {
+            graph.remove_wire(from_gate, to_gate);
+        }
+
+        for &gate in gates.iter() {
+            if !graph.graph.contains_node(gate) {
+                graph.add_gate(gate);
+            }
+        }
+        for (wire_entity, _, from_gate, to_gate) in wires {
+            if !graph.graph.contains_edge(from_gate, to_gate) {
+                graph.add_wire(from_gate, to_gate, wire_entity);
+            }
+        }
+
+        graph.compile();
+    }
+}
+
+/// A command that deep-copies a logic gate and its fans into the `target` entity, then
+/// adds the clone to the [`LogicGraph`] resource.
+///
+/// Reflectable components (`#[reflect(Component)]`) are copied from the gate and each fan.
+/// The clone gets fresh fan children and its own [`LogicGateFans`], so none of its entity
+/// references point back to the source gate. Outgoing wires are never copied, since they
+/// would drive the same inputs twice.
+///
+/// `target` should be an empty entity, such as one returned by `commands.spawn_empty().id()`.
+///
+/// [`LogicGraph`]: crate::resources::LogicGraph
+pub struct CloneGateCommand {
+    /// The gate to copy.
+    pub source: Entity,
+    /// The entity that becomes the clone.
+    pub target: Entity,
+    /// If true, wires that drive the source gate's inputs are duplicated so they
+    /// drive the clone's matching inputs as well.
+    pub with_incoming_wires: bool,
+}
+
+impl Command for CloneGateCommand {
+    fn apply(self, world: &mut World) {
+        let registry = world.resource::<AppTypeRegistry>().clone();
+        let registry = registry.read();
+        let excluded = excluded_components();
+
+        let source_fans = world
+            .get::<LogicGateFans>(self.source)
+            .expect("Cannot clone an entity without `LogicGateFans`.")
+            .clone();
+
+        let components = reflect_components(world, &registry, self.source, &excluded);
+        insert_reflected_components(world, &registry, self.target, &components);
+
+        let clone_fan = |world: &mut World, fan: Entity, is_input: bool| {
+            let components = reflect_components(world, &registry, fan, &excluded);
+            let clone = if is_input {
+                world.spawn(InputBundle::default()).id()
+            } else {
+                world.spawn(OutputBundle::default()).id()
+            };
+            insert_reflected_components(world, &registry, clone, &components);
+            clone
+        };
+
+        let inputs = source_fans.inputs
+            .iter()
+            .map(|fan| fan.map(|fan| clone_fan(world, fan, true)))
+            .collect::<Vec<_>>();
+        let outputs = source_fans.outputs
+            .iter()
+            .map(|fan| fan.map(|fan| clone_fan(world, fan, false)))
+            .collect::<Vec<_>>();
+
+        let fans = LogicGateFans { inputs, outputs };
+        world
+            .entity_mut(self.target)
+            .push_children(
+                &fans
+                    .some_inputs()
+                    .into_iter()
+                    .chain(fans.some_outputs())
+                    .collect::<Vec<_>>()
+            )
+            .insert(fans.clone());
+
+        world.resource_mut::<LogicGraph>().add_gate(self.target);
+
+        if self.with_incoming_wires {
+            // The graph has one edge per pair of gates, so find every wire through the
+            // input fans instead, including parallel wires from the same gate.
+            let incoming_wires = source_fans.inputs
+                .iter()
+                .enumerate()
+                .filter_map(|(index, fan)| Some((index, world.get::<ConnectedWires>((*fan)?)?)))
+                .flat_map(|(index, connected)| {
+                    connected.wires.iter().map(move |&wire_entity| (index, wire_entity))
+                })
+                .collect::<Vec<_>>();
+
+            for (index, wire_entity) in incoming_wires {
+                let Some(&wire) = world.get::<Wire>(wire_entity) else {
+                    continue;
+                };
+                let Some(to) = fans.inputs[index] else {
+                    continue;
+                };
+                let from_gate = world
+                    .get::<Parent>(wire.from)
+                    .expect("GateOutput does not have a parent gate")
+                    .get();
+
+                let new_wire = world
+                    .spawn(WireBundle {
+                        wire: Wire::new(wire.from, to),
+                        signal: Signal::Undefined,
+                    })
+                    .id();
+                world
+                    .get_mut::<GateOutput>(wire.from)
+                    .expect("Wire::from Entity does not have GateOutput component")
+                    .wires.insert(new_wire);
+                world
+                    .get_mut::<ConnectedWires>(to)
+                    .expect("Cloned GateInput does not have ConnectedWires component")
+                    .wires.insert(new_wire);
+                world.resource_mut::<LogicGraph>().add_wire(from_gate, self.target, new_wire);
+            }
+        }
+
+        world.resource_mut::<LogicGraph>().compile();
+    }
+}
+
+/// A command that despawns every entity with a [`CircuitMember`] of a circuit, and every wire
+/// connected to its gates, then removes them from the [`LogicGraph`] resource.
+///
+/// Wires that connect the circuit to other gates are despawned too, and removed from the
+/// [`GateOutput::wires`] and [`ConnectedWires`] sets of those gates' fans.
+///
+/// [`GateOutput::wires`]: crate::components::GateOutput::wires
+/// [`LogicGraph`]: crate::resources::LogicGraph
+pub struct DespawnCircuit(pub CircuitId);
+
+impl Command for DespawnCircuit {
+    fn apply(self, world: &mut World) {
+        let members = world
+            .query::<(Entity, &CircuitMember)>()
+            .iter(world)
+            .filter(|(_, member)| member.0 == self.0)
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+
+        let mut gates = EntityHashSet::default();
+        let mut wires = EntityHashSet::default();
+        for &entity in members.iter() {
+            if world.get::<LogicGateFans>(entity).is_some() {
+                gates.insert(entity);
+            } else if world.get::<Wire>(entity).is_some() {
+                wires.insert(entity);
+            }
+        }
+
+        despawn_gates(world, &gates, wires);
+        for entity in members {
+            if let Some(entity) = world.get_entity_mut(entity) {
+                entity.despawn_recursive();
+            }
+        }
+    }
+}
+
+/// A command that despawns a gate, its fans, and every wire connected to it, then removes
+/// them from the [`LogicGraph`] resource.
+///
+/// Wires are found through the fans' [`GateOutput::wires`] and [`ConnectedWires`] sets and
+/// the gate's edges in the graph, and removed from the wire sets of the fans on their other
+/// end. Unlike despawning the gate directly, this never leaves the graph or another fan
+/// pointing at a despawned entity.
+///
+/// [`GateOutput::wires`]: crate::components::GateOutput::wires
+/// [`LogicGraph`]: crate::resources::LogicGraph
+pub struct DespawnGateRecursive(pub Entity);
+
+impl Command for DespawnGateRecursive {
+    fn apply(self, world: &mut World) {
+        let mut gates = EntityHashSet::default();
+        gates.insert(self.0);
+        despawn_gates(world, &gates, EntityHashSet::default());
+    }
+}
+
+/// A command that removes a wire from the [`LogicGraph`] resource and the wire sets of its
+/// fans, then despawns it.
+///
+/// If no other wire drives the wire's input fan, the input's [`Signal`] is reset to
+/// [`Signal::Undefined`]. Does nothing if the entity does not have a [`Wire`].
+///
+/// [`LogicGraph`]: crate::resources::LogicGraph
+pub struct DespawnWire(pub Entity);
+
+impl Command for DespawnWire {
+    fn apply(self, world: &mut World) {
+        let wire_entity = self.0;
+        let Some(&wire) = world.get::<Wire>(wire_entity) else {
+            return;
+        };
+
+        if let Some(mut output) = world.get_mut::<GateOutput>(wire.from) {
+            output.wires.remove(&wire_entity);
+        }
+        let input_driven = match world.get_mut::<ConnectedWires>(wire.to) {
+            Some(mut connected) => {
+                connected.wires.remove(&wire_entity);
+                !connected.wires.is_empty()
+            }
+            None => false,
+        };
+        if !input_driven {
+            if let Some(mut signal) = world.get_mut::<Signal>(wire.to) {
+                signal.set_if_neq(Signal::Undefined);
+            }
+        }
+
+        let from_gate = world.get::<Parent>(wire.from).map(Parent::get);
+        let to_gate = world.get::<Parent>(wire.to).map(Parent::get);
+        if let (Some(from_gate), Some(to_gate)) = (from_gate, to_gate) {
+            remove_wire_edge(world, wire_entity, from_gate, to_gate);
+        }
+
+        world.entity_mut(wire_entity).despawn_recursive();
+    }
+}
+
+/// Remove the [`LogicGraph`] edge between two gates if it belongs to `wire_entity`, then
+/// recompile. The wire must already be removed from the [`GateOutput::wires`] set of its fan.
+///
+/// The graph has one edge per pair of gates, so the edge is handed to another wire between
+/// them if there is one.
+///
+/// [`GateOutput::wires`]: crate::components::GateOutput::wires
+/// [`LogicGraph`]: crate::resources::LogicGraph
+fn remove_wire_edge(world: &mut World, wire_entity: Entity, from_gate: Entity, to_gate: Entity) {
+    let graph = world.resource::<LogicGraph>();
+    if graph.graph.edge_weight(from_gate, to_gate) != Some(&wire_entity) {
+        return;
+    }
+
+    let other_wire = world
+        .get::<LogicGateFans>(from_gate)
+        .into_iter()
+        .flat_map(|fans| fans.some_outputs())
+        .filter_map(|output| world.get::<GateOutput>(output))
+        .flat_map(|output| output.wires.iter().copied())
+        .find(|&other| {
+            world
+                .get::<Wire>(other)
+                .and_then(|other| world.get::<Parent>(other.to))
+                .is_some_and(|parent| parent.get() == to_gate)
+        });
+
+    let mut graph = world.resource_mut::<LogicGraph>();
+    graph.remove_wire(from_gate, to_gate);
+    if let Some(other_wire) = other_wire {
+        graph.add_wire(from_gate, to_gate, other_wire);
+    }
+    graph.compile();
+}
+
+/// A command that disables a gate by inserting a [`GateDisabled`], replacing any it already
+/// has. The gate stays in the [`LogicGraph`] with all of its wires.
+///
+/// [`LogicGraph`]: crate::resources::LogicGraph
+pub struct DisableGate {
+    pub entity: Entity,
+    pub mode: GateDisabled,
+}
+
+impl Command for DisableGate {
+    fn apply(self, world: &mut World) {
+        if let Some(mut entity) = world.get_entity_mut(self.entity) {
+            entity.insert(self.mode);
+        }
+    }
+}
+
+/// A command that enables a gate disabled with [`DisableGate`], by removing its
+/// [`GateDisabled`].
+pub struct EnableGate(pub Entity);
+
+impl Command for EnableGate {
+    fn apply(self, world: &mut World) {
+        if let Some(mut entity) = world.get_entity_mut(self.0) {
+            entity.remove::<GateDisabled>();
+        }
+    }
+}
+
+/// A command that enables a disabled gate, or disables an enabled one with
+/// [`GateDisabled::Hold`].
+pub struct ToggleGate(pub Entity);
+
+impl Command for ToggleGate {
+    fn apply(self, world: &mut World) {
+        let Some(mut entity) = world.get_entity_mut(self.0) else {
+            return;
+        };
+        if entity.contains::<GateDisabled>() {
+            entity.remove::<GateDisabled>();
+        } else {
+            entity.insert(GateDisabled::Hold);
+        }
+    }
+}
+
+/// A command that returns a circuit to the state it was spawned in, or every circuit if the
+/// [`CircuitId`] is `None`.
+///
+/// The [`Signal`] of every fan and wire is set to [`Signal::Undefined`], and
+/// [`LogicGate::reset`] is called on every gate so stateful gates can clear their state.
+/// Outputs with a [`NoEvalOutput`], such as buttons and sensors, keep their signal.
+///
+/// Gates are included by their [`CircuitMember`]. Fans and wires are included through their
+/// gates, and wires also by their own [`CircuitMember`].
+pub struct ResetCircuit(pub Option<CircuitId>);
+
+impl Command for ResetCircuit {
+    fn apply(self, world: &mut World) {
+        let in_circuit = |member: Option<&CircuitMember>| {
+            self.0.is_none() || member.is_some_and(|member| Some(member.0) == self.0)
+        };
+
+        let mut fans = EntityHashSet::default();
+        let mut query_gates = world.query::<
+            (One<&mut dyn LogicGate>, &LogicGateFans, Option<&CircuitMember>)
+        >();
+        for (mut gate, gate_fans, member) in query_gates.iter_mut(world) {
+            if in_circuit(member) {
+                gate.reset();
+                fans.extend(gate_fans.some_inputs());
+                fans.extend(gate_fans.some_outputs());
+            }
+        }
+
+        let mut signals = fans.iter().copied().collect::<Vec<_>>();
+        let mut sources = Vec::new();
+        let mut query_wires = world.query::<(Entity, &Wire, Option<&CircuitMember>)>();
+        for (entity, wire, member) in query_wires.iter(world) {
+            let is_member = self.0.is_some() && in_circuit(member);
+            if is_member || fans.contains(&wire.from) || fans.contains(&wire.to) {
+                signals.push(entity);
+                sources.push(wire.from);
+            }
+        }
+
+        for entity in signals {
+            if world.get::<NoEvalOutput>(entity).is_some() {
+                continue;
+            }
+            if let Some(mut signal) = world.get_mut::<Signal>(entity) {
+                signal.set_if_neq(Signal::Undefined);
+            }
+        }
+
+        // Send the signals of buttons and sensors through their wires again.
+        for source in sources {
+            if world.get::<NoEvalOutput>(source).is_some() {
+                if let Some(mut signal) = world.get_mut::<Signal>(source) {
+                    signal.set_changed();
+                }
+            }
+        }
+    }
+}
+
+/// Despawn `gates` with their fans, every wire connected to them, and `wires`, keeping the
+/// [`LogicGraph`] and the wire sets of the remaining fans up to date.
+fn despawn_gates(world: &mut World, gates: &EntityHashSet, mut wires: EntityHashSet) {
+    for &gate in gates.iter() {
+        let Some(fans) = world.get::<LogicGateFans>(gate) else {
+            continue;
+        };
+        for fan in fans.some_inputs().into_iter().chain(fans.some_outputs()) {
+            if let Some(output) = world.get::<GateOutput>(fan) {
+                wires.extend(output.wires.iter().copied());
+            }
+            if let Some(connected) = world.get::<ConnectedWires>(fan) {
+                wires.extend(connected.wires.iter().copied());
+            }
+        }
+        let graph = world.resource::<LogicGraph>();
+        wires.extend(graph.iter_all_wires(gate).map(|(wire, _)| wire));
+    }
+
+    let mut graph = world.resource_mut::<LogicGraph>();
+    for &gate in gates.iter() {
+        graph.remove_gate(gate);
+    }
+    graph.compile();
+
+    for wire_entity in wires {
+        let Some(&wire) = world.get::<Wire>(wire_entity) else {
+            continue;
+        };
+        if let Some(mut output) = world.get_mut::<GateOutput>(wire.from) {
+            output.wires.remove(&wire_entity);
+        }
+        if let Some(mut connected) = world.get_mut::<ConnectedWires>(wire.to) {
+            connected.wires.remove(&wire_entity);
+        }
+
+        // Wires between two remaining gates are still in the graph.
+        let from_gate = world.get::<Parent>(wire.from).map(Parent::get);
+        let to_gate = world.get::<Parent>(wire.to).map(Parent::get);
+        if let (Some(from_gate), Some(to_gate)) = (from_gate, to_gate) {
+            let mut graph = world.resource_mut::<LogicGraph>();
+            if graph.graph.edge_weight(from_gate, to_gate) == Some(&wire_entity) {
+                graph.remove_wire(from_gate, to_gate).compile();
+            }
+        }
+
+        world.entity_mut(wire_entity).despawn_recursive();
+    }
+
+    for &gate in gates.iter() {
+        if let Some(entity) = world.get_entity_mut(gate) {
+            entity.despawn_recursive();
+        }
     }
 }
 
 /// A [`Command`] that adds or removes a wire entity from a [`GateOutput`] component's `wires` set.
 ///
 /// The set may be used to lookup out-going wires from a gate output entity, so it's important to
-/// keep it up-to-date when adding or removing wires.
+/// keep it up-to-date when adding or removing wires. If the wire entity still has a [`Wire`]
+/// component, the [`ConnectedWires`] set of its input fan is updated as well.
 ///
 /// This does not change the [`LogicGraph`] resource.
 ///
@@ -154,12 +703,24 @@ impl Command for UpdateOutputWireSet {
                     .get_mut::<GateOutput>(output_entity)
                     .expect("output entity does not have GateOutput component")
                     .wires.insert(wire_entity);
+
+                if let Some(&wire) = world.get::<Wire>(wire_entity) {
+                    if let Some(mut connected) = world.get_mut::<ConnectedWires>(wire.to) {
+                        connected.wires.insert(wire_entity);
+                    }
+                }
             }
             UpdateOutputWireSet::Remove { output_entity, wire_entity } => {
                 world
                     .get_mut::<GateOutput>(output_entity)
                     .expect("output entity does not have GateOutput component")
                     .wires.remove(&wire_entity);
+
+                if let Some(&wire) = world.get::<Wire>(wire_entity) {
+                    if let Some(mut connected) = world.get_mut::<ConnectedWires>(wire.to) {
+                        connected.wires.remove(&wire_entity);
+                    }
+                }
             }
         }
     }
@@ -202,3 +763,162 @@ pub fn extract_outgoing_wires(entity: Entity, world: &mut World) -> Vec<WireData
         .flatten()
         .collect::<Vec<_>>()
 }
+
+/// A command that rolls the simulation back to the [`LogicSnapshot`] recorded the given
+/// number of steps ago, where `0` is the latest step. Newer snapshots are dropped, so the
+/// next recorded step follows the restored one.
+///
+/// Does nothing if the [`LogicRollback`] resource does not exist or does not go back that far.
+///
+/// [`LogicSnapshot`]: crate::snapshot::LogicSnapshot
+pub struct RewindLogic(pub usize);
+
+impl Command for RewindLogic {
+    fn apply(self, world: &mut World) {
+        let Some(mut rollback) = world.get_resource_mut::<LogicRollback>() else {
+            return;
+        };
+        let Some(snapshot) = rollback.get(self.0).cloned() else {
+            return;
+        };
+        rollback.truncate_latest(self.0);
+
+        snapshot.apply(world);
+    }
+}
+
+/// A command that places a gate on a cell of the [`LogicBoard`] by inserting a [`BoardCell`],
+/// if the cell is empty. A gate that is already on the board is moved.
+///
+/// Logs a warning and does nothing if the cell is occupied by another entity, or if the
+/// [`LogicBoard`] resource does not exist.
+pub struct PlaceOnBoard {
+    pub entity: Entity,
+    pub cell: IVec2,
+}
+
+impl Command for PlaceOnBoard {
+    fn apply(self, world: &mut World) {
+        let Some(board) = world.get_resource::<LogicBoard>() else {
+            warn!("Cannot place {:?} without a LogicBoard resource", self.entity);
+            return;
+        };
+        if !board.can_place(self.cell, self.entity) {
+            warn!(
+                "Cannot place {:?} on board cell {}, which is occupied by {:?}",
+                self.entity,
+                self.cell,
+                board.get(self.cell)
+            );
+            return;
+        }
+        if let Some(mut entity) = world.get_entity_mut(self.entity) {
+            entity.insert(BoardCell(self.cell));
+        }
+    }
+}
+
+/// A command that removes a gate from the [`LogicBoard`], emptying its cell. The gate is not
+/// despawned.
+pub struct RemoveFromBoard(pub Entity);
+
+impl Command for RemoveFromBoard {
+    fn apply(self, world: &mut World) {
+        if let Some(mut entity) = world.get_entity_mut(self.0) {
+            entity.remove::<BoardCell>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        analysis::check_invariants,
+        logic::{ builder::LogicExt, gates::{ AndGate, Battery } },
+        LogicSimulationPlugin,
+    };
+
+    #[test]
+    fn clone_keeps_parallel_incoming_wires() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_plugins(LogicSimulationPlugin::default());
+        let world = app.world_mut();
+
+        let source = world.spawn_gate(Battery::ON).with_inputs(0).with_outputs(1).build();
+        let gate = world.spawn_gate(AndGate::default()).with_inputs(2).with_outputs(1).build();
+        world.resource_mut::<LogicGraph>().add_data(vec![source.clone(), gate.clone()]);
+        world.spawn_wire_between(source.output(0), gate.input(0));
+        world.spawn_wire_between(source.output(0), gate.input(1));
+
+        let target = world.spawn_empty().id();
+        CloneGateCommand { source: gate.id(), target, with_incoming_wires: true }.apply(world);
+
+        let fans = world.get::<LogicGateFans>(target).unwrap().clone();
+        for input in fans.some_inputs() {
+            let connected = world.get::<ConnectedWires>(input).unwrap();
+            assert_eq!(connected.wires.len(), 1);
+            let wire = world.get::<Wire>(connected.wires.as_slice()[0]).unwrap();
+            assert_eq!(wire.from, source.output(0));
+            assert_eq!(wire.to, input);
+        }
+        assert_eq!(world.get::<GateOutput>(source.output(0)).unwrap().wires.len(), 4);
+        assert_eq!(check_invariants(world, world.resource::<LogicGraph>()), Ok(()));
+    }
+
+    #[test]
+    fn removing_a_gate_drops_parallel_wires() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_plugins(LogicSimulationPlugin::default());
+        let world = app.world_mut();
+
+        let source = world.spawn_gate(Battery::ON).with_inputs(0).with_outputs(1).build();
+        let gate = world.spawn_gate(AndGate::default()).with_inputs(2).with_outputs(1).build();
+        world.resource_mut::<LogicGraph>().add_data(vec![source.clone(), gate.clone()]);
+        world.spawn_wire_between(source.output(0), gate.input(0));
+        world.spawn_wire_between(source.output(0), gate.input(1));
+        let sink = world.spawn_gate(AndGate::default()).with_inputs(1).with_outputs(1).build();
+        world.resource_mut::<LogicGraph>().add_data(sink.clone());
+        world.spawn_wire_between(gate.output(0), sink.input(0));
+
+        RemoveGateFromLogicGraph(gate.id()).apply(world);
+        assert!(world.get::<GateOutput>(source.output(0)).unwrap().wires.is_empty());
+        assert!(world.get::<GateOutput>(gate.output(0)).unwrap().wires.is_empty());
+        for input in [gate.input(0), gate.input(1), sink.input(0)] {
+            assert!(world.get::<ConnectedWires>(input).unwrap().wires.is_empty());
+        }
+        assert_eq!(check_invariants(world, world.resource::<LogicGraph>()), Ok(()));
+    }
+
+    #[test]
+    fn removing_a_stray_wire_does_not_panic() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_plugins(LogicSimulationPlugin::default());
+        let world = app.world_mut();
+
+        let source = world.spawn_gate(Battery::ON).with_inputs(0).with_outputs(1).build();
+        world.resource_mut::<LogicGraph>().add_data(vec![source.clone()]);
+        let stray = world.spawn_empty().id();
+        let wire = world.spawn(Wire::new(source.output(0), stray)).id();
+
+        RemoveWireFromLogicGraph(wire).apply(world);
+        RemoveWireFromLogicGraph(stray).apply(world);
+        assert_eq!(world.resource::<LogicGraph>().graph.node_count(), 1);
+    }
+
+    #[test]
+    fn adding_a_wire_to_a_stray_entity_does_not_panic() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_plugins(LogicSimulationPlugin::default());
+        let world = app.world_mut();
+
+        let source = world.spawn_gate(Battery::ON).with_inputs(0).with_outputs(1).build();
+        world.resource_mut::<LogicGraph>().add_data(vec![source.clone()]);
+        let stray = world.spawn_empty().id();
+        let wire = world.spawn(Wire::new(source.output(0), stray)).id();
+
+        AddWireToLogicGraph(wire).apply(world);
+        assert!(world.get::<GateOutput>(source.output(0)).unwrap().wires.is_empty());
+        assert_eq!(world.resource::<LogicGraph>().graph.edge_count(), 0);
+    }
+}
@@ -0,0 +1,723 @@
+//! A flat evaluation plan for large circuits of basic gates.
+//!
+//! [`step_logic`] queries the ECS for every gate, fan, and wire each step. For worlds with
+//! hundreds of thousands of gates, a [`CompiledCircuit`] lowers the [`LogicGraph`] into flat
+//! arrays instead: one buffer holds every fan signal, and each gate refers to a range of it.
+//! Steps are evaluated without any queries, and only the signals that changed are written
+//! back to their components.
+//!
+//! Insert the [`CompiledCircuit`] resource to switch the simulation over, and remove it to
+//! switch back:
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_logic::prelude::*;
+//! fn compile(world: &mut World) {
+//!     match CompiledCircuit::compile(world) {
+//!         Ok(circuit) => world.insert_resource(circuit),
+//!         Err(error) => warn!("the circuit can't be compiled: {error}"),
+//!     }
+//! }
+//! ```
+//!
+//! [`step_logic`]: crate::systems::step_logic
+
+use std::ops::Range;
+
+use bevy::{ ecs::{ entity::EntityHashMap, system::SystemParam }, prelude::*, utils::Instant };
+
+use crate::{
+    components::{
+        BidirectionalWire,
+        GateDisabled,
+        GateOutput,
+        LogicGateFans,
+        NoEvalOutput,
+        SignalOverride,
+        Wire,
+        WireChannel,
+        WireDisabled,
+        WireFalloff,
+        WireResistance,
+    },
+    diagnostics::LogicStepMetrics,
+    logic::{
+        gates::{ AndGate, Battery, Junction, NotGate, OrGate, XorGate },
+        schedule::ClockDomain,
+        signal::Signal,
+        LogicGate,
+    },
+    resources::{ LogicGraph, LogicSettings, PropagationMode },
+};
+
+pub mod prelude {
+    pub use super::{ CompileError, CompiledCircuit };
+}
+
+/// An error produced while compiling a [`CompiledCircuit`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CompileError {
+    /// The gate does not exist, or does not have [`LogicGateFans`].
+    MissingGate(Entity),
+    /// The gate is not one of the basic gates, so it can't be evaluated outside the ECS.
+    UnsupportedGate(Entity),
+    /// The gate was given as an input, but it is not a [`Battery`].
+    NotAnInput(Entity),
+    /// The fan has a [`SignalOverride`], which only [`step_logic`] applies.
+    ///
+    /// [`step_logic`]: crate::systems::step_logic
+    SignalOverride(Entity),
+    /// The gate has a [`ClockDomain`], which only [`step_logic`] follows.
+    ///
+    /// [`step_logic`]: crate::systems::step_logic
+    ClockDomain(Entity),
+    /// The [`LogicSettings`] use [`PropagationMode::Synchronous`], which only [`step_logic`]
+    /// follows.
+    ///
+    /// [`step_logic`]: crate::systems::step_logic
+    SynchronousPropagation,
+    /// [`LogicSettings::settle_limit`] is not zero, and only [`step_logic`] evaluates feedback
+    /// loops more than once per step.
+    ///
+    /// [`step_logic`]: crate::systems::step_logic
+    SettleLimit(u32),
+    /// The wire is a [`BidirectionalWire`] on a fan of the circuit, and only [`resolve_buses`]
+    /// resolves buses.
+    ///
+    /// [`resolve_buses`]: crate::systems::resolve_buses
+    BidirectionalWire(Entity),
+    /// The output has a [`NoEvalOutput`] and drives a fan of the circuit, and only
+    /// [`no_eval_output`] propagates its signal.
+    ///
+    /// [`no_eval_output`]: crate::systems::no_eval_output
+    NoEvalOutput(Entity),
+}
+
+impl std::fmt::Display for CompileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompileError::MissingGate(entity) => write!(f, "gate {entity} does not exist"),
+            CompileError::UnsupportedGate(entity) => {
+                write!(f, "gate {entity} is not a basic gate")
+            }
+            CompileError::NotAnInput(entity) => write!(f, "gate {entity} is not a battery"),
+            CompileError::SignalOverride(entity) => {
+                write!(f, "fan {entity} has a signal override")
+            }
+            CompileError::ClockDomain(entity) => write!(f, "gate {entity} is in a clock domain"),
+            CompileError::SynchronousPropagation => write!(f, "propagation is synchronous"),
+            CompileError::SettleLimit(limit) => write!(f, "the settle limit is {limit}"),
+            CompileError::BidirectionalWire(entity) => {
+                write!(f, "wire {entity} is part of a bus")
+            }
+            CompileError::NoEvalOutput(entity) => {
+                write!(f, "output {entity} is written outside the logic step")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+impl CompileError {
+    /// Returns an error if the gate, or one of its fans, has a component that can only be
+    /// simulated by [`step_logic`].
+    ///
+    /// [`step_logic`]: crate::systems::step_logic
+    pub(crate) fn check_ecs_only(world: &World, gate: Entity) -> Result<(), Self> {
+        if world.get::<ClockDomain>(gate).is_some() {
+            return Err(CompileError::ClockDomain(gate));
+        }
+        let Some(fans) = world.get::<LogicGateFans>(gate) else {
+            return Ok(());
+        };
+        let overridden = fans.inputs
+            .iter()
+            .chain(fans.outputs.iter())
+            .flatten()
+            .find(|&&fan| world.get::<SignalOverride>(fan).is_some());
+        match overridden {
+            Some(&fan) => Err(CompileError::SignalOverride(fan)),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns an error if the [`LogicSettings`] resource configures the step in a way that
+    /// only [`step_logic`] follows.
+    ///
+    /// [`step_logic`]: crate::systems::step_logic
+    fn check_settings(world: &World) -> Result<(), Self> {
+        let Some(settings) = world.get_resource::<LogicSettings>() else {
+            return Ok(());
+        };
+        if settings.propagation == PropagationMode::Synchronous {
+            return Err(CompileError::SynchronousPropagation);
+        }
+        if settings.settle_limit > 0 {
+            return Err(CompileError::SettleLimit(settings.settle_limit));
+        }
+        Ok(())
+    }
+
+    /// Returns an error if a bus or a [`NoEvalOutput`] writes to a fan of one of `gates`,
+    /// since only their own systems write those signals.
+    fn check_ecs_wires(world: &World, gates: &EntityHashMap<usize>) -> Result<(), Self> {
+        let in_circuit = |fan: Entity| {
+            world.get::<Parent>(fan).is_some_and(|parent| gates.contains_key(&parent.get()))
+        };
+
+        for entity in world.iter_entities() {
+            let bus = entity.get::<Wire>().filter(|_| entity.contains::<BidirectionalWire>());
+            if let Some(wire) = bus {
+                if in_circuit(wire.from) || in_circuit(wire.to) {
+                    return Err(CompileError::BidirectionalWire(entity.id()));
+                }
+            }
+            let no_eval = entity.get::<GateOutput>().filter(|_| entity.contains::<NoEvalOutput>());
+            if let Some(output) = no_eval {
+                let drives_circuit = output.wires
+                    .iter()
+                    .filter_map(|&wire| world.get::<Wire>(wire))
+                    .any(|wire| in_circuit(wire.to));
+                if drives_circuit {
+                    return Err(CompileError::NoEvalOutput(entity.id()));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The basic gates a [`CompiledCircuit`] can evaluate. Each one is evaluated with its
+/// own [`LogicGate`] implementation, so results match [`step_logic`] exactly.
+///
+/// [`step_logic`]: crate::systems::step_logic
+#[derive(Clone, Copy, Debug)]
+enum CompiledGateKind {
+    Battery(Battery),
+    And(AndGate),
+    Or(OrGate),
+    Not(NotGate),
+    Xor(XorGate),
+    Junction(Junction),
+}
+
+impl CompiledGateKind {
+    fn of(entity: EntityRef) -> Option<Self> {
+        if let Some(gate) = entity.get::<Battery>() {
+            Some(CompiledGateKind::Battery(*gate))
+        } else if let Some(gate) = entity.get::<AndGate>() {
+            Some(CompiledGateKind::And(*gate))
+        } else if let Some(gate) = entity.get::<OrGate>() {
+            Some(CompiledGateKind::Or(*gate))
+        } else if let Some(gate) = entity.get::<NotGate>() {
+            Some(CompiledGateKind::Not(*gate))
+        } else if let Some(gate) = entity.get::<XorGate>() {
+            Some(CompiledGateKind::Xor(*gate))
+        } else {
+            entity.get::<Junction>().map(|gate| CompiledGateKind::Junction(*gate))
+        }
+    }
+
+    fn evaluate(&mut self, inputs: &[Signal], outputs: &mut [Signal]) {
+        match self {
+            CompiledGateKind::Battery(gate) => gate.evaluate(inputs, outputs),
+            CompiledGateKind::And(gate) => gate.evaluate(inputs, outputs),
+            CompiledGateKind::Or(gate) => gate.evaluate(inputs, outputs),
+            CompiledGateKind::Not(gate) => gate.evaluate(inputs, outputs),
+            CompiledGateKind::Xor(gate) => gate.evaluate(inputs, outputs),
+            CompiledGateKind::Junction(gate) => gate.evaluate(inputs, outputs),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct CompiledGate {
+    entity: Entity,
+    kind: CompiledGateKind,
+    disabled: Option<GateDisabled>,
+    /// The gate's input fan signals, immediately followed by its output fan signals.
+    signals: Range<usize>,
+    /// The number of input fan signals at the start of `signals`.
+    inputs: usize,
+    /// The wires driven by the gate's outputs.
+    wires: Range<usize>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct CompiledWire {
+    entity: Entity,
+    /// The output fan signal the wire reads.
+    from: usize,
+    /// The input fan signal the wire writes, if the fan belongs to a compiled gate.
+    to: Option<usize>,
+    resistance: Option<WireResistance>,
+    falloff: Option<WireFalloff>,
+    /// The wire has a [`WireDisabled`], so it carries [`Signal::Undefined`].
+    disabled: bool,
+}
+
+/// Signals that changed since they were last written back to the world.
+#[derive(Clone, Debug, Default)]
+struct ChangedSignals {
+    flags: Vec<bool>,
+    indices: Vec<usize>,
+}
+
+impl ChangedSignals {
+    fn with_len(len: usize) -> Self {
+        Self {
+            flags: vec![false; len],
+            indices: Vec::new(),
+        }
+    }
+
+    fn insert(&mut self, index: usize) {
+        if !self.flags[index] {
+            self.flags[index] = true;
+            self.indices.push(index);
+        }
+    }
+
+    fn drain(&mut self) -> impl Iterator<Item = usize> + '_ {
+        self.indices.drain(..).inspect(|&index| {
+            self.flags[index] = false;
+        })
+    }
+}
+
+/// A [`LogicGraph`] lowered into flat arrays, so it can be stepped without ECS queries.
+///
+/// Only circuits made of [`Battery`], [`AndGate`], [`OrGate`], [`NotGate`], [`XorGate`], and
+/// [`Junction`] gates can be compiled. Gates are evaluated in [`LogicGraph::sorted`] order,
+/// and every gate is evaluated each step, as in [`EvalMode::Full`]. A [`GateDisabled`] gate
+/// holds or turns off its outputs, and a [`WireDisabled`] wire carries
+/// [`Signal::Undefined`], like in [`step_logic`].
+///
+/// Anything only [`step_logic`] and its neighbouring systems follow is a [`CompileError`]
+/// instead of being ignored, so a circuit never behaves differently once it is compiled:
+///
+/// - a [`SignalOverride`] on a fan, or a [`ClockDomain`] on a gate,
+/// - [`PropagationMode::Synchronous`], or a [`LogicSettings::settle_limit`] above zero,
+/// - a [`BidirectionalWire`] on a fan, or a [`NoEvalOutput`] that drives one.
+///
+/// While this resource exists, it replaces [`step_logic`]: [`step_compiled_circuit`] steps
+/// it and writes the signals that changed back to their components. The circuit owns its
+/// signals, so writes to fan and wire [`Signal`]s from other systems are not seen, but
+/// changes to a [`Battery`] are. Other changes to gate and wire components are only seen after
+/// the circuit is recompiled, which happens automatically when the [`LogicGraph`] changes, or
+/// when one of the components above is inserted, changed, or removed.
+///
+/// [`EvalMode::Full`]: crate::resources::EvalMode::Full
+/// [`step_logic`]: crate::systems::step_logic
+#[derive(Resource, Clone, Debug, Default)]
+pub struct CompiledCircuit {
+    gates: Vec<CompiledGate>,
+    wires: Vec<CompiledWire>,
+    /// The fan entity of each signal in `signals`.
+    fans: Vec<Entity>,
+    signals: Vec<Signal>,
+    wire_signals: Vec<Signal>,
+    changed_signals: ChangedSignals,
+    changed_wires: ChangedSignals,
+    fan_indices: EntityHashMap<usize>,
+    gate_indices: EntityHashMap<usize>,
+    /// The previous output signals of the gate being evaluated.
+    previous: Vec<Signal>,
+    /// Set when a component the circuit was compiled with changed.
+    stale: bool,
+}
+
+impl CompiledCircuit {
+    /// Compile the [`LogicGraph`] of `world`, starting from the current signals.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `world` does not have a [`LogicGraph`].
+    pub fn compile(world: &World) -> Result<Self, CompileError> {
+        CompileError::check_settings(world)?;
+        let graph = world.resource::<LogicGraph>();
+        let mut circuit = Self::default();
+
+        for &gate in graph.sorted() {
+            let entity = world.get_entity(gate).ok_or(CompileError::MissingGate(gate))?;
+            let fans = entity.get::<LogicGateFans>().ok_or(CompileError::MissingGate(gate))?;
+            let kind = CompiledGateKind::of(entity).ok_or(CompileError::UnsupportedGate(gate))?;
+            CompileError::check_ecs_only(world, gate)?;
+
+            let start = circuit.signals.len();
+            for &input in fans.inputs.iter().flatten() {
+                circuit.push_fan(world, input);
+            }
+            let inputs = circuit.signals.len() - start;
+            for &output in fans.outputs.iter().flatten() {
+                circuit.push_fan(world, output);
+            }
+
+            circuit.gate_indices.insert(gate, circuit.gates.len());
+            circuit.gates.push(CompiledGate {
+                entity: gate,
+                kind,
+                disabled: entity.get::<GateDisabled>().copied(),
+                signals: start..circuit.signals.len(),
+                inputs,
+                wires: 0..0,
+            });
+        }
+        CompileError::check_ecs_wires(world, &circuit.gate_indices)?;
+
+        // Every fan has an index now, so wires can be resolved.
+        for index in 0..circuit.gates.len() {
+            let start = circuit.wires.len();
+            let gate = &circuit.gates[index];
+
+            for from in gate.signals.start + gate.inputs..gate.signals.end {
+                // Wires are written in entity order, like `step_logic`.
+                let wires = world
+                    .get::<GateOutput>(circuit.fans[from])
+                    .map(|output| output.wires.as_slice())
+                    .unwrap_or_default();
+
+                for &entity in wires.iter() {
+                    let Some(wire) = world.get::<Wire>(entity) else {
+                        continue;
+                    };
+                    let connects = WireChannel::connects(
+                        world.get::<WireChannel>(entity),
+                        world.get::<WireChannel>(wire.to)
+                    );
+                    circuit.wires.push(CompiledWire {
+                        entity,
+                        from,
+                        to: circuit.fan_indices.get(&wire.to).copied().filter(|_| connects),
+                        resistance: world.get::<WireResistance>(entity).copied(),
+                        falloff: world.get::<WireFalloff>(entity).copied(),
+                        disabled: world.get::<WireDisabled>(entity).is_some(),
+                    });
+                    let signal = world.get::<Signal>(entity).copied().unwrap_or_default();
+                    circuit.wire_signals.push(signal);
+                }
+            }
+
+            circuit.gates[index].wires = start..circuit.wires.len();
+        }
+
+        circuit.changed_signals = ChangedSignals::with_len(circuit.signals.len());
+        circuit.changed_wires = ChangedSignals::with_len(circuit.wires.len());
+        Ok(circuit)
+    }
+
+    /// Add the signal of a fan, if it has one. Fans without a [`Signal`] are skipped,
+    /// like in `step_logic`.
+    fn push_fan(&mut self, world: &World, fan: Entity) {
+        if let Some(&signal) = world.get::<Signal>(fan) {
+            self.fan_indices.insert(fan, self.signals.len());
+            self.fans.push(fan);
+            self.signals.push(signal);
+        }
+    }
+
+    /// Returns the number of compiled gates.
+    pub fn gate_count(&self) -> usize {
+        self.gates.len()
+    }
+
+    /// Returns the number of compiled wires.
+    pub fn wire_count(&self) -> usize {
+        self.wires.len()
+    }
+
+    /// Returns the current signal of a fan, or `None` if it is not part of the circuit.
+    pub fn signal(&self, fan: Entity) -> Option<Signal> {
+        self.fan_indices.get(&fan).map(|&index| self.signals[index])
+    }
+
+    /// Set the signal emitted by a [`Battery`] gate. Returns `false` if `gate` is not a
+    /// compiled battery.
+    pub fn set_battery(&mut self, gate: Entity, signal: Signal) -> bool {
+        let Some(&index) = self.gate_indices.get(&gate) else {
+            return false;
+        };
+        match &mut self.gates[index].kind {
+            CompiledGateKind::Battery(battery) => {
+                battery.signal = signal;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Evaluate every gate once, and propagate its outputs through its wires.
+    ///
+    /// Returns the number of output signals that changed value.
+    pub fn step(&mut self) -> usize {
+        let Self {
+            gates,
+            wires,
+            signals,
+            wire_signals,
+            changed_signals,
+            changed_wires,
+            previous,
+            ..
+        } = self;
+        let mut signal_changes = 0;
+
+        for gate in gates.iter_mut() {
+            // Disabled gates that hold their outputs are not evaluated at all.
+            if gate.disabled == Some(GateDisabled::Hold) {
+                continue;
+            }
+
+            let (inputs, outputs) = signals[gate.signals.clone()].split_at_mut(gate.inputs);
+            previous.clear();
+            previous.extend_from_slice(outputs);
+
+            if gate.disabled.is_some() {
+                outputs.fill(Signal::OFF);
+            } else {
+                gate.kind.evaluate(inputs, outputs);
+            }
+
+            let first_output = gate.signals.start + gate.inputs;
+            for (i, (output, previous)) in outputs.iter().zip(previous.iter()).enumerate() {
+                if output != previous {
+                    changed_signals.insert(first_output + i);
+                    signal_changes += 1;
+                }
+            }
+
+            for index in gate.wires.clone() {
+                let wire = &wires[index];
+                let signal = if wire.disabled { Signal::Undefined } else { signals[wire.from] };
+                let signal = wire.resistance.map_or(signal, |resistance| resistance.apply(signal));
+                let signal = wire.falloff.map_or(signal, |falloff| falloff.apply(signal));
+
+                if wire_signals[index] != signal {
+                    wire_signals[index] = signal;
+                    changed_wires.insert(index);
+                }
+                if let Some(to) = wire.to {
+                    if signals[to] != signal {
+                        signals[to] = signal;
+                        changed_signals.insert(to);
+                    }
+                }
+            }
+        }
+
+        signal_changes
+    }
+
+    /// Write every fan and wire signal that changed since the last call back to `world`.
+    pub fn write_back(&mut self, world: &mut World) {
+        for index in self.changed_signals.drain() {
+            if let Some(mut signal) = world.get_mut::<Signal>(self.fans[index]) {
+                signal.set_if_neq(self.signals[index]);
+            }
+        }
+        for index in self.changed_wires.drain() {
+            if let Some(mut signal) = world.get_mut::<Signal>(self.wires[index].entity) {
+                signal.set_if_neq(self.wire_signals[index]);
+            }
+        }
+    }
+
+    /// Iterate over the compiled gates, in evaluation order.
+    pub fn gates(&self) -> impl ExactSizeIterator<Item = Entity> + '_ {
+        self.gates.iter().map(|gate| gate.entity)
+    }
+}
+
+/// Step the [`CompiledCircuit`] resource and write the signals that changed back to the world.
+///
+/// Changed [`Battery`] components are read into the circuit first. If the [`LogicGraph`]
+/// changed, or [`invalidate_compiled_circuit`] marked the circuit, it is recompiled. If it
+/// can't be, the resource is removed with a warning, and [`step_logic`] takes over again.
+///
+/// If the [`LogicStepMetrics`] resource exists, it is updated with measurements from this step.
+///
+/// [`step_logic`]: crate::systems::step_logic
+pub fn step_compiled_circuit(
+    world: &mut World,
+    changed_batteries: &mut QueryState<(Entity, &'static Battery), Changed<Battery>>
+) {
+    let stale = world.resource::<CompiledCircuit>().stale;
+    if stale || world.resource_ref::<LogicGraph>().is_changed() {
+        match CompiledCircuit::compile(world) {
+            Ok(circuit) => world.insert_resource(circuit),
+            Err(error) => {
+                warn!("Removing the CompiledCircuit, because {error}");
+                world.remove_resource::<CompiledCircuit>();
+                return;
+            }
+        }
+    }
+
+    world.resource_scope(|world, mut circuit: Mut<CompiledCircuit>| {
+        for (gate, battery) in changed_batteries.iter(world) {
+            circuit.set_battery(gate, battery.signal);
+        }
+
+        let step_start = Instant::now();
+        let signal_changes = circuit.step();
+        let evaluation_time = step_start.elapsed();
+        circuit.write_back(world);
+
+        if let Some(mut metrics) = world.get_resource_mut::<LogicStepMetrics>() {
+            metrics.clear();
+            metrics.gates_evaluated = circuit.gate_count();
+            metrics.signal_changes = signal_changes;
+            metrics.wires_propagated = circuit.wire_count();
+            metrics.evaluation_time = evaluation_time;
+        }
+    });
+}
+
+/// A filter for entities whose components a [`CompiledCircuit`] is compiled with.
+type CompiledComponentsChanged = Or<
+    (
+        Changed<GateDisabled>,
+        Changed<WireDisabled>,
+        Changed<SignalOverride>,
+        Changed<ClockDomain>,
+        Changed<BidirectionalWire>,
+        Changed<NoEvalOutput>,
+        (Changed<GateOutput>, With<NoEvalOutput>),
+    )
+>;
+
+/// The removals of the components a [`CompiledCircuit`] is compiled with.
+#[derive(SystemParam)]
+pub struct CompiledComponentsRemoved<'w, 's> {
+    gate_disabled: RemovedComponents<'w, 's, GateDisabled>,
+    wire_disabled: RemovedComponents<'w, 's, WireDisabled>,
+    overrides: RemovedComponents<'w, 's, SignalOverride>,
+    domains: RemovedComponents<'w, 's, ClockDomain>,
+    buses: RemovedComponents<'w, 's, BidirectionalWire>,
+    no_eval: RemovedComponents<'w, 's, NoEvalOutput>,
+}
+
+impl CompiledComponentsRemoved<'_, '_> {
+    /// Returns `true` if any of the components were removed since the last call.
+    fn any(&mut self) -> bool {
+        let removed =
+            self.gate_disabled.read().count() +
+            self.wire_disabled.read().count() +
+            self.overrides.read().count() +
+            self.domains.read().count() +
+            self.buses.read().count() +
+            self.no_eval.read().count();
+        removed > 0
+    }
+}
+
+/// Mark the [`CompiledCircuit`] to be recompiled when one of the components it is compiled
+/// with, such as a [`GateDisabled`] or a [`SignalOverride`], is inserted, changed, or removed,
+/// or when the [`LogicSettings`] change.
+pub fn invalidate_compiled_circuit(
+    mut circuit: ResMut<CompiledCircuit>,
+    settings: Option<Res<LogicSettings>>,
+    query_changed: Query<(), CompiledComponentsChanged>,
+    mut removed: CompiledComponentsRemoved
+) {
+    let settings_changed = settings.is_some_and(|settings| settings.is_changed());
+    if removed.any() || settings_changed || !query_changed.is_empty() {
+        circuit.stale = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ components::{ ConnectedWires, OutputBundle }, logic::builder::LogicExt };
+
+    fn not_gate_circuit(world: &mut World) -> (Entity, Entity) {
+        world.init_resource::<LogicGraph>();
+        let battery = world.spawn_gate(Battery::OFF).with_inputs(0).with_outputs(1).build();
+        let not = world.spawn_gate(NotGate).with_inputs(1).with_outputs(1).build();
+        let wire = world.spawn_wire(&battery, 0, &not, 0).downgrade();
+        world
+            .resource_mut::<LogicGraph>()
+            .add_data(vec![battery, not.clone()])
+            .add_data(vec![wire])
+            .compile();
+        (not.id(), not.output(0))
+    }
+
+    #[test]
+    fn disabled_gates_hold_or_turn_off() {
+        let mut world = World::new();
+        let (not, output) = not_gate_circuit(&mut world);
+
+        world.entity_mut(not).insert(GateDisabled::Hold);
+        let mut circuit = CompiledCircuit::compile(&world).unwrap();
+        circuit.step();
+        assert_eq!(circuit.signal(output), Some(Signal::Undefined));
+
+        world.entity_mut(not).insert(GateDisabled::Zero);
+        let mut circuit = CompiledCircuit::compile(&world).unwrap();
+        circuit.step();
+        assert_eq!(circuit.signal(output), Some(Signal::OFF));
+
+        world.entity_mut(not).remove::<GateDisabled>();
+        let mut circuit = CompiledCircuit::compile(&world).unwrap();
+        circuit.step();
+        assert_eq!(circuit.signal(output), Some(Signal::ON));
+    }
+
+    #[test]
+    fn disabled_wires_carry_undefined() {
+        let mut world = World::new();
+        let (not, _) = not_gate_circuit(&mut world);
+        let input = world.get::<LogicGateFans>(not).unwrap().inputs[0].unwrap();
+        let wire = world.get::<ConnectedWires>(input).unwrap().wires.as_slice()[0];
+
+        world.entity_mut(wire).insert(WireDisabled);
+        let mut circuit = CompiledCircuit::compile(&world).unwrap();
+        circuit.step();
+        assert_eq!(circuit.signal(input), Some(Signal::Undefined));
+    }
+
+    #[test]
+    fn overrides_and_clock_domains_are_rejected() {
+        let mut world = World::new();
+        let (not, output) = not_gate_circuit(&mut world);
+
+        world.entity_mut(output).insert(SignalOverride(Signal::ON));
+        let error = CompiledCircuit::compile(&world).unwrap_err();
+        assert_eq!(error, CompileError::SignalOverride(output));
+
+        world.entity_mut(output).remove::<SignalOverride>();
+        world.entity_mut(not).insert(ClockDomain(1));
+        let error = CompiledCircuit::compile(&world).unwrap_err();
+        assert_eq!(error, CompileError::ClockDomain(not));
+    }
+
+    #[test]
+    fn ecs_only_settings_and_wires_are_rejected() {
+        let mut world = World::new();
+        let (not, output) = not_gate_circuit(&mut world);
+        let input = world.get::<LogicGateFans>(not).unwrap().inputs[0].unwrap();
+
+        let propagation = PropagationMode::Synchronous;
+        world.insert_resource(LogicSettings { propagation, ..default() });
+        let error = CompiledCircuit::compile(&world).unwrap_err();
+        assert_eq!(error, CompileError::SynchronousPropagation);
+
+        world.insert_resource(LogicSettings { settle_limit: 2, ..default() });
+        let error = CompiledCircuit::compile(&world).unwrap_err();
+        assert_eq!(error, CompileError::SettleLimit(2));
+        world.insert_resource(LogicSettings::default());
+
+        let bus = world.spawn_bidirectional_wire(output, input);
+        let error = CompiledCircuit::compile(&world).unwrap_err();
+        assert_eq!(error, CompileError::BidirectionalWire(bus));
+        world.despawn(bus);
+
+        let button = world.spawn((OutputBundle::default(), NoEvalOutput)).id();
+        world.spawn_no_eval_wire(button, input);
+        let error = CompiledCircuit::compile(&world).unwrap_err();
+        assert_eq!(error, CompileError::NoEvalOutput(button));
+    }
+}
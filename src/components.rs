@@ -1,24 +1,56 @@
-use bevy::{ ecs::entity::EntityHashSet, prelude::* };
+use bevy::{
+    ecs::{
+        component::{ ComponentHooks, ComponentId, StorageType },
+        entity::{ EntityMapper, MapEntities },
+        reflect::ReflectMapEntities,
+        world::DeferredWorld,
+    },
+    prelude::*,
+};
+use uuid::Uuid;
 
-use crate::logic::signal::Signal;
+use crate::{ logic::{ signal::Signal, GateArity }, resources::LogicUuidRegistry };
 
 pub mod prelude {
     pub use super::{
         Wire,
         WireBundle,
+        WireResistance,
+        WireFalloff,
+        WireChannel,
+        BidirectionalWire,
+        WireDisabled,
         LogicGateFans,
         GateFan,
         GateInput,
         GateOutput,
+        ConnectedWires,
+        WireSet,
         InputBundle,
         OutputBundle,
         NoEvalOutput,
+        PrevSignal,
+        SignalOverride,
+        SignalForced,
+        SettleFailed,
+        GateArityViolation,
+        BudgetExceeded,
+        BudgetKind,
+        GraphCompiled,
+        CircuitId,
+        CircuitMember,
+        LogicUuid,
+        FeedbackLoopMember,
+        EvalPriority,
+        GateDisabled,
     };
 }
 
 /// A component that connects two logic gates with the entity IDs
 /// of their child fans.
 #[derive(Component, Clone, Copy, Debug, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, MapEntities)]
 pub struct Wire {
     /// The [`GateOutput`] entity.
     pub from: Entity,
@@ -26,6 +58,13 @@ pub struct Wire {
     pub to: Entity,
 }
 
+impl MapEntities for Wire {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        self.from = entity_mapper.map_entity(self.from);
+        self.to = entity_mapper.map_entity(self.to);
+    }
+}
+
 impl Wire {
     /// Create a new wire from an `Entity` with a [`GateOutput`] to an `Entity` with a [`GateInput`].
     pub fn new(from: Entity, to: Entity) -> Self {
@@ -33,6 +72,147 @@ impl Wire {
     }
 }
 
+/// An optional wire component that weakens signals as they travel through the wire.
+///
+/// The inner value is the fraction of signal strength lost, where `0.0` is a perfect
+/// conductor and `1.0` blocks the signal entirely.
+///
+/// - [`Signal::Analog`] values are scaled by `1.0 - resistance`.
+/// - [`Signal::Digital`] values are treated as `1.0` and turned off if the remaining
+///   strength falls below [`WireResistance::DIGITAL_THRESHOLD`].
+/// - [`Signal::Undefined`] is unaffected.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
+pub struct WireResistance(pub f32);
+
+impl WireResistance {
+    /// The minimum remaining strength required for a digital signal to stay on.
+    pub const DIGITAL_THRESHOLD: f32 = 0.5;
+
+    /// Returns the signal that arrives at the end of a wire with this resistance.
+    pub fn apply(&self, signal: Signal) -> Signal {
+        let strength = (1.0 - self.0).clamp(0.0, 1.0);
+        match signal {
+            Signal::Analog(value) => Signal::Analog(value * strength),
+            Signal::Digital(true) if strength < Self::DIGITAL_THRESHOLD => Signal::OFF,
+            signal => signal,
+        }
+    }
+}
+
+/// How much strength a signal loses through a wire, from the [`SignalFalloff`] resource.
+///
+/// - [`Signal::Digital`] values that are on start at `max_strength`.
+/// - [`Signal::Analog`] values move `loss` closer to zero, keeping their sign.
+/// - Signals with no strength left turn off, and [`Signal::Undefined`] is unaffected.
+///
+/// It is applied after any [`WireResistance`].
+///
+/// [`SignalFalloff`]: crate::resources::SignalFalloff
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
+pub struct WireFalloff {
+    pub max_strength: f32,
+    pub loss: f32,
+}
+
+impl WireFalloff {
+    /// Returns the signal that arrives at the end of a wire with this falloff.
+    pub fn apply(&self, signal: Signal) -> Signal {
+        let strength = match signal {
+            Signal::Digital(true) => self.max_strength,
+            Signal::Analog(value) => value,
+            Signal::Digital(false) | Signal::Undefined => {
+                return signal;
+            }
+        };
+        let remaining = strength.abs() - self.loss;
+        if remaining > 0.0 { Signal::Analog(remaining.copysign(strength)) } else { Signal::OFF }
+    }
+}
+
+/// The channel of a wire, like the red and green wires in Factorio, so independent signals
+/// can share the same route. Wires without one are on channel `0`.
+///
+/// - [`BidirectionalWire`]s only join a bus with wires on the same channel.
+/// - An input fan with a [`WireChannel`] only receives signals from wires on its channel, so
+///   the input of a [`Junction`] picks one channel out of the wires connected to it.
+///
+/// [`Junction`]: crate::logic::gates::Junction
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, PartialEq, Hash)]
+pub struct WireChannel(pub u8);
+
+impl WireChannel {
+    /// Returns `true` if a wire on the `wire` channel delivers its signal to an input fan on
+    /// the `input` channel.
+    pub fn connects(wire: Option<&WireChannel>, input: Option<&WireChannel>) -> bool {
+        match input {
+            Some(input) => wire.copied().unwrap_or_default() == *input,
+            None => true,
+        }
+    }
+}
+
+/// Marks a [`Wire`] that carries signals in both directions, like redstone dust. Its ends can
+/// be any two fans, inputs or outputs.
+///
+/// Fans connected by bidirectional wires on the same [`WireChannel`] form a bus. Before each
+/// step, [`resolve_buses`] gives every input fan and wire on a bus the strongest signal of its
+/// output fans, as in [`Signal::max_abs`]. These wires are not part of the [`LogicGraph`], so
+/// they don't order gates, and an input on a bus should not also be driven by a regular wire.
+///
+/// Spawn one with [`LogicExt::spawn_bidirectional_wire`].
+///
+/// [`resolve_buses`]: crate::systems::resolve_buses
+/// [`LogicGraph`]: crate::resources::LogicGraph
+/// [`LogicExt::spawn_bidirectional_wire`]: crate::logic::builder::LogicExt::spawn_bidirectional_wire
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
+pub struct BidirectionalWire;
+
+/// Severs a [`Wire`] without removing it from the [`LogicGraph`], for fuses, switches on
+/// wires, and damaged cables.
+///
+/// A disabled wire, and the input fan at its end, are [`Signal::Undefined`]. A disabled
+/// [`BidirectionalWire`] does not join its fans into a bus. The wire carries signals again
+/// as soon as the component is removed.
+///
+/// [`LogicGraph`]: crate::resources::LogicGraph
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+pub struct WireDisabled;
+
+impl Component for WireDisabled {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks.on_add(refresh_wire).on_remove(refresh_wire);
+    }
+}
+
+/// Propagate the signal of a wire's output fan again, whether it is driven by a gate or has
+/// a [`NoEvalOutput`].
+fn refresh_wire(mut world: DeferredWorld, entity: Entity, _: ComponentId) {
+    let Some(&wire) = world.get::<Wire>(entity) else {
+        return;
+    };
+    if let Some(mut signal) = world.get_mut::<Signal>(wire.from) {
+        signal.set_changed();
+    }
+    let Some(gate) = world.get::<Parent>(wire.from).map(Parent::get) else {
+        return;
+    };
+    if let Some(mut fans) = world.get_mut::<LogicGateFans>(gate) {
+        fans.set_changed();
+    }
+}
+
 /// A bundle used to create a wire between a [`GateOutput`] and [`GateInput`].
 #[derive(Bundle, Clone, Copy)]
 pub struct WireBundle {
@@ -43,6 +223,8 @@ pub struct WireBundle {
 /// Marks an entity as a logic gate entity, and stores the
 /// input and output fans of the gate.
 #[derive(Component, Clone, Debug, Default, PartialEq, Eq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, MapEntities)]
 pub struct LogicGateFans {
     pub inputs: Vec<Option<Entity>>,
     pub outputs: Vec<Option<Entity>>,
@@ -99,8 +281,18 @@ impl LogicGateFans {
     }
 }
 
+impl MapEntities for LogicGateFans {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        for fan in self.inputs.iter_mut().chain(self.outputs.iter_mut()).flatten() {
+            *fan = entity_mapper.map_entity(*fan);
+        }
+    }
+}
+
 /// Marks an entity as either an input or an output.
 #[derive(Component, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
 pub enum GateFan {
     Input,
     Output,
@@ -135,15 +327,265 @@ impl From<GateOutput> for GateFan {
 }
 
 /// Marks an entity as an input.
-#[derive(Component, Default)]
+#[derive(Component, Default, Reflect)]
+#[reflect(Component, Default)]
 pub struct GateInput;
 
+/// A set of wire [`Entity`] IDs, kept sorted so it is iterated in the same order every run.
+///
+/// Wires are written in this order during a step, so when several wires drive the same input,
+/// the signal it ends up with does not depend on hashing or on the order wires were added.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Default)]
+pub struct WireSet(Vec<Entity>);
+
+impl WireSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a wire. Returns `false` if the set already contained it.
+    pub fn insert(&mut self, wire: Entity) -> bool {
+        match self.0.binary_search(&wire) {
+            Ok(_) => false,
+            Err(index) => {
+                self.0.insert(index, wire);
+                true
+            }
+        }
+    }
+
+    /// Remove a wire. Returns `false` if the set did not contain it.
+    pub fn remove(&mut self, wire: &Entity) -> bool {
+        match self.0.binary_search(wire) {
+            Ok(index) => {
+                self.0.remove(index);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    pub fn contains(&self, wire: &Entity) -> bool {
+        self.0.binary_search(wire).is_ok()
+    }
+
+    /// Iterate over the wires in ascending [`Entity`] order.
+    pub fn iter(&self) -> std::slice::Iter<'_, Entity> {
+        self.0.iter()
+    }
+
+    /// Returns the wires in ascending [`Entity`] order.
+    pub fn as_slice(&self) -> &[Entity] {
+        &self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+impl FromIterator<Entity> for WireSet {
+    fn from_iter<T: IntoIterator<Item = Entity>>(iter: T) -> Self {
+        let mut wires = iter.into_iter().collect::<Vec<_>>();
+        wires.sort_unstable();
+        wires.dedup();
+        Self(wires)
+    }
+}
+
+impl Extend<Entity> for WireSet {
+    fn extend<T: IntoIterator<Item = Entity>>(&mut self, iter: T) {
+        self.0.extend(iter);
+        self.0.sort_unstable();
+        self.0.dedup();
+    }
+}
+
+impl<'a> IntoIterator for &'a WireSet {
+    type Item = &'a Entity;
+    type IntoIter = std::slice::Iter<'a, Entity>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 /// Marks an entity as an output, and stores
 /// the [`Entity`] IDs of out-going wires.
-#[derive(Component, Default)]
+#[derive(Component, Default, Reflect)]
+#[reflect(Component, Default, MapEntities)]
 pub struct GateOutput {
-    /// Holds [Entity] ids to outgoing wires.
-    pub wires: EntityHashSet,
+    /// Holds [Entity] ids to outgoing wires, in ascending order.
+    pub wires: WireSet,
+}
+
+impl MapEntities for GateOutput {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        self.wires = self.wires
+            .iter()
+            .map(|&wire| entity_mapper.map_entity(wire))
+            .collect();
+    }
+}
+
+/// Stores the [`Entity`] IDs of in-coming wires on a [`GateInput`] entity.
+///
+/// This mirrors [`GateOutput::wires`], so the wires driving an input can be found
+/// without scanning every [`Wire`]. Both sets are kept up-to-date by the crate's
+/// wire builders and commands.
+#[derive(Component, Clone, Debug, Default, Reflect)]
+#[reflect(Component, Default, MapEntities)]
+pub struct ConnectedWires {
+    /// Holds [Entity] ids to in-coming wires, in ascending order.
+    pub wires: WireSet,
+}
+
+impl MapEntities for ConnectedWires {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        self.wires = self.wires
+            .iter()
+            .map(|&wire| entity_mapper.map_entity(wire))
+            .collect();
+    }
+}
+
+/// Identifies a circuit, a group of gates, fans, and wires that make up one machine.
+///
+/// Any value can be used, as long as each machine has its own.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Reflect)]
+pub struct CircuitId(pub u32);
+
+/// Marks a gate, fan, or wire as part of a circuit, so the whole machine can be queried or
+/// despawned with [`LogicExt::despawn_circuit`] as a unit.
+///
+/// Inserted on gates and their fans built with [`GateBuilder::in_circuit`], and on wires
+/// spawned between two gates of the same circuit.
+///
+/// [`LogicExt::despawn_circuit`]: crate::logic::builder::LogicExt::despawn_circuit
+/// [`GateBuilder::in_circuit`]: crate::logic::builder::GateBuilder::in_circuit
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Component)]
+pub struct CircuitMember(pub CircuitId);
+
+/// A component on gates that are part of a feedback loop, such as a latch or an oscillator.
+///
+/// The value is the index of the loop in [`LogicGraph::find_cycles`]. It is kept up to date
+/// with the graph by the [`LogicSimulationPlugin`].
+///
+/// [`LogicGraph::find_cycles`]: crate::resources::LogicGraph::find_cycles
+/// [`LogicSimulationPlugin`]: crate::LogicSimulationPlugin
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Component)]
+pub struct FeedbackLoopMember(pub usize);
+
+/// Nudges the order a gate is evaluated in, for constructs that depend on it, such as clock
+/// distribution gates that should update before the gates they feed back into.
+///
+/// Gates with a higher priority are evaluated before independent gates and gates in the same
+/// feedback loop. A gate is still evaluated after every gate it depends on. The default
+/// priority is `0`.
+///
+/// The priority is copied to the [`LogicGraph`], which is recompiled, when the component is
+/// inserted, changed, or removed. Use [`LogicGraph::set_priority`] for gates without one.
+///
+/// [`LogicGraph`]: crate::resources::LogicGraph
+/// [`LogicGraph::set_priority`]: crate::resources::LogicGraph::set_priority
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
+pub struct EvalPriority(pub i32);
+
+/// Disables a gate without removing it from the [`LogicGraph`], for machines without power
+/// or gates soft-deleted in an editor.
+///
+/// A disabled gate is not evaluated, and its wires and place in the graph are kept. Gates
+/// are evaluated again as soon as the component is removed.
+///
+/// [`LogicGraph`]: crate::resources::LogicGraph
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+pub enum GateDisabled {
+    /// The outputs keep the signals they had when the gate was disabled.
+    #[default]
+    Hold,
+    /// The outputs are turned off, and [`Signal::OFF`] is sent down their wires.
+    Zero,
+}
+
+impl Component for GateDisabled {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        // Evaluate the gate in the next step, even if none of its inputs changed.
+        hooks
+            .on_insert(|mut world, entity, _| {
+                if let Some(mut fans) = world.get_mut::<LogicGateFans>(entity) {
+                    fans.set_changed();
+                }
+            })
+            .on_remove(|mut world, entity, _| {
+                if let Some(mut fans) = world.get_mut::<LogicGateFans>(entity) {
+                    fans.set_changed();
+                }
+            });
+    }
+}
+
+/// A persistent identifier for a gate or wire, which stays the same across saving and
+/// loading, unlike its [`Entity`].
+///
+/// The builders in [`LogicExt`] give every gate and wire a random one, unless it already
+/// has one. Entities with a [`LogicUuid`] are tracked by the [`LogicUuidRegistry`] resource.
+/// It is never copied by blueprints or [`CloneGateCommand`].
+///
+/// [`LogicExt`]: crate::logic::builder::LogicExt
+/// [`CloneGateCommand`]: crate::commands::CloneGateCommand
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, PartialEq, Hash)]
+pub struct LogicUuid(pub Uuid);
+
+impl Component for LogicUuid {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks
+            .on_insert(|mut world, entity, _| {
+                let uuid = world.get::<LogicUuid>(entity).expect("LogicUuid was just inserted").0;
+                if let Some(mut registry) = world.get_resource_mut::<LogicUuidRegistry>() {
+                    registry.insert(uuid, entity);
+                }
+            })
+            .on_remove(|mut world, entity, _| {
+                let uuid = world.get::<LogicUuid>(entity).expect("LogicUuid is being removed").0;
+                if let Some(mut registry) = world.get_resource_mut::<LogicUuidRegistry>() {
+                    registry.remove(uuid, entity);
+                }
+            });
+    }
+}
+
+impl Default for LogicUuid {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogicUuid {
+    /// Create a random identifier.
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
 }
 
 /// Marks an entity as an output that does not require
@@ -156,15 +598,125 @@ pub struct GateOutput {
 /// ONLY output a signal.
 ///
 /// See the `advanced_gates` example.
-#[derive(Component, Default)]
+#[derive(Component, Default, Reflect)]
+#[reflect(Component, Default)]
 pub struct NoEvalOutput;
 
+/// The [`Signal`] an entity had before the latest logic step.
+///
+/// Insert this on fans or wires to opt in. It is updated at the start of every
+/// [`LogicUpdate`] run by [`record_prev_signals`], so during and after a step it can be
+/// compared with the current signal to detect edges and animate transitions.
+///
+/// [`LogicUpdate`]: crate::logic::schedule::LogicUpdate
+/// [`record_prev_signals`]: crate::systems::record_prev_signals
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct PrevSignal(pub Signal);
+
+impl PrevSignal {
+    /// Returns `true` if the signal turned on during the latest step.
+    pub fn is_rising_edge(&self, current: Signal) -> bool {
+        self.0.is_falsy() && current.is_truthy()
+    }
+
+    /// Returns `true` if the signal turned off during the latest step.
+    pub fn is_falling_edge(&self, current: Signal) -> bool {
+        self.0.is_truthy() && current.is_falsy()
+    }
+}
+
+/// Forces the [`Signal`] of a fan, for debugging what a circuit does with a different value
+/// without rewiring it.
+///
+/// [`step_logic`] writes the override instead of the evaluated signal of an output fan, or
+/// the signal arriving at an input fan, so gates downstream see the forced value during the
+/// same step. A [`SignalForced`] event is sent whenever the override hides a different
+/// signal. When the override is removed, the gate is evaluated again to restore its signal.
+///
+/// A [`CompiledCircuit`] can't be compiled while a fan has an override.
+///
+/// [`step_logic`]: crate::systems::step_logic
+/// [`CompiledCircuit`]: crate::compiled::CompiledCircuit
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Reflect)]
+#[component(storage = "SparseSet")]
+#[reflect(Component)]
+pub struct SignalOverride(pub Signal);
+
+/// Sent when a [`SignalOverride`] replaces a different signal on a fan.
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct SignalForced {
+    pub fan: Entity,
+    /// The signal the fan would have had without the override.
+    pub signal: Signal,
+    /// The signal of the override.
+    pub forced: Signal,
+}
+
+/// Sent when a feedback loop is still changing after being evaluated
+/// [`LogicSettings::settle_limit`] more times during a step.
+///
+/// [`LogicSettings::settle_limit`]: crate::resources::LogicSettings::settle_limit
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct SettleFailed {
+    /// The tick of the step.
+    pub tick: u64,
+    /// The gates in the loop, in the order they are evaluated.
+    pub gates: Vec<Entity>,
+}
+
+/// Sent when the order computed by [`LogicGraph::compile_async`] is swapped into the graph.
+///
+/// [`LogicGraph::compile_async`]: crate::resources::LogicGraph::compile_async
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GraphCompiled {
+    /// The number of gates in the new order.
+    pub gates: usize,
+    /// How long the compilation took on the task pool.
+    pub duration: std::time::Duration,
+}
+
+/// Sent when a gate or wire is despawned because its circuit has no room left in its
+/// [`CircuitBudget`].
+///
+/// [`CircuitBudget`]: crate::resources::CircuitBudget
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BudgetExceeded {
+    pub circuit: CircuitId,
+    /// The gate or wire that was despawned.
+    pub entity: Entity,
+    pub kind: BudgetKind,
+    /// The most gates or wires the circuit may have.
+    pub limit: usize,
+}
+
+/// What a [`BudgetExceeded`] event ran out of.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Reflect)]
+pub enum BudgetKind {
+    Gates,
+    Wires,
+}
+
+/// Sent when a gate has a number of fans that its declared [`GateArity`] does not accept.
+///
+/// [`GateArity`]: crate::logic::GateArity
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct GateArityViolation {
+    pub gate: Entity,
+    /// The short type name of the gate component.
+    pub gate_type: String,
+    pub inputs: usize,
+    pub outputs: usize,
+    pub arity: GateArity,
+}
+
 /// A bundle that can be used to create a child
 /// **input** node of a logic gate entity.
 #[derive(Bundle)]
 pub struct InputBundle {
     pub signal: Signal,
     pub input: GateInput,
+    pub wires: ConnectedWires,
     pub fan: GateFan,
 }
 
@@ -173,6 +725,7 @@ impl Default for InputBundle {
         Self {
             signal: Signal::Undefined,
             input: GateInput,
+            wires: ConnectedWires::default(),
             fan: GateFan::Input,
         }
     }
@@ -0,0 +1,191 @@
+use std::time::Duration;
+
+use bevy::{
+    diagnostic::{ Diagnostic, DiagnosticPath, Diagnostics, DiagnosticsStore, RegisterDiagnostic },
+    prelude::*,
+    utils::{ get_short_name, HashMap },
+};
+
+use crate::{
+    logic::schedule::{ LogicClock, LogicSystemSet, LogicUpdate },
+    resources::{ LogicBacklog, LogicGraph },
+};
+
+pub mod prelude {
+    pub use super::{ LogicDiagnosticsPlugin, LogicStats, LogicStepMetrics };
+}
+
+/// A plugin that records logic simulation measurements as [`Diagnostic`]s.
+///
+/// A measurement is taken after every [`LogicUpdate`] step:
+///
+/// - [`Self::GATE_COUNT`]: the number of gates evaluated.
+/// - [`Self::EVALUATION_TIME`]: the time spent evaluating gates, in milliseconds.
+/// - [`Self::SIGNAL_CHANGES`]: the number of output signals that changed value.
+///
+/// The plugin also inserts the [`LogicStats`] resource, which keeps the same measurements
+/// along with the shape of the circuit, for HUDs and gameplay limits.
+///
+/// If `per_gate_type` is true, the evaluation time of each gate type is also recorded
+/// under `logic/gate_type/<TypeName>`. This times every single evaluation, so it is
+/// disabled by default.
+#[derive(Default)]
+pub struct LogicDiagnosticsPlugin {
+    pub per_gate_type: bool,
+}
+
+impl Plugin for LogicDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_diagnostic(Diagnostic::new(Self::GATE_COUNT).with_smoothing_factor(0.0))
+            .register_diagnostic(Diagnostic::new(Self::EVALUATION_TIME).with_suffix("ms"))
+            .register_diagnostic(Diagnostic::new(Self::SIGNAL_CHANGES))
+            .register_type::<LogicStats>()
+            .insert_resource(LogicStepMetrics {
+                record_gate_types: self.per_gate_type,
+                ..Default::default()
+            })
+            .init_resource::<LogicStats>()
+            .add_systems(
+                LogicUpdate,
+                (Self::diagnostic_system, update_logic_stats).in_set(LogicSystemSet::PostStep)
+            );
+
+        if self.per_gate_type {
+            app.add_systems(
+                LogicUpdate,
+                Self::gate_type_diagnostic_system.in_set(LogicSystemSet::PostStep)
+            );
+        }
+    }
+}
+
+impl LogicDiagnosticsPlugin {
+    pub const GATE_COUNT: DiagnosticPath = DiagnosticPath::const_new("logic/gate_count");
+    pub const EVALUATION_TIME: DiagnosticPath = DiagnosticPath::const_new("logic/evaluation_time");
+    pub const SIGNAL_CHANGES: DiagnosticPath = DiagnosticPath::const_new("logic/signal_changes");
+
+    /// Returns the [`DiagnosticPath`] used to record the evaluation time of a gate type.
+    pub fn gate_type_path(type_name: &str) -> DiagnosticPath {
+        DiagnosticPath::from_components(["logic", "gate_type", &get_short_name(type_name)])
+    }
+
+    pub fn diagnostic_system(mut diagnostics: Diagnostics, metrics: Res<LogicStepMetrics>) {
+        diagnostics.add_measurement(&Self::GATE_COUNT, || metrics.gates_evaluated as f64);
+        diagnostics.add_measurement(&Self::EVALUATION_TIME, || {
+            metrics.evaluation_time.as_secs_f64() * 1000.0
+        });
+        diagnostics.add_measurement(&Self::SIGNAL_CHANGES, || metrics.signal_changes as f64);
+    }
+
+    /// Gate types are only known once they are evaluated, so their diagnostics
+    /// are registered on the fly.
+    pub fn gate_type_diagnostic_system(
+        mut store: ResMut<DiagnosticsStore>,
+        metrics: Res<LogicStepMetrics>
+    ) {
+        for (type_name, duration) in metrics.gate_type_times.iter() {
+            let path = Self::gate_type_path(type_name);
+            if store.get(&path).is_none() {
+                store.add(Diagnostic::new(path.clone()).with_suffix("ms"));
+            }
+
+            let diagnostic = store.get_mut(&path).expect("gate type diagnostic was just added");
+            if diagnostic.is_enabled {
+                diagnostic.add_measurement(bevy::diagnostic::DiagnosticMeasurement {
+                    time: bevy::utils::Instant::now(),
+                    value: duration.as_secs_f64() * 1000.0,
+                });
+            }
+        }
+    }
+}
+
+/// Measurements collected by [`step_logic`] during the most recent logic step.
+///
+/// [`step_logic`] only records measurements if this resource exists. It is
+/// inserted by the [`LogicDiagnosticsPlugin`].
+///
+/// [`step_logic`]: crate::systems::step_logic
+#[derive(Resource, Clone, Debug, Default)]
+pub struct LogicStepMetrics {
+    /// The number of gates evaluated.
+    pub gates_evaluated: usize,
+    /// The number of output signals that changed value.
+    pub signal_changes: usize,
+    /// The number of wires that signals were written to.
+    pub wires_propagated: usize,
+    /// The total time spent evaluating gates.
+    pub evaluation_time: Duration,
+    /// The time spent evaluating each gate type, keyed by [`LogicGate::gate_type_name`].
+    ///
+    /// [`LogicGate::gate_type_name`]: crate::logic::LogicGate::gate_type_name
+    pub gate_type_times: HashMap<&'static str, Duration>,
+    /// If true, [`Self::gate_type_times`] is recorded.
+    pub record_gate_types: bool,
+}
+
+impl LogicStepMetrics {
+    /// Clear all measurements from the previous step.
+    pub fn clear(&mut self) {
+        self.gates_evaluated = 0;
+        self.signal_changes = 0;
+        self.wires_propagated = 0;
+        self.evaluation_time = Duration::ZERO;
+        self.gate_type_times.clear();
+    }
+}
+
+/// Statistics of the logic simulation, updated after every logic step by
+/// [`update_logic_stats`]. It is inserted by the [`LogicDiagnosticsPlugin`].
+///
+/// ```ignore
+/// fn limit_factory(stats: Res<LogicStats>) {
+///     if stats.gates_evaluated > 2_300 {
+///         warn!("Your factory uses {} gate-ticks", stats.gates_evaluated);
+///     }
+/// }
+/// ```
+#[derive(Resource, Clone, Debug, Default, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct LogicStats {
+    /// The [`LogicTick`] of the last step.
+    ///
+    /// [`LogicTick`]: crate::logic::schedule::LogicTick
+    pub tick: u64,
+    /// The number of gates evaluated during the last step.
+    pub gates_evaluated: usize,
+    /// The number of output signals that changed during the last step.
+    pub signal_changes: usize,
+    /// The number of wires that signals were written to during the last step.
+    pub wires_propagated: usize,
+    /// The number of feedback loops in the [`LogicGraph`]. See [`LogicGraph::find_cycles`].
+    pub feedback_loops: usize,
+    /// The number of gates on the longest path through the [`LogicGraph`]. See
+    /// [`LogicGraph::longest_path`].
+    pub longest_path: usize,
+    /// The number of gates left to visit before the current step completes. See
+    /// [`LogicBacklog`].
+    pub backlog: usize,
+    /// The number of gates evaluated since the resource was inserted.
+    pub total_gates_evaluated: u64,
+}
+
+/// Copy the [`LogicStepMetrics`] of the last step into [`LogicStats`], along with the shape of
+/// the [`LogicGraph`] and the [`LogicBacklog`].
+pub fn update_logic_stats(
+    mut stats: ResMut<LogicStats>,
+    metrics: Res<LogicStepMetrics>,
+    logic_graph: Res<LogicGraph>,
+    backlog: Option<Res<LogicBacklog>>,
+    clock: LogicClock
+) {
+    let stats = stats.as_mut();
+    stats.tick = clock.tick();
+    stats.gates_evaluated = metrics.gates_evaluated;
+    stats.signal_changes = metrics.signal_changes;
+    stats.wires_propagated = metrics.wires_propagated;
+    stats.feedback_loops = logic_graph.find_cycles().len();
+    stats.longest_path = logic_graph.longest_path();
+    stats.backlog = backlog.map_or(0, |backlog| backlog.remaining);
+    stats.total_gates_evaluated += metrics.gates_evaluated as u64;
+}
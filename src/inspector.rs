@@ -0,0 +1,245 @@
+//! An egui panel for debugging circuits. Requires the `egui` feature.
+
+use bevy::{ prelude::*, window::PrimaryWindow };
+use bevy_egui::{ egui, EguiContext, EguiPlugin };
+
+use crate::{
+    components::{ LogicGateFans, SignalOverride, Wire },
+    logic::{
+        gates::Battery,
+        schedule::{ FixedLogicStepExt, LogicStep, LogicWorldExt },
+        signal::Signal,
+        LogicGateTypes,
+    },
+    resources::{ LogicGraph, LogicIslands },
+};
+
+pub mod prelude {
+    pub use super::{ LogicInspectorPlugin, LogicInspectorState };
+}
+
+/// A plugin that shows a window listing every circuit, gate, and wire in the
+/// [`LogicGraph`], with controls to step the simulation, toggle batteries, and force fan
+/// signals.
+///
+/// Each circuit is an island of gates connected by wires. Gates are listed in the order they
+/// are evaluated, with their type and the signal of each fan.
+#[derive(Default)]
+pub struct LogicInspectorPlugin {
+    /// The initial state of the window.
+    pub state: LogicInspectorState,
+}
+
+impl Plugin for LogicInspectorPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin);
+        }
+
+        app.insert_resource(self.state.clone()).add_systems(
+            Update,
+            logic_inspector_ui.run_if(resource_exists::<LogicGraph>)
+        );
+    }
+}
+
+/// The state of the [`LogicInspectorPlugin`] window.
+#[derive(Resource, Clone, Debug)]
+pub struct LogicInspectorState {
+    /// If false, the window is hidden.
+    pub open: bool,
+    /// Only gates whose name or type contains this text are listed.
+    pub filter: String,
+}
+
+impl Default for LogicInspectorState {
+    fn default() -> Self {
+        Self {
+            open: true,
+            filter: String::new(),
+        }
+    }
+}
+
+enum InspectorAction {
+    Step,
+    SetPaused(bool),
+    ToggleBattery(Entity),
+    ForceSignal(Entity, Signal),
+    ReleaseSignal(Entity),
+}
+
+/// Draw the inspector window and apply the actions taken in it.
+pub fn logic_inspector_ui(world: &mut World) {
+    let Ok(ctx) = world
+        .query_filtered::<&mut EguiContext, With<PrimaryWindow>>()
+        .get_single_mut(world)
+        .map(|mut context| context.get_mut().clone()) else {
+        return;
+    };
+
+    let actions = world.resource_scope(|world, mut state: Mut<LogicInspectorState>| {
+        let mut actions = Vec::new();
+        let mut open = state.open;
+        egui::Window::new("Logic")
+            .open(&mut open)
+            .show(&ctx, |ui| draw_inspector(ui, world, &mut state, &mut actions));
+        state.open = open;
+        actions
+    });
+
+    for action in actions {
+        match action {
+            InspectorAction::Step => world.step_logic_once(),
+            InspectorAction::SetPaused(paused) => {
+                if let Some(mut time) = world.get_resource_mut::<Time<LogicStep>>() {
+                    if paused { time.pause() } else { time.unpause() }
+                }
+            }
+            InspectorAction::ToggleBattery(gate) => {
+                if let Some(mut battery) = world.get_mut::<Battery>(gate) {
+                    battery.signal = Signal::from(!battery.signal.is_truthy());
+                }
+            }
+            InspectorAction::ForceSignal(fan, signal) => {
+                if let Some(mut entity) = world.get_entity_mut(fan) {
+                    entity.insert(SignalOverride(signal));
+                }
+            }
+            InspectorAction::ReleaseSignal(fan) => {
+                if let Some(mut entity) = world.get_entity_mut(fan) {
+                    entity.remove::<SignalOverride>();
+                }
+            }
+        }
+    }
+}
+
+fn draw_inspector(
+    ui: &mut egui::Ui,
+    world: &World,
+    state: &mut LogicInspectorState,
+    actions: &mut Vec<InspectorAction>
+) {
+    let graph = world.resource::<LogicGraph>();
+    let gate_types = world.get_resource::<LogicGateTypes>();
+
+    ui.horizontal(|ui| {
+        if ui.button("Step").clicked() {
+            actions.push(InspectorAction::Step);
+        }
+        if let Some(time) = world.get_resource::<Time<LogicStep>>() {
+            let paused = time.is_paused();
+            if ui.button(if paused { "Resume" } else { "Pause" }).clicked() {
+                actions.push(InspectorAction::SetPaused(!paused));
+            }
+        }
+        ui.label(
+            format!("{} gates, {} wires", graph.sorted().len(), graph.graph.all_edges().count())
+        );
+    });
+    ui.horizontal(|ui| {
+        ui.label("Filter");
+        ui.text_edit_singleline(&mut state.filter);
+    });
+    ui.separator();
+
+    let mut islands = LogicIslands::default();
+    islands.rebuild(graph);
+    let order = |gate: Entity| graph.sorted().iter().position(|&sorted| sorted == gate);
+    let filter = state.filter.to_lowercase();
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        for (index, island) in islands.iter().enumerate() {
+            let header = format!("Circuit {index} ({} gates)", island.gates.len());
+            egui::CollapsingHeader::new(header).show(ui, |ui| {
+                for &gate in island.gates.iter() {
+                    let Some(entity) = world.get_entity(gate) else {
+                        continue;
+                    };
+                    let type_name = gate_types
+                        .and_then(|types| types.type_name_of(entity))
+                        .unwrap_or("Gate");
+                    let name = entity
+                        .get::<Name>()
+                        .map_or_else(|| gate.to_string(), |name| name.to_string());
+                    if
+                        !filter.is_empty() &&
+                        !name.to_lowercase().contains(&filter) &&
+                        !type_name.to_lowercase().contains(&filter)
+                    {
+                        continue;
+                    }
+
+                    let header = match order(gate) {
+                        Some(order) => format!("#{order} {name} ({type_name})"),
+                        None => format!("{gate} {name} ({type_name})"),
+                    };
+                    egui::CollapsingHeader::new(header).show(ui, |ui| {
+                        draw_gate(ui, world, gate, actions);
+                    });
+                }
+            });
+        }
+
+        egui::CollapsingHeader::new("Wires").show(ui, |ui| {
+            for (from_gate, to_gate, &wire) in graph.graph.all_edges() {
+                let Some(data) = world.get::<Wire>(wire) else {
+                    continue;
+                };
+                let signal = world.get::<Signal>(wire).copied().unwrap_or_default();
+                let (from, to) = (data.from, data.to);
+                ui.label(format!("{wire}: {from_gate} ({from}) -> {to_gate} ({to}) = {signal}"));
+            }
+        });
+    });
+}
+
+fn draw_gate(
+    ui: &mut egui::Ui,
+    world: &World,
+    gate: Entity,
+    actions: &mut Vec<InspectorAction>
+) {
+    if let Some(battery) = world.get::<Battery>(gate) {
+        ui.horizontal(|ui| {
+            ui.label(format!("Battery: {}", battery.signal));
+            if ui.button("Toggle").clicked() {
+                actions.push(InspectorAction::ToggleBattery(gate));
+            }
+        });
+    }
+
+    let Some(fans) = world.get::<LogicGateFans>(gate) else {
+        return;
+    };
+    let fans = fans.inputs
+        .iter()
+        .enumerate()
+        .map(|(index, fan)| ("in", index, fan))
+        .chain(fans.outputs.iter().enumerate().map(|(index, fan)| ("out", index, fan)));
+
+    for (kind, index, fan) in fans {
+        let Some(fan) = *fan else {
+            continue;
+        };
+        let signal = world.get::<Signal>(fan).copied().unwrap_or_default();
+        let forced = world.get::<SignalOverride>(fan).is_some();
+        ui.horizontal(|ui| {
+            if forced {
+                ui.label(format!("{kind} {index}: {signal} (forced)"));
+                if ui.small_button("Release").clicked() {
+                    actions.push(InspectorAction::ReleaseSignal(fan));
+                }
+            } else {
+                ui.label(format!("{kind} {index}: {signal}"));
+            }
+            if ui.small_button("Force on").clicked() {
+                actions.push(InspectorAction::ForceSignal(fan, Signal::ON));
+            }
+            if ui.small_button("Force off").clicked() {
+                actions.push(InspectorAction::ForceSignal(fan, Signal::OFF));
+            }
+        });
+    }
+}
@@ -0,0 +1,227 @@
+//! Click-drag wiring with `bevy_mod_picking`. Requires the `picking` feature.
+
+use bevy::{ ecs::system::SystemParam, prelude::* };
+use bevy_mod_picking::{
+    events::{ Drag, DragEnd, DragLeave, DragOver, DragStart, Drop as PointerDrop, Pointer },
+    pointer::PointerButton,
+    PickableBundle,
+};
+
+use crate::{
+    components::{ GateFan, GateInput, GateOutput, Wire },
+    logic::builder::LogicExt,
+    routing::{ WirePath, WireRoute },
+};
+
+pub mod prelude {
+    pub use super::{ InteractionPlugin, PendingWire, WireCreated };
+}
+
+/// A plugin that makes every [`GateFan`] pickable and creates wires by dragging from one fan
+/// and dropping on another.
+///
+/// While dragging, a [`PendingWire`] entity with a [`WirePath`] previews the wire. Drops are
+/// accepted between an output and an input, in either order, unless they are already
+/// connected. The wire is spawned with [`LogicExt::spawn_wire_between`] and a [`WireCreated`]
+/// event is sent.
+///
+/// Fans need a mesh or sprite for a picking backend to hit them. Add `DefaultPickingPlugins`
+/// from `bevy_mod_picking` alongside this plugin.
+pub struct InteractionPlugin;
+
+impl Plugin for InteractionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<WireCreated>().add_systems(
+            Update,
+            (
+                make_fans_pickable,
+                start_pending_wires,
+                update_pending_wires,
+                connect_pending_wires,
+            ).chain()
+        );
+    }
+}
+
+/// Sent when the [`InteractionPlugin`] spawns a wire.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WireCreated {
+    pub wire: Entity,
+    /// The [`GateOutput`] entity.
+    pub from: Entity,
+    /// The [`GateInput`] entity.
+    pub to: Entity,
+}
+
+/// A wire that is being dragged from `from`. Despawned when the drag ends.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PendingWire {
+    /// The fan the drag started on.
+    pub from: Entity,
+    /// The fan the pointer is over, if any.
+    pub target: Option<Entity>,
+    /// `true` if dropping on `target` would create a wire.
+    pub valid: bool,
+}
+
+/// Returns the `(output, input)` a wire between two fans would connect, in either order, or
+/// `None` if the fans are not an output and an input.
+pub fn wire_direction(
+    a: Entity,
+    b: Entity,
+    query_fans: &Query<(Has<GateOutput>, Has<GateInput>), With<GateFan>>
+) -> Option<(Entity, Entity)> {
+    let (a_output, a_input) = query_fans.get(a).ok()?;
+    let (b_output, b_input) = query_fans.get(b).ok()?;
+    match (a_output && b_input, a_input && b_output) {
+        (true, _) => Some((a, b)),
+        (_, true) => Some((b, a)),
+        _ => None,
+    }
+}
+
+/// The fans a [`PendingWire`] can be dropped on, and the wires already connecting them.
+#[derive(SystemParam)]
+pub struct WireTargets<'w, 's> {
+    fans: Query<'w, 's, (Has<GateOutput>, Has<GateInput>), With<GateFan>>,
+    outputs: Query<'w, 's, &'static GateOutput>,
+    wires: Query<'w, 's, &'static Wire>,
+}
+
+impl WireTargets<'_, '_> {
+    /// Returns the `(output, input)` a new wire between two fans would connect, or `None` if
+    /// the fans are not an output and an input, or a wire already connects them.
+    pub fn new_wire(&self, a: Entity, b: Entity) -> Option<(Entity, Entity)> {
+        wire_direction(a, b, &self.fans).filter(|&(from, to)| !self.is_connected(from, to))
+    }
+
+    /// Returns `true` if a wire already connects `from` to `to`.
+    fn is_connected(&self, from: Entity, to: Entity) -> bool {
+        self.outputs.get(from).is_ok_and(|output| {
+            output.wires.iter().any(|&wire| self.wires.get(wire).is_ok_and(|wire| wire.to == to))
+        })
+    }
+}
+
+/// Make every new [`GateFan`] pickable.
+pub fn make_fans_pickable(mut commands: Commands, query: Query<Entity, Added<GateFan>>) {
+    for entity in query.iter() {
+        commands.entity(entity).insert(PickableBundle::default());
+    }
+}
+
+/// Spawn a [`PendingWire`] when a drag starts on a fan.
+pub fn start_pending_wires(
+    mut commands: Commands,
+    mut events: EventReader<Pointer<DragStart>>,
+    query_fans: Query<&GlobalTransform, With<GateFan>>
+) {
+    for event in events.read() {
+        if event.button != PointerButton::Primary {
+            continue;
+        }
+        let Ok(transform) = query_fans.get(event.target) else {
+            continue;
+        };
+
+        let position = transform.translation();
+        commands.spawn((
+            PendingWire {
+                from: event.target,
+                target: None,
+                valid: false,
+            },
+            WirePath::straight(position, position),
+        ));
+    }
+}
+
+/// Follow the pointer with each [`PendingWire`], snapping to the fan it is over.
+pub fn update_pending_wires(
+    mut drag_events: EventReader<Pointer<Drag>>,
+    mut over_events: EventReader<Pointer<DragOver>>,
+    mut leave_events: EventReader<Pointer<DragLeave>>,
+    mut query_pending: Query<(&mut PendingWire, &mut WirePath, Option<&WireRoute>)>,
+    targets: WireTargets,
+    query_transforms: Query<&GlobalTransform, With<GateFan>>,
+    query_cameras: Query<(&Camera, &GlobalTransform)>
+) {
+    for event in over_events.read() {
+        for (mut pending, _, _) in query_pending.iter_mut() {
+            if pending.from == event.dragged && targets.fans.contains(event.target) {
+                pending.target = Some(event.target);
+                pending.valid = targets.new_wire(pending.from, event.target).is_some();
+            }
+        }
+    }
+    for event in leave_events.read() {
+        for (mut pending, _, _) in query_pending.iter_mut() {
+            if pending.from == event.dragged && pending.target == Some(event.target) {
+                pending.target = None;
+                pending.valid = false;
+            }
+        }
+    }
+
+    let camera = query_cameras.iter().find(|(camera, _)| camera.is_active);
+    for event in drag_events.read() {
+        for (pending, mut path, route) in query_pending.iter_mut() {
+            if pending.from != event.target {
+                continue;
+            }
+            let Ok(from) = query_transforms.get(pending.from).map(|t| t.translation()) else {
+                continue;
+            };
+
+            // Snap to the target fan, or project the pointer onto the plane of the fan.
+            let target = pending.target.and_then(|target| query_transforms.get(target).ok());
+            let to = match target {
+                Some(target) => Some(target.translation()),
+                None => {
+                    camera.and_then(|(camera, camera_transform)| {
+                        let position = event.pointer_location.position;
+                        let ray = camera.viewport_to_world(camera_transform, position)?;
+                        let plane = InfinitePlane3d::new(camera_transform.back());
+                        let distance = ray.intersect_plane(from, plane)?;
+                        Some(ray.get_point(distance))
+                    })
+                }
+            };
+
+            if let Some(to) = to {
+                *path = route.copied().unwrap_or_default().path(from, to);
+            }
+        }
+    }
+}
+
+/// Spawn a wire when a [`PendingWire`] is dropped on a valid fan, then despawn the
+/// [`PendingWire`]s of every drag that ended.
+pub fn connect_pending_wires(
+    mut commands: Commands,
+    mut drop_events: EventReader<Pointer<PointerDrop>>,
+    mut end_events: EventReader<Pointer<DragEnd>>,
+    mut created_events: EventWriter<WireCreated>,
+    query_pending: Query<(Entity, &PendingWire)>,
+    targets: WireTargets
+) {
+    for event in drop_events.read() {
+        if !query_pending.iter().any(|(_, pending)| pending.from == event.dropped) {
+            continue;
+        }
+        let Some((from, to)) = targets.new_wire(event.dropped, event.target) else {
+            continue;
+        };
+
+        let wire = commands.spawn_wire_between(from, to);
+        created_events.send(WireCreated { wire, from, to });
+    }
+
+    for event in end_events.read() {
+        for (entity, pending) in query_pending.iter() {
+            if pending.from == event.target {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! Automatic placement of gate fans.
+//!
+//! Add the [`FanLayoutPlugin`] and a [`FanLayout`] to a gate to position its input and output
+//! fans around it, instead of placing each fan while building the gate. Fans are placed again
+//! whenever the layout, the [`GateSize`], or the number of fans changes.
+
+use std::f32::consts::PI;
+
+use bevy::{ prelude::*, transform::TransformSystem };
+
+use crate::{ board::GateOrientation, components::{ GateFan, LogicGateFans } };
+
+pub mod prelude {
+    pub use super::{ FanLayout, FanLayoutPlugin, GateSize };
+}
+
+/// A plugin that positions the fans of every gate with a [`FanLayout`], in [`PostUpdate`]
+/// before transforms are propagated.
+pub struct FanLayoutPlugin;
+
+impl Plugin for FanLayoutPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<GateSize>().add_systems(
+            PostUpdate,
+            layout_fans.before(TransformSystem::TransformPropagate)
+        );
+    }
+}
+
+/// How the fans of a gate are placed around it, relative to its [`GateSize`].
+///
+/// Fans are placed on the XY plane of the gate. Their Z translation is kept.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub enum FanLayout {
+    /// Inputs on the left edge and outputs on the right edge, from top to bottom.
+    #[default]
+    LeftRight,
+    /// Inputs on the top edge and outputs on the bottom edge, from left to right.
+    TopBottom,
+    /// Inputs on the left half of the ellipse that fits the gate, and outputs on the right
+    /// half, from top to bottom.
+    Radial,
+    /// Each fan in the middle of the edge given by [`GateOrientation`], for a gate facing
+    /// east: inputs on the back, left, and right, and the output on the front. Fans without a
+    /// side are placed in the center.
+    ///
+    /// Rotate the gate with a [`GateOrientation`] to turn its fans with it.
+    Sides,
+    /// Place each fan with a function of its kind, its index, the number of fans of its
+    /// kind, and the size of the gate.
+    Custom(fn(&GateFan, usize, usize, Vec2) -> Vec2),
+}
+
+impl FanLayout {
+    /// Returns the position of a fan on the gate's XY plane.
+    ///
+    /// `index` is the index of the fan among the `len` fans of its `kind`.
+    pub fn position(&self, kind: &GateFan, index: usize, len: usize, size: Vec2) -> Vec2 {
+        let half_size = size * 0.5;
+        // How far along its side the fan is, from 0.0 to 1.0 exclusive.
+        let t = ((index + 1) as f32) / ((len + 1) as f32);
+        let side = match kind {
+            GateFan::Input => -1.0,
+            GateFan::Output => 1.0,
+        };
+
+        match self {
+            FanLayout::LeftRight => Vec2::new(side * half_size.x, half_size.y - size.y * t),
+            FanLayout::TopBottom => Vec2::new(size.x * t - half_size.x, -side * half_size.y),
+            FanLayout::Radial => {
+                let angle = PI * 0.5 - side * PI * t;
+                Vec2::new(angle.cos(), angle.sin()) * half_size
+            }
+            FanLayout::Sides => {
+                let edge = match kind {
+                    GateFan::Input => GateOrientation::INPUT_SIDES.get(index),
+                    GateFan::Output => GateOrientation::OUTPUT_SIDES.get(index),
+                };
+                edge.map_or(Vec2::ZERO, |edge| edge.offset().as_vec2() * half_size)
+            }
+            FanLayout::Custom(position) => position(kind, index, len, size),
+        }
+    }
+}
+
+/// The width and height of a gate, used by its [`FanLayout`]. Gates without one are
+/// `1.0` by `1.0`.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct GateSize(pub Vec2);
+
+impl Default for GateSize {
+    fn default() -> Self {
+        Self(Vec2::ONE)
+    }
+}
+
+/// A filter for gates whose fans need to be positioned again.
+type FanLayoutChanged = Or<(Changed<FanLayout>, Changed<GateSize>, Changed<LogicGateFans>)>;
+
+/// Position the fans of each gate whose [`FanLayout`], [`GateSize`], or [`LogicGateFans`]
+/// changed. Fans without a [`Transform`] are given a [`TransformBundle`].
+pub fn layout_fans(
+    mut commands: Commands,
+    query_gates: Query<(&FanLayout, Option<&GateSize>, &LogicGateFans), FanLayoutChanged>,
+    mut query_transforms: Query<&mut Transform, With<GateFan>>
+) {
+    for (layout, size, fans) in query_gates.iter() {
+        let size = size.copied().unwrap_or_default().0;
+        let sides = [
+            (GateFan::Input, &fans.inputs),
+            (GateFan::Output, &fans.outputs),
+        ];
+
+        for (kind, side) in sides {
+            for (index, fan) in side.iter().enumerate() {
+                let Some(fan) = *fan else {
+                    continue;
+                };
+                let position = layout.position(&kind, index, side.len(), size);
+
+                if let Ok(mut transform) = query_transforms.get_mut(fan) {
+                    if transform.translation.xy() != position {
+                        transform.translation = position.extend(transform.translation.z);
+                    }
+                } else if let Some(mut fan) = commands.get_entity(fan) {
+                    fan.insert(
+                        TransformBundle::from_transform(
+                            Transform::from_translation(position.extend(0.0))
+                        )
+                    );
+                }
+            }
+        }
+    }
+}
@@ -1,3 +1,5 @@
+extern crate alloc;
+
 use bevy::prelude::*;
 
 pub mod logic;
@@ -6,6 +8,30 @@ pub mod components;
 pub mod resources;
 pub mod commands;
 pub mod utils;
+pub mod diagnostics;
+pub mod blueprint;
+pub mod power;
+pub mod query;
+pub mod netlist;
+pub mod analysis;
+pub mod snapshot;
+pub mod compiled;
+pub mod packed;
+pub mod routing;
+pub mod layout;
+pub mod board;
+pub mod breakpoint;
+pub mod actuators;
+pub mod sensors;
+pub mod sim;
+#[cfg(any(feature = "render", feature = "render2d"))]
+pub mod render;
+#[cfg(feature = "picking")]
+pub mod interaction;
+#[cfg(feature = "egui")]
+pub mod inspector;
+#[cfg(feature = "audio")]
+pub mod audio;
 
 #[allow(unused_imports)]
 pub mod prelude {
@@ -14,28 +40,202 @@ pub mod prelude {
     pub use crate::resources::prelude::*;
     pub use crate::commands::prelude::*;
     pub use crate::utils::*;
+    pub use crate::diagnostics::prelude::*;
+    pub use crate::blueprint::prelude::*;
+    pub use crate::power::prelude::*;
+    pub use crate::query::prelude::*;
+    pub use crate::netlist::prelude::*;
+    pub use crate::analysis::prelude::*;
+    pub use crate::snapshot::prelude::*;
+    pub use crate::compiled::prelude::*;
+    pub use crate::packed::prelude::*;
+    pub use crate::routing::prelude::*;
+    pub use crate::layout::prelude::*;
+    pub use crate::board::prelude::*;
+    pub use crate::breakpoint::prelude::*;
+    pub use crate::actuators::prelude::*;
+    pub use crate::sensors::prelude::*;
+    pub use crate::sim::prelude::*;
+    #[cfg(any(feature = "render", feature = "render2d"))]
+    pub use crate::render::prelude::*;
+    #[cfg(feature = "picking")]
+    pub use crate::interaction::prelude::*;
+    #[cfg(feature = "egui")]
+    pub use crate::inspector::prelude::*;
+    #[cfg(feature = "audio")]
+    pub use crate::audio::prelude::*;
 
     pub use super::{ LogicSimulationPlugin, LogicReflectPlugin };
 }
 
 /// A plugin group that adds all crate features to an [`App`].
-#[derive(Default)]
-pub struct LogicSimulationPlugin;
+pub struct LogicSimulationPlugin {
+    /// The number of logic steps per second, used to configure [`Time<LogicStep>`].
+    ///
+    /// [`Time<LogicStep>`]: logic::schedule::LogicStep
+    pub tick_rate: f64,
+    /// Which gates are evaluated each step. See [`EvalMode`].
+    ///
+    /// [`EvalMode`]: resources::EvalMode
+    pub eval_mode: resources::EvalMode,
+    /// Caps the work done per logic step. See [`EvalBudget`].
+    ///
+    /// [`EvalBudget`]: resources::EvalBudget
+    pub eval_budget: resources::EvalBudget,
+    /// What happens when the simulation finds inconsistent data. See [`PanicPolicy`].
+    ///
+    /// [`PanicPolicy`]: resources::PanicPolicy
+    pub panic_policy: resources::PanicPolicy,
+    /// How many more times feedback loops are evaluated per step until they settle. See
+    /// [`LogicSettings::settle_limit`].
+    ///
+    /// [`LogicSettings::settle_limit`]: resources::LogicSettings::settle_limit
+    pub settle_limit: u32,
+    /// When gates see the signals written by other gates during a step. See
+    /// [`PropagationMode`].
+    ///
+    /// [`PropagationMode`]: resources::PropagationMode
+    pub propagation: resources::PropagationMode,
+    /// If true, [`systems::sync_logic_graph`] keeps the [`LogicGraph`] in sync with spawned
+    /// and despawned gates and wires each frame, so [`LogicGraph::add_data`] and
+    /// [`LogicGraph::compile`] do not need to be called manually.
+    ///
+    /// The graph is synced in [`LogicSystemSet::SyncGraph`] during [`Update`]. Gates spawned
+    /// in a later schedule are picked up on the next frame.
+    ///
+    /// [`LogicGraph`]: resources::LogicGraph
+    /// [`LogicGraph::add_data`]: resources::LogicGraph::add_data
+    /// [`LogicGraph::compile`]: resources::LogicGraph::compile
+    /// [`LogicSystemSet::SyncGraph`]: logic::schedule::LogicSystemSet::SyncGraph
+    pub auto_sync_graph: bool,
+    /// If true, [`systems::no_eval_output`] runs in [`LogicSystemSet::PropagateNoEval`],
+    /// before gates are evaluated. Disable this if you propagate [`NoEvalOutput`] signals yourself.
+    ///
+    /// [`LogicSystemSet::PropagateNoEval`]: logic::schedule::LogicSystemSet::PropagateNoEval
+    /// [`NoEvalOutput`]: components::NoEvalOutput
+    pub propagate_no_eval: bool,
+}
+
+impl Default for LogicSimulationPlugin {
+    fn default() -> Self {
+        Self {
+            tick_rate: 2.0,
+            eval_mode: Default::default(),
+            eval_budget: Default::default(),
+            panic_policy: Default::default(),
+            settle_limit: 0,
+            propagation: Default::default(),
+            auto_sync_graph: false,
+            propagate_no_eval: true,
+        }
+    }
+}
 
 impl Plugin for LogicSimulationPlugin {
     fn build(&self, app: &mut App) {
         use prelude::*;
 
         app.add_plugins((LogicSchedulePlugin, LogicReflectPlugin, LogicGatePlugin))
-            .insert_resource(Time::<LogicStep>::from_seconds(0.5))
+            .insert_resource(Time::<LogicStep>::from_hz(self.tick_rate))
+            .insert_resource(LogicSettings {
+                eval_mode: self.eval_mode,
+                eval_budget: self.eval_budget,
+                panic_policy: self.panic_policy,
+                settle_limit: self.settle_limit,
+                propagation: self.propagation,
+            })
+            .init_resource::<LogicBacklog>()
             .init_resource::<LogicGraph>()
+            .init_resource::<LogicIslands>()
+            .init_resource::<LogicUuidRegistry>()
+            .add_event::<SignalForced>()
+            .add_event::<SettleFailed>()
+            .add_event::<GateArityViolation>()
+            .add_event::<BudgetExceeded>()
+            .add_event::<GraphCompiled>()
+            .add_event::<BreakpointHit>()
+            .add_systems(
+                LogicUpdate,
+                systems::record_prev_signals.before(LogicSystemSet::PropagateNoEval)
+            )
+            .add_systems(
+                LogicUpdate,
+                systems::resolve_buses
+                    .after(LogicSystemSet::PropagateNoEval)
+                    .before(systems::apply_signal_overrides)
+            )
+            .add_systems(
+                LogicUpdate,
+                systems::apply_signal_overrides
+                    .after(LogicSystemSet::PropagateNoEval)
+                    .before(LogicSystemSet::StepLogic)
+            )
+            .add_systems(
+                Update,
+                systems::release_signal_overrides.in_set(LogicSystemSet::SyncGraph)
+            )
+            .add_systems(
+                Update,
+                systems::sync_eval_priorities.in_set(LogicSystemSet::SyncGraph)
+            )
+            .add_systems(
+                Update,
+                systems::poll_graph_compilation.in_set(LogicSystemSet::SyncGraph)
+            )
+            .add_systems(
+                Update,
+                compiled::invalidate_compiled_circuit
+                    .run_if(resource_exists::<compiled::CompiledCircuit>)
+                    .in_set(LogicSystemSet::SyncGraph)
+            )
+            .add_systems(Update, systems::tag_feedback_loops.after(LogicSystemSet::SyncGraph))
+            .add_systems(Update, systems::validate_gate_arity.after(LogicSystemSet::SyncGraph))
+            .add_systems(
+                Update,
+                systems::enforce_circuit_budgets
+                    .run_if(resource_exists::<CircuitBudgets>)
+                    .before(LogicSystemSet::SyncGraph)
+            )
+            .add_systems(
+                PostUpdate,
+                systems::update_wire_falloff.after(
+                    bevy::transform::TransformSystem::TransformPropagate
+                )
+            )
+            .add_systems(
+                LogicUpdate,
+                breakpoint::check_breakpoints.in_set(LogicSystemSet::PostStep)
+            )
             .add_systems(
                 LogicUpdate,
                 (
-                    systems::no_eval_output.in_set(LogicSystemSet::PropagateNoEval),
-                    systems::step_logic.in_set(LogicSystemSet::StepLogic),
-                ).chain()
+                    compiled::step_compiled_circuit.run_if(
+                        resource_exists::<compiled::CompiledCircuit>
+                    ),
+                    systems::step_logic.run_if(not(resource_exists::<compiled::CompiledCircuit>)),
+                )
+                    .chain()
+                    .in_set(LogicSystemSet::StepLogic)
+            );
+
+        if self.auto_sync_graph {
+            app.add_systems(Update, systems::sync_logic_graph.in_set(LogicSystemSet::SyncGraph));
+        }
+
+        #[cfg(feature = "scene")]
+        app.add_systems(
+            Update,
+            systems::rebuild_graph_on_scene_ready
+                .run_if(resource_exists::<Events<bevy::scene::SceneInstanceReady>>)
+                .in_set(LogicSystemSet::SyncGraph)
+        );
+
+        if self.propagate_no_eval {
+            app.add_systems(
+                LogicUpdate,
+                systems::no_eval_output.in_set(LogicSystemSet::PropagateNoEval)
             );
+        }
     }
 }
 
@@ -47,9 +247,36 @@ impl Plugin for LogicReflectPlugin {
         app.register_type::<Time<logic::schedule::LogicStep>>();
 
         app.register_type::<logic::signal::Signal>()
+            .register_type::<logic::GateArity>()
             .register_type::<components::Wire>()
+            .register_type::<components::WireResistance>()
+            .register_type::<components::WireFalloff>()
+            .register_type::<components::WireChannel>()
+            .register_type::<components::BidirectionalWire>()
+            .register_type::<components::WireDisabled>()
+            .register_type::<components::PrevSignal>()
+            .register_type::<components::SignalOverride>()
+            .register_type::<components::CircuitMember>()
+            .register_type::<components::FeedbackLoopMember>()
+            .register_type::<components::EvalPriority>()
+            .register_type::<components::GateDisabled>()
+            .register_type::<components::LogicUuid>()
             .register_type::<components::GateFan>()
+            .register_type::<components::GateInput>()
+            .register_type::<components::GateOutput>()
+            .register_type::<components::WireSet>()
+            .register_type::<components::ConnectedWires>()
+            .register_type::<components::NoEvalOutput>()
             .register_type::<components::LogicGateFans>()
-            .register_type::<resources::LogicGraph>();
+            .register_type::<components::CircuitId>()
+            .register_type::<resources::LogicGraph>()
+            .register_type::<resources::LogicSettings>()
+            .register_type::<resources::LogicBacklog>()
+            .register_type::<resources::PropagationMode>()
+            .register_type::<logic::schedule::ClockDomain>()
+            .register_type::<logic::schedule::ClockDomains>()
+            .register_type::<resources::SignalFalloff>()
+            .register_type::<resources::CircuitBudget>()
+            .register_type::<components::BudgetKind>();
     }
 }
@@ -0,0 +1,307 @@
+//! Stateful gates for analog and power circuits.
+
+use bevy::prelude::*;
+
+use super::{
+    registry::GateRegistry,
+    schedule::{ LogicSystemSet, LogicUpdate },
+    signal::{ Signal, SignalExt },
+    waveform::WaveformGate,
+    AppLogicGateExt,
+    GateArity,
+    GateContext,
+    LogicGate,
+};
+
+pub(crate) fn register_analog_gates(app: &mut App) {
+    app.register_logic_gate::<WaveformGate>()
+        .register_logic_gate::<Fuse>()
+        .register_logic_gate::<Capacitor>()
+        .register_logic_gate::<Comparator>()
+        .register_logic_gate::<WindowComparator>();
+
+    app.register_gate_arity::<Fuse>(GateArity::inputs(1, Some(2)))
+        .register_gate_arity::<Comparator>(GateArity::exact_inputs(1))
+        .register_gate_arity::<WindowComparator>(GateArity::exact_inputs(1));
+
+    app.world_mut()
+        .resource_mut::<GateRegistry>()
+        .set_default_fans::<WaveformGate>(0, 1)
+        .set_default_fans::<Fuse>(2, 1);
+
+    app.register_type::<WaveformGate>()
+        .register_type::<Fuse>()
+        .register_type::<FuseTripped>()
+        .register_type::<Capacitor>()
+        .register_type::<Comparator>()
+        .register_type::<WindowComparator>()
+        .add_systems(LogicUpdate, sync_fuse_tripped.in_set(LogicSystemSet::PostStep));
+}
+
+/// A fuse passes the signal of its first input to every output until the signal is stronger
+/// than `threshold` for `trip_ticks` ticks in a row. It then trips and turns its outputs off,
+/// until its second input, the reset, turns on.
+///
+/// While the fuse is tripped, the gate entity has a [`FuseTripped`] component, for visuals.
+///
+/// The ticks are counted with the [`LogicTick`]. With [`EvalMode::Dirty`], a fuse is only
+/// evaluated when its inputs change, so it trips at its first evaluation after `trip_ticks`
+/// ticks.
+///
+/// [`LogicTick`]: super::schedule::LogicTick
+/// [`EvalMode::Dirty`]: crate::resources::EvalMode::Dirty
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+pub struct Fuse {
+    /// The strength, as in [`Signal::as_f32`], above which the fuse is overloaded.
+    pub threshold: f32,
+    /// The number of ticks in a row the fuse can be overloaded before it trips.
+    pub trip_ticks: u32,
+    /// Whether the fuse tripped and has not been reset yet.
+    pub tripped: bool,
+    /// The tick the fuse was first overloaded in a row.
+    overloaded_since: Option<u64>,
+    /// The tick of the latest evaluation.
+    last_tick: u64,
+}
+
+impl Default for Fuse {
+    fn default() -> Self {
+        Self::new(1.0, 1)
+    }
+}
+
+impl Fuse {
+    /// Create a fuse that trips after its input is stronger than `threshold` for
+    /// `trip_ticks` ticks.
+    pub fn new(threshold: f32, trip_ticks: u32) -> Self {
+        Self {
+            threshold,
+            trip_ticks,
+            tripped: false,
+            overloaded_since: None,
+            last_tick: 0,
+        }
+    }
+
+    fn update(&mut self, tick: u64, reset: bool, inputs: &[Signal], outputs: &mut [Signal]) {
+        self.last_tick = tick;
+        if reset {
+            self.tripped = false;
+            self.overloaded_since = None;
+        }
+
+        let signal = inputs.first().copied().unwrap_or_default();
+        if self.tripped {
+            outputs.set_all(Signal::OFF);
+            return;
+        }
+
+        if signal.as_f32().abs() > self.threshold {
+            let since = *self.overloaded_since.get_or_insert(tick);
+            if tick.saturating_sub(since) >= u64::from(self.trip_ticks.max(1)) - 1 {
+                self.tripped = true;
+                self.overloaded_since = None;
+                outputs.set_all(Signal::OFF);
+                return;
+            }
+        } else {
+            self.overloaded_since = None;
+        }
+        outputs.set_all(signal);
+    }
+}
+
+impl LogicGate for Fuse {
+    /// Evaluate the fuse as if one tick passed since the last evaluation. The fuse is reset
+    /// while the reset input is on.
+    fn evaluate(&mut self, inputs: &[Signal], outputs: &mut [Signal]) {
+        let reset = inputs.get(1).is_some_and(Signal::is_truthy);
+        self.update(self.last_tick + 1, reset, inputs, outputs);
+    }
+
+    fn evaluate_with_context(
+        &mut self,
+        ctx: &GateContext,
+        inputs: &[Signal],
+        outputs: &mut [Signal]
+    ) {
+        let reset =
+            ctx.previous_input(1).is_falsy() && inputs.get(1).is_some_and(Signal::is_truthy);
+        self.update(ctx.tick, reset, inputs, outputs);
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.threshold, self.trip_ticks);
+    }
+}
+
+/// Marks a [`Fuse`] gate that is tripped. It is kept in sync with [`Fuse::tripped`] by
+/// [`sync_fuse_tripped`] after every step.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+pub struct FuseTripped;
+
+/// Insert a [`FuseTripped`] on every tripped [`Fuse`], and remove it from the others.
+pub fn sync_fuse_tripped(
+    mut commands: Commands,
+    query: Query<(Entity, &Fuse, Has<FuseTripped>), Changed<Fuse>>
+) {
+    for (entity, fuse, has_tripped) in query.iter() {
+        match (fuse.tripped, has_tripped) {
+            (true, false) => {
+                commands.entity(entity).insert(FuseTripped);
+            }
+            (false, true) => {
+                commands.entity(entity).remove::<FuseTripped>();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A capacitor stores the strength of its inputs as charge, and releases it onto its outputs,
+/// for smoothing and delaying analog signals and for energy mechanics.
+///
+/// Each evaluation, the sum of the input strengths, up to `charge_rate`, is added to the
+/// charge, which is capped at `capacity`. Then up to `discharge_rate` of the charge is
+/// removed and emitted as an analog signal on every output. Negative inputs don't drain it.
+///
+/// Like a clock, the capacitor changes its outputs on its own while it discharges, so it
+/// should not be used with [`EvalMode::Dirty`].
+///
+/// [`EvalMode::Dirty`]: crate::resources::EvalMode::Dirty
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+pub struct Capacitor {
+    /// The most charge the capacitor stores.
+    pub capacity: f32,
+    /// The most charge added per tick.
+    pub charge_rate: f32,
+    /// The most charge released per tick.
+    pub discharge_rate: f32,
+    /// The stored charge.
+    pub charge: f32,
+}
+
+impl Default for Capacitor {
+    fn default() -> Self {
+        Self::new(1.0, 0.1, 0.1)
+    }
+}
+
+impl Capacitor {
+    /// Create an empty capacitor.
+    pub fn new(capacity: f32, charge_rate: f32, discharge_rate: f32) -> Self {
+        Self {
+            capacity,
+            charge_rate,
+            discharge_rate,
+            charge: 0.0,
+        }
+    }
+
+    /// Start with `charge`, capped at the capacity.
+    pub fn with_charge(mut self, charge: f32) -> Self {
+        self.charge = charge.clamp(0.0, self.capacity.max(0.0));
+        self
+    }
+
+    /// Returns the charge as a fraction of the capacity, from `0.0` to `1.0`.
+    pub fn level(&self) -> f32 {
+        if self.capacity > 0.0 { (self.charge / self.capacity).clamp(0.0, 1.0) } else { 0.0 }
+    }
+}
+
+impl LogicGate for Capacitor {
+    fn evaluate(&mut self, inputs: &[Signal], outputs: &mut [Signal]) {
+        let charge_rate = self.charge_rate.max(0.0);
+        let input = inputs.iter().map(Signal::as_f32).sum::<f32>().clamp(0.0, charge_rate);
+        self.charge = (self.charge + input).min(self.capacity);
+
+        let output = self.charge.min(self.discharge_rate).max(0.0);
+        self.charge -= output;
+        outputs.set_all(Signal::Analog(output));
+    }
+
+    fn reset(&mut self) {
+        self.charge = 0.0;
+    }
+}
+
+/// A comparator turns its outputs on while the strength of its first input is above
+/// `threshold`, converting an analog signal to a digital one.
+///
+/// With a `hysteresis`, the input has to rise above `threshold + hysteresis / 2.0` to turn
+/// the outputs on, and fall to `threshold - hysteresis / 2.0` to turn them off again, so a
+/// noisy signal near the threshold doesn't make the outputs flicker.
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+pub struct Comparator {
+    pub threshold: f32,
+    /// The width of the band around `threshold` in which the outputs keep their state.
+    pub hysteresis: f32,
+    /// Whether the outputs are on.
+    pub on: bool,
+}
+
+impl Comparator {
+    /// Create a comparator without hysteresis.
+    pub fn new(threshold: f32) -> Self {
+        Self {
+            threshold,
+            hysteresis: 0.0,
+            on: false,
+        }
+    }
+
+    pub fn with_hysteresis(mut self, hysteresis: f32) -> Self {
+        self.hysteresis = hysteresis;
+        self
+    }
+}
+
+impl LogicGate for Comparator {
+    fn evaluate(&mut self, inputs: &[Signal], outputs: &mut [Signal]) {
+        let value = inputs.first().map_or(0.0, Signal::as_f32);
+        let half_band = self.hysteresis.abs() * 0.5;
+
+        if value > self.threshold + half_band {
+            self.on = true;
+        } else if value <= self.threshold - half_band {
+            self.on = false;
+        }
+        outputs.set_all(self.on.into());
+    }
+
+    fn reset(&mut self) {
+        self.on = false;
+    }
+}
+
+/// A window comparator turns its outputs on while the strength of its first input is between
+/// `low` and `high`, inclusive.
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+pub struct WindowComparator {
+    pub low: f32,
+    pub high: f32,
+}
+
+impl WindowComparator {
+    pub fn new(low: f32, high: f32) -> Self {
+        Self { low, high }
+    }
+}
+
+impl LogicGate for WindowComparator {
+    fn evaluate(&mut self, inputs: &[Signal], outputs: &mut [Signal]) {
+        let value = inputs.first().map_or(0.0, Signal::as_f32);
+        outputs.set_all((self.low..=self.high).contains(&value).into());
+    }
+}
@@ -0,0 +1,206 @@
+//! Circuit generators for benchmarks and for sizing worlds.
+//!
+//! Each generator spawns a circuit of basic gates of a given size into a [`World`], and adds
+//! it to the [`LogicGraph`] without compiling it, so the compilation can be measured too. The
+//! random generators take a seed, and spawn the same circuit for the same seed and size.
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_logic::prelude::*;
+//! # use bevy_logic::logic::bench_util;
+//! fn measure(world: &mut World) {
+//!     let circuit = bench_util::random_dag(world, 10_000, 16, 7);
+//!     world.resource_mut::<LogicGraph>().compile();
+//!     world.step_logic_once();
+//!     println!("{} gates, {} wires", circuit.gates.len(), circuit.wires.len());
+//! }
+//! ```
+//!
+//! [`LogicGraph`]: crate::resources::LogicGraph
+
+use bevy::prelude::*;
+
+use crate::resources::LogicGraph;
+
+use super::{
+    builder::{ GateData, Known, LogicExt, WireData },
+    gates::{ AndGate, Battery, NotGate, OrGate, XorGate },
+};
+
+/// The gates and wires spawned by a generator.
+#[derive(Clone, Debug, Default)]
+pub struct GeneratedCircuit {
+    /// Every gate, sources first.
+    pub gates: Vec<GateData<Known, Known>>,
+    pub wires: Vec<WireData>,
+    /// The [`Battery`] gates that drive the circuit.
+    pub sources: Vec<Entity>,
+}
+
+impl GeneratedCircuit {
+    fn source(&mut self, world: &mut World, signal: bool) -> usize {
+        let battery = Battery::new(signal.into());
+        let gate = world.spawn_gate(battery).with_inputs(0).with_outputs(1).build();
+        self.sources.push(gate.id());
+        self.gates.push(gate);
+        self.gates.len() - 1
+    }
+
+    fn gate(&mut self, world: &mut World, bundle: impl Bundle, inputs: usize) -> usize {
+        let gate = world.spawn_gate(bundle).with_inputs(inputs).with_outputs(1).build();
+        self.gates.push(gate);
+        self.gates.len() - 1
+    }
+
+    fn wire(&mut self, world: &mut World, from: usize, to: usize, input: usize) {
+        let wire = world.spawn_wire(&self.gates[from], 0, &self.gates[to], input).downgrade();
+        self.wires.push(wire);
+    }
+
+    /// Add every gate and wire to the [`LogicGraph`].
+    fn add_to_graph(self, world: &mut World) -> Self {
+        world
+            .resource_mut::<LogicGraph>()
+            .add_data(self.gates.clone())
+            .add_data(self.wires.clone());
+        self
+    }
+}
+
+/// A small, seeded pseudo-random number generator (SplitMix64), so generated circuits are
+/// the same on every run and platform.
+#[derive(Clone, Copy, Debug)]
+pub struct BenchRng(u64);
+
+impl BenchRng {
+    /// Create a generator that starts from `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// Returns the next random number.
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a random number in `0..bound`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bound` is zero.
+    pub fn below(&mut self, bound: usize) -> usize {
+        assert!(bound > 0, "bound must be positive");
+        (self.next_u64() % (bound as u64)) as usize
+    }
+
+    /// Returns `true` or `false` with the same probability.
+    pub fn bool(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+}
+
+/// Spawn a [`Battery`] followed by a chain of `len` [`NotGate`]s, each driving the next.
+///
+/// A chain is as deep as a circuit of its size can be, so a signal needs the whole step to
+/// pass through it.
+pub fn chain(world: &mut World, len: usize) -> GeneratedCircuit {
+    let mut circuit = GeneratedCircuit::default();
+    let mut previous = circuit.source(world, true);
+    for _ in 0..len {
+        let gate = circuit.gate(world, NotGate, 1);
+        circuit.wire(world, previous, gate, 0);
+        previous = gate;
+    }
+    circuit.add_to_graph(world)
+}
+
+/// Spawn a [`Battery`] whose single output drives `width` [`NotGate`]s.
+///
+/// Every wire comes from the same output, so this measures writing through a large
+/// [`GateOutput::wires`] set.
+///
+/// [`GateOutput::wires`]: crate::components::GateOutput::wires
+pub fn fanout(world: &mut World, width: usize) -> GeneratedCircuit {
+    let mut circuit = GeneratedCircuit::default();
+    let source = circuit.source(world, true);
+    for _ in 0..width {
+        let gate = circuit.gate(world, NotGate, 1);
+        circuit.wire(world, source, gate, 0);
+    }
+    circuit.add_to_graph(world)
+}
+
+/// Spawn a random acyclic circuit of `gates` two-input gates, driven by `sources` batteries.
+///
+/// Each input is wired to the output of a random earlier gate or battery, so the circuit
+/// has no feedback loops, and gates are a random mix of AND, OR, and XOR gates.
+///
+/// # Panics
+///
+/// Panics if `sources` is zero and `gates` is not.
+pub fn random_dag(
+    world: &mut World,
+    gates: usize,
+    sources: usize,
+    seed: u64
+) -> GeneratedCircuit {
+    let mut rng = BenchRng::new(seed);
+    let mut circuit = GeneratedCircuit::default();
+    for _ in 0..sources {
+        circuit.source(world, rng.bool());
+    }
+    for _ in 0..gates {
+        let gate = random_gate(world, &mut circuit, &mut rng);
+        for input in 0..2 {
+            let from = rng.below(gate);
+            circuit.wire(world, from, gate, input);
+        }
+    }
+    circuit.add_to_graph(world)
+}
+
+/// Spawn `loops` feedback loops of `loop_len` two-input gates each, for circuits where most
+/// gates are part of a strongly connected component.
+///
+/// The first input of each gate is wired to the previous gate of its loop, closing the
+/// loop, and the second to a random gate of the previous loop, or to a battery for the first
+/// loop. Gates are a random mix of AND, OR, and XOR gates.
+pub fn scc_heavy(
+    world: &mut World,
+    loops: usize,
+    loop_len: usize,
+    seed: u64
+) -> GeneratedCircuit {
+    let mut rng = BenchRng::new(seed);
+    let mut circuit = GeneratedCircuit::default();
+    let source = circuit.source(world, true);
+    let mut previous_loop = source..source + 1;
+    for _ in 0..loops {
+        let start = circuit.gates.len();
+        for _ in 0..loop_len {
+            random_gate(world, &mut circuit, &mut rng);
+        }
+        let end = circuit.gates.len();
+        for gate in start..end {
+            let previous = if gate == start { end - 1 } else { gate - 1 };
+            circuit.wire(world, previous, gate, 0);
+            let from = previous_loop.start + rng.below(previous_loop.len());
+            circuit.wire(world, from, gate, 1);
+        }
+        previous_loop = start..end;
+    }
+    circuit.add_to_graph(world)
+}
+
+/// Spawn a random two-input gate, and return its index.
+fn random_gate(world: &mut World, circuit: &mut GeneratedCircuit, rng: &mut BenchRng) -> usize {
+    match rng.below(3) {
+        0 => circuit.gate(world, AndGate::default(), 2),
+        1 => circuit.gate(world, OrGate::default(), 2),
+        _ => circuit.gate(world, XorGate, 2),
+    }
+}
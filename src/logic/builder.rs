@@ -1,9 +1,21 @@
 use std::marker::PhantomData;
-use bevy::{ ecs::system::EntityCommands, prelude::* };
+use bevy::{ ecs::{ system::EntityCommands, world::Command }, prelude::* };
 use crate::{
-    commands::UpdateOutputWireSet,
-    components::{ GateOutput, InputBundle, LogicGateFans, OutputBundle, Wire, WireBundle },
-    logic::signal::Signal,
+    commands::{ AddWireToLogicGraph, DespawnCircuit, UpdateOutputWireSet },
+    components::{
+        BidirectionalWire,
+        CircuitId,
+        CircuitMember,
+        ConnectedWires,
+        GateOutput,
+        InputBundle,
+        LogicGateFans,
+        LogicUuid,
+        OutputBundle,
+        Wire,
+        WireBundle,
+    },
+    logic::{ gates::Junction, signal::Signal },
 };
 
 /// A builder trait that helps construct logic gate hierarchies and wires.
@@ -31,6 +43,40 @@ pub trait LogicExt {
     /// Spawn a wire that connects two fans. The output entity **must** have a [`NoEvalOutput`] component
     /// and not require evaluation or ordering in the [`LogicGraph`] resource.
     fn spawn_no_eval_wire(&mut self, from_output: Entity, to_input: Entity) -> Entity;
+
+    /// Spawn a wire that connects two fan entities, then add it to the [`LogicGraph`] resource
+    /// with [`AddWireToLogicGraph`].
+    ///
+    /// Unlike [`LogicExt::spawn_wire`], this only needs the fan entities, so wires can be created
+    /// from entities picked in an editor. The parent gates are looked up from the fans.
+    ///
+    /// # Panics
+    ///
+    /// Panics (when applied) if `from_output` does not have a [`GateOutput`] component,
+    /// or if either fan does not have a parent gate.
+    ///
+    /// [`LogicGraph`]: crate::resources::LogicGraph
+    fn spawn_wire_between(&mut self, from_output: Entity, to_input: Entity) -> Entity;
+
+    /// Spawn a [`BidirectionalWire`] between two fans of any kind. It is not added to the
+    /// [`LogicGraph`], and neither fan's wire set is updated.
+    ///
+    /// [`LogicGraph`]: crate::resources::LogicGraph
+    fn spawn_bidirectional_wire(&mut self, a: Entity, b: Entity) -> Entity;
+
+    /// Despawn every gate, fan, and wire of a circuit with [`DespawnCircuit`].
+    fn despawn_circuit(&mut self, circuit: CircuitId);
+
+    /// Spawn a [`Junction`] with one input and `outputs` outputs.
+    ///
+    /// ```ignore
+    /// let split = commands.junction(3);
+    /// commands.spawn_wire(&source, 0, &split, 0);
+    /// for (output, target) in targets.iter().enumerate() {
+    ///     commands.spawn_wire(&split, output, target, 0);
+    /// }
+    /// ```
+    fn junction(&mut self, outputs: usize) -> GateData<Known, Known>;
 }
 
 impl LogicExt for World {
@@ -46,6 +92,7 @@ impl LogicExt for World {
             data: GateData {
                 entity,
                 fans: LogicGateFans::default(),
+                circuit: None,
                 _state: PhantomData,
             },
         }
@@ -74,11 +121,17 @@ impl LogicExt for World {
     ) -> WireBuilder<'_, Self::WireBuilder> {
         let from = from_gate.output(from_output);
         let to = to_gate.input(to_input);
-        let entity = self.spawn((Signal::Undefined, Wire::new(from, to))).id();
+        let entity = self.spawn((Signal::Undefined, Wire::new(from, to), LogicUuid::new())).id();
 
         self.get_mut::<GateOutput>(from)
             .expect("from_gate entity does not have GateOutput component")
             .wires.insert(entity);
+        self.get_mut::<ConnectedWires>(to)
+            .expect("to_gate entity does not have ConnectedWires component")
+            .wires.insert(entity);
+        if let Some(circuit) = shared_circuit(from_gate, to_gate) {
+            self.entity_mut(entity).insert(CircuitMember(circuit));
+        }
 
         WireBuilder {
             cmd: self,
@@ -94,21 +147,64 @@ impl LogicExt for World {
 
     fn spawn_no_eval_wire(&mut self, from_output: Entity, to_input: Entity) -> Entity {
         let wire_entity = self
-            .spawn(WireBundle {
-                wire: Wire {
-                    from: from_output,
-                    to: to_input,
+            .spawn((
+                WireBundle {
+                    wire: Wire {
+                        from: from_output,
+                        to: to_input,
+                    },
+                    signal: Signal::default(),
                 },
-                signal: Signal::default(),
-            })
+                LogicUuid::new(),
+            ))
             .id();
 
         self.get_mut::<GateOutput>(from_output)
             .expect("from_output entity does not have GateOutput component")
             .wires.insert(wire_entity);
+        self.get_mut::<ConnectedWires>(to_input)
+            .expect("to_input entity does not have ConnectedWires component")
+            .wires.insert(wire_entity);
 
         wire_entity
     }
+
+    fn spawn_wire_between(&mut self, from_output: Entity, to_input: Entity) -> Entity {
+        let wire_entity = self
+            .spawn((
+                WireBundle {
+                    wire: Wire::new(from_output, to_input),
+                    signal: Signal::Undefined,
+                },
+                LogicUuid::new(),
+            ))
+            .id();
+
+        AddWireToLogicGraph(wire_entity).apply(self);
+
+        wire_entity
+    }
+
+    fn spawn_bidirectional_wire(&mut self, a: Entity, b: Entity) -> Entity {
+        self
+            .spawn((
+                WireBundle {
+                    wire: Wire::new(a, b),
+                    signal: Signal::Undefined,
+                },
+                BidirectionalWire,
+                LogicUuid::new(),
+            ))
+            .id()
+    }
+
+    fn despawn_circuit(&mut self, circuit: CircuitId) {
+        DespawnCircuit(circuit).apply(self);
+    }
+
+    fn junction(&mut self, outputs: usize) -> GateData<Known, Known> {
+        self.spawn_gate(Junction).with_inputs(1).with_outputs(outputs).build()
+    }
 }
 
 impl<'w, 's> LogicExt for Commands<'w, 's> {
@@ -124,6 +220,7 @@ impl<'w, 's> LogicExt for Commands<'w, 's> {
             data: GateData {
                 entity,
                 fans: LogicGateFans::default(),
+                circuit: None,
                 _state: PhantomData,
             },
         }
@@ -152,9 +249,12 @@ impl<'w, 's> LogicExt for Commands<'w, 's> {
     ) -> WireBuilder<'_, Self::WireBuilder> {
         let from = from_gate.output(from_output);
         let to = to_gate.input(to_input);
-        let entity = self.spawn((Signal::Undefined, Wire::new(from, to))).id();
+        let entity = self.spawn((Signal::Undefined, Wire::new(from, to), LogicUuid::new())).id();
 
         self.add(UpdateOutputWireSet::Add { output_entity: from, wire_entity: entity });
+        if let Some(circuit) = shared_circuit(from_gate, to_gate) {
+            self.entity(entity).insert(CircuitMember(circuit));
+        }
 
         WireBuilder {
             cmd: self,
@@ -170,13 +270,16 @@ impl<'w, 's> LogicExt for Commands<'w, 's> {
 
     fn spawn_no_eval_wire(&mut self, from_output: Entity, to_input: Entity) -> Entity {
         let wire_entity = self
-            .spawn(WireBundle {
-                wire: Wire {
-                    from: from_output,
-                    to: to_input,
+            .spawn((
+                WireBundle {
+                    wire: Wire {
+                        from: from_output,
+                        to: to_input,
+                    },
+                    signal: Signal::default(),
                 },
-                signal: Signal::default(),
-            })
+                LogicUuid::new(),
+            ))
             .id();
 
         self.add(UpdateOutputWireSet::Add {
@@ -186,6 +289,48 @@ impl<'w, 's> LogicExt for Commands<'w, 's> {
 
         wire_entity
     }
+
+    fn spawn_wire_between(&mut self, from_output: Entity, to_input: Entity) -> Entity {
+        let wire_entity = self
+            .spawn((
+                WireBundle {
+                    wire: Wire::new(from_output, to_input),
+                    signal: Signal::Undefined,
+                },
+                LogicUuid::new(),
+            ))
+            .id();
+
+        self.add(AddWireToLogicGraph(wire_entity));
+
+        wire_entity
+    }
+
+    fn spawn_bidirectional_wire(&mut self, a: Entity, b: Entity) -> Entity {
+        self
+            .spawn((
+                WireBundle {
+                    wire: Wire::new(a, b),
+                    signal: Signal::Undefined,
+                },
+                BidirectionalWire,
+                LogicUuid::new(),
+            ))
+            .id()
+    }
+
+    fn despawn_circuit(&mut self, circuit: CircuitId) {
+        self.add(DespawnCircuit(circuit));
+    }
+
+    fn junction(&mut self, outputs: usize) -> GateData<Known, Known> {
+        self.spawn_gate(Junction).with_inputs(1).with_outputs(outputs).build()
+    }
+}
+
+/// Returns the circuit two gates belong to, if they are in the same one.
+fn shared_circuit<I, O, J, P>(a: &GateData<I, O>, b: &GateData<J, P>) -> Option<CircuitId> {
+    a.circuit.filter(|&circuit| b.circuit == Some(circuit))
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -198,6 +343,7 @@ pub struct Known;
 pub struct GateData<I = Unknown, O = Unknown> {
     entity: Entity,
     fans: LogicGateFans,
+    circuit: Option<CircuitId>,
     _state: PhantomData<(I, O)>,
 }
 
@@ -213,6 +359,11 @@ impl<I, O> GateData<I, O> {
     pub fn outputs(&self) -> &[Option<Entity>] {
         &self.fans.outputs
     }
+
+    /// Returns the circuit the gate belongs to, if any.
+    pub fn circuit(&self) -> Option<CircuitId> {
+        self.circuit
+    }
 }
 
 impl<O> GateData<Known, O> {
@@ -277,6 +428,13 @@ impl<'a, I, O> GateBuilder<'a, World, I, O> {
         self.cmd
     }
 
+    /// Add the gate and its fans to a circuit when it is built. Wires spawned between two
+    /// gates of the same circuit join it as well.
+    pub fn in_circuit(mut self, circuit: CircuitId) -> Self {
+        self.data.circuit = Some(circuit);
+        self
+    }
+
     pub fn entity_commands(&mut self) -> EntityWorldMut<'_> {
         self.cmd.entity_mut(self.data.entity)
     }
@@ -309,6 +467,7 @@ impl<'a, O> GateBuilder<'a, World, Unknown, O> {
                     inputs,
                     outputs: self.data.fans.outputs,
                 },
+                circuit: self.data.circuit,
                 _state: PhantomData,
             },
         }
@@ -340,6 +499,7 @@ impl<'a, O> GateBuilder<'a, World, Unknown, O> {
                     inputs,
                     outputs: self.data.fans.outputs,
                 },
+                circuit: self.data.circuit,
                 _state: PhantomData,
             },
         }
@@ -363,6 +523,7 @@ impl<'a, I> GateBuilder<'a, World, I, Unknown> {
                     inputs: self.data.fans.inputs,
                     outputs,
                 },
+                circuit: self.data.circuit,
                 _state: PhantomData,
             },
         }
@@ -394,6 +555,7 @@ impl<'a, I> GateBuilder<'a, World, I, Unknown> {
                     inputs: self.data.fans.inputs,
                     outputs,
                 },
+                circuit: self.data.circuit,
                 _state: PhantomData,
             },
         }
@@ -402,21 +564,30 @@ impl<'a, I> GateBuilder<'a, World, I, Unknown> {
 
 impl<'a, I, O> GateBuilder<'a, World, I, O> {
     /// Finalize construction of the gate hierarchy, link children, and insert a [`LogicEntity`]
-    /// component into the root entity from [`Self::data`].
+    /// component into the root entity from [`Self::data`]. The gate gets a new [`LogicUuid`]
+    /// unless it already has one.
     ///
     /// Returns [`Self::data`], which can be used to wire inputs/outputs together
     /// by their [`Entity`] IDs and link gates in a logic graph.
     pub fn build(self) -> GateData<I, O> {
+        let fans = self.data.fans
+            .some_inputs()
+            .into_iter()
+            .chain(self.data.fans.some_outputs())
+            .collect::<Vec<_>>();
         self.cmd
             .entity_mut(self.data.entity)
-            .push_children(
-                &self.data.fans
-                    .some_inputs()
-                    .into_iter()
-                    .chain(self.data.fans.some_outputs())
-                    .collect::<Vec<_>>()
-            )
+            .push_children(&fans)
             .insert(self.data.fans.clone());
+        if !self.cmd.entity(self.data.entity).contains::<LogicUuid>() {
+            self.cmd.entity_mut(self.data.entity).insert(LogicUuid::new());
+        }
+
+        if let Some(circuit) = self.data.circuit {
+            for entity in fans.into_iter().chain([self.data.entity]) {
+                self.cmd.entity_mut(entity).insert(CircuitMember(circuit));
+            }
+        }
 
         self.data
     }
@@ -429,6 +600,13 @@ impl<'w, 's, 'a, I, O> GateBuilder<'a, Commands<'w, 's>, I, O> {
         self.cmd.entity(self.data.entity)
     }
 
+    /// Add the gate and its fans to a circuit when it is built. Wires spawned between two
+    /// gates of the same circuit join it as well.
+    pub fn in_circuit(mut self, circuit: CircuitId) -> Self {
+        self.data.circuit = Some(circuit);
+        self
+    }
+
     pub fn insert_bundle(mut self, bundle: impl Bundle) -> Self {
         self.entity_commands().insert(bundle);
         self
@@ -457,6 +635,7 @@ impl<'w, 's, 'a, O> GateBuilder<'a, Commands<'w, 's>, Unknown, O> {
                     inputs,
                     outputs: self.data.fans.outputs,
                 },
+                circuit: self.data.circuit,
                 _state: PhantomData,
             },
         }
@@ -488,6 +667,7 @@ impl<'w, 's, 'a, O> GateBuilder<'a, Commands<'w, 's>, Unknown, O> {
                     inputs,
                     outputs: self.data.fans.outputs,
                 },
+                circuit: self.data.circuit,
                 _state: PhantomData,
             },
         }
@@ -511,6 +691,7 @@ impl<'w, 's, 'a, I> GateBuilder<'a, Commands<'w, 's>, I, Unknown> {
                     inputs: self.data.fans.inputs,
                     outputs,
                 },
+                circuit: self.data.circuit,
                 _state: PhantomData,
             },
         }
@@ -542,6 +723,7 @@ impl<'w, 's, 'a, I> GateBuilder<'a, Commands<'w, 's>, I, Unknown> {
                     inputs: self.data.fans.inputs,
                     outputs,
                 },
+                circuit: self.data.circuit,
                 _state: PhantomData,
             },
         }
@@ -550,21 +732,32 @@ impl<'w, 's, 'a, I> GateBuilder<'a, Commands<'w, 's>, I, Unknown> {
 
 impl<'w, 's, 'a, I, O> GateBuilder<'a, Commands<'w, 's>, I, O> {
     /// Finalize construction of the gate hierarchy, link children, and insert a [`LogicEntity`]
-    /// component into the root entity from [`Self::data`].
+    /// component into the root entity from [`Self::data`]. The gate gets a new [`LogicUuid`]
+    /// unless it already has one.
     ///
     /// Returns [`Self::data`], which can be used to wire inputs/outputs together
     /// by their [`Entity`] IDs and link gates in a logic graph.
     pub fn build(self) -> GateData<I, O> {
+        let fans = self.data.fans
+            .some_inputs()
+            .into_iter()
+            .chain(self.data.fans.some_outputs())
+            .collect::<Vec<_>>();
         self.cmd
             .entity(self.data.entity)
-            .push_children(
-                &self.data.fans
-                    .some_inputs()
-                    .into_iter()
-                    .chain(self.data.fans.some_outputs())
-                    .collect::<Vec<_>>()
-            )
+            .push_children(&fans)
             .insert(self.data.fans.clone());
+        self.cmd.entity(self.data.entity).add(|mut gate: EntityWorldMut| {
+            if !gate.contains::<LogicUuid>() {
+                gate.insert(LogicUuid::new());
+            }
+        });
+
+        if let Some(circuit) = self.data.circuit {
+            for entity in fans.into_iter().chain([self.data.entity]) {
+                self.cmd.entity(entity).insert(CircuitMember(circuit));
+            }
+        }
 
         self.data
     }
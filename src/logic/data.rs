@@ -0,0 +1,101 @@
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+
+use crate::components::{ GateInput, GateOutput, Wire, WireChannel, WireDisabled };
+
+use super::schedule::{ LogicSystemSet, LogicUpdate };
+
+/// A value that can be routed through the wire network with a [`DataSignal`].
+pub trait DataPayload: Clone + PartialEq + Send + Sync + 'static {}
+
+impl<T: Clone + PartialEq + Send + Sync + 'static> DataPayload for T {}
+
+/// A secondary channel that carries arbitrary game data (item IDs, colors, small structs)
+/// through the same wires as [`Signal`].
+///
+/// Add a `DataSignal<T>` to output and input fans that should exchange data. Wires do not
+/// need the component, but if a wire has one it mirrors the last value sent through it.
+///
+/// Data is propagated by [`DataSignalPlugin<T>`], right after [`Signal`]s are evaluated.
+/// Gates that read or write data should do so in their own systems, since [`LogicGate`]
+/// only operates on [`Signal`]s.
+///
+/// [`Signal`]: super::signal::Signal
+/// [`LogicGate`]: super::LogicGate
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct DataSignal<T: DataPayload>(pub Option<T>);
+
+impl<T: DataPayload> Default for DataSignal<T> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<T: DataPayload> DataSignal<T> {
+    /// Create a new signal holding `value`.
+    pub fn new(value: T) -> Self {
+        Self(Some(value))
+    }
+
+    /// Returns a reference to the value, if any.
+    pub fn get(&self) -> Option<&T> {
+        self.0.as_ref()
+    }
+}
+
+/// A plugin that propagates [`DataSignal<T>`] values from output fans, through wires, to input fans.
+///
+/// Add one instance per payload type.
+pub struct DataSignalPlugin<T: DataPayload>(PhantomData<T>);
+
+impl<T: DataPayload> Default for DataSignalPlugin<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: DataPayload> Plugin for DataSignalPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            LogicUpdate,
+            propagate_data_signals::<T>.in_set(LogicSystemSet::StepLogic).after(
+                crate::systems::step_logic
+            )
+        );
+    }
+}
+
+/// Copy each output fan's [`DataSignal<T>`] to its out-going wires and their input fans.
+///
+/// Like [`Signal`]s, data is not copied to input fans on another [`WireChannel`], and a
+/// [`WireDisabled`] wire carries no value.
+///
+/// [`Signal`]: super::signal::Signal
+pub fn propagate_data_signals<T: DataPayload>(
+    query_outputs: Query<(&GateOutput, &DataSignal<T>)>,
+    query_wires: Query<(&Wire, Option<&WireChannel>, Has<WireDisabled>)>,
+    query_channels: Query<&WireChannel, With<GateInput>>,
+    mut query_wire_data: Query<&mut DataSignal<T>, (Without<GateOutput>, Without<GateInput>)>,
+    mut query_inputs: Query<&mut DataSignal<T>, (With<GateInput>, Without<GateOutput>)>
+) {
+    for (output, data) in query_outputs.iter() {
+        for &wire_entity in output.wires.iter() {
+            let Ok((wire, channel, disabled)) = query_wires.get(wire_entity) else {
+                continue;
+            };
+            let data = if disabled { DataSignal(None) } else { data.clone() };
+
+            if let Ok(mut wire_data) = query_wire_data.get_mut(wire_entity) {
+                wire_data.set_if_neq(data.clone());
+            }
+
+            if !WireChannel::connects(channel, query_channels.get(wire.to).ok()) {
+                continue;
+            }
+            if let Ok(mut input_data) = query_inputs.get_mut(wire.to) {
+                input_data.set_if_neq(data);
+            }
+        }
+    }
+}
@@ -0,0 +1,257 @@
+use std::sync::Arc;
+
+use bevy::{ prelude::*, utils::HashMap };
+
+use super::{ signal::Signal, LogicGate };
+
+/// A function that evaluates a [`DynamicGate`] with its reflected state.
+pub type DynamicEvaluateFn = Arc<dyn Fn(&mut dyn Reflect, &[Signal], &mut [Signal]) + Send + Sync>;
+
+/// The definition of a gate type registered at runtime.
+pub struct DynamicGateDefinition {
+    /// The initial state of every gate of this kind. It is cloned into each gate when resolved.
+    pub prototype: Box<dyn Reflect>,
+    /// The function that evaluates gates of this kind.
+    pub evaluate: DynamicEvaluateFn,
+}
+
+impl Clone for DynamicGateDefinition {
+    fn clone(&self) -> Self {
+        Self {
+            prototype: self.prototype.clone_value(),
+            evaluate: self.evaluate.clone(),
+        }
+    }
+}
+
+/// A resource that stores gate types registered at runtime, keyed by their kind.
+///
+/// This lets modding and scripting layers add new gates without compile-time types. Gates
+/// are spawned with a [`DynamicGate`] component naming their kind, and are resolved against
+/// this registry before each logic step.
+///
+/// ```no_run
+/// # use bevy::{ prelude::*, reflect::DynamicStruct };
+/// # use bevy_logic::prelude::*;
+/// fn register_threshold_gate(mut registry: ResMut<DynamicGateRegistry>) {
+///     let mut state = DynamicStruct::default();
+///     state.insert("threshold", 0.5f32);
+///
+///     registry.register("threshold", state, |state, inputs, outputs| {
+///         let threshold = state.path::<f32>("threshold").copied().unwrap_or_default();
+///         let high = matches!(inputs.first(), Some(Signal::Analog(value)) if *value >= threshold);
+///         outputs.set_all(Signal::from(high));
+///     });
+/// }
+/// ```
+#[derive(Resource, Clone, Default)]
+pub struct DynamicGateRegistry {
+    definitions: HashMap<String, DynamicGateDefinition>,
+    /// The generation each kind was last registered in, so gates only resolve again when
+    /// their own kind changes.
+    generations: HashMap<String, u64>,
+    next_generation: u64,
+}
+
+impl DynamicGateRegistry {
+    /// Register a gate `kind` with its initial `prototype` state and `evaluate` function.
+    ///
+    /// Registering a kind again replaces its definition. Existing gates of that kind are
+    /// resolved again and their state is reset to the new prototype. Gates of other kinds
+    /// keep their state.
+    pub fn register(
+        &mut self,
+        kind: impl Into<String>,
+        prototype: impl Reflect,
+        evaluate: impl Fn(&mut dyn Reflect, &[Signal], &mut [Signal]) + Send + Sync + 'static
+    ) -> &mut Self {
+        let kind = kind.into();
+        self.next_generation += 1;
+        self.generations.insert(kind.clone(), self.next_generation);
+        self.definitions.insert(kind, DynamicGateDefinition {
+            prototype: Box::new(prototype),
+            evaluate: Arc::new(evaluate),
+        });
+        self
+    }
+
+    /// Remove a gate kind. Gates of this kind stop evaluating.
+    pub fn unregister(&mut self, kind: &str) -> Option<DynamicGateDefinition> {
+        self.generations.remove(kind);
+        self.definitions.remove(kind)
+    }
+
+    /// Returns the generation a gate kind was last registered in. It changes every time the
+    /// kind is registered, and is `None` if the kind is not registered.
+    pub fn generation(&self, kind: &str) -> Option<u64> {
+        self.generations.get(kind).copied()
+    }
+
+    /// Returns the definition of a gate kind.
+    pub fn get(&self, kind: &str) -> Option<&DynamicGateDefinition> {
+        self.definitions.get(kind)
+    }
+
+    /// Returns `true` if a gate kind is registered.
+    pub fn contains(&self, kind: &str) -> bool {
+        self.definitions.contains_key(kind)
+    }
+
+    /// Iterate over all registered gate kinds.
+    pub fn kinds(&self) -> impl Iterator<Item = &str> {
+        self.definitions.keys().map(String::as_str)
+    }
+}
+
+/// A logic gate whose behavior is looked up in the [`DynamicGateRegistry`] by `kind`.
+///
+/// Only `kind` is reflected, so it is what gets saved and cloned. The gate's state is
+/// cloned from the registered prototype whenever the gate is resolved, and an unresolved
+/// gate leaves its outputs unchanged.
+///
+/// Since the state is not reflected, a [`LogicSnapshot`] does not capture it, and gates
+/// copied from a blueprint or with [`CloneGateCommand`] start from the prototype again.
+/// Read and write it with [`DynamicGate::state`] and [`DynamicGate::state_mut`] to save it.
+///
+/// [`LogicSnapshot`]: crate::snapshot::LogicSnapshot
+/// [`CloneGateCommand`]: crate::commands::CloneGateCommand
+#[derive(Component, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct DynamicGate {
+    /// The registered kind of this gate. Changing it resolves the gate again.
+    pub kind: String,
+    #[reflect(ignore)]
+    state: Option<Box<dyn Reflect>>,
+    /// The kind and generation the gate was resolved against, and a copy of its definition.
+    #[reflect(ignore)]
+    resolved: Option<(String, u64, DynamicGateDefinition)>,
+}
+
+impl DynamicGate {
+    /// Create a new gate of `kind`.
+    pub fn new(kind: impl Into<String>) -> Self {
+        Self {
+            kind: kind.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns `true` if the gate was resolved against its current `kind`.
+    pub fn is_resolved(&self) -> bool {
+        self.resolved.as_ref().is_some_and(|(kind, ..)| *kind == self.kind)
+    }
+
+    /// Returns the gate's state, if it was resolved.
+    pub fn state(&self) -> Option<&dyn Reflect> {
+        self.state.as_deref()
+    }
+
+    /// Returns the gate's state mutably, if it was resolved.
+    pub fn state_mut(&mut self) -> Option<&mut dyn Reflect> {
+        self.state.as_deref_mut()
+    }
+
+    /// Resolve the gate's evaluate function and reset its state from the `registry`.
+    pub fn resolve(&mut self, registry: &DynamicGateRegistry) {
+        match registry.get(&self.kind).zip(registry.generation(&self.kind)) {
+            Some((definition, generation)) => {
+                self.state = Some(definition.prototype.clone_value());
+                self.resolved = Some((self.kind.clone(), generation, definition.clone()));
+            }
+            None => {
+                self.state = None;
+                self.resolved = None;
+            }
+        }
+    }
+}
+
+impl LogicGate for DynamicGate {
+    fn evaluate(&mut self, inputs: &[Signal], outputs: &mut [Signal]) {
+        if let (Some((.., definition)), Some(state)) = (&self.resolved, self.state.as_deref_mut()) {
+            (definition.evaluate)(state, inputs, outputs);
+        }
+    }
+
+    /// Reset the state to a copy of the prototype the gate was resolved with.
+    fn reset(&mut self) {
+        if let Some((.., definition)) = &self.resolved {
+            self.state = Some(definition.prototype.clone_value());
+        }
+    }
+}
+
+/// Resolve new and changed [`DynamicGate`]s against the [`DynamicGateRegistry`].
+///
+/// A gate is resolved again when its `kind` changes, or when its kind is registered again
+/// or unregistered. Gates of other kinds keep their state.
+pub fn resolve_dynamic_gates(
+    registry: Res<DynamicGateRegistry>,
+    mut query_gates: Query<&mut DynamicGate>
+) {
+    for mut gate in query_gates.iter_mut() {
+        let stale = match &gate.resolved {
+            Some((kind, generation, _)) => {
+                *kind != gate.kind || registry.generation(kind) != Some(*generation)
+            }
+            None => registry.contains(&gate.kind),
+        };
+
+        if stale {
+            gate.resolve(&registry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    fn count(state: &mut dyn Reflect, _: &[Signal], _: &mut [Signal]) {
+        if let Some(count) = state.downcast_mut::<u32>() {
+            *count += 1;
+        }
+    }
+
+    #[test]
+    fn registering_a_kind_only_resets_gates_of_that_kind() {
+        let mut world = World::new();
+        let mut registry = DynamicGateRegistry::default();
+        registry.register("a", 0u32, count).register("b", 0u32, count);
+        world.insert_resource(registry);
+
+        let a = world.spawn(DynamicGate::new("a")).id();
+        let b = world.spawn(DynamicGate::new("b")).id();
+        world.run_system_once(resolve_dynamic_gates);
+
+        for entity in [a, b] {
+            world.get_mut::<DynamicGate>(entity).unwrap().evaluate(&[], &mut []);
+        }
+
+        world.resource_mut::<DynamicGateRegistry>().register("b", 10u32, count);
+        world.run_system_once(resolve_dynamic_gates);
+
+        let state = |entity| {
+            let gate = world.get::<DynamicGate>(entity).unwrap();
+            gate.state().and_then(|state| state.downcast_ref::<u32>()).copied()
+        };
+        assert_eq!(state(a), Some(1));
+        assert_eq!(state(b), Some(10));
+    }
+
+    #[test]
+    fn reset_restores_the_prototype() {
+        let mut registry = DynamicGateRegistry::default();
+        registry.register("a", 3u32, count);
+
+        let mut gate = DynamicGate::new("a");
+        gate.resolve(&registry);
+        gate.evaluate(&[], &mut []);
+        gate.reset();
+
+        let state = gate.state().and_then(|state| state.downcast_ref::<u32>());
+        assert_eq!(state, Some(&3));
+    }
+}
@@ -0,0 +1,483 @@
+use std::hash::{ DefaultHasher, Hash, Hasher };
+
+use bevy::{
+    ecs::component::{ ComponentHooks, StorageType },
+    prelude::*,
+};
+
+use super::{ signal::Signal, LogicGate };
+
+/// A logic gate whose behavior is defined by a small expression language.
+///
+/// The source is a list of assignments separated by `;` or new lines:
+///
+/// ```text
+/// out0 = (a & b) | !c
+/// out1 = a ^ in3
+/// ```
+///
+/// - Inputs are named `a` through `z`, or `in0`, `in1`, ... by index.
+/// - Outputs are named `out0`, `out1`, ... by index.
+/// - Operators, from lowest to highest precedence: `|`, `^`, `&`, and unary `!`.
+/// - `0`, `1`, `false`, and `true` are constants. Parentheses group expressions.
+///
+/// Inputs are read as truthy or falsy (see [`Signal::is_truthy`]), and outputs are
+/// [`Signal::Digital`]. Outputs that are not assigned are left unchanged.
+///
+/// The source is compiled to bytecode when the component is inserted, or when it is
+/// changed with [`ExpressionGate::set_source`]. A source changed any other way, such as
+/// through reflection in an inspector or a scene, is compiled by
+/// [`recompile_expression_gates`] before the next step. A gate whose source fails to compile
+/// logs an error and leaves its outputs unchanged.
+#[derive(Clone, Debug, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+pub struct ExpressionGate {
+    source: String,
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    program: Option<ExpressionProgram>,
+    /// The hash of the source the program was last compiled from, even if it failed.
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    compiled_hash: Option<u64>,
+}
+
+impl Component for ExpressionGate {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks.on_insert(|mut world, entity, _| {
+            let mut gate = world
+                .get_mut::<ExpressionGate>(entity)
+                .expect("ExpressionGate was just inserted");
+            gate.compile_if_changed(entity);
+        });
+    }
+}
+
+impl ExpressionGate {
+    /// Create a new gate from `source`, compiling it immediately.
+    pub fn new(source: impl Into<String>) -> Result<Self, ExpressionError> {
+        let mut gate = Self {
+            source: source.into(),
+            ..Default::default()
+        };
+        gate.compile()?;
+        Ok(gate)
+    }
+
+    /// Returns the source of the expression.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Replace the source of the expression and compile it.
+    ///
+    /// If compilation fails, the gate stops evaluating until a valid source is set.
+    pub fn set_source(&mut self, source: impl Into<String>) -> Result<(), ExpressionError> {
+        self.source = source.into();
+        self.compile()
+    }
+
+    /// Returns the compiled program, if the source compiled successfully.
+    pub fn program(&self) -> Option<&ExpressionProgram> {
+        self.program.as_ref()
+    }
+
+    fn compile(&mut self) -> Result<(), ExpressionError> {
+        self.program = None;
+        self.compiled_hash = Some(source_hash(&self.source));
+        self.program = Some(ExpressionProgram::compile(&self.source)?);
+        Ok(())
+    }
+
+    /// Compile the source if it changed since it was last compiled, logging any error.
+    fn compile_if_changed(&mut self, entity: Entity) {
+        if self.compiled_hash == Some(source_hash(&self.source)) {
+            return;
+        }
+        if let Err(error) = self.compile() {
+            error!("Failed to compile ExpressionGate on {entity}: {error}");
+        }
+    }
+}
+
+fn source_hash(source: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    source.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Compile [`ExpressionGate`]s whose source changed without [`ExpressionGate::set_source`],
+/// such as through reflection.
+pub fn recompile_expression_gates(
+    mut query_gates: Query<(Entity, &mut ExpressionGate), Changed<ExpressionGate>>
+) {
+    for (entity, mut gate) in query_gates.iter_mut() {
+        gate.bypass_change_detection().compile_if_changed(entity);
+    }
+}
+
+impl LogicGate for ExpressionGate {
+    fn evaluate(&mut self, inputs: &[Signal], outputs: &mut [Signal]) {
+        if let Some(program) = &self.program {
+            program.run(inputs, outputs);
+        }
+    }
+}
+
+/// An error produced while compiling an [`ExpressionGate`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExpressionError {
+    /// The byte offset in the source where the error was found.
+    pub position: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ExpressionError {}
+
+/// A single bytecode instruction of an [`ExpressionProgram`].
+///
+/// Instructions operate on a stack of booleans.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExpressionOp {
+    /// Push the truthiness of an input. Missing inputs are falsy.
+    Input(usize),
+    /// Push a constant.
+    Const(bool),
+    Not,
+    And,
+    Or,
+    Xor,
+    /// Pop a value into an output. Outputs that do not exist are skipped.
+    Store(usize),
+}
+
+/// The compiled bytecode of an [`ExpressionGate`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExpressionProgram {
+    pub ops: Vec<ExpressionOp>,
+}
+
+impl ExpressionProgram {
+    /// Compile `source` to bytecode.
+    pub fn compile(source: &str) -> Result<Self, ExpressionError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            index: 0,
+            ops: Vec::new(),
+            end: source.len(),
+        };
+        parser.program()?;
+        Ok(Self { ops: parser.ops })
+    }
+
+    /// Run the program, writing assigned outputs.
+    pub fn run(&self, inputs: &[Signal], outputs: &mut [Signal]) {
+        let mut stack = Vec::with_capacity(8);
+
+        for op in self.ops.iter() {
+            match *op {
+                ExpressionOp::Input(index) => {
+                    stack.push(inputs.get(index).is_some_and(Signal::is_truthy));
+                }
+                ExpressionOp::Const(value) => stack.push(value),
+                ExpressionOp::Not => {
+                    let value = stack.pop().unwrap_or_default();
+                    stack.push(!value);
+                }
+                ExpressionOp::And | ExpressionOp::Or | ExpressionOp::Xor => {
+                    let rhs = stack.pop().unwrap_or_default();
+                    let lhs = stack.pop().unwrap_or_default();
+                    stack.push(match op {
+                        ExpressionOp::And => lhs & rhs,
+                        ExpressionOp::Or => lhs | rhs,
+                        _ => lhs ^ rhs,
+                    });
+                }
+                ExpressionOp::Store(index) => {
+                    let value = stack.pop().unwrap_or_default();
+                    if let Some(output) = outputs.get_mut(index) {
+                        *output = Signal::Digital(value);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Number(u32),
+    Assign,
+    Not,
+    And,
+    Or,
+    Xor,
+    Open,
+    Close,
+    Separator,
+}
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, ExpressionError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some((position, c)) = chars.next() {
+        let token = match c {
+            '\n' | ';' => Token::Separator,
+            c if c.is_whitespace() => {
+                continue;
+            }
+            '=' => Token::Assign,
+            '!' => Token::Not,
+            '&' => Token::And,
+            '|' => Token::Or,
+            '^' => Token::Xor,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            c if c.is_ascii_alphanumeric() || c == '_' => {
+                let mut word = String::from(c);
+                while let Some(&(_, next)) = chars.peek() {
+                    if !(next.is_ascii_alphanumeric() || next == '_') {
+                        break;
+                    }
+                    word.push(next);
+                    chars.next();
+                }
+
+                if c.is_ascii_digit() {
+                    let number = word.parse().map_err(|_| ExpressionError {
+                        position,
+                        message: format!("invalid number `{word}`"),
+                    })?;
+                    Token::Number(number)
+                } else {
+                    Token::Ident(word)
+                }
+            }
+            c => {
+                return Err(ExpressionError {
+                    position,
+                    message: format!("unexpected character `{c}`"),
+                });
+            }
+        };
+        tokens.push((position, token));
+    }
+
+    Ok(tokens)
+}
+
+/// Parse `name` followed by an index, such as `out2`.
+fn indexed(ident: &str, name: &str) -> Option<usize> {
+    ident.strip_prefix(name).filter(|index| !index.is_empty())?.parse().ok()
+}
+
+struct Parser<'a> {
+    tokens: &'a [(usize, Token)],
+    index: usize,
+    ops: Vec<ExpressionOp>,
+    end: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.index).map(|(_, token)| token)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens.get(self.index).map_or(self.end, |(position, _)| *position)
+    }
+
+    fn error(&self, message: impl Into<String>) -> ExpressionError {
+        ExpressionError {
+            position: self.position(),
+            message: message.into(),
+        }
+    }
+
+    fn next(&mut self) -> Option<&'a Token> {
+        let token = self.peek();
+        self.index += 1;
+        token
+    }
+
+    fn program(&mut self) -> Result<(), ExpressionError> {
+        loop {
+            while self.peek() == Some(&Token::Separator) {
+                self.index += 1;
+            }
+            if self.peek().is_none() {
+                return Ok(());
+            }
+
+            self.assignment()?;
+
+            match self.peek() {
+                None | Some(Token::Separator) => {}
+                Some(_) => {
+                    return Err(self.error("expected `;` or a new line"));
+                }
+            }
+        }
+    }
+
+    fn assignment(&mut self) -> Result<(), ExpressionError> {
+        let output = match self.next() {
+            Some(Token::Ident(ident)) => indexed(ident, "out"),
+            _ => None,
+        };
+        let Some(output) = output else {
+            self.index -= 1;
+            return Err(self.error("expected an output such as `out0`"));
+        };
+
+        if self.next() != Some(&Token::Assign) {
+            self.index -= 1;
+            return Err(self.error("expected `=`"));
+        }
+
+        self.or()?;
+        self.ops.push(ExpressionOp::Store(output));
+        Ok(())
+    }
+
+    fn binary(
+        &mut self,
+        token: Token,
+        op: ExpressionOp,
+        operand: fn(&mut Self) -> Result<(), ExpressionError>
+    ) -> Result<(), ExpressionError> {
+        operand(self)?;
+        while self.peek() == Some(&token) {
+            self.index += 1;
+            operand(self)?;
+            self.ops.push(op);
+        }
+        Ok(())
+    }
+
+    fn or(&mut self) -> Result<(), ExpressionError> {
+        self.binary(Token::Or, ExpressionOp::Or, Self::xor)
+    }
+
+    fn xor(&mut self) -> Result<(), ExpressionError> {
+        self.binary(Token::Xor, ExpressionOp::Xor, Self::and)
+    }
+
+    fn and(&mut self) -> Result<(), ExpressionError> {
+        self.binary(Token::And, ExpressionOp::And, Self::unary)
+    }
+
+    fn unary(&mut self) -> Result<(), ExpressionError> {
+        if self.peek() == Some(&Token::Not) {
+            self.index += 1;
+            self.unary()?;
+            self.ops.push(ExpressionOp::Not);
+            return Ok(());
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<(), ExpressionError> {
+        let position = self.index;
+        let op = match self.next() {
+            Some(Token::Open) => {
+                self.or()?;
+                if self.next() != Some(&Token::Close) {
+                    self.index -= 1;
+                    return Err(self.error("expected `)`"));
+                }
+                return Ok(());
+            }
+            Some(Token::Number(0)) => ExpressionOp::Const(false),
+            Some(Token::Number(1)) => ExpressionOp::Const(true),
+            Some(Token::Ident(ident)) =>
+                match ident.as_str() {
+                    "false" => ExpressionOp::Const(false),
+                    "true" => ExpressionOp::Const(true),
+                    letter if letter.len() == 1 && letter.as_bytes()[0].is_ascii_lowercase() => {
+                        ExpressionOp::Input((letter.as_bytes()[0] - b'a') as usize)
+                    }
+                    ident =>
+                        match indexed(ident, "in") {
+                            Some(index) => ExpressionOp::Input(index),
+                            None => {
+                                self.index = position;
+                                return Err(self.error(format!("unknown input `{ident}`")));
+                            }
+                        }
+                }
+            _ => {
+                self.index = position;
+                return Err(self.error("expected an input, constant, or `(`"));
+            }
+        };
+
+        self.ops.push(op);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(source: &str, inputs: &[bool]) -> Vec<Signal> {
+        let inputs = inputs.iter().map(|&input| Signal::from(input)).collect::<Vec<_>>();
+        let mut outputs = vec![Signal::Undefined; 2];
+        ExpressionProgram::compile(source).unwrap().run(&inputs, &mut outputs);
+        outputs
+    }
+
+    #[test]
+    fn evaluates_precedence() {
+        let source = "out0 = (a & b) | !c";
+        assert_eq!(run(source, &[true, true, true])[0], Signal::ON);
+        assert_eq!(run(source, &[true, false, true])[0], Signal::OFF);
+        assert_eq!(run(source, &[false, false, false])[0], Signal::ON);
+        assert_eq!(run("out0 = a | b & c", &[true, false, false])[0], Signal::ON);
+    }
+
+    #[test]
+    fn assigns_multiple_outputs() {
+        let outputs = run("out0 = a ^ in1; out1 = !true\n", &[true, true]);
+        assert_eq!(outputs, vec![Signal::OFF, Signal::OFF]);
+        assert_eq!(run("out1 = 1", &[])[0], Signal::Undefined);
+    }
+
+    #[test]
+    fn reports_errors() {
+        assert_eq!(ExpressionProgram::compile("out0 = a &").unwrap_err().position, 10);
+        assert!(ExpressionProgram::compile("a = b").is_err());
+        assert!(ExpressionProgram::compile("out0 = (a").is_err());
+        assert!(ExpressionProgram::compile("out0 = a b").is_err());
+        assert!(ExpressionProgram::compile("out0 = foo").is_err());
+    }
+
+    #[test]
+    fn recompiles_a_source_changed_through_reflection() {
+        use bevy::{ ecs::system::RunSystemOnce, reflect::GetField };
+
+        let mut world = World::new();
+        let entity = world.spawn(ExpressionGate::new("out0 = 1").unwrap()).id();
+
+        let mut gate = world.get_mut::<ExpressionGate>(entity).unwrap();
+        *gate.get_field_mut::<String>("source").unwrap() = "out0 = 0".into();
+        world.run_system_once(recompile_expression_gates);
+
+        let mut outputs = [Signal::Undefined];
+        world.get_mut::<ExpressionGate>(entity).unwrap().evaluate(&[], &mut outputs);
+        assert_eq!(outputs, [Signal::OFF]);
+    }
+}
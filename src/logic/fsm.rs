@@ -0,0 +1,192 @@
+//! State machine gates, declared as a table of states and transitions instead of code.
+
+use bevy::prelude::*;
+
+use super::{ signal::Signal, GateContext, LogicGate };
+
+/// A condition on one input of an [`FsmGate`], which triggers a [`FsmTransition`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FsmCondition {
+    /// The input is on.
+    On(usize),
+    /// The input is off or undefined.
+    Off(usize),
+    /// The input turned on since the previous evaluation.
+    Rising(usize),
+    /// The input turned off since the previous evaluation.
+    Falling(usize),
+}
+
+impl FsmCondition {
+    /// Returns `true` if the condition holds for `inputs`.
+    ///
+    /// Without `previous` inputs, [`FsmCondition::Rising`] and [`FsmCondition::Falling`] are
+    /// true while the input is on and off.
+    pub fn holds(&self, inputs: &[Signal], previous: Option<&[Signal]>) -> bool {
+        let is_on = |signals: &[Signal], index: usize| {
+            signals.get(index).is_some_and(Signal::is_truthy)
+        };
+        let was_on = |index: usize| previous.map(|previous| is_on(previous, index));
+
+        match *self {
+            FsmCondition::On(index) => is_on(inputs, index),
+            FsmCondition::Off(index) => !is_on(inputs, index),
+            FsmCondition::Rising(index) => is_on(inputs, index) && was_on(index) != Some(true),
+            FsmCondition::Falling(index) => !is_on(inputs, index) && was_on(index) != Some(false),
+        }
+    }
+}
+
+/// A transition of an [`FsmGate`] to the state at index `target`, taken when all of its
+/// `conditions` hold. A transition without conditions is always taken.
+///
+/// To take a transition when any of several conditions hold, add a transition for each.
+#[derive(Clone, Debug, PartialEq, Eq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FsmTransition {
+    pub conditions: Vec<FsmCondition>,
+    pub target: usize,
+}
+
+impl FsmTransition {
+    /// Returns `true` if all of the conditions hold for `inputs`.
+    pub fn holds(&self, inputs: &[Signal], previous: Option<&[Signal]>) -> bool {
+        self.conditions.iter().all(|condition| condition.holds(inputs, previous))
+    }
+}
+
+/// A state of an [`FsmGate`]: the signals it emits while in the state, and the transitions
+/// out of it.
+#[derive(Clone, Debug, Default, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FsmState {
+    /// A name for the state, for inspectors and debugging.
+    pub name: String,
+    /// The signal of each output while in this state. Outputs without one are off.
+    pub outputs: Vec<Signal>,
+    /// The transitions out of this state, in order of priority.
+    pub transitions: Vec<FsmTransition>,
+}
+
+/// A gate that runs a finite state machine declared as a table of [`FsmState`]s, covering
+/// most stateful gates like selectors, sequencers, and lock controllers without writing a
+/// [`LogicGate`].
+///
+/// Each evaluation, the first transition of the current state whose conditions hold is
+/// taken, then the gate emits the outputs of its new state. At most one transition is taken
+/// per evaluation. The outputs are [`Signal::Undefined`] if the current state does not
+/// exist.
+///
+/// The table is reflected, so it can be edited in an inspector and saved with the gate.
+///
+/// ```
+/// # use bevy_logic::prelude::*;
+/// // A toggle: the output turns on and off with each pulse of the first input.
+/// let toggle = FsmGate::default()
+///     .with_state("off", [Signal::OFF])
+///     .with_state("on", [Signal::ON])
+///     .with_transition(0, [FsmCondition::Rising(0)], 1)
+///     .with_transition(1, [FsmCondition::Rising(0)], 0);
+/// ```
+#[derive(Component, Clone, Debug, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+pub struct FsmGate {
+    pub states: Vec<FsmState>,
+    /// The index of the state the gate starts in, and returns to on reset.
+    pub initial: usize,
+    /// The index of the current state.
+    pub state: usize,
+}
+
+impl FsmGate {
+    /// Add a state named `name` that emits `outputs`.
+    pub fn with_state(
+        mut self,
+        name: impl Into<String>,
+        outputs: impl IntoIterator<Item = Signal>
+    ) -> Self {
+        self.states.push(FsmState {
+            name: name.into(),
+            outputs: outputs.into_iter().collect(),
+            transitions: Vec::new(),
+        });
+        self
+    }
+
+    /// Add a transition from the state at index `from` to the state at index `to`, taken when
+    /// all `conditions` hold. Does nothing if there is no state at `from`.
+    pub fn with_transition(
+        mut self,
+        from: usize,
+        conditions: impl IntoIterator<Item = FsmCondition>,
+        to: usize
+    ) -> Self {
+        if let Some(state) = self.states.get_mut(from) {
+            state.transitions.push(FsmTransition {
+                conditions: conditions.into_iter().collect(),
+                target: to,
+            });
+        }
+        self
+    }
+
+    /// Start in, and reset to, the state at index `initial`.
+    pub fn with_initial(mut self, initial: usize) -> Self {
+        self.initial = initial;
+        self.state = initial;
+        self
+    }
+
+    /// Returns the current state.
+    pub fn current(&self) -> Option<&FsmState> {
+        self.states.get(self.state)
+    }
+
+    /// Returns the index of the first state named `name`.
+    pub fn state_index(&self, name: &str) -> Option<usize> {
+        self.states.iter().position(|state| state.name == name)
+    }
+
+    fn step(&mut self, inputs: &[Signal], previous: Option<&[Signal]>, outputs: &mut [Signal]) {
+        let target = self
+            .current()
+            .and_then(|state| {
+                state.transitions
+                    .iter()
+                    .find(|transition| transition.holds(inputs, previous))
+            })
+            .map(|transition| transition.target);
+        if let Some(target) = target {
+            self.state = target;
+        }
+
+        let Some(state) = self.current() else {
+            outputs.fill(Signal::Undefined);
+            return;
+        };
+        for (index, output) in outputs.iter_mut().enumerate() {
+            *output = state.outputs.get(index).copied().unwrap_or(Signal::OFF);
+        }
+    }
+}
+
+impl LogicGate for FsmGate {
+    fn evaluate(&mut self, inputs: &[Signal], outputs: &mut [Signal]) {
+        self.step(inputs, None, outputs);
+    }
+
+    fn evaluate_with_context(
+        &mut self,
+        ctx: &GateContext,
+        inputs: &[Signal],
+        outputs: &mut [Signal]
+    ) {
+        self.step(inputs, Some(ctx.previous_inputs), outputs);
+    }
+
+    fn reset(&mut self) {
+        self.state = self.initial;
+    }
+}
@@ -2,12 +2,35 @@ use bevy::prelude::*;
 
 use crate::{ logic::{ signal::Signal, LogicGate }, utils::NumExt };
 
-use super::{ signal::SignalExt, AppLogicGateExt };
+use super::{
+    dynamic::{ resolve_dynamic_gates, DynamicGate, DynamicGateRegistry },
+    expression::{ recompile_expression_gates, ExpressionGate },
+    schedule::{ LogicSystemSet, LogicUpdate },
+    signal::SignalExt,
+    AppLogicGateExt,
+};
 
-/// This plugin registers basic logic gates and a battery component.
+/// This plugin registers the built-in logic gates.
 ///
 /// They can be queried using the [`LogicGate`] trait.
 ///
+/// The gates are split into cargo features, which are all enabled by default, and only the
+/// gates of enabled features are registered:
+///
+/// - `gates-basic`: the [`AndGate`], [`OrGate`], [`NotGate`], [`XorGate`], [`Battery`],
+///   [`Junction`], and [`DisplayGate`].
+/// - `gates-sequential`: the gates in [`sequential`](super::sequential) and the
+///   [`FsmGate`](super::fsm::FsmGate).
+/// - `gates-analog`: the gates in [`analog`](super::analog) and the
+///   [`WaveformGate`](super::waveform::WaveformGate).
+/// - `gates-memory`: the gates in [`memory`](super::memory).
+///
+/// The basic gates are always compiled, since the importers and compiled circuits build on
+/// them, but they are only registered with `gates-basic`.
+///
+/// It also adds the [`DynamicGateRegistry`], so gate types can be registered at runtime
+/// and spawned as [`DynamicGate`]s, and the [`ExpressionGate`].
+///
 /// # Example
 ///
 /// ```no_run
@@ -20,23 +43,70 @@ pub struct LogicGatePlugin;
 
 impl Plugin for LogicGatePlugin {
     fn build(&self, app: &mut App) {
-        app.register_logic_gate::<AndGate>()
-            .register_logic_gate::<OrGate>()
-            .register_logic_gate::<NotGate>()
-            .register_logic_gate::<XorGate>()
-            .register_logic_gate::<Battery>();
-
-        // Register the components' reflection data.
-        app.register_type::<AndGate>()
-            .register_type::<OrGate>()
-            .register_type::<NotGate>()
-            .register_type::<XorGate>()
-            .register_type::<Battery>();
+        #[cfg(feature = "gates-basic")]
+        register_basic_gates(app);
+        #[cfg(feature = "gates-sequential")]
+        super::sequential::register_sequential_gates(app);
+        #[cfg(feature = "gates-analog")]
+        super::analog::register_analog_gates(app);
+        #[cfg(feature = "gates-memory")]
+        super::memory::register_memory_gates(app);
+
+        app.register_logic_gate::<DynamicGate>()
+            .register_logic_gate::<ExpressionGate>()
+            .register_type::<DynamicGate>()
+            .register_type::<ExpressionGate>();
+
+        #[cfg(feature = "wasm")]
+        app.register_logic_gate::<super::wasm::WasmGate>();
+
+        #[cfg(debug_assertions)]
+        app.add_systems(Startup, super::registry::warn_unreflected_gates);
+
+        app.init_resource::<DynamicGateRegistry>().add_systems(
+            LogicUpdate,
+            (resolve_dynamic_gates, recompile_expression_gates).in_set(LogicSystemSet::SyncGraph)
+        );
     }
 }
 
+#[cfg(feature = "gates-basic")]
+fn register_basic_gates(app: &mut App) {
+    use super::{ registry::GateRegistry, GateArity };
+
+    app.register_logic_gate::<AndGate>()
+        .register_logic_gate::<OrGate>()
+        .register_logic_gate::<NotGate>()
+        .register_logic_gate::<XorGate>()
+        .register_logic_gate::<Battery>()
+        .register_logic_gate::<Junction>()
+        .register_logic_gate::<DisplayGate>();
+
+    app.register_gate_arity::<NotGate>(GateArity::inputs(1, None))
+        .register_gate_arity::<Battery>(GateArity::exact_inputs(0))
+        .register_gate_arity::<Junction>(GateArity::exact_inputs(1))
+        .register_gate_arity::<DisplayGate>(GateArity::inputs(1, Some(64)).with_outputs(0));
+
+    app.world_mut()
+        .resource_mut::<GateRegistry>()
+        .set_default_fans::<Junction>(1, 2)
+        .set_default_fans::<DisplayGate>(8, 0);
+
+    app.register_type::<AndGate>()
+        .register_type::<OrGate>()
+        .register_type::<NotGate>()
+        .register_type::<XorGate>()
+        .register_type::<Battery>()
+        .register_type::<Junction>()
+        .register_type::<DisplayGate>()
+        .register_type::<DisplayValue>()
+        .add_systems(LogicUpdate, sync_display_values.in_set(LogicSystemSet::PostStep));
+}
+
 /// A [`Battery`] emits a constant signal.
 #[derive(Component, Clone, Copy, Debug, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
 pub struct Battery {
     pub signal: Signal,
 }
@@ -81,6 +151,8 @@ impl LogicGate for Battery {
 /// | 1 | 1 | 1 |
 /// ```
 #[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
 pub struct AndGate {
     pub invert_output: bool,
 }
@@ -107,6 +179,8 @@ impl LogicGate for AndGate {
 /// | 1 | 0 |
 /// ```
 #[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
 pub struct NotGate;
 
 impl LogicGate for NotGate {
@@ -133,6 +207,8 @@ impl LogicGate for NotGate {
 /// |  1 | -1 |  1 |
 /// ```
 #[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
 pub struct OrGate {
     /// If true, the gate will be a NOR gate instead of an OR gate.
     pub invert_output: bool,
@@ -170,6 +246,8 @@ impl LogicGate for OrGate {
 /// | 1 | 1 | 0 |
 /// ```
 #[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
 pub struct XorGate;
 
 impl LogicGate for XorGate {
@@ -184,3 +262,79 @@ impl LogicGate for XorGate {
         outputs.set_all(signal);
     }
 }
+
+/// A junction copies the signal of its single input to every output, without any logic.
+///
+/// Use it to fan one output out to many destinations, so the split is a node of its own in
+/// the [`LogicGraph`] and in saved circuits, instead of many wires from the same output fan.
+/// Spawn one with [`LogicExt::junction`].
+///
+/// [`LogicGraph`]: crate::resources::LogicGraph
+/// [`LogicExt::junction`]: crate::logic::builder::LogicExt::junction
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+pub struct Junction;
+
+impl LogicGate for Junction {
+    fn evaluate(&mut self, inputs: &[Signal], outputs: &mut [Signal]) {
+        outputs.set_all(inputs.first().copied().unwrap_or(Signal::Undefined));
+    }
+}
+
+/// A display reads its inputs as the bits of a number, from the least significant bit, so UI
+/// systems can show numeric readouts from a circuit without decoding bits themselves.
+///
+/// After each step, the number is copied into the gate's [`DisplayValue`] component, which is
+/// inserted if the gate does not have one yet. Inputs after the 64th are ignored.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_logic::prelude::*;
+/// fn show_score(displays: Query<&DisplayValue, Changed<DisplayValue>>) {
+///     for display in displays.iter() {
+///         println!("score: {}", display.0);
+///     }
+/// }
+/// ```
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+pub struct DisplayGate {
+    /// The number read during the latest evaluation.
+    pub value: u64,
+}
+
+impl LogicGate for DisplayGate {
+    fn evaluate(&mut self, inputs: &[Signal], _: &mut [Signal]) {
+        self.value = inputs
+            .iter()
+            .take(64)
+            .enumerate()
+            .filter(|(_, signal)| signal.is_truthy())
+            .fold(0, |value, (bit, _)| value | (1 << bit));
+    }
+}
+
+/// The number shown by a [`DisplayGate`], updated by [`sync_display_values`].
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+pub struct DisplayValue(pub u64);
+
+/// Copy the number of every [`DisplayGate`] that was evaluated into its [`DisplayValue`].
+pub fn sync_display_values(
+    mut commands: Commands,
+    mut query: Query<(Entity, &DisplayGate, Option<&mut DisplayValue>), Changed<DisplayGate>>
+) {
+    for (entity, gate, display) in query.iter_mut() {
+        match display {
+            Some(mut display) => {
+                display.set_if_neq(DisplayValue(gate.value));
+            }
+            None => {
+                commands.entity(entity).insert(DisplayValue(gate.value));
+            }
+        }
+    }
+}
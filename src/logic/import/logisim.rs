@@ -0,0 +1,743 @@
+//! An importer for circuits saved by [Logisim](http://www.cburch.com/logisim/).
+//!
+//! Logisim connects components by position: a wire connects to every port that lies on one of
+//! its ends. This importer recomputes each supported component's port positions the way
+//! Logisim lays them out, joins the ports and wires that meet into nets, and spawns one
+//! bevy_logic gate per component.
+//!
+//! - `AND`, `NAND`, `OR`, `NOR`, `XOR`, `XNOR`, and `NOT` gates and buffers are supported,
+//!   including negated inputs. Gates must be one bit wide. Inputs that are not driven by
+//!   anything are left out, matching how Logisim ignores floating gate inputs.
+//! - Input pins are spawned as [`Battery`] gates and output pins as single-input [`OrGate`]s.
+//!   Pins wider than one bit are spawned once per bit, named `<label>[<bit>]`.
+//! - Constants, power, and ground are spawned as [`Battery`] gates.
+//! - Splitters and tunnels only join nets, so they don't spawn anything.
+//! - Other components, such as labels and probes, are skipped with a warning.
+//!
+//! Each gate is spawned with a [`Transform`] at its component's location. Logisim's y axis
+//! points down, so it is flipped.
+
+use bevy::{ prelude::*, utils::HashMap };
+use petgraph::unionfind::UnionFind;
+
+use crate::{
+    commands::AddGateToLogicGraph,
+    logic::{
+        builder::{ GateData, Known, LogicExt },
+        gates::{ AndGate, Battery, NotGate, OrGate, XorGate },
+        signal::Signal,
+    },
+};
+
+use super::{ ImportError, ImportedCircuit };
+
+/// A circuit parsed from a Logisim `.circ` file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogisimCircuit {
+    pub name: String,
+    pub components: Vec<LogisimComponent>,
+    /// The ends of every wire segment, in Logisim's coordinates.
+    pub wires: Vec<(IVec2, IVec2)>,
+}
+
+/// A component placed in a [`LogisimCircuit`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogisimComponent {
+    /// The component's name in its library, such as `AND Gate` or `Pin`.
+    pub name: String,
+    pub location: IVec2,
+    pub attributes: HashMap<String, String>,
+    /// The line in the source where the component was declared.
+    pub line: usize,
+}
+
+impl LogisimComponent {
+    /// Returns the value of an attribute, if it was saved.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(String::as_str)
+    }
+
+    /// Returns the component's label, or a name made from its location.
+    pub fn label(&self) -> String {
+        match self.attribute("label") {
+            Some(label) if !label.is_empty() => label.to_string(),
+            _ => format!("{}({},{})", self.name, self.location.x, self.location.y),
+        }
+    }
+
+    fn number(&self, name: &str, default: u32) -> Result<u32, ImportError> {
+        match self.attribute(name) {
+            None => Ok(default),
+            Some(value) =>
+                parse_number(value).ok_or_else(|| {
+                    ImportError::new(self.line, format!("invalid `{name}`: `{value}`"))
+                }),
+        }
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.attribute(name) == Some("true")
+    }
+
+    fn facing(&self) -> Result<Facing, ImportError> {
+        match self.attribute("facing").unwrap_or("east") {
+            "east" => Ok(Facing::East),
+            "west" => Ok(Facing::West),
+            "north" => Ok(Facing::North),
+            "south" => Ok(Facing::South),
+            facing => Err(ImportError::new(self.line, format!("invalid `facing`: `{facing}`"))),
+        }
+    }
+
+    fn width(&self) -> Result<u32, ImportError> {
+        let width = self.number("width", 1)?;
+        if width == 0 || width > MAX_WIDTH {
+            return Err(ImportError::new(self.line, format!("unsupported width `{width}`")));
+        }
+        Ok(width)
+    }
+}
+
+/// The widest bus supported, matching Logisim's own limit.
+const MAX_WIDTH: u32 = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Facing {
+    East,
+    West,
+    North,
+    South,
+}
+
+impl Facing {
+    /// Returns the offset of a port that is `behind` the component's location along its
+    /// facing, and `across` it.
+    fn offset(self, behind: i32, across: i32) -> IVec2 {
+        match self {
+            Facing::East => IVec2::new(-behind, across),
+            Facing::West => IVec2::new(behind, across),
+            Facing::North => IVec2::new(across, behind),
+            Facing::South => IVec2::new(across, -behind),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum GateKind {
+    And,
+    Nand,
+    Or,
+    Nor,
+    Xor,
+    Xnor,
+    Not,
+    Buffer,
+}
+
+impl GateKind {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "AND Gate" => Some(GateKind::And),
+            "NAND Gate" => Some(GateKind::Nand),
+            "OR Gate" => Some(GateKind::Or),
+            "NOR Gate" => Some(GateKind::Nor),
+            "XOR Gate" => Some(GateKind::Xor),
+            "XNOR Gate" => Some(GateKind::Xnor),
+            "NOT Gate" => Some(GateKind::Not),
+            "Buffer" => Some(GateKind::Buffer),
+            _ => None,
+        }
+    }
+
+    /// Returns the distance from a gate's output to its inputs, past its body.
+    fn axis_length(self, size: i32) -> i32 {
+        match self {
+            GateKind::And | GateKind::Or => size,
+            GateKind::Nand | GateKind::Nor | GateKind::Xor => size + 10,
+            GateKind::Xnor => size + 20,
+            GateKind::Not | GateKind::Buffer => size,
+        }
+    }
+}
+
+/// Parse a Logisim `.circ` file and return one of its circuits.
+///
+/// If `circuit` is `None`, the file's main circuit is returned.
+pub fn parse_logisim(source: &str, circuit: Option<&str>) -> Result<LogisimCircuit, ImportError> {
+    let document = roxmltree::Document
+        ::parse(source)
+        .map_err(|error| ImportError::new(error.pos().row as usize, error.to_string()))?;
+    let line = |node: roxmltree::Node| document.text_pos_at(node.range().start).row as usize;
+
+    let project = document.root_element();
+    if !project.has_tag_name("project") {
+        return Err(ImportError::new(line(project), "expected a `project` element"));
+    }
+
+    let main = project
+        .children()
+        .find(|node| node.has_tag_name("main"))
+        .and_then(|node| node.attribute("name"));
+    let name = circuit.or(main);
+
+    let element = project
+        .children()
+        .filter(|node| node.has_tag_name("circuit"))
+        .find(|node| name.is_none() || node.attribute("name") == name)
+        .ok_or_else(|| {
+            let message = match name {
+                Some(name) => format!("circuit `{name}` not found"),
+                None => "the file has no circuits".to_string(),
+            };
+            ImportError::new(0, message)
+        })?;
+
+    // Logisim 2.x defaults to five gate inputs, while later versions default to two.
+    let legacy = project.attribute("source").is_some_and(|source| source.starts_with("2."));
+
+    let mut circuit = LogisimCircuit {
+        name: element.attribute("name").unwrap_or_default().to_string(),
+        ..Default::default()
+    };
+
+    for node in element.children().filter(|node| node.is_element()) {
+        let point = |name: &str| -> Result<IVec2, ImportError> {
+            let value = node
+                .attribute(name)
+                .ok_or_else(|| ImportError::new(line(node), format!("missing `{name}`")))?;
+            parse_point(value).ok_or_else(|| {
+                ImportError::new(line(node), format!("invalid `{name}`: `{value}`"))
+            })
+        };
+
+        match node.tag_name().name() {
+            "wire" => {
+                circuit.wires.push((point("from")?, point("to")?));
+            }
+            "comp" => {
+                let mut component = LogisimComponent {
+                    name: node.attribute("name").unwrap_or_default().to_string(),
+                    location: point("loc")?,
+                    line: line(node),
+                    ..Default::default()
+                };
+
+                if legacy && GateKind::from_name(&component.name).is_some() {
+                    component.attributes.insert("inputs".to_string(), "5".to_string());
+                }
+
+                for attribute in node.children().filter(|node| node.has_tag_name("a")) {
+                    let (Some(name), Some(value)) = (
+                        attribute.attribute("name"),
+                        attribute.attribute("val"),
+                    ) else {
+                        continue;
+                    };
+                    component.attributes.insert(name.to_string(), value.to_string());
+                }
+
+                circuit.components.push(component);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(circuit)
+}
+
+/// Spawn the components of a Logisim `circuit` and wire them together, then add them to
+/// the [`LogicGraph`] resource through [`AddGateToLogicGraph`].
+///
+/// Every gate gets a [`Name`] from its component's label, and a [`Transform`] at its
+/// component's location multiplied by `scale`.
+///
+/// [`LogicGraph`]: crate::resources::LogicGraph
+pub fn spawn_logisim(
+    commands: &mut Commands,
+    circuit: &LogisimCircuit,
+    scale: f32
+) -> Result<ImportedCircuit, ImportError> {
+    let max_width = circuit.components
+        .iter()
+        .filter_map(|component| {
+            let width = component.number("width", 1).ok()?;
+            let incoming = component.number("incoming", 1).ok()?;
+            Some(width.max(incoming))
+        })
+        .max()
+        .unwrap_or(1)
+        .min(MAX_WIDTH);
+
+    let mut nets = Nets::default();
+    let mut parts: Vec<Part> = Vec::new();
+    let mut tunnels: HashMap<&str, Vec<(IVec2, u32)>> = HashMap::default();
+
+    for (a, b) in circuit.wires.iter() {
+        for bit in 0..max_width {
+            nets.connect((*a, bit), (*b, bit));
+        }
+    }
+
+    for component in circuit.components.iter() {
+        let location = component.location;
+
+        if let Some(kind) = GateKind::from_name(&component.name) {
+            if component.width()? != 1 {
+                return Err(ImportError::new(component.line, "gates must be one bit wide"));
+            }
+
+            let facing = component.facing()?;
+            let (inputs, size) = match kind {
+                GateKind::Not => (1, component.number("size", 30)? as i32),
+                GateKind::Buffer => (1, 20),
+                _ => (component.number("inputs", 2)?, component.number("size", 50)? as i32),
+            };
+            let axis_length = kind.axis_length(size);
+
+            let inputs = (0..inputs)
+                .map(|index| {
+                    let negated = component.flag(&format!("negate{index}"));
+                    let behind = axis_length + if negated { 10 } else { 0 };
+                    let across = match kind {
+                        GateKind::Not | GateKind::Buffer => 0,
+                        _ => input_spacing(size, inputs, index),
+                    };
+                    let node = nets.node((location + facing.offset(behind, across), 0));
+                    (node, negated)
+                })
+                .collect();
+
+            parts.push(Part::Gate {
+                component,
+                kind,
+                output: nets.node((location, 0)),
+                inputs,
+            });
+            continue;
+        }
+
+        match component.name.as_str() {
+            "Pin" => {
+                let nodes = (0..component.width()?).map(|bit| nets.node((location, bit))).collect();
+                parts.push(Part::Pin {
+                    component,
+                    output: component.flag("output"),
+                    nodes,
+                });
+            }
+            "Constant" | "Power" | "Ground" => {
+                let width = component.width()?;
+                let value = match component.name.as_str() {
+                    "Constant" => component.number("value", 1)?,
+                    "Power" => u32::MAX,
+                    _ => 0,
+                };
+                let nodes = (0..width)
+                    .map(|bit| (nets.node((location, bit)), (value >> bit) & 1 == 1))
+                    .collect();
+                parts.push(Part::Constant { component, nodes });
+            }
+            "Splitter" => {
+                let facing = component.facing()?;
+                let fanout = component.number("fanout", 2)?;
+                let incoming = component.number("incoming", 2)?.min(MAX_WIDTH);
+                let (first, step) = splitter_ends(component, facing, fanout)?;
+
+                // The number of bits already routed to each end.
+                let mut routed = vec![0; fanout as usize];
+                for bit in 0..incoming {
+                    let end = match component.attribute(&format!("bit{bit}")) {
+                        Some("none") => continue,
+                        Some(end) =>
+                            parse_number(end)
+                                .filter(|end| *end < fanout)
+                                .ok_or_else(|| {
+                                    ImportError::new(
+                                        component.line,
+                                        format!("invalid `bit{bit}`: `{end}`")
+                                    )
+                                })?,
+                        None => (bit * fanout) / incoming,
+                    };
+
+                    let end_location = location + first + step * (end as i32);
+                    let end_bit = routed[end as usize];
+                    routed[end as usize] += 1;
+                    nets.connect((location, bit), (end_location, end_bit));
+                }
+            }
+            "Tunnel" => {
+                let label = component.attribute("label").unwrap_or_default();
+                let tunnel = tunnels.entry(label).or_default();
+                tunnel.push((location, component.width()?));
+            }
+            name => {
+                warn!("skipping unsupported Logisim component `{name}` on line {}", component.line);
+            }
+        }
+    }
+
+    for tunnel in tunnels.values() {
+        let Some(&(first, _)) = tunnel.first() else {
+            continue;
+        };
+        for &(location, width) in tunnel.iter().skip(1) {
+            for bit in 0..width {
+                nets.connect((first, bit), (location, bit));
+            }
+        }
+    }
+
+    let nets = nets.build();
+
+    // The part and bit driving each net.
+    let mut drivers: HashMap<usize, (usize, usize)> = HashMap::default();
+    for (index, part) in parts.iter().enumerate() {
+        for (bit, node) in part.driven_nodes().into_iter().enumerate() {
+            let net = nets.find(node);
+            if drivers.insert(net, (index, bit)).is_some() {
+                let line = part.component().line;
+                return Err(ImportError::new(line, "more than one component drives the same net"));
+            }
+        }
+    }
+
+    let mut circuit = ImportedCircuit::default();
+    let mut gates: Vec<GateData<Known, Known>> = Vec::new();
+    // The gate spawned for each part's driven bits.
+    let mut outputs: Vec<Vec<usize>> = Vec::with_capacity(parts.len());
+    // (net, gate index, input index)
+    let mut connections: Vec<(usize, usize, usize)> = Vec::new();
+
+    for part in parts.iter() {
+        let component = part.component();
+        let transform = Transform::from_xyz(
+            (component.location.x as f32) * scale,
+            -(component.location.y as f32) * scale,
+            0.0
+        );
+        let label = component.label();
+
+        match part {
+            Part::Gate { kind, inputs, .. } => {
+                let inputs = inputs
+                    .iter()
+                    .filter(|(node, _)| drivers.contains_key(&nets.find(*node)))
+                    .collect::<Vec<_>>();
+                let count = inputs.len();
+                let gates = &mut gates;
+                let name = Name::new(label.clone());
+
+                let (input_gate, output_gate) = match kind {
+                    GateKind::Xnor => {
+                        let not_name = Name::new(format!("{label}.not"));
+                        let xor = spawn_gate(commands, gates, (name, XorGate, transform), count);
+                        let not = spawn_gate(commands, gates, (not_name, NotGate, transform), 1);
+                        commands.spawn_wire(&gates[xor], 0, &gates[not], 0);
+                        (xor, not)
+                    }
+                    kind => {
+                        let index = match kind {
+                            GateKind::And => {
+                                let bundle = (name, AndGate::default(), transform);
+                                spawn_gate(commands, gates, bundle, count)
+                            }
+                            GateKind::Nand => {
+                                spawn_gate(commands, gates, (name, AndGate::NAND, transform), count)
+                            }
+                            GateKind::Nor => {
+                                spawn_gate(commands, gates, (name, OrGate::NOR, transform), count)
+                            }
+                            GateKind::Xor => {
+                                spawn_gate(commands, gates, (name, XorGate, transform), count)
+                            }
+                            GateKind::Not => {
+                                spawn_gate(commands, gates, (name, NotGate, transform), count)
+                            }
+                            _ => {
+                                let bundle = (name, OrGate::default(), transform);
+                                spawn_gate(commands, gates, bundle, count)
+                            }
+                        };
+                        (index, index)
+                    }
+                };
+
+                for (i, &&(node, negated)) in inputs.iter().enumerate() {
+                    let net = nets.find(node);
+                    if negated {
+                        let not_name = Name::new(format!("{label}.negate{i}"));
+                        let not = spawn_gate(commands, gates, (not_name, NotGate, transform), 1);
+                        commands.spawn_wire(&gates[not], 0, &gates[input_gate], i);
+                        connections.push((net, not, 0));
+                    } else {
+                        connections.push((net, input_gate, i));
+                    }
+                }
+                outputs.push(vec![output_gate]);
+            }
+            Part::Pin { output, nodes, .. } => {
+                let mut spawned = Vec::with_capacity(nodes.len());
+                for (bit, &node) in nodes.iter().enumerate() {
+                    let name = match nodes.len() {
+                        1 => label.clone(),
+                        _ => format!("{label}[{bit}]"),
+                    };
+
+                    if *output {
+                        let bundle = (Name::new(name.clone()), OrGate::default(), transform);
+                        let index = spawn_gate(commands, &mut gates, bundle, 1);
+                        connections.push((nets.find(node), index, 0));
+                        circuit.outputs.insert(name, gates[index].id());
+                    } else {
+                        let bundle = (Name::new(name.clone()), Battery::OFF, transform);
+                        let index = spawn_gate(commands, &mut gates, bundle, 0);
+                        circuit.inputs.insert(name, gates[index].id());
+                        spawned.push(index);
+                    }
+                }
+                outputs.push(spawned);
+            }
+            Part::Constant { nodes, .. } => {
+                let spawned = nodes
+                    .iter()
+                    .enumerate()
+                    .map(|(bit, &(_, value))| {
+                        let name = match nodes.len() {
+                            1 => label.clone(),
+                            _ => format!("{label}[{bit}]"),
+                        };
+                        let battery = Battery::new(Signal::from(value));
+                        spawn_gate(commands, &mut gates, (Name::new(name), battery, transform), 0)
+                    })
+                    .collect();
+                outputs.push(spawned);
+            }
+        }
+    }
+
+    for (net, to_gate, to_input) in connections {
+        let Some(&(part, bit)) = drivers.get(&net) else {
+            continue;
+        };
+        let from_gate = outputs[part][bit];
+        commands.spawn_wire(&gates[from_gate], 0, &gates[to_gate], to_input);
+    }
+
+    for gate in gates.iter() {
+        commands.add(AddGateToLogicGraph(gate.id()));
+    }
+    circuit.gates.extend(gates.iter().map(|gate| gate.id()));
+
+    Ok(circuit)
+}
+
+/// A supported component that spawns gates, with the nodes of its ports.
+enum Part<'a> {
+    Gate {
+        component: &'a LogisimComponent,
+        kind: GateKind,
+        output: usize,
+        /// The node of each input, and whether the input is negated.
+        inputs: Vec<(usize, bool)>,
+    },
+    Pin {
+        component: &'a LogisimComponent,
+        output: bool,
+        nodes: Vec<usize>,
+    },
+    Constant {
+        component: &'a LogisimComponent,
+        nodes: Vec<(usize, bool)>,
+    },
+}
+
+impl Part<'_> {
+    fn component(&self) -> &LogisimComponent {
+        match self {
+            Part::Gate { component, .. } => component,
+            Part::Pin { component, .. } => component,
+            Part::Constant { component, .. } => component,
+        }
+    }
+
+    /// Returns the nodes this part drives, one per bit.
+    fn driven_nodes(&self) -> Vec<usize> {
+        match self {
+            Part::Gate { output, .. } => vec![*output],
+            Part::Pin { output: true, .. } => Vec::new(),
+            Part::Pin { nodes, .. } => nodes.clone(),
+            Part::Constant { nodes, .. } => nodes.iter().map(|(node, _)| *node).collect(),
+        }
+    }
+}
+
+/// Points and bits that are joined into nets.
+#[derive(Default)]
+struct Nets {
+    nodes: HashMap<(IVec2, u32), usize>,
+    edges: Vec<(usize, usize)>,
+}
+
+impl Nets {
+    fn node(&mut self, key: (IVec2, u32)) -> usize {
+        let len = self.nodes.len();
+        *self.nodes.entry(key).or_insert(len)
+    }
+
+    fn connect(&mut self, a: (IVec2, u32), b: (IVec2, u32)) {
+        let edge = (self.node(a), self.node(b));
+        self.edges.push(edge);
+    }
+
+    fn build(self) -> UnionFind<usize> {
+        let mut union_find = UnionFind::new(self.nodes.len());
+        for (a, b) in self.edges {
+            union_find.union(a, b);
+        }
+        union_find
+    }
+}
+
+/// Returns the offset across a gate of input `index`, following Logisim's layout.
+fn input_spacing(size: i32, inputs: u32, index: u32) -> i32 {
+    let (inputs, index) = (inputs as i32, index as i32);
+    let (start, distance, lower_even) = if inputs <= 3 {
+        if size < 40 {
+            (-5, 10, 10)
+        } else if size < 60 || inputs <= 2 {
+            (-10, 20, 20)
+        } else {
+            (-15, 30, 30)
+        }
+    } else if inputs == 4 && size >= 60 {
+        (-5, 20, 0)
+    } else {
+        (-5, 10, 10)
+    };
+
+    if inputs % 2 == 1 {
+        start * (inputs - 1) + distance * index
+    } else if index >= inputs / 2 {
+        start * inputs + distance * index + lower_even
+    } else {
+        start * inputs + distance * index
+    }
+}
+
+/// Returns the offset of a splitter's first end, and the step between its ends.
+fn splitter_ends(
+    component: &LogisimComponent,
+    facing: Facing,
+    fanout: u32
+) -> Result<(IVec2, IVec2), ImportError> {
+    let fanout = fanout as i32;
+    let justify = match component.attribute("appear").unwrap_or("left") {
+        "left" => -1,
+        "center" => 0,
+        "right" => 1,
+        appear => {
+            let message = format!("unsupported splitter appearance `{appear}`");
+            return Err(ImportError::new(component.line, message));
+        }
+    };
+
+    Ok(match facing {
+        Facing::North | Facing::South => {
+            let m = if facing == Facing::North { 1 } else { -1 };
+            let x = if justify == 0 {
+                10 * ((fanout + 1) / 2 - 1)
+            } else if m * justify < 0 {
+                -10
+            } else {
+                10 * fanout
+            };
+            (IVec2::new(x, -m * 20), IVec2::new(-10, 0))
+        }
+        Facing::East | Facing::West => {
+            let m = if facing == Facing::West { -1 } else { 1 };
+            let y = if justify == 0 {
+                -10 * (fanout / 2)
+            } else if m * justify > 0 {
+                -10 * fanout
+            } else {
+                10
+            };
+            (IVec2::new(m * 20, y), IVec2::new(0, 10))
+        }
+    })
+}
+
+/// Parse a point saved as `(x,y)`.
+fn parse_point(value: &str) -> Option<IVec2> {
+    let (x, y) = value.trim().strip_prefix('(')?.strip_suffix(')')?.split_once(',')?;
+    Some(IVec2::new(x.trim().parse().ok()?, y.trim().parse().ok()?))
+}
+
+/// Parse a decimal or `0x` prefixed hexadecimal number.
+fn parse_number(value: &str) -> Option<u32> {
+    match value.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+fn spawn_gate(
+    commands: &mut Commands,
+    gates: &mut Vec<GateData<Known, Known>>,
+    bundle: impl Bundle,
+    inputs: usize
+) -> usize {
+    gates.push(commands.spawn_gate(bundle).with_inputs(inputs).with_outputs(1).build());
+    gates.len() - 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HALF_ADDER: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>
+<project source="2.7.1" version="1.0">
+  <main name="main"/>
+  <circuit name="main">
+    <wire from="(100,80)" to="(200,80)"/>
+    <wire from="(100,120)" to="(200,120)"/>
+    <comp lib="0" loc="(100,80)" name="Pin">
+      <a name="label" val="a"/>
+    </comp>
+    <comp lib="0" loc="(100,120)" name="Pin">
+      <a name="label" val="b"/>
+    </comp>
+    <comp lib="1" loc="(250,100)" name="AND Gate">
+      <a name="inputs" val="2"/>
+    </comp>
+    <comp lib="0" loc="(300,100)" name="Pin">
+      <a name="output" val="true"/>
+      <a name="label" val="carry"/>
+    </comp>
+    <wire from="(250,100)" to="(300,100)"/>
+  </circuit>
+</project>"#;
+
+    #[test]
+    fn parse_circuit() {
+        let circuit = parse_logisim(HALF_ADDER, None).unwrap();
+        assert_eq!(circuit.name, "main");
+        assert_eq!(circuit.wires.len(), 3);
+        assert_eq!(circuit.components.len(), 4);
+        assert_eq!(circuit.components[2].location, IVec2::new(250, 100));
+        assert_eq!(circuit.components[2].attribute("inputs"), Some("2"));
+        assert!(parse_logisim(HALF_ADDER, Some("missing")).is_err());
+    }
+
+    #[test]
+    fn gate_input_layout() {
+        // A medium two-input gate facing east has inputs 50 behind and 20 to either side.
+        let offsets = (0..2)
+            .map(|index| Facing::East.offset(50, input_spacing(50, 2, index)))
+            .collect::<Vec<_>>();
+        assert_eq!(offsets, [IVec2::new(-50, -20), IVec2::new(-50, 20)]);
+        let spacing = (0..5).map(|index| input_spacing(50, 5, index)).collect::<Vec<_>>();
+        assert_eq!(spacing, [-20, -10, 0, 10, 20]);
+    }
+}
@@ -0,0 +1,57 @@
+use bevy::{ prelude::*, utils::HashMap };
+
+pub mod verilog;
+#[cfg(feature = "logisim")]
+pub mod logisim;
+
+pub mod prelude {
+    pub use super::{ ImportError, ImportedCircuit };
+    pub use super::verilog::{ parse_verilog, spawn_verilog, VerilogModule };
+    #[cfg(feature = "logisim")]
+    pub use super::logisim::{ parse_logisim, spawn_logisim, LogisimCircuit };
+}
+
+/// An error produced while importing a circuit from a file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImportError {
+    /// The line in the source where the error was found, starting at `1`.
+    /// This is `0` if the error is not tied to a line, such as a port declared twice.
+    pub line: usize,
+    pub message: String,
+}
+
+impl ImportError {
+    pub fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+/// The entities spawned for an imported circuit.
+///
+/// Input ports are spawned as [`Battery`] gates that start off. Set [`Battery::signal`]
+/// to drive the circuit. Output ports are spawned as single-input [`OrGate`]s, so their
+/// output fan carries the port's signal.
+///
+/// [`Battery`]: super::gates::Battery
+/// [`Battery::signal`]: super::gates::Battery::signal
+/// [`OrGate`]: super::gates::OrGate
+#[derive(Clone, Debug, Default)]
+pub struct ImportedCircuit {
+    /// The gate driving each input port, keyed by port name.
+    pub inputs: HashMap<String, Entity>,
+    /// The gate reading each output port, keyed by port name.
+    pub outputs: HashMap<String, Entity>,
+    /// Every gate that was spawned, including ports.
+    pub gates: Vec<Entity>,
+}
@@ -0,0 +1,547 @@
+//! A parser for a structural subset of Verilog.
+//!
+//! Only a single module of gate primitives is supported:
+//!
+//! ```verilog
+//! module half_adder (a, b, sum, carry);
+//!   input a, b;
+//!   output sum, carry;
+//!   xor g0 (sum, a, b);
+//!   and g1 (carry, a, b);
+//! endmodule
+//! ```
+//!
+//! - Ports can be declared in the header (`module m (input a, output y);`) or in the body.
+//! - `wire` declarations are accepted but not required.
+//! - The primitives `and`, `nand`, `or`, `nor`, `xor`, `xnor`, `not`, and `buf` are supported.
+//!   The first connection is the output, and the rest are inputs. Instance names are optional.
+//! - `1'b0` and `1'b1` can be used as constant inputs.
+//! - Vectors, `assign` statements, and module instances are not supported.
+
+use bevy::{ prelude::*, utils::{ HashMap, HashSet } };
+
+use crate::{
+    commands::AddGateToLogicGraph,
+    logic::{
+        builder::{ GateData, Known, LogicExt },
+        gates::{ AndGate, Battery, NotGate, OrGate, XorGate },
+    },
+};
+
+use super::{ ImportError, ImportedCircuit };
+
+/// A gate primitive in a [`VerilogModule`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VerilogPrimitive {
+    And,
+    Nand,
+    Or,
+    Nor,
+    Xor,
+    Xnor,
+    Not,
+    Buf,
+}
+
+impl VerilogPrimitive {
+    /// Returns the primitive for a Verilog keyword, such as `nand`.
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        Some(match keyword {
+            "and" => Self::And,
+            "nand" => Self::Nand,
+            "or" => Self::Or,
+            "nor" => Self::Nor,
+            "xor" => Self::Xor,
+            "xnor" => Self::Xnor,
+            "not" => Self::Not,
+            "buf" => Self::Buf,
+            _ => {
+                return None;
+            }
+        })
+    }
+
+    /// Returns the Verilog keyword of the primitive.
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            Self::And => "and",
+            Self::Nand => "nand",
+            Self::Or => "or",
+            Self::Nor => "nor",
+            Self::Xor => "xor",
+            Self::Xnor => "xnor",
+            Self::Not => "not",
+            Self::Buf => "buf",
+        }
+    }
+
+    /// Returns `true` if the primitive takes exactly one input.
+    pub fn is_unary(&self) -> bool {
+        matches!(self, Self::Not | Self::Buf)
+    }
+}
+
+/// A primitive gate instance in a [`VerilogModule`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerilogInstance {
+    pub primitive: VerilogPrimitive,
+    pub name: Option<String>,
+    /// The net driven by the gate.
+    pub output: String,
+    /// The nets read by the gate, in order.
+    pub inputs: Vec<String>,
+    /// The line the instance was declared on, used for error messages.
+    pub line: usize,
+}
+
+/// A parsed structural Verilog module. See the [module documentation](self) for the
+/// supported syntax.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VerilogModule {
+    pub name: String,
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
+    pub instances: Vec<VerilogInstance>,
+}
+
+impl VerilogModule {
+    /// Check that every net has exactly one driver, and that every primitive has a valid
+    /// number of inputs.
+    pub fn validate(&self) -> Result<(), ImportError> {
+        let mut drivers: HashMap<&str, usize> = HashMap::default();
+        for input in self.inputs.iter() {
+            if drivers.insert(input, 0).is_some() {
+                return Err(ImportError::new(0, format!("input `{input}` is declared twice")));
+            }
+        }
+        let mut outputs = HashSet::new();
+        for output in self.outputs.iter() {
+            if !outputs.insert(output) {
+                return Err(ImportError::new(0, format!("output `{output}` is declared twice")));
+            }
+        }
+
+        for instance in self.instances.iter() {
+            let line = instance.line;
+            let keyword = instance.primitive.keyword();
+            if instance.inputs.is_empty() {
+                return Err(ImportError::new(line, format!("`{keyword}` has no inputs")));
+            }
+            if instance.primitive.is_unary() && instance.inputs.len() != 1 {
+                return Err(ImportError::new(line, format!("`{keyword}` takes exactly one input")));
+            }
+            if constant(&instance.output).is_some() {
+                return Err(ImportError::new(line, "a constant cannot be driven"));
+            }
+            if let Some(previous) = drivers.insert(&instance.output, line) {
+                let driver = match previous {
+                    0 => "an input".to_string(),
+                    previous => format!("line {previous}"),
+                };
+                return Err(
+                    ImportError::new(
+                        line,
+                        format!("net `{}` is already driven by {driver}", instance.output)
+                    )
+                );
+            }
+        }
+
+        let reads = self.instances
+            .iter()
+            .flat_map(|instance| instance.inputs.iter().map(move |net| (instance.line, net)))
+            .chain(self.outputs.iter().map(|net| (0, net)));
+        for (line, net) in reads {
+            if !drivers.contains_key(net.as_str()) && constant(net).is_none() {
+                return Err(ImportError::new(line, format!("net `{net}` is not driven")));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the value of a constant net, such as `1'b1`.
+fn constant(net: &str) -> Option<bool> {
+    match net {
+        "1'b0" => Some(false),
+        "1'b1" => Some(true),
+        _ => None,
+    }
+}
+
+/// Parse a structural Verilog module from `source`. The module is validated with
+/// [`VerilogModule::validate`].
+pub fn parse_verilog(source: &str) -> Result<VerilogModule, ImportError> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser { tokens: &tokens, index: 0 };
+
+    parser.keyword("module")?;
+    let mut module = VerilogModule {
+        name: parser.ident()?.1.to_string(),
+        ..Default::default()
+    };
+
+    // Ports declared in the header, which may also have a direction.
+    let mut direction = None;
+    if parser.eat('(') && !parser.eat(')') {
+        loop {
+            let (_, word) = parser.ident()?;
+            match word {
+                "input" | "output" => {
+                    direction = Some(word);
+                    continue;
+                }
+                "wire" => {
+                    continue;
+                }
+                _ => {}
+            }
+
+            match direction {
+                Some("input") => module.inputs.push(word.to_string()),
+                Some("output") => module.outputs.push(word.to_string()),
+                _ => {}
+            }
+
+            if !parser.eat(',') {
+                parser.expect(')')?;
+                break;
+            }
+        }
+    }
+    parser.expect(';')?;
+
+    loop {
+        let (line, word) = parser.ident()?;
+        match word {
+            "endmodule" => {
+                break;
+            }
+            "input" | "output" | "wire" => {
+                let names = parser.names()?;
+                match word {
+                    "input" => module.inputs.extend(names),
+                    "output" => module.outputs.extend(names),
+                    _ => {}
+                }
+            }
+            keyword => {
+                let Some(primitive) = VerilogPrimitive::from_keyword(keyword) else {
+                    let message = format!("unsupported statement `{keyword}`");
+                    return Err(ImportError::new(line, message));
+                };
+
+                let name = match parser.peek() {
+                    Some(Token::Ident(name)) => {
+                        parser.index += 1;
+                        Some(name.clone())
+                    }
+                    _ => None,
+                };
+
+                parser.expect('(')?;
+                let mut nets = Vec::new();
+                loop {
+                    nets.push(parser.ident()?.1.to_string());
+                    if !parser.eat(',') {
+                        break;
+                    }
+                }
+                parser.expect(')')?;
+                parser.expect(';')?;
+
+                let output = nets.remove(0);
+                module.instances.push(VerilogInstance {
+                    primitive,
+                    name,
+                    output,
+                    inputs: nets,
+                    line,
+                });
+            }
+        }
+    }
+
+    module.validate()?;
+    Ok(module)
+}
+
+/// Spawn the gates of `module` and wire them together, then add them to the [`LogicGraph`]
+/// resource through [`AddGateToLogicGraph`].
+///
+/// Every gate gets a [`Name`]: instance names for primitives (or `<keyword><index>` if the
+/// instance is unnamed), and port names for ports. `xnor` is spawned as an [`XorGate`]
+/// followed by a [`NotGate`] named `<name>.not`, and `buf` as a single-input [`OrGate`].
+///
+/// [`LogicGraph`]: crate::resources::LogicGraph
+pub fn spawn_verilog(
+    commands: &mut Commands,
+    module: &VerilogModule
+) -> Result<ImportedCircuit, ImportError> {
+    module.validate()?;
+
+    let mut circuit = ImportedCircuit::default();
+    let mut gates: Vec<GateData<Known, Known>> = Vec::new();
+    let mut drivers: HashMap<&str, usize> = HashMap::default();
+    // (net, gate index, input index)
+    let mut connections: Vec<(&str, usize, usize)> = Vec::new();
+
+    for input in module.inputs.iter() {
+        let bundle = (Name::new(input.clone()), Battery::OFF);
+        let index = spawn_gate(commands, &mut gates, bundle, 0);
+        drivers.insert(input, index);
+        circuit.inputs.insert(input.clone(), gates[index].id());
+    }
+
+    for (i, instance) in module.instances.iter().enumerate() {
+        let name = instance.name
+            .clone()
+            .unwrap_or_else(|| format!("{}{i}", instance.primitive.keyword()));
+        let inputs = instance.inputs.len();
+        let gates = &mut gates;
+
+        // The gate that reads the instance's inputs, and the gate that drives its output.
+        let (input_gate, output_gate) = match instance.primitive {
+            VerilogPrimitive::Xnor => {
+                let not_name = Name::new(format!("{name}.not"));
+                let xor = spawn_gate(commands, gates, (Name::new(name), XorGate), inputs);
+                let not = spawn_gate(commands, gates, (not_name, NotGate), 1);
+                commands.spawn_wire(&gates[xor], 0, &gates[not], 0);
+                (xor, not)
+            }
+            primitive => {
+                let name = Name::new(name);
+                let index = match primitive {
+                    VerilogPrimitive::And => {
+                        spawn_gate(commands, gates, (name, AndGate::default()), inputs)
+                    }
+                    VerilogPrimitive::Nand => {
+                        spawn_gate(commands, gates, (name, AndGate::NAND), inputs)
+                    }
+                    VerilogPrimitive::Nor => {
+                        spawn_gate(commands, gates, (name, OrGate::NOR), inputs)
+                    }
+                    VerilogPrimitive::Xor => {
+                        spawn_gate(commands, gates, (name, XorGate), inputs)
+                    }
+                    VerilogPrimitive::Not => {
+                        spawn_gate(commands, gates, (name, NotGate), inputs)
+                    }
+                    _ => {
+                        spawn_gate(commands, gates, (name, OrGate::default()), inputs)
+                    }
+                };
+                (index, index)
+            }
+        };
+
+        for (i, net) in instance.inputs.iter().enumerate() {
+            connections.push((net, input_gate, i));
+        }
+        drivers.insert(&instance.output, output_gate);
+    }
+
+    for output in module.outputs.iter() {
+        let bundle = (Name::new(output.clone()), OrGate::default());
+        let index = spawn_gate(commands, &mut gates, bundle, 1);
+        connections.push((output, index, 0));
+        circuit.outputs.insert(output.clone(), gates[index].id());
+    }
+
+    for (net, to_gate, to_input) in connections {
+        let from_gate = match (drivers.get(net), constant(net)) {
+            (Some(&from_gate), _) => from_gate,
+            (None, Some(value)) => {
+                let bundle = (Name::new(net.to_string()), Battery::new(value.into()));
+                let from_gate = spawn_gate(commands, &mut gates, bundle, 0);
+                drivers.insert(net, from_gate);
+                from_gate
+            }
+            (None, None) => unreachable!("the module was validated"),
+        };
+
+        commands.spawn_wire(&gates[from_gate], 0, &gates[to_gate], to_input);
+    }
+
+    for gate in gates.iter() {
+        commands.add(AddGateToLogicGraph(gate.id()));
+    }
+    circuit.gates.extend(gates.iter().map(|gate| gate.id()));
+
+    Ok(circuit)
+}
+
+fn spawn_gate(
+    commands: &mut Commands,
+    gates: &mut Vec<GateData<Known, Known>>,
+    bundle: impl Bundle,
+    inputs: usize
+) -> usize {
+    gates.push(commands.spawn_gate(bundle).with_inputs(inputs).with_outputs(1).build());
+    gates.len() - 1
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Punct(char),
+}
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, ImportError> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut chars = source.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => {
+                line += 1;
+            }
+            c if c.is_whitespace() => {}
+            '/' if chars.peek() == Some(&'/') => {
+                while chars.next_if(|&c| c != '\n').is_some() {}
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                loop {
+                    let Some(c) = chars.next() else {
+                        return Err(ImportError::new(line, "unterminated comment"));
+                    };
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            '(' | ')' | ',' | ';' => tokens.push((line, Token::Punct(c))),
+            c if is_ident_char(c) => {
+                let mut word = String::from(c);
+                while let Some(c) = chars.next_if(|&c| is_ident_char(c) || c == '\'') {
+                    word.push(c);
+                }
+                tokens.push((line, Token::Ident(word)));
+            }
+            c => {
+                return Err(ImportError::new(line, format!("unexpected character `{c}`")));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '$'
+}
+
+struct Parser<'a> {
+    tokens: &'a [(usize, Token)],
+    index: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.index).map(|(_, token)| token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.index)
+            .or(self.tokens.last())
+            .map_or(1, |(line, _)| *line)
+    }
+
+    fn eat(&mut self, punct: char) -> bool {
+        let matches = self.peek() == Some(&Token::Punct(punct));
+        if matches {
+            self.index += 1;
+        }
+        matches
+    }
+
+    fn expect(&mut self, punct: char) -> Result<(), ImportError> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(ImportError::new(self.line(), format!("expected `{punct}`")))
+        }
+    }
+
+    fn ident(&mut self) -> Result<(usize, &'a str), ImportError> {
+        match self.tokens.get(self.index) {
+            Some((line, Token::Ident(ident))) => {
+                self.index += 1;
+                Ok((*line, ident))
+            }
+            _ => Err(ImportError::new(self.line(), "expected a name")),
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str) -> Result<(), ImportError> {
+        match self.ident()? {
+            (_, ident) if ident == keyword => Ok(()),
+            (line, ident) => {
+                Err(ImportError::new(line, format!("expected `{keyword}`, found `{ident}`")))
+            }
+        }
+    }
+
+    /// Parse a comma separated list of names, ending with `;`.
+    fn names(&mut self) -> Result<Vec<String>, ImportError> {
+        let mut names = Vec::new();
+        loop {
+            names.push(self.ident()?.1.to_string());
+            if !self.eat(',') {
+                self.expect(';')?;
+                return Ok(names);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_half_adder() {
+        let module = parse_verilog(
+            "// A half adder
+            module half_adder (input a, b, output sum, carry);
+              /* gates */
+              xor g0 (sum, a, b);
+              and (carry, a, b);
+            endmodule"
+        ).unwrap();
+
+        assert_eq!(module.name, "half_adder");
+        assert_eq!(module.inputs, vec!["a", "b"]);
+        assert_eq!(module.outputs, vec!["sum", "carry"]);
+        assert_eq!(module.instances.len(), 2);
+        assert_eq!(module.instances[0].name.as_deref(), Some("g0"));
+        assert_eq!(module.instances[1].primitive, VerilogPrimitive::And);
+        assert_eq!(module.instances[1].line, 5);
+    }
+
+    #[test]
+    fn rejects_invalid_netlists() {
+        let source = "module m (a, y); input a; output y; not (y, a); not (y, a); endmodule";
+        assert!(parse_verilog(source).unwrap_err().message.contains("already driven"));
+
+        let source = "module m (a, y);\ninput a;\noutput y;\nand (y, a, b);\nendmodule";
+        let error = parse_verilog(source).unwrap_err();
+        assert_eq!(error, ImportError::new(4, "net `b` is not driven"));
+
+        let source = "module m (a, y); input a; output y; assign y = a; endmodule";
+        assert!(parse_verilog(source).is_err());
+
+        let source = "module m (a, y); input a; output y, y; not (y, a); endmodule";
+        let error = parse_verilog(source).unwrap_err();
+        assert_eq!(error, ImportError::new(0, "output `y` is declared twice"));
+    }
+}
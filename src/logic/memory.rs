@@ -0,0 +1,230 @@
+//! Gates that store signals: latches, sample-and-holds, and queues.
+
+use bevy::prelude::*;
+
+use super::{
+    registry::GateRegistry,
+    signal::{ Signal, SignalExt },
+    AppLogicGateExt,
+    GateArity,
+    GateContext,
+    LogicGate,
+};
+
+pub(crate) fn register_memory_gates(app: &mut App) {
+    app.register_logic_gate::<SampleAndHold>()
+        .register_logic_gate::<AnalogLatch>()
+        .register_logic_gate::<FifoGate>();
+
+    app.register_gate_arity::<SampleAndHold>(GateArity::exact_inputs(2))
+        .register_gate_arity::<AnalogLatch>(GateArity::exact_inputs(2))
+        .register_gate_arity::<FifoGate>(GateArity::exact_inputs(3));
+
+    app.world_mut().resource_mut::<GateRegistry>().set_default_fans::<FifoGate>(3, 3);
+
+    app.register_type::<SampleAndHold>()
+        .register_type::<AnalogLatch>()
+        .register_type::<FifoGate>();
+}
+
+/// A sample-and-hold captures the signal of its first input when its second input, the
+/// clock, turns on, and holds it on every output until the next rising edge.
+///
+/// The outputs are [`Signal::Undefined`] until the first sample. Without a [`GateContext`],
+/// as in [`LogicGate::evaluate`], the gate samples whenever the clock is on.
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+pub struct SampleAndHold {
+    /// The signal captured by the latest sample.
+    pub held: Signal,
+}
+
+impl LogicGate for SampleAndHold {
+    fn evaluate(&mut self, inputs: &[Signal], outputs: &mut [Signal]) {
+        if inputs.get(1).is_some_and(Signal::is_truthy) {
+            self.held = inputs.first().copied().unwrap_or_default();
+        }
+        outputs.set_all(self.held);
+    }
+
+    fn evaluate_with_context(
+        &mut self,
+        ctx: &GateContext,
+        inputs: &[Signal],
+        outputs: &mut [Signal]
+    ) {
+        let rising =
+            ctx.previous_input(1).is_falsy() && inputs.get(1).is_some_and(Signal::is_truthy);
+        if rising {
+            self.held = inputs.first().copied().unwrap_or_default();
+        }
+        outputs.set_all(self.held);
+    }
+
+    fn reset(&mut self) {
+        self.held = Signal::Undefined;
+    }
+}
+
+/// An analog latch passes the signal of its first input to every output while its second
+/// input, the enable, is on, and holds the last signal it passed while the enable is off.
+///
+/// The outputs are [`Signal::Undefined`] until the latch is first enabled.
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+pub struct AnalogLatch {
+    /// The signal passed while the latch was last enabled.
+    pub held: Signal,
+}
+
+impl LogicGate for AnalogLatch {
+    fn evaluate(&mut self, inputs: &[Signal], outputs: &mut [Signal]) {
+        if inputs.get(1).is_some_and(Signal::is_truthy) {
+            self.held = inputs.first().copied().unwrap_or_default();
+        }
+        outputs.set_all(self.held);
+    }
+
+    fn reset(&mut self) {
+        self.held = Signal::Undefined;
+    }
+}
+
+/// A first-in, first-out queue of signals, for pipelines and buffered machines.
+///
+/// The inputs are the data, push, and pop, in that order. When the push input turns on, the
+/// data signal is added to the back of the queue, unless it is full. When the pop input turns
+/// on, the signal at the front is removed. A pop happens before a push in the same step, so a
+/// full queue can take a new signal while it releases one.
+///
+/// The outputs are the signal at the front of the queue, or [`Signal::Undefined`] if it is
+/// empty, and whether the queue is full and empty.
+///
+/// Without a [`GateContext`], as in [`LogicGate::evaluate`], the gate pushes and pops
+/// whenever those inputs are on.
+#[derive(Component, Clone, Debug, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+pub struct FifoGate {
+    /// A ring buffer of queued signals, as long as the capacity of the queue.
+    slots: Vec<Signal>,
+    /// The index of the front of the queue in `slots`.
+    head: usize,
+    /// The number of queued signals.
+    len: usize,
+}
+
+impl Default for FifoGate {
+    fn default() -> Self {
+        Self::new(8)
+    }
+}
+
+impl FifoGate {
+    /// The index of the data input.
+    pub const DATA: usize = 0;
+    /// The index of the push input.
+    pub const PUSH: usize = 1;
+    /// The index of the pop input.
+    pub const POP: usize = 2;
+
+    /// Create an empty queue that holds up to `capacity` signals.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            slots: vec![Signal::Undefined; capacity],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == self.capacity()
+    }
+
+    /// Returns the signal at the front of the queue.
+    pub fn front(&self) -> Option<Signal> {
+        (!self.is_empty()).then(|| self.slots[self.head])
+    }
+
+    /// Add `signal` to the back of the queue. Returns `false` if the queue is full.
+    pub fn push(&mut self, signal: Signal) -> bool {
+        if self.is_full() {
+            return false;
+        }
+        let tail = (self.head + self.len) % self.capacity();
+        self.slots[tail] = signal;
+        self.len += 1;
+        true
+    }
+
+    /// Remove and return the signal at the front of the queue.
+    pub fn pop(&mut self) -> Option<Signal> {
+        let signal = self.front()?;
+        self.slots[self.head] = Signal::Undefined;
+        self.head = (self.head + 1) % self.capacity();
+        self.len -= 1;
+        Some(signal)
+    }
+
+    /// Remove every signal from the queue.
+    pub fn clear(&mut self) {
+        self.slots.fill(Signal::Undefined);
+        self.head = 0;
+        self.len = 0;
+    }
+
+    fn update(&mut self, push: bool, pop: bool, inputs: &[Signal], outputs: &mut [Signal]) {
+        if pop {
+            self.pop();
+        }
+        if push {
+            self.push(inputs.get(Self::DATA).copied().unwrap_or_default());
+        }
+
+        let signals = [
+            self.front().unwrap_or_default(),
+            self.is_full().into(),
+            self.is_empty().into(),
+        ];
+        for (output, signal) in outputs.iter_mut().zip(signals) {
+            *output = signal;
+        }
+    }
+}
+
+impl LogicGate for FifoGate {
+    fn evaluate(&mut self, inputs: &[Signal], outputs: &mut [Signal]) {
+        let is_on = |index: usize| inputs.get(index).is_some_and(Signal::is_truthy);
+        self.update(is_on(Self::PUSH), is_on(Self::POP), inputs, outputs);
+    }
+
+    fn evaluate_with_context(
+        &mut self,
+        ctx: &GateContext,
+        inputs: &[Signal],
+        outputs: &mut [Signal]
+    ) {
+        let rising = |index: usize| {
+            ctx.previous_input(index).is_falsy() && inputs.get(index).is_some_and(Signal::is_truthy)
+        };
+        self.update(rising(Self::PUSH), rising(Self::POP), inputs, outputs);
+    }
+
+    fn reset(&mut self) {
+        self.clear();
+    }
+}
@@ -0,0 +1,193 @@
+//! Parametric meshes for gate bodies and fans. Requires the `meshgen` feature.
+//!
+//! Gate bodies are sized by their fan counts with [`gate_size`], which gives every fan half a
+//! unit of height. Use the same size for the gate's [`GateSize`] so its [`FanLayout`] puts the
+//! fans on the edges of the body.
+//!
+//! [`GateSize`]: crate::layout::GateSize
+//! [`FanLayout`]: crate::layout::FanLayout
+
+use std::f32::consts::{ FRAC_PI_2, TAU };
+
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{ Indices, MeshBuilder, PrimitiveTopology },
+        render_asset::RenderAssetUsages,
+    },
+};
+
+use crate::components::GateFan;
+
+/// The height of a gate body per fan on its busiest side.
+pub const FAN_SPACING: f32 = 0.5;
+
+/// Returns the size of a gate body with room for `inputs` and `outputs` fans. Bodies are at
+/// least one unit wide and tall.
+pub fn gate_size(inputs: usize, outputs: usize) -> Vec2 {
+    let height = ((inputs.max(outputs) as f32) * FAN_SPACING).max(1.0);
+    Vec2::new(1.0, height)
+}
+
+/// Builds the mesh of a gate body: a rounded rectangle on the XY plane, extruded along Z.
+///
+/// The front face has UVs that span the whole rectangle, so a gate icon can be drawn on it.
+/// A `thickness` of `0.0` builds the front face only, for 2D gates.
+///
+/// ```ignore
+/// let mesh = meshes.add(GateMeshBuilder::for_fans(3, 1).with_thickness(0.1));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GateMeshBuilder {
+    /// The width and height of the body.
+    pub size: Vec2,
+    /// The radius of each corner. It is clamped to half of the smaller side.
+    pub corner_radius: f32,
+    /// The number of segments in each corner.
+    pub corner_segments: usize,
+    /// The depth of the body along Z, centered on the origin.
+    pub thickness: f32,
+}
+
+impl Default for GateMeshBuilder {
+    fn default() -> Self {
+        Self::new(Vec2::ONE)
+    }
+}
+
+impl GateMeshBuilder {
+    pub fn new(size: Vec2) -> Self {
+        Self {
+            size,
+            corner_radius: 0.1,
+            corner_segments: 4,
+            thickness: 0.1,
+        }
+    }
+
+    /// Create a builder for a body with room for `inputs` and `outputs` fans. See [`gate_size`].
+    pub fn for_fans(inputs: usize, outputs: usize) -> Self {
+        Self::new(gate_size(inputs, outputs))
+    }
+
+    pub fn with_corner_radius(mut self, corner_radius: f32) -> Self {
+        self.corner_radius = corner_radius;
+        self
+    }
+
+    pub fn with_corner_segments(mut self, corner_segments: usize) -> Self {
+        self.corner_segments = corner_segments;
+        self
+    }
+
+    pub fn with_thickness(mut self, thickness: f32) -> Self {
+        self.thickness = thickness;
+        self
+    }
+
+    /// Returns the outline of the body in counter-clockwise order, starting at the right edge.
+    pub fn contour(&self) -> Vec<Vec2> {
+        let half_size = self.size * 0.5;
+        let radius = self.corner_radius.clamp(0.0, half_size.min_element().max(0.0));
+        let inner = half_size - Vec2::splat(radius);
+        let corners = [
+            inner,
+            Vec2::new(-inner.x, inner.y),
+            -inner,
+            Vec2::new(inner.x, -inner.y),
+        ];
+
+        if radius <= 0.0 {
+            return corners.to_vec();
+        }
+
+        let segments = self.corner_segments.max(1);
+        corners
+            .iter()
+            .enumerate()
+            .flat_map(|(corner, &center)| {
+                (0..=segments).map(move |i| {
+                    let angle = ((corner as f32) + (i as f32) / (segments as f32)) * FRAC_PI_2;
+                    center + Vec2::from_angle(angle) * radius
+                })
+            })
+            .collect()
+    }
+}
+
+impl MeshBuilder for GateMeshBuilder {
+    fn build(&self) -> Mesh {
+        let contour = self.contour();
+        let half_thickness = self.thickness * 0.5;
+        let uv = |point: Vec2| [point.x / self.size.x + 0.5, 0.5 - point.y / self.size.y];
+
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        let mut uvs = Vec::new();
+        let mut indices = Vec::new();
+
+        // The front and back faces are fans around the center, which works because the
+        // contour is convex.
+        let faces: &[(f32, f32)] = if self.thickness > 0.0 {
+            &[(1.0, half_thickness), (-1.0, -half_thickness)]
+        } else {
+            &[(1.0, 0.0)]
+        };
+        for &(facing, z) in faces {
+            let center = positions.len() as u32;
+            positions.push([0.0, 0.0, z]);
+            uvs.push(uv(Vec2::ZERO));
+            for &point in contour.iter() {
+                positions.push([point.x, point.y, z]);
+                uvs.push(uv(point));
+            }
+            normals.resize(positions.len(), [0.0, 0.0, facing]);
+
+            let len = contour.len() as u32;
+            for i in 0..len {
+                let (a, b) = (center + 1 + i, center + 1 + ((i + 1) % len));
+                if facing > 0.0 {
+                    indices.extend([center, a, b]);
+                } else {
+                    indices.extend([center, b, a]);
+                }
+            }
+        }
+
+        // Each wall is a quad with its own vertices, so it is shaded flat.
+        if self.thickness > 0.0 {
+            for (i, &a) in contour.iter().enumerate() {
+                let b = contour[(i + 1) % contour.len()];
+                let normal = Vec2::new(b.y - a.y, a.x - b.x).normalize_or_zero();
+                let start = positions.len() as u32;
+                positions.extend([
+                    [a.x, a.y, -half_thickness],
+                    [b.x, b.y, -half_thickness],
+                    [b.x, b.y, half_thickness],
+                    [a.x, a.y, half_thickness],
+                ]);
+                normals.extend([[normal.x, normal.y, 0.0]; 4]);
+                uvs.extend([[0.0, 0.0]; 4]);
+                indices.extend([start, start + 1, start + 2, start, start + 2, start + 3]);
+            }
+        }
+
+        Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+            .with_inserted_indices(Indices::U32(indices))
+    }
+}
+
+/// Returns the mesh of a fan marker on the XY plane: a circle for inputs, and a triangle
+/// pointing along +X for outputs, both `radius` in size.
+pub fn fan_marker_mesh(kind: &GateFan, radius: f32) -> Mesh {
+    match kind {
+        GateFan::Input => Mesh::from(Circle::new(radius)),
+        GateFan::Output => {
+            let corner = |turns: f32| Vec2::from_angle(turns * TAU) * radius;
+            Mesh::from(Triangle2d::new(corner(0.0), corner(1.0 / 3.0), corner(2.0 / 3.0)))
+        }
+    }
+}
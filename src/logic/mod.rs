@@ -1,24 +1,232 @@
 pub mod signal;
 pub mod gates;
+#[cfg(feature = "gates-analog")]
+pub mod analog;
+#[cfg(any(test, feature = "bench"))]
+pub mod bench_util;
 pub mod builder;
 pub mod schedule;
+pub mod data;
+pub mod dynamic;
+pub mod registry;
+pub mod expression;
+#[cfg(feature = "gates-sequential")]
+pub mod fsm;
+pub mod import;
+#[cfg(feature = "gates-memory")]
+pub mod memory;
+#[cfg(feature = "gates-sequential")]
+pub mod sequential;
+pub mod verify;
+#[cfg(feature = "gates-analog")]
+pub mod waveform;
+#[cfg(feature = "meshgen")]
+pub mod meshgen;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 pub mod prelude {
     pub use super::builder::LogicExt;
     pub use super::gates::*;
+    #[cfg(feature = "gates-analog")]
+    pub use super::analog::{ Capacitor, Comparator, Fuse, FuseTripped, WindowComparator };
+    #[cfg(feature = "gates-memory")]
+    pub use super::memory::{ AnalogLatch, FifoGate, SampleAndHold };
+    #[cfg(feature = "gates-sequential")]
+    pub use super::sequential::{ Counter, LfsrGate, SequenceDetector, Selector };
     pub use super::schedule::prelude::*;
-    pub use super::signal::{ Signal, SignalExt };
-    pub use super::{ LogicGate, AppLogicGateExt };
+    pub use super::signal::{ OrderedSignal, Signal, SignalExt };
+    pub use super::data::{ DataSignal, DataSignalPlugin };
+    pub use super::dynamic::{ DynamicGate, DynamicGateRegistry };
+    pub use super::registry::{ GateRegistration, GateRegistry, GateSpawner };
+    pub use super::expression::ExpressionGate;
+    #[cfg(feature = "gates-sequential")]
+    pub use super::fsm::{ FsmCondition, FsmGate, FsmState, FsmTransition };
+    pub use super::import::prelude::*;
+    pub use super::verify::prelude::*;
+    #[cfg(feature = "gates-analog")]
+    pub use super::waveform::{ Waveform, WaveformGate };
+    #[cfg(feature = "meshgen")]
+    pub use super::meshgen::{ fan_marker_mesh, gate_size, GateMeshBuilder };
+    #[cfg(feature = "wasm")]
+    pub use super::wasm::WasmGate;
+    pub use super::{
+        LogicGate,
+        GateContext,
+        GateEffects,
+        GateArity,
+        LogicGateTypes,
+        AppLogicGateExt,
+    };
 }
 
-use bevy::prelude::*;
+use std::{ cell::RefCell, time::Duration };
+
+use bevy::{
+    ecs::{
+        component::ComponentId,
+        system::{ SystemBuffer, SystemMeta },
+        world::{ Command, CommandQueue, DeferredWorld, EntityRef },
+    },
+    prelude::*,
+    utils::get_short_name,
+};
+use registry::{ GateRegistration, GateRegistry };
 use signal::Signal;
 
+use crate::sim::{ CircuitContext, CircuitGate };
+
 /// A trait that defines the behavior of a logic gate.
 #[bevy_trait_query::queryable]
 pub trait LogicGate {
     /// Evaluate the current state of inputs (in order), and update the outputs (in order).
     fn evaluate(&mut self, inputs: &[Signal], outputs: &mut [Signal]);
+
+    /// Evaluate the gate like [`LogicGate::evaluate`], with access to the [`GateContext`] of
+    /// the step, such as the gate entity and the current tick.
+    ///
+    /// This is what [`step_logic`] calls. The default implementation ignores the context and
+    /// calls [`LogicGate::evaluate`], so existing gates keep working. Gates that need time or
+    /// identity override this, and still implement [`LogicGate::evaluate`] for callers
+    /// without a context.
+    ///
+    /// [`step_logic`]: crate::systems::step_logic
+    fn evaluate_with_context(
+        &mut self,
+        ctx: &GateContext,
+        inputs: &[Signal],
+        outputs: &mut [Signal]
+    ) {
+        let _ = ctx;
+        self.evaluate(inputs, outputs);
+    }
+
+    /// Return the gate to its initial state, such as clearing the count of a counter or the
+    /// stored bit of a flip-flop. Called by [`ResetCircuit`]. Does nothing by default.
+    ///
+    /// [`ResetCircuit`]: crate::commands::ResetCircuit
+    fn reset(&mut self) {}
+
+    /// Called when the gate component is added to `entity`, before the gate is evaluated.
+    /// Does nothing by default.
+    fn on_added(&mut self, entity: Entity) {
+        let _ = entity;
+    }
+
+    /// Called when the gate component is removed from `entity`, or the entity is despawned.
+    /// Does nothing by default.
+    fn on_removed(&mut self, entity: Entity) {
+        let _ = entity;
+    }
+
+    /// Returns the type name of the gate. This is used to group measurements in diagnostics.
+    fn gate_type_name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+}
+
+/// The step a gate is evaluated in, passed to [`LogicGate::evaluate_with_context`].
+#[derive(Clone, Copy, Debug)]
+pub struct GateContext<'a> {
+    /// The gate entity.
+    pub entity: Entity,
+    /// The [`LogicTick`] of the current step.
+    ///
+    /// [`LogicTick`]: schedule::LogicTick
+    pub tick: u64,
+    /// The simulated duration of a step, from [`Time<LogicStep>`].
+    ///
+    /// [`Time<LogicStep>`]: schedule::LogicStep
+    pub timestep: Duration,
+    /// The input signals of the gate's previous evaluation, which is empty if this is its
+    /// first evaluation.
+    pub previous_inputs: &'a [Signal],
+    /// Side effects to apply to the world after the step.
+    pub effects: &'a GateEffects,
+}
+
+impl GateContext<'_> {
+    /// Returns the signal of an input during the previous evaluation, or
+    /// [`Signal::Undefined`] if there was none.
+    pub fn previous_input(&self, index: usize) -> Signal {
+        self.previous_inputs.get(index).copied().unwrap_or_default()
+    }
+
+    /// Returns the simulated time since the first step, `tick` timesteps.
+    pub fn elapsed(&self) -> Duration {
+        self.timestep.saturating_mul(self.tick.try_into().unwrap_or(u32::MAX))
+    }
+}
+
+impl<T: LogicGate + ?Sized> CircuitGate<Signal> for T {
+    fn evaluate_gate(
+        &mut self,
+        context: &CircuitContext<'_, Signal>,
+        inputs: &[Signal],
+        outputs: &mut [Signal]
+    ) {
+        let effects = GateEffects::default();
+        let context = GateContext {
+            entity: Entity::from_raw(context.gate.index() as u32),
+            tick: context.tick,
+            timestep: context.timestep,
+            previous_inputs: context.previous_inputs,
+            effects: &effects,
+        };
+        self.evaluate_with_context(&context, inputs, outputs);
+    }
+}
+
+/// A buffer of side effects queued by gates during evaluation, such as spawning a particle
+/// or sending an event.
+///
+/// Gates can't access the world while they are evaluated. Effects queued through
+/// [`GateContext::effects`] are applied in order after [`step_logic`], before
+/// [`LogicSystemSet::PostStep`], like [`Commands`].
+///
+/// [`step_logic`]: crate::systems::step_logic
+/// [`LogicSystemSet::PostStep`]: schedule::LogicSystemSet::PostStep
+#[derive(Default)]
+pub struct GateEffects {
+    queue: RefCell<CommandQueue>,
+}
+
+impl GateEffects {
+    /// Queue a [`Command`].
+    pub fn add(&self, command: impl Command) {
+        self.queue.borrow_mut().push(command);
+    }
+
+    /// Queue spawning an entity with `bundle`.
+    pub fn spawn(&self, bundle: impl Bundle) {
+        self.add(move |world: &mut World| {
+            world.spawn(bundle);
+        });
+    }
+
+    /// Queue sending an event.
+    pub fn send_event<E: Event>(&self, event: E) {
+        self.add(move |world: &mut World| {
+            world.send_event(event);
+        });
+    }
+
+    /// Returns `true` if no effects are queued.
+    pub fn is_empty(&self) -> bool {
+        self.queue.borrow().is_empty()
+    }
+}
+
+impl std::fmt::Debug for GateEffects {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GateEffects").field("queue", &self.queue).finish()
+    }
+}
+
+impl SystemBuffer for GateEffects {
+    fn apply(&mut self, _system_meta: &SystemMeta, world: &mut World) {
+        self.queue.get_mut().apply(world);
+    }
 }
 
 /// An [App] extension for registering `LogicGate` components through `bevy_trait_query`.
@@ -29,15 +237,268 @@ pub trait AppLogicGateExt {
     ///
     /// Calling this multiple times with the same arguments will do nothing on subsequent calls.
     ///
+    /// The component is also recorded in the [`LogicGateTypes`] and [`GateRegistry`] resources,
+    /// and given component hooks that call [`LogicGate::on_added`] and
+    /// [`LogicGate::on_removed`], unless it already has `on_add` or `on_remove` hooks of its own.
+    ///
     /// # Panics
     ///
     /// Panics if called after starting the [`World`] simulation.
     fn register_logic_gate<T: Component + LogicGate>(&mut self) -> &mut Self;
+
+    /// Declare how many fans a gate type accepts, registering it with
+    /// [`register_logic_gate`](Self::register_logic_gate) if it isn't yet.
+    ///
+    /// Gates that violate their arity are reported by [`validate_gate_arity`] with a warning
+    /// and a [`GateArityViolation`] event.
+    ///
+    /// [`validate_gate_arity`]: crate::systems::validate_gate_arity
+    /// [`GateArityViolation`]: crate::components::GateArityViolation
+    fn register_gate_arity<T: Component + LogicGate>(&mut self, arity: GateArity) -> &mut Self;
 }
 
 impl AppLogicGateExt for App {
     fn register_logic_gate<T: Component + LogicGate>(&mut self) -> &mut Self {
         use bevy_trait_query::RegisterExt;
+        let world = self.world_mut();
+        let id = world.init_component::<T>();
+        world
+            .get_resource_or_insert_with(LogicGateTypes::default)
+            .insert(LogicGateType {
+                id,
+                name: get_short_name(std::any::type_name::<T>()),
+                arity: None,
+                register: |app| {
+                    app.register_logic_gate::<T>();
+                },
+            });
+        world
+            .get_resource_or_insert_with(GateRegistry::default)
+            .insert(GateRegistration::new::<T>(id));
+        // Hooks can't be registered once the component is in use.
+        if !world.archetypes().iter().any(|archetype| archetype.contains(id)) {
+            let hooks = world.register_component_hooks::<T>();
+            hooks.try_on_add(gate_added::<T>);
+            hooks.try_on_remove(gate_removed::<T>);
+        }
         self.register_component_as::<dyn LogicGate, T>()
     }
+
+    fn register_gate_arity<T: Component + LogicGate>(&mut self, arity: GateArity) -> &mut Self {
+        self.register_logic_gate::<T>();
+        let world = self.world_mut();
+        let id = world.init_component::<T>();
+        world.resource_mut::<LogicGateTypes>().set_arity(id, arity);
+        world.resource_mut::<GateRegistry>().set_arity(std::any::TypeId::of::<T>(), arity);
+        self
+    }
+}
+
+/// The number of fans a gate type accepts. Declare it with
+/// [`AppLogicGateExt::register_gate_arity`].
+///
+/// ```ignore
+/// app.register_gate_arity::<Selector>(GateArity::inputs(3, Some(3)).with_outputs(1));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Reflect)]
+pub struct GateArity {
+    pub min_inputs: usize,
+    /// The most inputs the gate accepts, or `None` for any number.
+    pub max_inputs: Option<usize>,
+    /// The exact number of outputs, or `None` for any number.
+    pub outputs: Option<usize>,
+}
+
+impl Default for GateArity {
+    fn default() -> Self {
+        Self::ANY
+    }
+}
+
+impl GateArity {
+    /// Any number of inputs and outputs.
+    pub const ANY: GateArity = GateArity {
+        min_inputs: 0,
+        max_inputs: None,
+        outputs: None,
+    };
+
+    /// Between `min` and `max` inputs, and any number of outputs.
+    pub const fn inputs(min: usize, max: Option<usize>) -> Self {
+        Self {
+            min_inputs: min,
+            max_inputs: max,
+            outputs: None,
+        }
+    }
+
+    /// Exactly `inputs` inputs, and any number of outputs.
+    pub const fn exact_inputs(inputs: usize) -> Self {
+        Self::inputs(inputs, Some(inputs))
+    }
+
+    pub const fn with_outputs(mut self, outputs: usize) -> Self {
+        self.outputs = Some(outputs);
+        self
+    }
+
+    /// Returns the number of input and output fans to give a new gate with this arity: its
+    /// fewest inputs, or two if it accepts any number, and one output unless it expects a
+    /// different number.
+    pub fn default_fans(&self) -> (usize, usize) {
+        let inputs = match (self.min_inputs, self.max_inputs) {
+            (0, None) => 2,
+            (min, _) => min,
+        };
+        (inputs, self.outputs.unwrap_or(1))
+    }
+
+    /// Returns `true` if a gate with `inputs` and `outputs` fans satisfies this arity.
+    pub fn accepts(&self, inputs: usize, outputs: usize) -> bool {
+        let max_inputs = self.max_inputs.unwrap_or(usize::MAX);
+        let outputs_match = match self.outputs {
+            Some(expected) => outputs == expected,
+            None => true,
+        };
+        (self.min_inputs..=max_inputs).contains(&inputs) && outputs_match
+    }
+}
+
+impl std::fmt::Display for GateArity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.max_inputs {
+            Some(max) if max == self.min_inputs => write!(f, "{max} inputs")?,
+            Some(max) => write!(f, "{} to {max} inputs", self.min_inputs)?,
+            None => write!(f, "at least {} inputs", self.min_inputs)?,
+        }
+        match self.outputs {
+            Some(outputs) => write!(f, " and {outputs} outputs"),
+            None => Ok(()),
+        }
+    }
+}
+
+fn gate_added<T: Component + LogicGate>(
+    mut world: DeferredWorld,
+    entity: Entity,
+    _: ComponentId
+) {
+    if let Some(mut gate) = world.get_mut::<T>(entity) {
+        gate.on_added(entity);
+    }
+}
+
+fn gate_removed<T: Component + LogicGate>(
+    mut world: DeferredWorld,
+    entity: Entity,
+    _: ComponentId
+) {
+    if let Some(mut gate) = world.get_mut::<T>(entity) {
+        gate.on_removed(entity);
+    }
+}
+
+/// A resource that records every component registered as a [`LogicGate`]
+/// through [`AppLogicGateExt::register_logic_gate`].
+///
+/// This identifies the type of a gate without a trait query, such as when only
+/// a `&World` is available, and lets the same gates be registered in another [`App`].
+#[derive(Resource, Default, Debug)]
+pub struct LogicGateTypes {
+    types: Vec<LogicGateType>,
+}
+
+#[derive(Debug)]
+struct LogicGateType {
+    id: ComponentId,
+    name: String,
+    arity: Option<GateArity>,
+    register: fn(&mut App),
+}
+
+impl LogicGateTypes {
+    fn insert(&mut self, gate_type: LogicGateType) {
+        if !self.types.iter().any(|other| other.id == gate_type.id) {
+            self.types.push(gate_type);
+        }
+    }
+
+    fn set_arity(&mut self, id: ComponentId, arity: GateArity) {
+        if let Some(gate_type) = self.types.iter_mut().find(|gate_type| gate_type.id == id) {
+            gate_type.arity = Some(arity);
+        }
+    }
+
+    /// Returns the short type name and declared [`GateArity`] of the gate component on
+    /// `entity`, if it has one with a declared arity.
+    pub fn arity_of(&self, entity: EntityRef) -> Option<(&str, GateArity)> {
+        self.types
+            .iter()
+            .find(|gate_type| entity.contains_id(gate_type.id))
+            .and_then(|gate_type| Some((gate_type.name.as_str(), gate_type.arity?)))
+    }
+
+    /// Returns the short type name of the gate component on `entity`, if it has one.
+    pub fn type_name_of(&self, entity: EntityRef) -> Option<&str> {
+        self.types
+            .iter()
+            .find(|gate_type| entity.contains_id(gate_type.id))
+            .map(|gate_type| gate_type.name.as_str())
+    }
+
+    /// Iterate over the component ids and short type names of all registered gates.
+    pub fn iter(&self) -> impl Iterator<Item = (ComponentId, &str)> {
+        self.types.iter().map(|gate_type| (gate_type.id, gate_type.name.as_str()))
+    }
+
+    /// Register every recorded gate, and its arity, in another `app`.
+    pub fn register_all(&self, app: &mut App) {
+        for gate_type in self.types.iter() {
+            (gate_type.register)(app);
+        }
+
+        let mut types = app.world_mut().resource_mut::<LogicGateTypes>();
+        for gate_type in self.types.iter() {
+            let Some(arity) = gate_type.arity else {
+                continue;
+            };
+            let other = types.types.iter_mut().find(|other| other.name == gate_type.name);
+            if let Some(other) = other {
+                other.arity = Some(arity);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "gates-analog"))]
+mod tests {
+    use super::{
+        builder::LogicExt,
+        schedule::{ FixedLogicStepExt, LogicStep, LogicTick, LogicWorldExt },
+        waveform::WaveformGate,
+        *,
+    };
+    use crate::{ resources::LogicGraph, sim::Circuit, LogicSimulationPlugin };
+
+    #[test]
+    fn circuit_ticks_like_step_logic() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_plugins(LogicSimulationPlugin::default());
+        let world = app.world_mut();
+
+        let waveform = WaveformGate::sine(0.3);
+        let gate = world.spawn_gate(waveform.clone()).with_inputs(0).with_outputs(1).build();
+        world.resource_mut::<LogicGraph>().add_data(vec![gate.clone()]).compile();
+
+        let timestep = world.resource::<Time<LogicStep>>().timestep();
+        let mut circuit = Circuit::new().with_timestep(timestep);
+        let id = circuit.add_gate(waveform, 0, 1);
+
+        for _ in 0..5 {
+            world.step_logic_once();
+            circuit.step();
+            assert_eq!(circuit.tick(), world.resource::<LogicTick>().0);
+            assert_eq!(circuit.output(id, 0), world.get::<Signal>(gate.output(0)).copied());
+        }
+    }
 }
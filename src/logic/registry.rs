@@ -0,0 +1,237 @@
+//! A registry of gate types, for building gate palettes and toolbars, and spawning gates by
+//! name.
+
+use std::any::TypeId;
+
+use bevy::{
+    ecs::{ component::ComponentId, reflect::ReflectCommandExt, system::SystemParam },
+    prelude::*,
+    reflect::TypeRegistry,
+};
+
+use super::{ builder::{ GateBuilder, Known, LogicExt }, GateArity };
+
+/// A resource that lists every gate type registered with
+/// [`AppLogicGateExt::register_logic_gate`], in the order they were registered.
+///
+/// Each entry has what an in-game palette needs to show and spawn a gate: its name, the number
+/// of fans to give a new gate, its reflected [`TypeId`], and an optional icon.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_logic::prelude::*;
+/// fn build_palette(registry: Res<GateRegistry>) {
+///     for gate in registry.iter() {
+///         println!("{}: {} in, {} out", gate.name, gate.default_inputs, gate.default_outputs);
+///     }
+/// }
+/// ```
+///
+/// [`AppLogicGateExt::register_logic_gate`]: super::AppLogicGateExt::register_logic_gate
+#[derive(Resource, Clone, Default, Debug)]
+pub struct GateRegistry {
+    gates: Vec<GateRegistration>,
+}
+
+/// A gate type in the [`GateRegistry`].
+#[derive(Clone, Debug)]
+pub struct GateRegistration {
+    /// The short type name of the gate component, such as `"AndGate"`.
+    pub name: String,
+    /// The [`TypeId`] of the gate component, to look it up in the `AppTypeRegistry`.
+    pub type_id: TypeId,
+    pub component_id: ComponentId,
+    /// The number of input fans to give a new gate.
+    pub default_inputs: usize,
+    /// The number of output fans to give a new gate.
+    pub default_outputs: usize,
+    /// The declared arity of the gate, if any.
+    pub arity: Option<GateArity>,
+    /// The asset path of an icon to show for the gate.
+    pub icon: Option<String>,
+    /// Whether the default fans were set explicitly, rather than from the arity.
+    custom_fans: bool,
+}
+
+impl GateRegistration {
+    pub(crate) fn new<T: Component>(component_id: ComponentId) -> Self {
+        let (default_inputs, default_outputs) = GateArity::ANY.default_fans();
+        Self {
+            name: bevy::utils::get_short_name(std::any::type_name::<T>()),
+            type_id: TypeId::of::<T>(),
+            component_id,
+            default_inputs,
+            default_outputs,
+            arity: None,
+            icon: None,
+            custom_fans: false,
+        }
+    }
+}
+
+impl GateRegistry {
+    pub(crate) fn insert(&mut self, registration: GateRegistration) {
+        if !self.gates.iter().any(|gate| gate.type_id == registration.type_id) {
+            self.gates.push(registration);
+        }
+    }
+
+    /// Set the arity of a gate type, and its default fans unless they were set with
+    /// [`GateRegistry::set_default_fans`].
+    pub(crate) fn set_arity(&mut self, type_id: TypeId, arity: GateArity) {
+        let Some(gate) = self.get_by_type_id_mut(type_id) else {
+            return;
+        };
+        gate.arity = Some(arity);
+        if !gate.custom_fans {
+            (gate.default_inputs, gate.default_outputs) = arity.default_fans();
+        }
+    }
+
+    /// Set the number of fans to give a new gate of type `T`. Does nothing if `T` is not
+    /// registered.
+    pub fn set_default_fans<T: Component>(&mut self, inputs: usize, outputs: usize) -> &mut Self {
+        if let Some(gate) = self.get_by_type_id_mut(TypeId::of::<T>()) {
+            gate.default_inputs = inputs;
+            gate.default_outputs = outputs;
+            gate.custom_fans = true;
+        }
+        self
+    }
+
+    /// Set the asset path of the icon of gate type `T`. Does nothing if `T` is not registered.
+    pub fn set_icon<T: Component>(&mut self, icon: impl Into<String>) -> &mut Self {
+        if let Some(gate) = self.get_by_type_id_mut(TypeId::of::<T>()) {
+            gate.icon = Some(icon.into());
+        }
+        self
+    }
+
+    /// Returns the gate type with the short type `name`.
+    pub fn get(&self, name: &str) -> Option<&GateRegistration> {
+        self.gates.iter().find(|gate| gate.name == name)
+    }
+
+    /// Returns the gate type `T`, if it is registered.
+    pub fn get_by_type<T: Component>(&self) -> Option<&GateRegistration> {
+        self.get_by_type_id(TypeId::of::<T>())
+    }
+
+    pub fn get_by_type_id(&self, type_id: TypeId) -> Option<&GateRegistration> {
+        self.gates.iter().find(|gate| gate.type_id == type_id)
+    }
+
+    fn get_by_type_id_mut(&mut self, type_id: TypeId) -> Option<&mut GateRegistration> {
+        self.gates.iter_mut().find(|gate| gate.type_id == type_id)
+    }
+
+    /// Returns a new gate component of the type `name`, created with its reflected
+    /// [`Default`].
+    ///
+    /// Returns `None` if the type is not registered, or is not registered in `type_registry`
+    /// with `#[reflect(Default)]`.
+    pub fn instantiate(
+        &self,
+        name: &str,
+        type_registry: &TypeRegistry
+    ) -> Option<Box<dyn Reflect>> {
+        let gate = self.get(name)?;
+        let reflect_default = type_registry.get_type_data::<ReflectDefault>(gate.type_id)?;
+        Some(reflect_default.default())
+    }
+
+    /// Iterate over the registered gate types without reflected [`Component`] data in
+    /// `type_registry`, which can't be shown in inspectors, saved in scenes, or spawned by
+    /// name.
+    pub fn unreflected<'a>(
+        &'a self,
+        type_registry: &'a TypeRegistry
+    ) -> impl Iterator<Item = &'a GateRegistration> {
+        self.gates
+            .iter()
+            .filter(|gate| type_registry.get_type_data::<ReflectComponent>(gate.type_id).is_none())
+    }
+
+    /// Returns `true` if a gate type with the short type `name` is registered.
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Iterate over all registered gate types, in the order they were registered.
+    pub fn iter(&self) -> impl Iterator<Item = &GateRegistration> {
+        self.gates.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.gates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.gates.is_empty()
+    }
+}
+
+/// Warn about every gate type in the [`GateRegistry`] without reflected [`Component`] data.
+/// Added in debug builds by the [`LogicGatePlugin`], so a gate type registered without
+/// `app.register_type::<T>()` is caught early.
+///
+/// [`LogicGatePlugin`]: super::gates::LogicGatePlugin
+pub fn warn_unreflected_gates(registry: Res<GateRegistry>, type_registry: Res<AppTypeRegistry>) {
+    let type_registry = type_registry.read();
+    for gate in registry.unreflected(&type_registry) {
+        // A `WasmGate` holds a compiled module, which can't be reflected.
+        #[cfg(feature = "wasm")]
+        if gate.type_id == TypeId::of::<super::wasm::WasmGate>() {
+            continue;
+        }
+        warn!(
+            "Gate type `{}` is not registered with `#[reflect(Component)]`, so it can't be \
+             inspected, saved in scenes, or spawned by name. Register it with \
+             `app.register_type::<{}>()`.",
+            gate.name,
+            gate.name
+        );
+    }
+}
+
+/// A [`SystemParam`] that spawns gates by the name of their type in the [`GateRegistry`],
+/// for level files, consoles, and other places that only have a string.
+///
+/// Gate types must be registered with `#[reflect(Component, Default)]`, like the built-in
+/// gates.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_logic::prelude::*;
+/// fn place_gate(mut gates: GateSpawner) {
+///     if let Some(gate) = gates.spawn_gate_by_name("AndGate") {
+///         let gate = gate.build();
+///     }
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct GateSpawner<'w, 's> {
+    pub commands: Commands<'w, 's>,
+    pub registry: Res<'w, GateRegistry>,
+    pub type_registry: Res<'w, AppTypeRegistry>,
+}
+
+impl<'w, 's> GateSpawner<'w, 's> {
+    /// Spawn a gate of the type `name` with its [`Default`] state, and return a
+    /// [`GateBuilder`] with the default number of input and output fans of the type.
+    ///
+    /// Returns `None` without spawning anything if the type can't be instantiated. See
+    /// [`GateRegistry::instantiate`].
+    pub fn spawn_gate_by_name(
+        &mut self,
+        name: &str
+    ) -> Option<GateBuilder<'_, Commands<'w, 's>, Known, Known>> {
+        let gate = self.registry.instantiate(name, &self.type_registry.read())?;
+        let registration = self.registry.get(name)?;
+        let (inputs, outputs) = (registration.default_inputs, registration.default_outputs);
+
+        let mut builder = self.commands.spawn_gate(());
+        builder.entity_commands().insert_reflect(gate);
+        Some(builder.with_inputs(inputs).with_outputs(outputs))
+    }
+}
@@ -1,21 +1,77 @@
 use std::time::Duration;
 
-use bevy::{ app::FixedMain, ecs::schedule::ScheduleLabel, prelude::* };
+use bevy::{
+    app::FixedMain,
+    ecs::{ schedule::ScheduleLabel, system::SystemParam },
+    prelude::*,
+    utils::HashMap,
+};
+
+use super::signal::Signal;
+use crate::resources::LogicBacklog;
 
 pub mod prelude {
-    pub use super::{ LogicSchedulePlugin, LogicStep, LogicUpdate, FixedLogicStepExt };
+    pub use super::{
+        LogicSchedulePlugin,
+        LogicStep,
+        LogicUpdate,
+        FixedLogicStepExt,
+        LogicWorldExt,
+        StepOverrun,
+        LogicTickInterpolation,
+        LogicTick,
+        LogicClock,
+        ClockDomain,
+        ClockDomains,
+    };
     pub use super::LogicSystemSet;
 }
 
 /// Stages of the logic simulation. You can order systems during or around these stages.
 ///
-/// Configured order: `PropagateNoEval` -> `StepLogic`
+/// Configured order: `SyncGraph` -> `PropagateNoEval` -> `StepLogic` -> `PostStep`
+///
+/// The sets are configured in [`LogicUpdate`], [`FixedUpdate`], and [`Update`], so systems
+/// can be ordered relative to the simulation in whichever schedule they run.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_logic::prelude::*;
+/// fn read_outputs(query: Query<&Signal, With<GateOutput>>) {
+///     // Signals computed during this step are visible here.
+/// }
+///
+/// App::new().add_systems(LogicUpdate, read_outputs.in_set(LogicSystemSet::PostStep));
+/// ```
 #[derive(SystemSet, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum LogicSystemSet {
+    /// Bring the [`LogicGraph`] up to date with the world before anything is evaluated.
+    ///
+    /// Systems that add or remove gates and wires should run in or before this set.
+    ///
+    /// [`LogicGraph`]: crate::resources::LogicGraph
+    SyncGraph,
     /// Propagate changed signals that do not require evaluation.
     PropagateNoEval,
     /// Evaluates the [`LogicGraph`] resource and updates all entities in a single step.
+    ///
+    /// [`LogicGraph`]: crate::resources::LogicGraph
     StepLogic,
+    /// Runs after evaluation, in the same [`LogicUpdate`] run. Systems in this set can
+    /// read freshly computed signals before the next step.
+    PostStep,
+}
+
+impl LogicSystemSet {
+    /// Returns every set in the order they are configured to run.
+    pub const fn ordered() -> [LogicSystemSet; 4] {
+        [
+            LogicSystemSet::SyncGraph,
+            LogicSystemSet::PropagateNoEval,
+            LogicSystemSet::StepLogic,
+            LogicSystemSet::PostStep,
+        ]
+    }
 }
 
 /// A plugin that initializes the [`LogicUpdate`] schedule for an [`App`].
@@ -28,23 +84,21 @@ pub struct LogicSchedulePlugin;
 
 impl Plugin for LogicSchedulePlugin {
     fn build(&self, app: &mut App) {
-        app.init_schedule(LogicUpdate).add_systems(
-            bevy::app::RunFixedMainLoop,
-            run_fixed_main_schedule
-        );
-
-        app.configure_sets(
-            Update,
-            (LogicSystemSet::PropagateNoEval, LogicSystemSet::StepLogic).chain()
-        )
-            .configure_sets(
-                FixedUpdate,
-                (LogicSystemSet::PropagateNoEval, LogicSystemSet::StepLogic).chain()
-            )
-            .configure_sets(
-                LogicUpdate,
-                (LogicSystemSet::PropagateNoEval, LogicSystemSet::StepLogic).chain()
-            );
+        app.init_schedule(LogicUpdate)
+            .init_resource::<LogicTickInterpolation>()
+            .init_resource::<LogicTick>()
+            .register_type::<LogicTickInterpolation>()
+            .register_type::<LogicTick>()
+            .add_systems(bevy::app::RunFixedMainLoop, (
+                run_fixed_main_schedule,
+                update_tick_interpolation.after(run_fixed_main_schedule),
+            ));
+
+        let [sync, no_eval, step, post] = LogicSystemSet::ordered();
+        app.configure_sets(Update, (sync, no_eval, step, post).chain())
+            .configure_sets(FixedUpdate, (sync, no_eval, step, post).chain())
+            .configure_sets(LogicUpdate, (sync, no_eval, step, post).chain())
+            .add_systems(LogicUpdate, advance_logic_tick.before(sync));
     }
 }
 
@@ -54,26 +108,285 @@ impl Plugin for LogicSchedulePlugin {
 #[derive(ScheduleLabel, Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub struct LogicUpdate;
 
+/// Run the [`LogicUpdate`] schedule once for every timestep of accumulated time, up to
+/// [`FixedLogicStepExt::max_steps_per_frame`] times.
+///
+/// Virtual time is scaled by [`FixedLogicStepExt::relative_speed`] before it is accumulated,
+/// and is not accumulated at all while the clock is [paused](FixedLogicStepExt::pause). If a
+/// step pauses the clock, such as a [`LogicBreakpoint`], no more steps run this frame.
+///
+/// [`LogicBreakpoint`]: crate::breakpoint::LogicBreakpoint
 pub fn run_fixed_main_schedule(world: &mut World) {
     let delta = world.resource::<Time<Virtual>>().delta();
-    world.resource_mut::<Time<LogicStep>>().accumulate(delta);
+    let mut time = world.resource_mut::<Time<LogicStep>>();
+    if time.is_paused() {
+        return;
+    }
+    let delta = delta.mul_f64(time.relative_speed_f64());
+    time.accumulate(delta);
+
+    // Run the schedule until we run out of accumulated time, or reach the step limit.
+    let result = world.try_schedule_scope(LogicUpdate, |world, schedule| {
+        let mut steps = 0;
+        loop {
+            let mut time = world.resource_mut::<Time<LogicStep>>();
+            if time.is_paused() {
+                break;
+            }
+            if time.max_steps_per_frame().is_some_and(|max_steps| steps >= max_steps) {
+                time.handle_overrun();
+                break;
+            }
+            if !time.expend() {
+                break;
+            }
+            steps += 1;
 
-    // Run the schedule until we run out of accumulated time
-    let _ = world.try_schedule_scope(LogicUpdate, |world, schedule| {
-        while world.resource_mut::<Time<LogicStep>>().expend() {
             *world.resource_mut::<Time>() = world.resource::<Time<LogicStep>>().as_generic();
             schedule.run(world);
         }
     });
+    if let Err(error) = result {
+        warn!("The logic simulation did not step: {error}");
+    }
 
     *world.resource_mut::<Time>() = world.resource::<Time<Virtual>>().as_generic();
 }
 
+/// How far the current frame is between the last logic step and the next one.
+///
+/// When the simulation steps less often than frames are rendered, visuals that follow signals
+/// change in visible jumps. Systems in [`Update`] can instead blend the signals of the previous
+/// and current steps by [`Self::fraction`]:
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_logic::prelude::*;
+/// #[derive(Component)]
+/// struct Glow {
+///     previous: Signal,
+///     current: Signal,
+///     intensity: f32,
+/// }
+///
+/// fn animate_glow(interpolation: Res<LogicTickInterpolation>, mut query: Query<&mut Glow>) {
+///     for mut glow in query.iter_mut() {
+///         glow.intensity = interpolation.lerp_signal(glow.previous, glow.current);
+///     }
+/// }
+/// ```
+///
+/// This is updated every frame, after [`run_fixed_main_schedule`].
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct LogicTickInterpolation {
+    /// The [overstep fraction](FixedLogicStepExt::overstep_fraction) of [`Time<LogicStep>`],
+    /// clamped to `0.0..=1.0`.
+    pub fraction: f32,
+}
+
+impl LogicTickInterpolation {
+    /// Linearly interpolate from `previous` to `current` by [`Self::fraction`].
+    pub fn lerp(&self, previous: f32, current: f32) -> f32 {
+        previous + (current - previous) * self.fraction
+    }
+
+    /// Linearly interpolate between two signals by [`Self::fraction`], using
+    /// [`Signal::as_f32`].
+    pub fn lerp_signal(&self, previous: Signal, current: Signal) -> f32 {
+        self.lerp(previous.as_f32(), current.as_f32())
+    }
+}
+
+/// Update the [`LogicTickInterpolation`] resource from [`Time<LogicStep>`].
+pub fn update_tick_interpolation(
+    time: Option<Res<Time<LogicStep>>>,
+    mut interpolation: ResMut<LogicTickInterpolation>
+) {
+    let fraction = time.map_or(0.0, |time| time.overstep_fraction());
+    interpolation.fraction = if fraction.is_finite() { fraction.clamp(0.0, 1.0) } else { 0.0 };
+}
+
+/// The number of logic steps the simulation has started, used as its clock.
+///
+/// The tick is advanced at the start of each [`LogicUpdate`] run, before
+/// [`LogicSystemSet::SyncGraph`], so every system in a step sees the same tick and the first
+/// step is tick `1`. If an [`EvalBudget`] splits a step over several runs, the tick only
+/// advances when a new step starts.
+///
+/// [`EvalBudget`]: crate::resources::EvalBudget
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Reflect)]
+#[reflect(Resource)]
+pub struct LogicTick(pub u64);
+
+/// Puts a gate in a clock domain, whose gates are only evaluated on the steps its
+/// [`ClockDomains`] divider allows. Gates without one are in domain `0`.
+///
+/// A gate keeps its output signals between the steps of its domain, and reads its input
+/// signals as they are when its domain steps, so a wire between two domains carries the last
+/// signal of the faster one into the slower one.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Reflect)]
+#[reflect(Component)]
+pub struct ClockDomain(pub u32);
+
+/// A resource that divides the [`LogicTick`] of each [`ClockDomain`], so part of a circuit can
+/// run slower than the rest within the same [`LogicUpdate`].
+///
+/// A domain with a divider of `n` steps on every `n`th tick. Domains without a divider step
+/// on every tick.
+///
+/// ```ignore
+/// // With a tick rate of 64 Hz, domain 1 runs at 1 Hz and domain 2 at 16 Hz.
+/// let mut domains = ClockDomains::default();
+/// domains.set(ClockDomain(1), 64).set(ClockDomain(2), 4);
+/// app.insert_resource(domains);
+/// ```
+#[derive(Resource, Clone, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Resource)]
+pub struct ClockDomains {
+    dividers: HashMap<ClockDomain, u32>,
+}
+
+impl ClockDomains {
+    /// Step `domain` on every `divider`th tick. A divider of `0` or `1` steps on every tick.
+    pub fn set(&mut self, domain: ClockDomain, divider: u32) -> &mut Self {
+        self.dividers.insert(domain, divider.max(1));
+        self
+    }
+
+    /// Step `domain` at `hz` steps per second, as close as the `tick_rate` of the
+    /// simulation allows.
+    pub fn set_hz(&mut self, domain: ClockDomain, tick_rate: f64, hz: f64) -> &mut Self {
+        let divider = (tick_rate / hz).round().clamp(1.0, u32::MAX as f64) as u32;
+        self.set(domain, divider)
+    }
+
+    /// Returns the divider of `domain`.
+    pub fn divider(&self, domain: ClockDomain) -> u32 {
+        self.dividers.get(&domain).copied().unwrap_or(1)
+    }
+
+    /// Returns `true` if `domain` steps on `tick`.
+    pub fn steps_on(&self, domain: ClockDomain, tick: u64) -> bool {
+        tick % u64::from(self.divider(domain)) == 0
+    }
+}
+
+/// A [`SystemParam`] that reads the simulation clock: the [`LogicTick`], the timestep of
+/// [`Time<LogicStep>`], and the [`ClockDomains`]. Any of the resources may be missing.
+#[derive(SystemParam)]
+pub struct LogicClock<'w> {
+    tick: Option<Res<'w, LogicTick>>,
+    time: Option<Res<'w, Time<LogicStep>>>,
+    domains: Option<Res<'w, ClockDomains>>,
+}
+
+impl LogicClock<'_> {
+    /// Returns the current [`LogicTick`], or `0` if it does not exist.
+    pub fn tick(&self) -> u64 {
+        self.tick.as_ref().map_or(0, |tick| tick.0)
+    }
+
+    /// Returns the timestep of [`Time<LogicStep>`], or zero if it does not exist.
+    pub fn timestep(&self) -> Duration {
+        self.time.as_ref().map_or(Duration::ZERO, |time| time.timestep())
+    }
+
+    /// Returns `true` if gates in `domain` are evaluated during the current tick. Every domain
+    /// steps on every tick without the [`ClockDomains`] resource.
+    pub fn domain_steps(&self, domain: ClockDomain) -> bool {
+        match self.domains.as_ref() {
+            Some(domains) => domains.steps_on(domain, self.tick()),
+            None => true,
+        }
+    }
+}
+
+/// Advance the [`LogicTick`], unless the last run did not complete its step.
+pub fn advance_logic_tick(mut tick: ResMut<LogicTick>, backlog: Option<Res<LogicBacklog>>) {
+    if backlog.map_or(true, |backlog| backlog.is_settled()) {
+        tick.0 += 1;
+    }
+}
+
+/// A [`World`] extension for driving the logic simulation manually.
+pub trait LogicWorldExt {
+    /// Run the [`LogicUpdate`] schedule exactly once, regardless of how much time
+    /// has accumulated in [`Time<LogicStep>`].
+    ///
+    /// This is useful for turn-based games, tests, and tools where ticks should be
+    /// driven by gameplay events rather than wall-clock time. The clock is advanced
+    /// by a single timestep, and the generic [`Time`] resource is restored afterwards.
+    ///
+    /// If the [`LogicUpdate`] schedule does not exist, such as when the
+    /// [`LogicSimulationPlugin`] is not installed, nothing is evaluated and a warning is logged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the world does not have a [`Time<LogicStep>`] resource.
+    ///
+    /// [`LogicSimulationPlugin`]: crate::LogicSimulationPlugin
+    fn step_logic_once(&mut self);
+}
+
+impl LogicWorldExt for World {
+    fn step_logic_once(&mut self) {
+        let timestep = self.resource::<Time<LogicStep>>().timestep();
+        self.resource_mut::<Time<LogicStep>>().advance_by(timestep);
+
+        let previous_time = self.get_resource::<Time>().copied();
+        let result = self.try_schedule_scope(LogicUpdate, |world, schedule| {
+            world.insert_resource(world.resource::<Time<LogicStep>>().as_generic());
+            schedule.run(world);
+        });
+        if let Err(error) = result {
+            warn!("The logic simulation did not step: {error}");
+        }
+
+        match previous_time {
+            Some(time) => self.insert_resource(time),
+            None => {
+                self.remove_resource::<Time>();
+            }
+        }
+    }
+}
+
 /// A fixed timestep context for logic simulation.
-#[derive(Clone, Copy, Debug, Default, Reflect)]
+#[derive(Clone, Copy, Debug, Reflect)]
 pub struct LogicStep {
     timestep: Duration,
     overstep: Duration,
+    max_steps_per_frame: Option<u32>,
+    overrun: StepOverrun,
+    paused: bool,
+    relative_speed: f64,
+}
+
+impl Default for LogicStep {
+    fn default() -> Self {
+        Self {
+            timestep: Duration::ZERO,
+            overstep: Duration::ZERO,
+            max_steps_per_frame: None,
+            overrun: StepOverrun::default(),
+            paused: false,
+            relative_speed: 1.0,
+        }
+    }
+}
+
+/// What happens to the accumulated time that is left when [`run_fixed_main_schedule`] reaches
+/// [`FixedLogicStepExt::max_steps_per_frame`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+pub enum StepOverrun {
+    /// Discard every whole timestep that is left, so the simulation falls behind real time
+    /// instead of trying to catch up. The fraction of a timestep that is left is kept.
+    #[default]
+    Drop,
+    /// Keep the time, so the remaining steps run during later frames, still limited to
+    /// `max_steps_per_frame` each. If the simulation is always too slow, the backlog grows.
+    Spread,
 }
 
 impl FixedLogicStepExt for Time<LogicStep> {
@@ -221,6 +534,101 @@ pub trait FixedLogicStepExt: Default {
         self.context().overstep.as_secs_f64() / self.context().timestep.as_secs_f64()
     }
 
+    /// Returns the most steps [`run_fixed_main_schedule`] runs per frame, or `None` if
+    /// it runs a step for every timestep of accumulated time.
+    #[inline]
+    fn max_steps_per_frame(&self) -> Option<u32> {
+        self.context().max_steps_per_frame
+    }
+
+    /// Limit how many steps [`run_fixed_main_schedule`] runs per frame, so a hitch does not
+    /// cause a burst of catch-up steps that makes the next frame slower still.
+    ///
+    /// What happens to the time that is left is determined by [`Self::overrun_policy`].
+    #[inline]
+    fn set_max_steps_per_frame(&mut self, max_steps: Option<u32>) {
+        self.context_mut().max_steps_per_frame = max_steps;
+    }
+
+    /// Returns what happens to accumulated time when the step limit is reached.
+    #[inline]
+    fn overrun_policy(&self) -> StepOverrun {
+        self.context().overrun
+    }
+
+    /// Sets what happens to accumulated time when the step limit is reached.
+    #[inline]
+    fn set_overrun_policy(&mut self, overrun: StepOverrun) {
+        self.context_mut().overrun = overrun;
+    }
+
+    /// Apply the [`Self::overrun_policy`] to the accumulated time that is left after
+    /// running the most steps allowed in a frame.
+    fn handle_overrun(&mut self) {
+        if self.overrun_policy() == StepOverrun::Drop {
+            let timestep = self.timestep().as_nanos().max(1);
+            let overstep = self.overstep().as_nanos();
+            let whole_steps = overstep - (overstep % timestep);
+            self.discard_overstep(Duration::from_nanos(whole_steps as u64));
+        }
+    }
+
+    /// Stop accumulating time, so [`run_fixed_main_schedule`] does not run any steps until
+    /// [`Self::unpause`] is called. [`LogicWorldExt::step_logic_once`] still works while paused.
+    #[inline]
+    fn pause(&mut self) {
+        self.context_mut().paused = true;
+    }
+
+    /// Resume accumulating time after [`Self::pause`].
+    #[inline]
+    fn unpause(&mut self) {
+        self.context_mut().paused = false;
+    }
+
+    /// Returns `true` if the clock is paused.
+    #[inline]
+    fn is_paused(&self) -> bool {
+        self.context().paused
+    }
+
+    /// Returns the speed the simulation runs at, relative to virtual time, as [`f32`].
+    #[inline]
+    fn relative_speed(&self) -> f32 {
+        self.relative_speed_f64() as f32
+    }
+
+    /// Returns the speed the simulation runs at, relative to virtual time, as [`f64`].
+    #[inline]
+    fn relative_speed_f64(&self) -> f64 {
+        self.context().relative_speed
+    }
+
+    /// Sets the speed the simulation runs at, relative to virtual time, as [`f32`].
+    ///
+    /// For example, `0.5` runs half as many steps per second, for a bullet-time effect.
+    /// This does not change the timestep, so each step still advances by the same amount.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ratio` is negative or not finite.
+    #[inline]
+    fn set_relative_speed(&mut self, ratio: f32) {
+        self.set_relative_speed_f64(ratio as f64);
+    }
+
+    /// Sets the speed the simulation runs at, relative to virtual time, as [`f64`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ratio` is negative or not finite.
+    #[inline]
+    fn set_relative_speed_f64(&mut self, ratio: f64) {
+        assert!(ratio.is_finite(), "tried to go infinitely fast");
+        assert!(ratio >= 0.0, "tried to go back in time");
+        self.context_mut().relative_speed = ratio;
+    }
+
     /// Returns a reference to the context of this specific clock.
     fn context(&self) -> &LogicStep;
 
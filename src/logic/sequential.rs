@@ -0,0 +1,434 @@
+//! Stateful gates that step through states on pulses: shift registers, selectors, counters,
+//! and sequence detectors.
+
+use bevy::prelude::*;
+
+use super::{
+    fsm::{ FsmCondition, FsmGate, FsmState, FsmTransition },
+    registry::GateRegistry,
+    signal::{ Signal, SignalExt },
+    AppLogicGateExt,
+    GateArity,
+    GateContext,
+    LogicGate,
+};
+
+pub(crate) fn register_sequential_gates(app: &mut App) {
+    app.register_logic_gate::<LfsrGate>()
+        .register_logic_gate::<FsmGate>()
+        .register_logic_gate::<Selector>()
+        .register_logic_gate::<Counter>()
+        .register_logic_gate::<SequenceDetector>();
+
+    app.register_gate_arity::<LfsrGate>(GateArity::inputs(0, Some(1)))
+        .register_gate_arity::<Counter>(GateArity::exact_inputs(2));
+
+    app.world_mut()
+        .resource_mut::<GateRegistry>()
+        .set_default_fans::<LfsrGate>(1, 1)
+        .set_default_fans::<Selector>(3, 2);
+
+    app.register_type::<LfsrGate>()
+        .register_type::<FsmGate>()
+        .register_type::<FsmState>()
+        .register_type::<FsmTransition>()
+        .register_type::<FsmCondition>()
+        .register_type::<Selector>()
+        .register_type::<Counter>()
+        .register_type::<SequenceDetector>();
+}
+
+/// A linear-feedback shift register, which emits a deterministic sequence of pseudo-random
+/// bits for noise and puzzles.
+///
+/// Each time its first input, the clock, turns on, the register shifts its `width` bits
+/// left, and shifts in the parity of the bits selected by `taps`. Without inputs, it shifts
+/// every step. Output `i` emits bit `i` of the register, so a single output emits a random
+/// bit stream.
+///
+/// The register starts at `seed`, and returns to it on reset, so the same seed always gives
+/// the same sequence. A register of all zeros never changes.
+///
+/// The default register is 16 bits wide, with taps that go through all 65535 non-zero states
+/// before repeating.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+pub struct LfsrGate {
+    /// The number of bits in the register, from `1` to `64`.
+    pub width: u32,
+    /// A mask of the bits whose parity is shifted in.
+    pub taps: u64,
+    /// The initial state of the register.
+    pub seed: u64,
+    /// The current state of the register.
+    pub state: u64,
+}
+
+impl Default for LfsrGate {
+    fn default() -> Self {
+        Self::new(16, 0xb400, 1)
+    }
+}
+
+impl LfsrGate {
+    /// Create a register with `width` bits, `taps`, and a `seed`.
+    pub fn new(width: u32, taps: u64, seed: u64) -> Self {
+        let mut gate = Self {
+            width,
+            taps,
+            seed,
+            state: 0,
+        };
+        gate.state = seed & gate.mask();
+        gate
+    }
+
+    /// A 16-bit register with the default taps, starting at `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::new(16, 0xb400, seed)
+    }
+
+    fn mask(&self) -> u64 {
+        match self.width {
+            0 => 0,
+            width @ 1..=63 => (1 << width) - 1,
+            _ => u64::MAX,
+        }
+    }
+
+    /// Shift the register once, and return its new state.
+    pub fn shift(&mut self) -> u64 {
+        let feedback = u64::from((self.state & self.taps).count_ones() % 2);
+        self.state = ((self.state << 1) | feedback) & self.mask();
+        self.state
+    }
+
+    fn emit(&self, outputs: &mut [Signal]) {
+        for (bit, output) in outputs.iter_mut().enumerate() {
+            *output = (bit < 64 && (self.state >> bit) & 1 == 1).into();
+        }
+    }
+}
+
+impl LogicGate for LfsrGate {
+    fn evaluate(&mut self, inputs: &[Signal], outputs: &mut [Signal]) {
+        if !inputs.first().is_some_and(Signal::is_falsy) {
+            self.shift();
+        }
+        self.emit(outputs);
+    }
+
+    fn evaluate_with_context(
+        &mut self,
+        ctx: &GateContext,
+        inputs: &[Signal],
+        outputs: &mut [Signal]
+    ) {
+        let clock = match inputs.first() {
+            Some(clock) => ctx.previous_input(0).is_falsy() && clock.is_truthy(),
+            None => true,
+        };
+        if clock {
+            self.shift();
+        }
+        self.emit(outputs);
+    }
+
+    fn reset(&mut self) {
+        self.state = self.seed & self.mask();
+    }
+}
+
+/// A selector turns on one of its outputs at a time, like a rotary switch.
+///
+/// Its first input cycles to the next state each time it turns on, wrapping around after the
+/// last one. Each other input selects a state directly while it is on: the second input
+/// selects the first state, the third input the second state, and so on. When several of
+/// them are on, the last one wins.
+///
+/// A selector with `states` states should have `states + 1` inputs and `states` outputs. The
+/// output of the selected state is on, and every other output is off.
+///
+/// Without a [`GateContext`], as in [`LogicGate::evaluate`], the selector cycles on every
+/// evaluation while the cycle input is on.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+pub struct Selector {
+    /// The number of states.
+    pub states: usize,
+    /// The index of the selected state.
+    pub selected: usize,
+}
+
+impl Default for Selector {
+    fn default() -> Self {
+        Self::new(2)
+    }
+}
+
+impl Selector {
+    /// The index of the cycle input.
+    pub const CYCLE: usize = 0;
+
+    /// Create a selector with `states` states, starting at the first one.
+    pub fn new(states: usize) -> Self {
+        Self { states, selected: 0 }
+    }
+
+    /// Select the next state, wrapping around after the last one.
+    pub fn cycle(&mut self) {
+        self.selected = if self.selected + 1 < self.states { self.selected + 1 } else { 0 };
+    }
+
+    fn update(&mut self, cycle: bool, inputs: &[Signal], outputs: &mut [Signal]) {
+        let selected = inputs
+            .iter()
+            .enumerate()
+            .skip(1)
+            .take(self.states)
+            .rev()
+            .find(|(_, input)| input.is_truthy());
+
+        if let Some((index, _)) = selected {
+            self.selected = index - 1;
+        } else if cycle {
+            self.cycle();
+        }
+
+        for (index, output) in outputs.iter_mut().enumerate() {
+            *output = (index == self.selected && index < self.states).into();
+        }
+    }
+}
+
+impl LogicGate for Selector {
+    fn evaluate(&mut self, inputs: &[Signal], outputs: &mut [Signal]) {
+        let cycle = inputs.get(Self::CYCLE).is_some_and(Signal::is_truthy);
+        self.update(cycle, inputs, outputs);
+    }
+
+    fn evaluate_with_context(
+        &mut self,
+        ctx: &GateContext,
+        inputs: &[Signal],
+        outputs: &mut [Signal]
+    ) {
+        let cycle =
+            ctx.previous_input(Self::CYCLE).is_falsy() &&
+            inputs.get(Self::CYCLE).is_some_and(Signal::is_truthy);
+        self.update(cycle, inputs, outputs);
+    }
+
+    fn reset(&mut self) {
+        self.selected = 0;
+    }
+}
+
+/// A counter counts the pulses of its second input, up to `target`, and turns its outputs on
+/// once the target is reached.
+///
+/// A pulse of a negative signal counts down instead, stopping at zero. The count returns to
+/// zero while the first input, the reset, is on.
+///
+/// If `signal_strength` is true, the outputs emit the progress towards the target instead, as
+/// an analog signal from `0.0` to `1.0`.
+///
+/// Without a [`GateContext`], as in [`LogicGate::evaluate`], the counter counts on every
+/// evaluation while the count input is on.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+pub struct Counter {
+    /// The current count.
+    pub current: u32,
+    /// The count at which the outputs turn on.
+    pub target: u32,
+    /// If true, emit the progress towards the target instead of an on or off signal.
+    pub signal_strength: bool,
+}
+
+impl Default for Counter {
+    fn default() -> Self {
+        Self::new(10)
+    }
+}
+
+impl Counter {
+    /// The index of the reset input.
+    pub const RESET: usize = 0;
+    /// The index of the count input.
+    pub const COUNT: usize = 1;
+
+    /// Create a counter that counts from zero to `target`.
+    pub fn new(target: u32) -> Self {
+        Self {
+            current: 0,
+            target,
+            signal_strength: false,
+        }
+    }
+
+    pub fn with_signal_strength(mut self, signal_strength: bool) -> Self {
+        self.signal_strength = signal_strength;
+        self
+    }
+
+    /// Increment the count, unless it reached the target.
+    pub fn increment(&mut self) {
+        if self.current < self.target {
+            self.current += 1;
+        }
+    }
+
+    /// Decrement the count, unless it is zero.
+    pub fn decrement(&mut self) {
+        self.current = self.current.saturating_sub(1);
+    }
+
+    /// Returns `true` if the count reached the target.
+    pub fn is_done(&self) -> bool {
+        self.current >= self.target
+    }
+
+    /// Returns the signal of the outputs.
+    pub fn signal(&self) -> Signal {
+        if !self.signal_strength {
+            return self.is_done().into();
+        }
+        let progress = if self.target == 0 {
+            1.0
+        } else {
+            (self.current as f32) / (self.target as f32)
+        };
+        Signal::Analog(progress)
+    }
+
+    fn update(&mut self, count: bool, inputs: &[Signal], outputs: &mut [Signal]) {
+        if inputs.get(Self::RESET).is_some_and(Signal::is_truthy) {
+            self.current = 0;
+        } else if count {
+            let signal = inputs[Self::COUNT];
+            if signal.is_sign_negative() {
+                self.decrement();
+            } else {
+                self.increment();
+            }
+        }
+        outputs.set_all(self.signal());
+    }
+}
+
+impl LogicGate for Counter {
+    fn evaluate(&mut self, inputs: &[Signal], outputs: &mut [Signal]) {
+        let count = inputs.get(Self::COUNT).is_some_and(Signal::is_truthy);
+        self.update(count, inputs, outputs);
+    }
+
+    fn evaluate_with_context(
+        &mut self,
+        ctx: &GateContext,
+        inputs: &[Signal],
+        outputs: &mut [Signal]
+    ) {
+        let count =
+            ctx.previous_input(Self::COUNT).is_falsy() &&
+            inputs.get(Self::COUNT).is_some_and(Signal::is_truthy);
+        self.update(count, inputs, outputs);
+    }
+
+    fn reset(&mut self) {
+        self.current = 0;
+    }
+}
+
+/// A sequence detector turns its outputs on once its inputs pulse in a configured order, for
+/// keypads and combination locks.
+///
+/// `sequence` lists the indices of the inputs in the order they must turn on. A pulse of
+/// the next input in the sequence advances it, and a pulse of any other input is ignored,
+/// or starts the sequence over if `reset_on_wrong` is true. Once the whole sequence is
+/// entered, the outputs stay on until the next pulse, which starts a new attempt.
+///
+/// Inputs that turn on during the same step are handled in order. Without a
+/// [`GateContext`], as in [`LogicGate::evaluate`], every input that is on counts as a pulse.
+///
+/// ```
+/// # use bevy_logic::prelude::*;
+/// // The code `2, 1, 1, 8`, on a keypad whose inputs are the keys 1 to 9.
+/// let lock = SequenceDetector::new([1, 0, 0, 7]).with_reset_on_wrong(true);
+/// ```
+#[derive(Component, Clone, Debug, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+pub struct SequenceDetector {
+    /// The indices of the inputs that must pulse, in order.
+    pub sequence: Vec<usize>,
+    /// If true, a pulse of the wrong input starts the sequence over.
+    pub reset_on_wrong: bool,
+    /// The number of inputs of the sequence that pulsed in order so far.
+    pub progress: usize,
+}
+
+impl SequenceDetector {
+    /// Create a detector for the input indices of `sequence`.
+    pub fn new(sequence: impl IntoIterator<Item = usize>) -> Self {
+        Self {
+            sequence: sequence.into_iter().collect(),
+            reset_on_wrong: false,
+            progress: 0,
+        }
+    }
+
+    pub fn with_reset_on_wrong(mut self, reset_on_wrong: bool) -> Self {
+        self.reset_on_wrong = reset_on_wrong;
+        self
+    }
+
+    /// Returns `true` if the whole sequence was entered.
+    pub fn is_complete(&self) -> bool {
+        !self.sequence.is_empty() && self.progress >= self.sequence.len()
+    }
+
+    /// Handle a pulse of the input at `index`.
+    pub fn pulse(&mut self, index: usize) {
+        if self.is_complete() {
+            self.progress = 0;
+        }
+        if self.sequence.get(self.progress) == Some(&index) {
+            self.progress += 1;
+        } else if self.reset_on_wrong {
+            self.progress = usize::from(self.sequence.first() == Some(&index));
+        }
+    }
+}
+
+impl LogicGate for SequenceDetector {
+    fn evaluate(&mut self, inputs: &[Signal], outputs: &mut [Signal]) {
+        for (index, input) in inputs.iter().enumerate() {
+            if input.is_truthy() {
+                self.pulse(index);
+            }
+        }
+        outputs.set_all(self.is_complete().into());
+    }
+
+    fn evaluate_with_context(
+        &mut self,
+        ctx: &GateContext,
+        inputs: &[Signal],
+        outputs: &mut [Signal]
+    ) {
+        for (index, input) in inputs.iter().enumerate() {
+            if input.is_truthy() && ctx.previous_input(index).is_falsy() {
+                self.pulse(index);
+            }
+        }
+        outputs.set_all(self.is_complete().into());
+    }
+
+    fn reset(&mut self) {
+        self.progress = 0;
+    }
+}
@@ -1,7 +1,9 @@
 use bevy::prelude::*;
 
 /// State storage for logic simulation.
-#[derive(Component, Clone, Copy, Debug, PartialEq, PartialOrd, Reflect)]
+#[derive(Component, Clone, Copy, Debug, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
 pub enum Signal {
     Analog(f32),
     Digital(bool),
@@ -36,6 +38,26 @@ impl Default for Signal {
     }
 }
 
+/// Orders signals by their value, as in [`Signal::as_f32`], with [`Signal::Undefined`] below
+/// every other signal. An analog and a digital signal with the same value, like `1.0` and
+/// [`Signal::ON`], are ordered analog first, as they are not equal.
+///
+/// Returns `None` if either signal is an analog `NaN`. See [`Signal::total_cmp`] for an order
+/// of every signal.
+impl PartialOrd for Signal {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        match (self, other) {
+            (Signal::Undefined, Signal::Undefined) => Some(std::cmp::Ordering::Equal),
+            (Signal::Undefined, _) => Some(std::cmp::Ordering::Less),
+            (_, Signal::Undefined) => Some(std::cmp::Ordering::Greater),
+            _ => {
+                let order = self.as_f32().partial_cmp(&other.as_f32())?;
+                Some(order.then(self.rank().cmp(&other.rank())))
+            }
+        }
+    }
+}
+
 impl Signal {
     pub const OFF: Signal = Signal::Digital(false);
     pub const ON: Signal = Signal::Digital(true);
@@ -140,6 +162,18 @@ impl Signal {
         matches!(self, Self::Undefined)
     }
 
+    /// Returns the signal as a number: `1.0` if it is `Digital(true)`, the value if it is
+    /// [`Analog`], and `0.0` otherwise.
+    ///
+    /// [`Analog`]: Signal::Analog
+    pub fn as_f32(&self) -> f32 {
+        match self {
+            Signal::Digital(true) => 1.0,
+            Signal::Analog(value) => *value,
+            _ => 0.0,
+        }
+    }
+
     /// Compare two signals and return the signal with a greater
     /// absolute value.
     ///
@@ -166,6 +200,96 @@ impl Signal {
             (Signal::Undefined, v) | (v, Signal::Undefined) => v,
         }
     }
+
+    /// Returns the ordering between two signals, like [`PartialOrd`] but for every signal:
+    /// analog values are compared with [`f32::total_cmp`], so `-0.0` is less than `0.0` and
+    /// `NaN` is ordered by its sign, above or below every number.
+    pub fn total_cmp(&self, other: &Self) -> std::cmp::Ordering {
+        match (self, other) {
+            (Signal::Undefined, Signal::Undefined) => std::cmp::Ordering::Equal,
+            (Signal::Undefined, _) => std::cmp::Ordering::Less,
+            (_, Signal::Undefined) => std::cmp::Ordering::Greater,
+            _ =>
+                self
+                    .as_f32()
+                    .total_cmp(&other.as_f32())
+                    .then(self.rank().cmp(&other.rank())),
+        }
+    }
+
+    /// Orders the variants with the same value in [`Signal::partial_cmp`] and
+    /// [`Signal::total_cmp`].
+    fn rank(&self) -> u8 {
+        match self {
+            Signal::Undefined => 0,
+            Signal::Analog(_) => 1,
+            Signal::Digital(_) => 2,
+        }
+    }
+
+    /// Returns the absolute value of an [`Analog`] signal. Other signals are unchanged.
+    ///
+    /// [`Analog`]: Signal::Analog
+    pub fn abs(self) -> Signal {
+        match self {
+            Signal::Analog(value) => Signal::Analog(value.abs()),
+            signal => signal,
+        }
+    }
+
+    /// Restrict an [`Analog`] signal to the range `min..=max`. Other signals are unchanged.
+    ///
+    /// [`Analog`]: Signal::Analog
+    pub fn clamp(self, min: f32, max: f32) -> Signal {
+        match self {
+            Signal::Analog(value) => Signal::Analog(value.clamp(min, max)),
+            signal => signal,
+        }
+    }
+
+    /// Returns `true` if the signal, as in [`Signal::as_f32`], is greater than `threshold`.
+    /// [`Signal::Undefined`] is never above or below a threshold.
+    pub fn is_above(&self, threshold: f32) -> bool {
+        !self.is_undefined() && self.as_f32() > threshold
+    }
+
+    /// Returns `true` if the signal, as in [`Signal::as_f32`], is less than `threshold`.
+    /// [`Signal::Undefined`] is never above or below a threshold.
+    pub fn is_below(&self, threshold: f32) -> bool {
+        !self.is_undefined() && self.as_f32() < threshold
+    }
+
+    /// Returns `true` if both signals are undefined, or neither is and their values, as in
+    /// [`Signal::as_f32`], are at most `epsilon` apart.
+    pub fn approx_eq(&self, other: Signal, epsilon: f32) -> bool {
+        match (self.is_undefined(), other.is_undefined()) {
+            (true, true) => true,
+            (false, false) => (self.as_f32() - other.as_f32()).abs() <= epsilon,
+            _ => false,
+        }
+    }
+
+    /// Add two signals like [`Add`], keeping an analog result in the range of a full-strength
+    /// signal, from [`Signal::NEG`] to [`Signal::ON`].
+    ///
+    /// [`Add`]: std::ops::Add
+    pub fn saturating_add(self, rhs: Signal) -> Signal {
+        (self + rhs).clamp(-1.0, 1.0)
+    }
+
+    /// Subtract two signals like [`Sub`], keeping an analog result from `-1.0` to `1.0`.
+    ///
+    /// [`Sub`]: std::ops::Sub
+    pub fn saturating_sub(self, rhs: Signal) -> Signal {
+        (self - rhs).clamp(-1.0, 1.0)
+    }
+
+    /// Multiply two signals like [`Mul`], keeping an analog result from `-1.0` to `1.0`.
+    ///
+    /// [`Mul`]: std::ops::Mul
+    pub fn saturating_mul(self, rhs: Signal) -> Signal {
+        (self * rhs).clamp(-1.0, 1.0)
+    }
 }
 
 impl std::ops::Add for Signal {
@@ -226,6 +350,80 @@ impl std::ops::Sub<f32> for Signal {
     }
 }
 
+/// Multiplies two signals.
+///
+/// - Analog signals are multiplied as numbers.
+/// - A digital signal multiplied with an analog one passes the analog value when it is on,
+///   and turns it into `0.0` when it is off.
+/// - Two digital signals are on if both are on, like an AND gate.
+/// - [`Signal::Undefined`] makes the result undefined.
+impl std::ops::Mul for Signal {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        match (self, rhs) {
+            (Signal::Undefined, _) | (_, Signal::Undefined) => Signal::Undefined,
+            (Signal::Digital(lhs), Signal::Digital(rhs)) => Signal::Digital(lhs && rhs),
+            (lhs, rhs) => Signal::Analog(lhs.as_f32() * rhs.as_f32()),
+        }
+    }
+}
+
+impl std::ops::Mul<f32> for Signal {
+    type Output = Self;
+
+    fn mul(self, rhs: f32) -> Self::Output {
+        match self {
+            Signal::Undefined => Signal::Undefined,
+            signal => Signal::Analog(signal.as_f32() * rhs),
+        }
+    }
+}
+
+/// Divides two signals, as numbers: a digital signal is `1.0` when it is on and `0.0` when it
+/// is off, and the result is analog.
+///
+/// Dividing by zero, or by an off signal, is [`Signal::Undefined`], as is dividing with
+/// [`Signal::Undefined`] on either side.
+impl std::ops::Div for Signal {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        match (self, rhs) {
+            (Signal::Undefined, _) | (_, Signal::Undefined) => Signal::Undefined,
+            (lhs, rhs) => lhs / rhs.as_f32(),
+        }
+    }
+}
+
+impl std::ops::Div<f32> for Signal {
+    type Output = Self;
+
+    fn div(self, rhs: f32) -> Self::Output {
+        match self {
+            Signal::Undefined => Signal::Undefined,
+            _ if rhs == 0.0 => Signal::Undefined,
+            signal => Signal::Analog(signal.as_f32() / rhs),
+        }
+    }
+}
+
+/// Negates a signal, as a number. Unlike [`Not`], a digital signal that is on becomes
+/// [`Signal::NEG`], and one that is off stays off.
+///
+/// [`Not`]: std::ops::Not
+impl std::ops::Neg for Signal {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        match self {
+            Signal::Analog(value) => Signal::Analog(-value),
+            Signal::Digital(true) => Signal::NEG,
+            signal => signal,
+        }
+    }
+}
+
 impl std::ops::Not for Signal {
     type Output = Self;
 
@@ -258,3 +456,211 @@ impl SignalExt for [Signal] {
         });
     }
 }
+
+/// A [`Signal`] that is [`Eq`], [`Ord`], and [`Hash`], for keys of maps and sets, and for
+/// sorting and deduplicating signals deterministically.
+///
+/// Signals are ordered with [`Signal::total_cmp`], and are equal only if they are the same
+/// variant with the same bits, so `Analog(-0.0)` and `Analog(0.0)` are different keys, and
+/// a `NaN` is equal to itself.
+///
+/// ```
+/// # use std::collections::BTreeSet;
+/// # use bevy_logic::prelude::*;
+/// let signals = [Signal::ON, Signal::Undefined, Signal::Analog(0.5), Signal::ON];
+/// let unique: BTreeSet<OrderedSignal> = signals.into_iter().map(OrderedSignal).collect();
+/// assert_eq!(unique.len(), 3);
+/// assert_eq!(unique.first(), Some(&OrderedSignal(Signal::Undefined)));
+/// ```
+#[derive(Clone, Copy, Debug, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OrderedSignal(pub Signal);
+
+impl PartialEq for OrderedSignal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for OrderedSignal {}
+
+impl PartialOrd for OrderedSignal {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrderedSignal {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl std::hash::Hash for OrderedSignal {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.rank().hash(state);
+        match self.0 {
+            Signal::Analog(value) => value.to_bits().hash(state),
+            Signal::Digital(value) => value.hash(state),
+            Signal::Undefined => {}
+        }
+    }
+}
+
+impl From<Signal> for OrderedSignal {
+    fn from(signal: Signal) -> Self {
+        Self(signal)
+    }
+}
+
+impl From<OrderedSignal> for Signal {
+    fn from(signal: OrderedSignal) -> Self {
+        signal.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLES: [Signal; 9] = [
+        Signal::Undefined,
+        Signal::OFF,
+        Signal::ON,
+        Signal::Analog(0.0),
+        Signal::Analog(0.5),
+        Signal::Analog(-0.25),
+        Signal::Analog(1.0),
+        Signal::Analog(-3.0),
+        Signal::Analog(8.0),
+    ];
+
+    fn pairs() -> impl Iterator<Item = (Signal, Signal)> {
+        SAMPLES.into_iter().flat_map(|a| SAMPLES.into_iter().map(move |b| (a, b)))
+    }
+
+    #[test]
+    fn undefined_is_absorbing() {
+        for signal in SAMPLES {
+            assert_eq!(signal * Signal::Undefined, Signal::Undefined);
+            assert_eq!(Signal::Undefined * signal, Signal::Undefined);
+            assert_eq!(signal / Signal::Undefined, Signal::Undefined);
+            assert_eq!(Signal::Undefined / signal, Signal::Undefined);
+        }
+        assert_eq!(-Signal::Undefined, Signal::Undefined);
+    }
+
+    #[test]
+    fn mul_is_commutative() {
+        for (a, b) in pairs() {
+            assert_eq!(a * b, b * a, "{a} * {b}");
+        }
+    }
+
+    #[test]
+    fn mul_by_on_keeps_value() {
+        for signal in SAMPLES {
+            assert!((signal * Signal::ON).approx_eq(signal, 0.0), "{signal}");
+        }
+    }
+
+    #[test]
+    fn div_undoes_mul() {
+        for (a, b) in pairs() {
+            if a.is_undefined() || b.is_undefined() || b.as_f32() == 0.0 {
+                continue;
+            }
+            assert!((a * b / b).approx_eq(a, 1e-6), "{a} * {b} / {b}");
+        }
+    }
+
+    #[test]
+    fn div_by_zero_is_undefined() {
+        for signal in SAMPLES {
+            assert_eq!(signal / Signal::OFF, Signal::Undefined);
+            assert_eq!(signal / Signal::Analog(0.0), Signal::Undefined);
+            assert_eq!(signal / 0.0, Signal::Undefined);
+        }
+    }
+
+    #[test]
+    fn neg_negates_value() {
+        for signal in SAMPLES {
+            assert!((-signal).approx_eq(signal * -1.0, 0.0), "{signal}");
+            assert_eq!((-signal).is_truthy(), signal.is_truthy());
+        }
+        assert_eq!(-Signal::ON, Signal::NEG);
+        assert_eq!(-Signal::OFF, Signal::OFF);
+    }
+
+    #[test]
+    fn abs_and_clamp() {
+        for signal in SAMPLES {
+            assert!(!signal.abs().is_below(0.0), "{signal}");
+            let clamped = signal.clamp(-0.5, 0.5);
+            if signal.is_analog() {
+                assert!(!clamped.is_below(-0.5) && !clamped.is_above(0.5), "{signal}");
+            } else {
+                assert_eq!(clamped, signal);
+            }
+        }
+    }
+
+    #[test]
+    fn saturating_ops_stay_in_range() {
+        for (a, b) in pairs() {
+            for result in [a.saturating_add(b), a.saturating_sub(b), a.saturating_mul(b)] {
+                assert!(!result.is_below(-1.0) && !result.is_above(1.0), "{a}, {b}: {result}");
+            }
+        }
+    }
+
+    #[test]
+    fn comparisons_ignore_undefined() {
+        assert!(!Signal::Undefined.is_above(-1.0));
+        assert!(!Signal::Undefined.is_below(1.0));
+        assert!(Signal::ON.is_above(0.5));
+        assert!(Signal::OFF.is_below(0.5));
+        assert!(Signal::Analog(1.0).approx_eq(Signal::ON, 0.0));
+        assert!(!Signal::Undefined.approx_eq(Signal::OFF, 1.0));
+    }
+
+    #[test]
+    fn partial_cmp_is_consistent_with_eq() {
+        for (a, b) in pairs() {
+            assert_eq!(a.partial_cmp(&b).is_some_and(|order| order.is_eq()), a == b, "{a}, {b}");
+            assert_eq!(a.partial_cmp(&b), b.partial_cmp(&a).map(std::cmp::Ordering::reverse));
+        }
+        for signal in SAMPLES {
+            assert!(signal.is_undefined() || Signal::Undefined < signal, "{signal}");
+        }
+    }
+
+    #[test]
+    fn total_cmp_agrees_with_partial_cmp() {
+        for (a, b) in pairs() {
+            assert_eq!(a.partial_cmp(&b), Some(a.total_cmp(&b)), "{a}, {b}");
+        }
+        let nan = Signal::Analog(f32::NAN);
+        assert_eq!(nan.partial_cmp(&nan), None);
+        assert!(nan.total_cmp(&nan).is_eq());
+    }
+
+    #[test]
+    fn ordered_signal_hash_matches_eq() {
+        use std::hash::{ BuildHasher, RandomState };
+
+        let hasher = RandomState::new();
+        let mut signals = SAMPLES.map(OrderedSignal).to_vec();
+        signals.extend([-0.0, f32::NAN].map(|value| OrderedSignal(Signal::Analog(value))));
+        for a in &signals {
+            for b in &signals {
+                if a == b {
+                    assert_eq!(hasher.hash_one(a), hasher.hash_one(b), "{a:?}, {b:?}");
+                }
+                assert_eq!(a == b, a.0.total_cmp(&b.0).is_eq());
+            }
+        }
+        assert_ne!(OrderedSignal(Signal::Analog(-0.0)), OrderedSignal(Signal::Analog(0.0)));
+    }
+}
@@ -0,0 +1,244 @@
+//! Puzzle validation with test vectors.
+//!
+//! A game declares the expected behavior of a circuit as [`TestVector`]s: a sequence of
+//! input signals, and the outputs expected after each tick. A [`TestSuite`] runs every
+//! vector against its own [`HeadlessCircuit`] copy of the circuit, so vectors can't affect
+//! each other or the game world.
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_logic::prelude::*;
+//! fn check_half_adder(world: &World, circuit: &ImportedCircuit) -> bool {
+//!     let inputs = [circuit.inputs["a"], circuit.inputs["b"]];
+//!     let outputs = [circuit.outputs["sum"], circuit.outputs["carry"]];
+//!
+//!     let suite = TestSuite::default().with_vector(
+//!         TestVector::new("adds")
+//!             .step([false, false], [false, false])
+//!             .step([true, false], [true, false])
+//!             .step([true, true], [false, true])
+//!     );
+//!
+//!     suite.run(world, &inputs, &outputs).is_ok_and(|report| report.passed())
+//! }
+//! ```
+
+use bevy::prelude::*;
+
+use crate::analysis::{ AnalysisError, HeadlessCircuit };
+
+use super::signal::Signal;
+
+pub mod prelude {
+    pub use super::{ TestReport, TestSuite, TestTick, TestVector, TickFailure, VectorResult };
+}
+
+/// A sequence of input signals, and the outputs expected after each tick.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TestVector {
+    pub name: String,
+    pub ticks: Vec<TestTick>,
+}
+
+/// The inputs applied during one tick of a [`TestVector`], and the expected outputs.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TestTick {
+    pub inputs: Vec<Signal>,
+    /// The expected signal of each output. `None` matches any signal.
+    pub expected: Vec<Option<Signal>>,
+}
+
+impl TestVector {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ticks: Vec::new(),
+        }
+    }
+
+    /// Add a tick that applies `inputs` and expects exactly `expected`.
+    pub fn step(
+        self,
+        inputs: impl IntoIterator<Item = impl Into<Signal>>,
+        expected: impl IntoIterator<Item = impl Into<Signal>>
+    ) -> Self {
+        self.step_partial(inputs, expected.into_iter().map(|signal| Some(signal.into())))
+    }
+
+    /// Add a tick that applies `inputs` and expects `expected`, where `None` matches
+    /// any signal.
+    pub fn step_partial(
+        mut self,
+        inputs: impl IntoIterator<Item = impl Into<Signal>>,
+        expected: impl IntoIterator<Item = Option<Signal>>
+    ) -> Self {
+        self.ticks.push(TestTick {
+            inputs: inputs.into_iter().map(Into::into).collect(),
+            expected: expected.into_iter().collect(),
+        });
+        self
+    }
+}
+
+/// A set of [`TestVector`]s to run against a circuit.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TestSuite {
+    pub vectors: Vec<TestVector>,
+    /// The number of logic steps run per tick. Combinational circuits settle in one step,
+    /// but circuits with cycles may need more.
+    pub steps_per_tick: usize,
+}
+
+impl Default for TestSuite {
+    fn default() -> Self {
+        Self {
+            vectors: Vec::new(),
+            steps_per_tick: 1,
+        }
+    }
+}
+
+impl TestSuite {
+    pub fn with_vector(mut self, vector: TestVector) -> Self {
+        self.vectors.push(vector);
+        self
+    }
+
+    pub fn with_steps_per_tick(mut self, steps_per_tick: usize) -> Self {
+        self.steps_per_tick = steps_per_tick;
+        self
+    }
+
+    /// Run every vector against the circuit between `inputs` and `outputs` in `world`.
+    ///
+    /// Each vector runs in a new [`HeadlessCircuit`] copy of the circuit. `inputs` must be
+    /// [`Battery`] gates, and every output fan of each output gate is compared, in order.
+    ///
+    /// Returns an error if the circuit can't be copied, or if a tick has the wrong number
+    /// of inputs or expected outputs.
+    ///
+    /// [`Battery`]: super::gates::Battery
+    pub fn run(
+        &self,
+        world: &World,
+        inputs: &[Entity],
+        outputs: &[Entity]
+    ) -> Result<TestReport, AnalysisError> {
+        let mut report = TestReport::default();
+
+        for vector in self.vectors.iter() {
+            let mut circuit = HeadlessCircuit::extract(world, inputs, outputs)?;
+            let mut failure = None;
+
+            for (tick, test_tick) in vector.ticks.iter().enumerate() {
+                if test_tick.inputs.len() != inputs.len() {
+                    return Err(AnalysisError::InvalidVector {
+                        name: vector.name.clone(),
+                        tick,
+                    });
+                }
+
+                circuit.set_inputs(&test_tick.inputs);
+                for _ in 0..self.steps_per_tick {
+                    circuit.step();
+                }
+
+                let actual = circuit.outputs();
+                if test_tick.expected.len() != actual.len() {
+                    return Err(AnalysisError::InvalidVector {
+                        name: vector.name.clone(),
+                        tick,
+                    });
+                }
+
+                let mismatched = test_tick.expected
+                    .iter()
+                    .zip(actual.iter())
+                    .enumerate()
+                    .filter(|(_, (expected, actual))| expected.is_some_and(|e| e != **actual))
+                    .map(|(output, _)| output)
+                    .collect::<Vec<_>>();
+
+                if !mismatched.is_empty() {
+                    failure = Some(TickFailure {
+                        tick,
+                        inputs: test_tick.inputs.clone(),
+                        expected: test_tick.expected.clone(),
+                        actual,
+                        mismatched,
+                    });
+                    break;
+                }
+            }
+
+            report.results.push(VectorResult {
+                name: vector.name.clone(),
+                failure,
+            });
+        }
+
+        Ok(report)
+    }
+}
+
+/// The results of running a [`TestSuite`], one per vector.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TestReport {
+    pub results: Vec<VectorResult>,
+}
+
+impl TestReport {
+    /// Returns `true` if every vector passed.
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(VectorResult::passed)
+    }
+
+    /// Iterate over the vectors that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &VectorResult> {
+        self.results.iter().filter(|result| !result.passed())
+    }
+}
+
+/// The result of running one [`TestVector`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VectorResult {
+    pub name: String,
+    /// The first tick whose outputs did not match, if any.
+    pub failure: Option<TickFailure>,
+}
+
+impl VectorResult {
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// The first tick of a [`TestVector`] whose outputs did not match.
+///
+/// It displays as one line per mismatched output, such as
+/// `tick 2: output 1 expected true, got false`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TickFailure {
+    /// The index of the tick in [`TestVector::ticks`].
+    pub tick: usize,
+    pub inputs: Vec<Signal>,
+    pub expected: Vec<Option<Signal>>,
+    pub actual: Vec<Signal>,
+    /// The indices of the outputs that did not match.
+    pub mismatched: Vec<usize>,
+}
+
+impl std::fmt::Display for TickFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, &output) in self.mismatched.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            let expected = self.expected[output]
+                .map_or_else(|| "any".to_string(), |expected| expected.to_string());
+            let actual = self.actual[output];
+            write!(f, "tick {}: output {output} expected {expected}, got {actual}", self.tick)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+use bevy::prelude::*;
+use wasmi::{ errors::MemoryError, Config, Engine, Linker, Memory, Module, Store, TypedFunc };
+
+use super::{ signal::Signal, LogicGate };
+
+/// A logic gate implemented by a sandboxed WebAssembly module.
+///
+/// The module must export:
+///
+/// - `memory`: its linear memory.
+/// - `io_buffer() -> i32`: a pointer to a buffer large enough for every input and output signal.
+/// - `evaluate(input_count: i32, output_count: i32)`: reads the inputs from the buffer and
+///   writes the outputs after them.
+///
+/// Each signal is 8 bytes, in little endian: an `i32` tag (`0` = [`Signal::Undefined`],
+/// `1` = [`Signal::Digital`], `2` = [`Signal::Analog`]) followed by an `f32` value. Digital
+/// values are `0.0` or `1.0`. Outputs are filled with their previous values before `evaluate`
+/// is called, so a module only needs to write the outputs it changes.
+///
+/// The module runs without any imports. Each evaluation may consume up to
+/// [`WasmGate::DEFAULT_FUEL`], or the limit set with [`WasmGate::with_fuel`]. If the module runs
+/// out of fuel or traps, the outputs become [`Signal::Undefined`] and the error is kept in
+/// [`WasmGate::last_error`].
+#[derive(Component)]
+pub struct WasmGate {
+    store: Store<()>,
+    memory: Memory,
+    io_buffer: TypedFunc<(), i32>,
+    evaluate: TypedFunc<(i32, i32), ()>,
+    fuel: u64,
+    last_error: Option<WasmGateError>,
+}
+
+impl WasmGate {
+    /// The size of one encoded signal, in bytes.
+    pub const SIGNAL_SIZE: usize = 8;
+
+    /// The fuel each evaluation may consume unless the gate is built [`with_fuel`], enough for
+    /// most modules while keeping one that never returns from stalling the simulation.
+    ///
+    /// [`with_fuel`]: WasmGate::with_fuel
+    pub const DEFAULT_FUEL: u64 = 1_000_000;
+
+    /// Compile and instantiate a gate from a binary WebAssembly module.
+    pub fn new(wasm: &[u8]) -> Result<Self, WasmGateError> {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, wasm)?;
+        let mut store = Store::new(&engine, ());
+        store.set_fuel(Self::DEFAULT_FUEL)?;
+
+        let instance = Linker::<()>::new(&engine)
+            .instantiate(&mut store, &module)?
+            .start(&mut store)?;
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or(WasmGateError::MissingExport("memory"))?;
+        let io_buffer = instance
+            .get_typed_func::<(), i32>(&store, "io_buffer")
+            .map_err(|_| WasmGateError::MissingExport("io_buffer"))?;
+        let evaluate = instance
+            .get_typed_func::<(i32, i32), ()>(&store, "evaluate")
+            .map_err(|_| WasmGateError::MissingExport("evaluate"))?;
+
+        Ok(Self {
+            store,
+            memory,
+            io_buffer,
+            evaluate,
+            fuel: Self::DEFAULT_FUEL,
+            last_error: None,
+        })
+    }
+
+    /// Set the amount of fuel (roughly, instructions) each evaluation may consume, in place
+    /// of [`WasmGate::DEFAULT_FUEL`].
+    pub fn with_fuel(mut self, fuel: u64) -> Self {
+        self.fuel = fuel;
+        self
+    }
+
+    /// Returns the error from the most recent evaluation, if it failed.
+    pub fn last_error(&self) -> Option<&WasmGateError> {
+        self.last_error.as_ref()
+    }
+
+    fn try_evaluate(
+        &mut self,
+        inputs: &[Signal],
+        outputs: &mut [Signal]
+    ) -> Result<(), WasmGateError> {
+        self.store.set_fuel(self.fuel)?;
+
+        let pointer = self.io_buffer.call(&mut self.store, ())? as usize;
+        let bytes = inputs
+            .iter()
+            .chain(outputs.iter())
+            .flat_map(|signal| encode_signal(*signal))
+            .collect::<Vec<_>>();
+        self.memory.write(&mut self.store, pointer, &bytes)?;
+
+        self.evaluate.call(&mut self.store, (inputs.len() as i32, outputs.len() as i32))?;
+
+        let mut bytes = vec![0; outputs.len() * Self::SIGNAL_SIZE];
+        let offset = pointer + inputs.len() * Self::SIGNAL_SIZE;
+        self.memory.read(&self.store, offset, &mut bytes)?;
+
+        for (output, chunk) in outputs.iter_mut().zip(bytes.chunks_exact(Self::SIGNAL_SIZE)) {
+            *output = decode_signal(chunk);
+        }
+        Ok(())
+    }
+}
+
+impl LogicGate for WasmGate {
+    fn evaluate(&mut self, inputs: &[Signal], outputs: &mut [Signal]) {
+        match self.try_evaluate(inputs, outputs) {
+            Ok(()) => {
+                self.last_error = None;
+            }
+            Err(error) => {
+                outputs.iter_mut().for_each(|output| *output = Signal::Undefined);
+                self.last_error = Some(error);
+            }
+        }
+    }
+}
+
+fn encode_signal(signal: Signal) -> [u8; WasmGate::SIGNAL_SIZE] {
+    let (tag, value) = match signal {
+        Signal::Undefined => (0i32, 0.0f32),
+        Signal::Digital(value) => (1, if value { 1.0 } else { 0.0 }),
+        Signal::Analog(value) => (2, value),
+    };
+
+    let mut bytes = [0; WasmGate::SIGNAL_SIZE];
+    bytes[..4].copy_from_slice(&tag.to_le_bytes());
+    bytes[4..].copy_from_slice(&value.to_le_bytes());
+    bytes
+}
+
+fn decode_signal(bytes: &[u8]) -> Signal {
+    let tag = i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    let value = f32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+
+    match tag {
+        1 => Signal::Digital(value != 0.0),
+        2 => Signal::Analog(value),
+        _ => Signal::Undefined,
+    }
+}
+
+/// An error produced while loading or evaluating a [`WasmGate`].
+#[derive(Debug)]
+pub enum WasmGateError {
+    /// The module failed to compile or instantiate, trapped, or ran out of fuel.
+    Wasm(wasmi::Error),
+    /// The module's memory could not be read or written.
+    Memory(MemoryError),
+    /// The module does not export a required item.
+    MissingExport(&'static str),
+}
+
+impl std::fmt::Display for WasmGateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WasmGateError::Wasm(error) => write!(f, "{error}"),
+            WasmGateError::Memory(error) => write!(f, "{error}"),
+            WasmGateError::MissingExport(name) => write!(f, "missing export `{name}`"),
+        }
+    }
+}
+
+impl std::error::Error for WasmGateError {}
+
+impl From<wasmi::Error> for WasmGateError {
+    fn from(error: wasmi::Error) -> Self {
+        WasmGateError::Wasm(error)
+    }
+}
+
+impl From<MemoryError> for WasmGateError {
+    fn from(error: MemoryError) -> Self {
+        WasmGateError::Memory(error)
+    }
+}
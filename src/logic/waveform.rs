@@ -0,0 +1,136 @@
+use std::f32::consts::TAU;
+
+use bevy::{ math::cubic_splines::CubicCurve, prelude::* };
+
+use super::{ signal::Signal, GateContext, LogicGate };
+
+/// The shape of one period of a [`WaveformGate`], sampled at a phase between `0.0` and `1.0`.
+#[derive(Clone, Debug, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Waveform {
+    /// A sine wave between `-1.0` and `1.0`, starting at `0.0` and rising.
+    #[default]
+    Sine,
+    /// `1.0` for the first half of the period, and `-1.0` for the second half.
+    Square,
+    /// A triangle wave between `-1.0` and `1.0`, starting at `0.0` and rising.
+    Triangle,
+    /// A ramp from `-1.0` to `1.0`.
+    Sawtooth,
+    /// A custom curve, whose segments are stretched over one period.
+    ///
+    /// With the `serde` feature, a curve can't be serialized, because [`CubicCurve`] isn't.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Curve(CubicCurve<f32>),
+}
+
+impl Waveform {
+    /// Sample the waveform at `phase`, wrapped to one period.
+    pub fn sample(&self, phase: f32) -> f32 {
+        let phase = phase.rem_euclid(1.0);
+        match self {
+            Waveform::Sine => (phase * TAU).sin(),
+            Waveform::Square => if phase < 0.5 { 1.0 } else { -1.0 }
+            Waveform::Triangle => 1.0 - (4.0 * ((phase + 0.25) % 1.0) - 2.0).abs(),
+            Waveform::Sawtooth => 2.0 * phase - 1.0,
+            Waveform::Curve(curve) => curve.position(phase * (curve.segments().len() as f32)),
+        }
+    }
+}
+
+/// A source gate that emits an analog signal sampled from a [`Waveform`], for testing analog
+/// circuits and driving animations.
+///
+/// The waveform is sampled at the simulated time of the step, [`GateContext::elapsed`], so the
+/// output only depends on the [`LogicTick`] and is reproduced by replays and rewinds. Every
+/// output emits the same signal:
+///
+/// ```text
+/// offset + amplitude * waveform.sample(frequency * elapsed + phase)
+/// ```
+///
+/// Like a clock, the gate changes its outputs on its own, so it should not be used with
+/// [`EvalMode::Dirty`].
+///
+/// [`LogicTick`]: super::schedule::LogicTick
+/// [`EvalMode::Dirty`]: crate::resources::EvalMode::Dirty
+#[derive(Component, Clone, Debug, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+pub struct WaveformGate {
+    pub waveform: Waveform,
+    /// The number of periods per simulated second.
+    pub frequency: f32,
+    /// The peak value of the signal, relative to `offset`.
+    pub amplitude: f32,
+    /// The phase at tick zero, as a fraction of a period.
+    pub phase: f32,
+    /// The value the signal oscillates around.
+    pub offset: f32,
+}
+
+impl Default for WaveformGate {
+    fn default() -> Self {
+        Self::new(Waveform::Sine, 1.0)
+    }
+}
+
+impl WaveformGate {
+    /// Create a new gate with an amplitude of `1.0`, no phase, and no offset.
+    pub fn new(waveform: Waveform, frequency: f32) -> Self {
+        Self {
+            waveform,
+            frequency,
+            amplitude: 1.0,
+            phase: 0.0,
+            offset: 0.0,
+        }
+    }
+
+    pub fn sine(frequency: f32) -> Self {
+        Self::new(Waveform::Sine, frequency)
+    }
+
+    pub fn square(frequency: f32) -> Self {
+        Self::new(Waveform::Square, frequency)
+    }
+
+    pub fn triangle(frequency: f32) -> Self {
+        Self::new(Waveform::Triangle, frequency)
+    }
+
+    pub fn with_amplitude(mut self, amplitude: f32) -> Self {
+        self.amplitude = amplitude;
+        self
+    }
+
+    pub fn with_phase(mut self, phase: f32) -> Self {
+        self.phase = phase;
+        self
+    }
+
+    pub fn with_offset(mut self, offset: f32) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Returns the value of the signal `seconds` of simulated time after tick zero.
+    pub fn sample(&self, seconds: f32) -> f32 {
+        self.offset + self.amplitude * self.waveform.sample(self.frequency * seconds + self.phase)
+    }
+}
+
+impl LogicGate for WaveformGate {
+    fn evaluate(&mut self, _: &[Signal], outputs: &mut [Signal]) {
+        outputs.fill(Signal::Analog(self.sample(0.0)));
+    }
+
+    fn evaluate_with_context(
+        &mut self,
+        ctx: &GateContext,
+        _: &[Signal],
+        outputs: &mut [Signal]
+    ) {
+        outputs.fill(Signal::Analog(self.sample(ctx.elapsed().as_secs_f32())));
+    }
+}
@@ -0,0 +1,391 @@
+//! Export a [`LogicGraph`] as a [`Netlist`], so a circuit built in-game can be verified or
+//! synthesized outside the engine.
+
+use std::fmt::Write;
+
+use bevy::{ prelude::*, utils::{ HashMap, HashSet } };
+
+use crate::{
+    components::{ GateOutput, LogicGateFans, Wire },
+    logic::{ gates::{ AndGate, Battery, NotGate, OrGate, XorGate }, LogicGateTypes },
+    resources::LogicGraph,
+};
+
+pub mod prelude {
+    pub use super::{ Netlist, NetlistGate, NetlistPrimitive, NetlistWire };
+}
+
+/// A structured description of the gates and wires in a [`LogicGraph`].
+///
+/// Created with [`LogicGraph::export_netlist`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Netlist {
+    pub gates: Vec<NetlistGate>,
+    /// Every wire between two gates in the netlist, sorted by the fans they connect.
+    pub wires: Vec<NetlistWire>,
+}
+
+/// A gate in a [`Netlist`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct NetlistGate {
+    pub entity: Entity,
+    /// The short type name of the gate's [`LogicGate`] component, such as `AndGate`.
+    /// This is `None` if the component was not registered through [`register_logic_gate`].
+    ///
+    /// [`LogicGate`]: crate::logic::LogicGate
+    /// [`register_logic_gate`]: crate::logic::AppLogicGateExt::register_logic_gate
+    pub kind: Option<String>,
+    /// The gate's [`Name`], if it has one.
+    pub label: Option<String>,
+    /// The primitive this gate is equivalent to, if it is one of the basic gates.
+    pub primitive: Option<NetlistPrimitive>,
+    /// The number of input fans.
+    pub inputs: usize,
+    /// The number of output fans.
+    pub outputs: usize,
+}
+
+/// A wire in a [`Netlist`], connecting an output fan to an input fan.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NetlistWire {
+    pub entity: Entity,
+    /// The index of the gate the wire starts from, in [`Netlist::gates`].
+    pub from_gate: usize,
+    pub from_output: usize,
+    /// The index of the gate the wire ends at, in [`Netlist::gates`].
+    pub to_gate: usize,
+    pub to_input: usize,
+}
+
+/// The basic gates that have an equivalent Verilog primitive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NetlistPrimitive {
+    /// A [`Battery`], exported as a module input.
+    Input,
+    And,
+    Nand,
+    Or,
+    Nor,
+    Xor,
+    Not,
+}
+
+impl NetlistPrimitive {
+    /// Returns the primitive's Verilog keyword, or `None` for [`NetlistPrimitive::Input`].
+    pub fn keyword(&self) -> Option<&'static str> {
+        match self {
+            NetlistPrimitive::Input => None,
+            NetlistPrimitive::And => Some("and"),
+            NetlistPrimitive::Nand => Some("nand"),
+            NetlistPrimitive::Or => Some("or"),
+            NetlistPrimitive::Nor => Some("nor"),
+            NetlistPrimitive::Xor => Some("xor"),
+            NetlistPrimitive::Not => Some("not"),
+        }
+    }
+
+    /// Returns the primitive matching the basic gate on `entity`, if any.
+    fn of(entity: EntityRef) -> Option<Self> {
+        if entity.contains::<Battery>() {
+            Some(NetlistPrimitive::Input)
+        } else if let Some(gate) = entity.get::<AndGate>() {
+            Some(if gate.invert_output { NetlistPrimitive::Nand } else { NetlistPrimitive::And })
+        } else if let Some(gate) = entity.get::<OrGate>() {
+            // Adders sum analog signals, which a Verilog `or` can't represent.
+            match (gate.is_adder, gate.invert_output) {
+                (true, _) => None,
+                (false, false) => Some(NetlistPrimitive::Or),
+                (false, true) => Some(NetlistPrimitive::Nor),
+            }
+        } else if entity.contains::<XorGate>() {
+            Some(NetlistPrimitive::Xor)
+        } else if entity.contains::<NotGate>() {
+            Some(NetlistPrimitive::Not)
+        } else {
+            None
+        }
+    }
+}
+
+impl LogicGraph {
+    /// Export the gates in the graph, and the wires between them, as a [`Netlist`].
+    ///
+    /// Gates are listed in the order they were added to the graph. Gate types are looked up
+    /// in the [`LogicGateTypes`] resource.
+    pub fn export_netlist(&self, world: &World) -> Netlist {
+        let types = world.get_resource::<LogicGateTypes>();
+        let mut netlist = Netlist::default();
+        let mut indices: HashMap<Entity, usize> = HashMap::default();
+
+        for gate in self.graph.nodes() {
+            let Some(entity) = world.get_entity(gate) else {
+                continue;
+            };
+            let fans = entity.get::<LogicGateFans>();
+
+            indices.insert(gate, netlist.gates.len());
+            netlist.gates.push(NetlistGate {
+                entity: gate,
+                kind: types.and_then(|types| types.type_name_of(entity)).map(str::to_string),
+                label: entity.get::<Name>().map(|name| name.as_str().to_string()),
+                primitive: NetlistPrimitive::of(entity),
+                inputs: fans.map_or(0, |fans| fans.inputs.len()),
+                outputs: fans.map_or(0, |fans| fans.outputs.len()),
+            });
+        }
+
+        for (from_gate, gate) in netlist.gates.iter().enumerate() {
+            let Some(fans) = world.get::<LogicGateFans>(gate.entity) else {
+                continue;
+            };
+
+            for (from_output, output) in fans.outputs.iter().enumerate() {
+                let gate_output = output.and_then(|output| world.get::<GateOutput>(output));
+                let Some(gate_output) = gate_output else {
+                    continue;
+                };
+
+                for &wire_entity in gate_output.wires.iter() {
+                    let Some(wire) = world.get::<Wire>(wire_entity) else {
+                        continue;
+                    };
+                    let Some(&to_gate) = world
+                        .get::<Parent>(wire.to)
+                        .and_then(|parent| indices.get(&parent.get())) else {
+                        continue;
+                    };
+                    let to_input = world
+                        .get::<LogicGateFans>(netlist.gates[to_gate].entity)
+                        .and_then(|fans| fans.inputs.iter().position(|&f| f == Some(wire.to)));
+                    let Some(to_input) = to_input else {
+                        continue;
+                    };
+
+                    netlist.wires.push(NetlistWire {
+                        entity: wire_entity,
+                        from_gate,
+                        from_output,
+                        to_gate,
+                        to_input,
+                    });
+                }
+            }
+        }
+
+        netlist.wires.sort_by_key(|wire| {
+            (wire.from_gate, wire.from_output, wire.to_gate, wire.to_input, wire.entity)
+        });
+        netlist
+    }
+}
+
+impl Netlist {
+    /// Serialize the netlist to JSON.
+    ///
+    /// Entities are written as their [`Entity::to_bits`] value, and wires refer to gates
+    /// by their index in `gates`.
+    pub fn to_json(&self) -> String {
+        let optional = |value: &Option<String>| {
+            value.as_deref().map_or_else(|| "null".to_string(), json_string)
+        };
+
+        let mut json = String::from("{\n  \"gates\": [");
+        for (i, gate) in self.gates.iter().enumerate() {
+            let primitive = gate.primitive.map(|primitive| format!("{primitive:?}").to_lowercase());
+            let _ = write!(
+                json,
+                concat!(
+                    "{}\n    {{ \"entity\": {}, \"kind\": {}, \"label\": {}, ",
+                    "\"primitive\": {}, \"inputs\": {}, \"outputs\": {} }}"
+                ),
+                if i == 0 { "" } else { "," },
+                gate.entity.to_bits(),
+                optional(&gate.kind),
+                optional(&gate.label),
+                optional(&primitive),
+                gate.inputs,
+                gate.outputs
+            );
+        }
+
+        json.push_str("\n  ],\n  \"wires\": [");
+        for (i, wire) in self.wires.iter().enumerate() {
+            let _ = write!(
+                json,
+                concat!(
+                    "{}\n    {{ \"entity\": {}, \"from_gate\": {}, \"from_output\": {}, ",
+                    "\"to_gate\": {}, \"to_input\": {} }}"
+                ),
+                if i == 0 { "" } else { "," },
+                wire.entity.to_bits(),
+                wire.from_gate,
+                wire.from_output,
+                wire.to_gate,
+                wire.to_input
+            );
+        }
+
+        json.push_str("\n  ]\n}\n");
+        json
+    }
+
+    /// Serialize the netlist to a structural Verilog module named `module_name`.
+    ///
+    /// - Gates with a [`NetlistPrimitive`] become primitive instances, and [`Battery`] gates
+    ///   become module inputs.
+    /// - Other gates become instances of a module named after their `kind`, with ports
+    ///   `in0`, `in1`, ... and `out0`, `out1`, ... These modules must be provided separately.
+    /// - Output fans without any wires become module outputs.
+    /// - Inputs without any wires are tied to `1'bx`. Inputs with more than one wire get their
+    ///   own net with one `assign` per wire.
+    ///
+    /// Identifiers are made from gate labels where possible, or `g<index>` otherwise.
+    pub fn to_verilog(&self, module_name: &str) -> String {
+        let names = self.identifiers();
+        let output_net = |gate: usize, output: usize| match self.gates[gate].primitive {
+            Some(NetlistPrimitive::Input) => names[gate].clone(),
+            _ => format!("{}_out{output}", names[gate]),
+        };
+
+        // The wires into each gate input.
+        let mut drivers: HashMap<(usize, usize), Vec<&NetlistWire>> = HashMap::default();
+        // The outputs with at least one wire.
+        let mut driven: HashSet<(usize, usize)> = HashSet::default();
+        for wire in self.wires.iter() {
+            drivers.entry((wire.to_gate, wire.to_input)).or_default().push(wire);
+            driven.insert((wire.from_gate, wire.from_output));
+        }
+
+        let mut inputs = Vec::new();
+        let mut outputs = Vec::new();
+        let mut nets = Vec::new();
+        for (i, gate) in self.gates.iter().enumerate() {
+            if gate.primitive == Some(NetlistPrimitive::Input) {
+                inputs.push(names[i].clone());
+                continue;
+            }
+            for output in 0..gate.outputs {
+                if driven.contains(&(i, output)) {
+                    nets.push(output_net(i, output));
+                } else {
+                    outputs.push(output_net(i, output));
+                }
+            }
+        }
+
+        let mut verilog = String::new();
+        let ports = inputs.iter().chain(outputs.iter()).cloned().collect::<Vec<_>>();
+        let _ = writeln!(verilog, "module {} ({});", identifier(module_name), ports.join(", "));
+        if !inputs.is_empty() {
+            let _ = writeln!(verilog, "  input {};", inputs.join(", "));
+        }
+        if !outputs.is_empty() {
+            let _ = writeln!(verilog, "  output {};", outputs.join(", "));
+        }
+        if !nets.is_empty() {
+            let _ = writeln!(verilog, "  wire {};", nets.join(", "));
+        }
+
+        for (i, gate) in self.gates.iter().enumerate() {
+            if gate.primitive == Some(NetlistPrimitive::Input) {
+                continue;
+            }
+
+            let input_nets = (0..gate.inputs)
+                .map(|input| {
+                    match drivers.get(&(i, input)).map(Vec::as_slice) {
+                        None | Some([]) => "1'bx".to_string(),
+                        Some([wire]) => output_net(wire.from_gate, wire.from_output),
+                        Some(wires) => {
+                            let net = format!("{}_in{input}", names[i]);
+                            let _ = writeln!(verilog, "  wire {net};");
+                            for wire in wires {
+                                let driver = output_net(wire.from_gate, wire.from_output);
+                                let _ = writeln!(verilog, "  assign {net} = {driver};");
+                            }
+                            net
+                        }
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let (module, connections) = match gate.primitive.and_then(|p| p.keyword()) {
+                Some(keyword) => {
+                    let connections = (0..gate.outputs)
+                        .map(|output| output_net(i, output))
+                        .chain(input_nets)
+                        .collect::<Vec<_>>();
+                    (keyword.to_string(), connections)
+                }
+                None => {
+                    let connections = input_nets
+                        .iter()
+                        .enumerate()
+                        .map(|(input, net)| format!(".in{input}({net})"))
+                        .chain((0..gate.outputs).map(|output| {
+                            format!(".out{output}({})", output_net(i, output))
+                        }))
+                        .collect::<Vec<_>>();
+                    (identifier(gate.kind.as_deref().unwrap_or("LogicGate")), connections)
+                }
+            };
+            let _ = writeln!(verilog, "  {module} {} ({});", names[i], connections.join(", "));
+        }
+
+        verilog.push_str("endmodule\n");
+        verilog
+    }
+
+    /// Returns a unique Verilog identifier for each gate.
+    fn identifiers(&self) -> Vec<String> {
+        let mut used = HashSet::default();
+        self.gates
+            .iter()
+            .enumerate()
+            .map(|(i, gate)| {
+                let name = gate.label.as_deref().map(identifier);
+                match name {
+                    Some(name) if used.insert(name.clone()) => name,
+                    _ => {
+                        let name = format!("g{i}");
+                        used.insert(name.clone());
+                        name
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// Turn `name` into a valid Verilog identifier.
+fn identifier(name: &str) -> String {
+    let mut identifier = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+        .collect::<String>();
+    if !identifier.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        identifier.insert(0, '_');
+    }
+    identifier
+}
+
+/// Quote and escape `value` as a JSON string.
+fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
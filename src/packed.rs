@@ -0,0 +1,324 @@
+//! A bit-packed evaluation backend for purely digital circuits.
+//!
+//! A [`PackedCircuit`] stores the output of each gate as a `u64`, where every bit is the
+//! signal of one independent instance of the circuit. A single step evaluates
+//! [`PACKED_LANES`] instances at once with plain bitwise operations, which makes exhaustive
+//! checks such as [`PackedCircuit::truth_table`] far cheaper than simulating each row.
+
+use std::ops::Range;
+
+use bevy::{ ecs::entity::EntityHashMap, prelude::* };
+
+use crate::{
+    analysis::{ AnalysisError, TruthTable, MAX_TRUTH_TABLE_INPUTS },
+    compiled::CompileError,
+    components::{
+        GateDisabled,
+        GateOutput,
+        LogicGateFans,
+        Wire,
+        WireChannel,
+        WireDisabled,
+        WireResistance,
+    },
+    logic::{ gates::{ AndGate, Battery, NotGate, OrGate, XorGate }, signal::Signal },
+    resources::LogicGraph,
+};
+
+pub mod prelude {
+    pub use super::{ PackedCircuit, PACKED_LANES };
+}
+
+/// The number of circuit instances a [`PackedCircuit`] evaluates at once.
+pub const PACKED_LANES: usize = u64::BITS as usize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PackedOp {
+    /// A [`Battery`] or a disabled gate, which keeps its lanes until they are set.
+    Hold,
+    And,
+    Nand,
+    Or,
+    Nor,
+    Xor,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PackedSource {
+    /// The input fan follows the outputs of the gate at this index.
+    Gate(usize),
+    /// The input fan is not driven, its wire is disabled, or its wire's resistance turns
+    /// every signal off.
+    Off,
+}
+
+#[derive(Clone, Debug)]
+struct PackedGate {
+    op: PackedOp,
+    /// The sources of the gate's input fans.
+    inputs: Range<usize>,
+    /// The number of output fans.
+    outputs: usize,
+}
+
+/// A [`LogicGraph`] of digital basic gates, evaluated for [`PACKED_LANES`] instances at once.
+///
+/// Only [`Battery`], [`AndGate`], [`OrGate`] (not as an adder), [`NotGate`], and [`XorGate`]
+/// gates can be packed. Every signal is a single bit: [`Signal::Undefined`] and unconnected
+/// input fans read as off. Each gate drives all of its output fans with the same lanes.
+///
+/// Gates are evaluated in [`LogicGraph::sorted`] order. If an input fan is driven by several
+/// wires, it follows the one [`step_logic`] writes last.
+///
+/// A [`GateDisabled`] gate keeps the signal of its first output fan in every lane, or turns
+/// its outputs off, and a [`WireDisabled`] wire reads as off. Circuits with a
+/// [`SignalOverride`] or a [`ClockDomain`] can't be packed.
+///
+/// [`SignalOverride`]: crate::components::SignalOverride
+/// [`ClockDomain`]: crate::logic::schedule::ClockDomain
+/// [`step_logic`]: crate::systems::step_logic
+#[derive(Clone, Debug, Default)]
+pub struct PackedCircuit {
+    gates: Vec<PackedGate>,
+    sources: Vec<PackedSource>,
+    /// The output lanes of each gate.
+    lanes: Vec<u64>,
+    gate_indices: EntityHashMap<usize>,
+    /// The index of each input battery in `gates`.
+    inputs: Vec<usize>,
+    acyclic: bool,
+}
+
+impl PackedCircuit {
+    /// Pack the [`LogicGraph`] of `world`.
+    ///
+    /// `inputs` are the [`Battery`] gates whose lanes are set with [`Self::set_input`].
+    /// Every other battery is a constant, with its current signal in every lane.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `world` does not have a [`LogicGraph`].
+    pub fn compile(world: &World, inputs: &[Entity]) -> Result<Self, CompileError> {
+        let graph = world.resource::<LogicGraph>();
+        let mut circuit = Self::default();
+
+        for (index, &gate) in graph.sorted().iter().enumerate() {
+            let entity = world.get_entity(gate).ok_or(CompileError::MissingGate(gate))?;
+            CompileError::check_ecs_only(world, gate)?;
+
+            let (op, lanes) = if let Some(disabled) = entity.get::<GateDisabled>() {
+                let holds = *disabled == GateDisabled::Hold && first_output_is_on(world, gate);
+                (PackedOp::Hold, if holds { u64::MAX } else { 0 })
+            } else if let Some(battery) = entity.get::<Battery>() {
+                let on = !inputs.contains(&gate) && battery.signal.is_truthy();
+                (PackedOp::Hold, if on { u64::MAX } else { 0 })
+            } else if let Some(gate) = entity.get::<AndGate>() {
+                (if gate.invert_output { PackedOp::Nand } else { PackedOp::And }, 0)
+            } else if let Some(gate) = entity.get::<OrGate>().filter(|gate| !gate.is_adder) {
+                (if gate.invert_output { PackedOp::Nor } else { PackedOp::Or }, 0)
+            } else if entity.contains::<NotGate>() {
+                // A NOT gate is on unless all of its inputs are on.
+                (PackedOp::Nand, 0)
+            } else if entity.contains::<XorGate>() {
+                (PackedOp::Xor, 0)
+            } else {
+                return Err(CompileError::UnsupportedGate(gate));
+            };
+
+            circuit.gate_indices.insert(gate, index);
+            circuit.gates.push(PackedGate {
+                op,
+                inputs: 0..0,
+                outputs: 0,
+            });
+            circuit.lanes.push(lanes);
+        }
+
+        for &input in inputs.iter() {
+            let battery = world.get::<Battery>(input).is_some();
+            let disabled = world.get::<GateDisabled>(input).is_some();
+            match circuit.gate_indices.get(&input) {
+                Some(&index) if battery && !disabled => {
+                    circuit.inputs.push(index);
+                }
+                Some(_) => {
+                    return Err(CompileError::NotAnInput(input));
+                }
+                None => {
+                    return Err(CompileError::MissingGate(input));
+                }
+            }
+        }
+
+        // Find the wire that is written last to each input fan: the one from the gate that is
+        // evaluated last, then its last output, then the wire with the greatest entity.
+        let mut drivers: EntityHashMap<((usize, usize, Entity), bool)> = EntityHashMap::default();
+        for (index, &gate) in graph.sorted().iter().enumerate() {
+            let Some(fans) = world.get::<LogicGateFans>(gate) else {
+                return Err(CompileError::MissingGate(gate));
+            };
+            let outputs = fans.outputs
+                .iter()
+                .flatten()
+                .filter(|&&output| world.get::<Signal>(output).is_some());
+
+            for (output_index, &output) in outputs.enumerate() {
+                circuit.gates[index].outputs += 1;
+
+                let Some(output) = world.get::<GateOutput>(output) else {
+                    continue;
+                };
+                for &entity in output.wires.iter() {
+                    let Some(wire) = world.get::<Wire>(entity) else {
+                        continue;
+                    };
+                    let channel = world.get::<WireChannel>(entity);
+                    if !WireChannel::connects(channel, world.get::<WireChannel>(wire.to)) {
+                        continue;
+                    }
+                    let key = (index, output_index, entity);
+                    let blocked =
+                        world.get::<WireDisabled>(entity).is_some() ||
+                        world
+                            .get::<WireResistance>(entity)
+                            .is_some_and(|resistance| resistance.apply(Signal::ON) != Signal::ON);
+                    let driver = drivers.entry(wire.to).or_insert((key, blocked));
+                    if key > driver.0 {
+                        *driver = (key, blocked);
+                    }
+                }
+            }
+        }
+
+        circuit.acyclic = true;
+        for (index, &gate) in graph.sorted().iter().enumerate() {
+            let Some(fans) = world.get::<LogicGateFans>(gate) else {
+                return Err(CompileError::MissingGate(gate));
+            };
+            let start = circuit.sources.len();
+            let inputs = fans.inputs
+                .iter()
+                .flatten()
+                .filter(|&&input| world.get::<Signal>(input).is_some());
+
+            for input in inputs {
+                let source = match drivers.get(input) {
+                    Some(((driver, _, _), blocked)) => {
+                        // A gate evaluated at or after this one is read from the previous step.
+                        circuit.acyclic &= *driver < index;
+
+                        if *blocked { PackedSource::Off } else { PackedSource::Gate(*driver) }
+                    }
+                    None => PackedSource::Off,
+                };
+                circuit.sources.push(source);
+            }
+
+            circuit.gates[index].inputs = start..circuit.sources.len();
+        }
+
+        Ok(circuit)
+    }
+
+    /// Returns the number of input batteries.
+    pub fn input_count(&self) -> usize {
+        self.inputs.len()
+    }
+
+    /// Returns `true` if every gate only reads gates evaluated before it, so the circuit
+    /// settles in a single step.
+    pub fn is_acyclic(&self) -> bool {
+        self.acyclic
+    }
+
+    /// Set the lanes emitted by the input battery at `index`, where bit `n` is the signal
+    /// of instance `n`. Does nothing if `index` is out of range.
+    pub fn set_input(&mut self, index: usize, lanes: u64) {
+        if let Some(&gate) = self.inputs.get(index) {
+            self.lanes[gate] = lanes;
+        }
+    }
+
+    /// Returns the output lanes of a gate, or `None` if it is not part of the circuit.
+    pub fn lanes(&self, gate: Entity) -> Option<u64> {
+        self.gate_indices.get(&gate).map(|&index| self.lanes[index])
+    }
+
+    /// Evaluate every gate once, in every lane.
+    pub fn step(&mut self) {
+        for (index, gate) in self.gates.iter().enumerate() {
+            let inputs = self.sources[gate.inputs.clone()].iter().map(|source| {
+                match source {
+                    PackedSource::Gate(driver) => self.lanes[*driver],
+                    PackedSource::Off => 0,
+                }
+            });
+
+            let lanes = match gate.op {
+                PackedOp::Hold => {
+                    continue;
+                }
+                PackedOp::And => inputs.fold(u64::MAX, |acc, lanes| acc & lanes),
+                PackedOp::Nand => !inputs.fold(u64::MAX, |acc, lanes| acc & lanes),
+                PackedOp::Or => inputs.fold(0, |acc, lanes| acc | lanes),
+                PackedOp::Nor => !inputs.fold(0, |acc, lanes| acc | lanes),
+                PackedOp::Xor => inputs.fold(0, |acc, lanes| acc ^ lanes),
+            };
+            self.lanes[index] = lanes;
+        }
+    }
+
+    /// Exhaustively evaluate the circuit, [`PACKED_LANES`] rows at a time, and return the
+    /// [`TruthTable`] of every output fan of each gate in `outputs`.
+    ///
+    /// Unlike [`TruthTable::extract`], every output is [`Signal::ON`] or [`Signal::OFF`].
+    /// Returns [`AnalysisError::Cyclic`] if the circuit is not [acyclic](Self::is_acyclic).
+    pub fn truth_table(&mut self, outputs: &[Entity]) -> Result<TruthTable, AnalysisError> {
+        let inputs = self.inputs.len();
+        if inputs > MAX_TRUTH_TABLE_INPUTS {
+            return Err(AnalysisError::TooManyInputs(inputs));
+        }
+        if !self.acyclic {
+            return Err(AnalysisError::Cyclic);
+        }
+        let outputs = outputs
+            .iter()
+            .map(|&gate| {
+                self.gate_indices.get(&gate).copied().ok_or(AnalysisError::NotInGraph(gate))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let row_count = 1usize << inputs;
+        let mut rows = Vec::with_capacity(row_count);
+        for first_row in (0..row_count).step_by(PACKED_LANES) {
+            for bit in 0..inputs {
+                let lanes = (0..PACKED_LANES)
+                    .filter(|lane| ((first_row + lane) >> bit) & 1 == 1)
+                    .fold(0, |lanes, lane| lanes | (1 << lane));
+                self.set_input(bit, lanes);
+            }
+            self.step();
+
+            for lane in 0..PACKED_LANES.min(row_count - first_row) {
+                let row = outputs
+                    .iter()
+                    .flat_map(|&gate| {
+                        let signal = Signal::from((self.lanes[gate] >> lane) & 1 == 1);
+                        std::iter::repeat(signal).take(self.gates[gate].outputs)
+                    })
+                    .collect();
+                rows.push(row);
+            }
+        }
+
+        Ok(TruthTable { inputs, rows })
+    }
+}
+
+/// Returns `true` if the first output fan of `gate` is on.
+fn first_output_is_on(world: &World, gate: Entity) -> bool {
+    world
+        .get::<LogicGateFans>(gate)
+        .and_then(|fans| fans.outputs.iter().flatten().find_map(|&fan| world.get::<Signal>(fan)))
+        .is_some_and(|signal| signal.is_truthy())
+}
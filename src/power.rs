@@ -0,0 +1,148 @@
+use bevy::{ prelude::*, utils::HashMap };
+use petgraph::unionfind::UnionFind;
+
+use crate::{
+    logic::{ schedule::{ LogicSystemSet, LogicUpdate }, signal::Signal },
+    resources::LogicGraph,
+};
+
+pub mod prelude {
+    pub use super::{ PowerNetworkPlugin, PowerSource, PowerConsumer, PowerNetworks, PowerNetwork };
+}
+
+/// An optional plugin that distributes power across the [`LogicGraph`] each logic step.
+///
+/// Gates connected by wires (in either direction) form a power network. Every network
+/// sums the capacity of its [`PowerSource`]s and the demand of its [`PowerConsumer`]s,
+/// then writes the fraction of demand that could be supplied to each consumer.
+pub struct PowerNetworkPlugin;
+
+impl Plugin for PowerNetworkPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PowerNetworks>()
+            .register_type::<PowerSource>()
+            .register_type::<PowerConsumer>()
+            .add_systems(LogicUpdate, distribute_power.in_set(LogicSystemSet::PostStep));
+    }
+}
+
+/// A gate that supplies up to `capacity` units of power to its network.
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct PowerSource {
+    pub capacity: f32,
+}
+
+/// A gate that demands `demand` units of power from its network.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component)]
+pub struct PowerConsumer {
+    pub demand: f32,
+    /// The fraction of `demand` supplied during the last step, as [`Signal::Analog`]
+    /// in the range `0.0..=1.0`. Anything below `1.0` is a brownout.
+    ///
+    /// This is [`Signal::Undefined`] until the consumer is part of the [`LogicGraph`].
+    pub supplied: Signal,
+}
+
+impl Default for PowerConsumer {
+    fn default() -> Self {
+        Self {
+            demand: 0.0,
+            supplied: Signal::Undefined,
+        }
+    }
+}
+
+impl PowerConsumer {
+    /// Create a new consumer with `demand`.
+    pub fn new(demand: f32) -> Self {
+        Self {
+            demand,
+            ..Default::default()
+        }
+    }
+
+    /// Returns `true` if the consumer did not receive all of its demand during the last step.
+    pub fn is_brownout(&self) -> bool {
+        match self.supplied {
+            Signal::Analog(value) => value < 1.0,
+            _ => false,
+        }
+    }
+}
+
+/// The state of a single power network.
+#[derive(Clone, Debug, Default)]
+pub struct PowerNetwork {
+    /// All gates in the network.
+    pub gates: Vec<Entity>,
+    /// The total capacity of all sources.
+    pub supply: f32,
+    /// The total demand of all consumers.
+    pub demand: f32,
+}
+
+impl PowerNetwork {
+    /// Returns the fraction of demand that can be supplied, in the range `0.0..=1.0`.
+    pub fn satisfaction(&self) -> f32 {
+        if self.demand <= 0.0 { 1.0 } else { (self.supply / self.demand).clamp(0.0, 1.0) }
+    }
+
+    /// Returns `true` if the network cannot supply all of its demand.
+    pub fn is_brownout(&self) -> bool {
+        self.supply < self.demand
+    }
+}
+
+/// Every power network computed during the last logic step.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct PowerNetworks {
+    pub networks: Vec<PowerNetwork>,
+}
+
+/// Group the [`LogicGraph`] into networks and distribute power from sources to consumers.
+pub fn distribute_power(
+    logic_graph: Res<LogicGraph>,
+    mut networks: ResMut<PowerNetworks>,
+    query_sources: Query<&PowerSource>,
+    mut query_consumers: Query<&mut PowerConsumer>
+) {
+    let graph = &logic_graph.graph;
+    let indices = graph
+        .nodes()
+        .enumerate()
+        .map(|(index, gate)| (gate, index))
+        .collect::<HashMap<_, _>>();
+
+    let mut union_find = UnionFind::<usize>::new(indices.len());
+    for (from, to, _) in graph.all_edges() {
+        union_find.union(indices[&from], indices[&to]);
+    }
+
+    let mut grouped: HashMap<usize, PowerNetwork> = HashMap::default();
+    for (gate, index) in indices.iter() {
+        let network = grouped.entry(union_find.find(*index)).or_default();
+        network.gates.push(*gate);
+
+        if let Ok(source) = query_sources.get(*gate) {
+            network.supply += source.capacity;
+        }
+        if let Ok(consumer) = query_consumers.get(*gate) {
+            network.demand += consumer.demand;
+        }
+    }
+
+    networks.networks = grouped.into_values().collect();
+
+    for network in networks.networks.iter() {
+        let satisfaction = Signal::Analog(network.satisfaction());
+        for gate in network.gates.iter() {
+            if let Ok(mut consumer) = query_consumers.get_mut(*gate) {
+                if consumer.supplied != satisfaction {
+                    consumer.supplied = satisfaction;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use bevy::{ ecs::system::SystemParam, prelude::* };
+
+use crate::{
+    components::{ ConnectedWires, GateFan, GateOutput, LogicGateFans, Wire },
+    logic::signal::Signal,
+    resources::LogicGraph,
+};
+
+pub mod prelude {
+    pub use super::LogicQuery;
+}
+
+/// A [`SystemParam`] that bundles read-only access to gates, fans, and wires, with helpers
+/// for common joins between them.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_logic::prelude::*;
+/// fn count_powered_neighbors(logic: LogicQuery, query_gates: Query<Entity, With<LogicGateFans>>) {
+///     for gate in query_gates.iter() {
+///         let powered = logic
+///             .downstream_gates(gate)
+///             .into_iter()
+///             .filter(|&next| logic.input_signals(next).iter().any(|signal| signal.is_truthy()))
+///             .count();
+///         info!("{gate} drives {powered} powered gates");
+///     }
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct LogicQuery<'w, 's> {
+    pub graph: Res<'w, LogicGraph>,
+    pub gates: Query<'w, 's, &'static LogicGateFans>,
+    pub fans: Query<'w, 's, (&'static Signal, &'static Parent), With<GateFan>>,
+    pub outputs: Query<'w, 's, &'static GateOutput>,
+    pub inputs: Query<'w, 's, &'static ConnectedWires>,
+    pub wires: Query<'w, 's, (&'static Wire, &'static Signal)>,
+}
+
+impl<'w, 's> LogicQuery<'w, 's> {
+    /// Returns the [`Signal`] of a fan or wire entity.
+    pub fn signal(&self, entity: Entity) -> Option<Signal> {
+        self.fans
+            .get(entity)
+            .map(|(signal, _)| *signal)
+            .or_else(|_| self.wires.get(entity).map(|(_, signal)| *signal))
+            .ok()
+    }
+
+    /// Returns the gate that owns a fan entity.
+    pub fn gate_of(&self, fan: Entity) -> Option<Entity> {
+        self.fans.get(fan).ok().map(|(_, parent)| parent.get())
+    }
+
+    /// Returns the input signals of a gate, in the same order [`LogicGate::evaluate`] receives them.
+    ///
+    /// [`LogicGate::evaluate`]: crate::logic::LogicGate::evaluate
+    pub fn input_signals(&self, gate: Entity) -> Vec<Signal> {
+        self.gates
+            .get(gate)
+            .map(|fans| fans.some_inputs())
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|input| self.signal(input))
+            .collect()
+    }
+
+    /// Returns the output signals of a gate, in the same order [`LogicGate::evaluate`] writes them.
+    ///
+    /// [`LogicGate::evaluate`]: crate::logic::LogicGate::evaluate
+    pub fn output_signals(&self, gate: Entity) -> Vec<Signal> {
+        self.gates
+            .get(gate)
+            .map(|fans| fans.some_outputs())
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|output| self.signal(output))
+            .collect()
+    }
+
+    /// Returns the output fans whose wires drive an input fan.
+    pub fn driving_outputs(&self, input: Entity) -> Vec<Entity> {
+        let Ok(connected) = self.inputs.get(input) else {
+            return Vec::new();
+        };
+
+        connected.wires
+            .iter()
+            .filter_map(|&wire_entity| self.wires.get(wire_entity).ok())
+            .map(|(wire, _)| wire.from)
+            .collect()
+    }
+
+    /// Returns every gate that one of `gate`'s outputs is wired to. Each gate is listed once.
+    pub fn downstream_gates(&self, gate: Entity) -> Vec<Entity> {
+        let Ok(fans) = self.gates.get(gate) else {
+            return Vec::new();
+        };
+
+        let mut gates = fans
+            .some_outputs()
+            .into_iter()
+            .filter_map(|output| self.outputs.get(output).ok())
+            .flat_map(|output| output.wires.iter())
+            .filter_map(|&wire_entity| self.wires.get(wire_entity).ok())
+            .filter_map(|(wire, _)| self.gate_of(wire.to))
+            .collect::<Vec<_>>();
+        gates.sort();
+        gates.dedup();
+        gates
+    }
+
+    /// Returns every gate wired to one of `gate`'s inputs. Each gate is listed once.
+    pub fn upstream_gates(&self, gate: Entity) -> Vec<Entity> {
+        let Ok(fans) = self.gates.get(gate) else {
+            return Vec::new();
+        };
+
+        let mut gates = fans
+            .some_inputs()
+            .into_iter()
+            .flat_map(|input| self.driving_outputs(input))
+            .filter_map(|output| self.gate_of(output))
+            .collect::<Vec<_>>();
+        gates.sort();
+        gates.dedup();
+        gates
+    }
+}
@@ -0,0 +1,192 @@
+//! Signal-driven gate visuals.
+
+use std::any::TypeId;
+
+use bevy::{ ecs::world::EntityRef, prelude::*, utils::HashMap };
+
+use crate::{
+    components::{ GateOutput, LogicGateFans },
+    logic::{ gates::{ AndGate, Battery, NotGate, OrGate, XorGate }, signal::Signal },
+};
+
+/// A plugin that gives each gate the mesh and material from the [`GateVisualTheme`] of its
+/// gate type, and switches between the theme's on and off emissive colors when any of its
+/// outputs is truthy.
+///
+/// Insert a [`GateVisualTheme`] before the app starts to replace the default theme.
+pub struct GateVisualPlugin;
+
+impl Plugin for GateVisualPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostUpdate, (insert_gate_visuals, update_gate_visuals).chain());
+    }
+
+    fn finish(&self, app: &mut App) {
+        app.init_resource::<GateVisualTheme>();
+    }
+}
+
+/// How a gate type is drawn.
+#[derive(Clone, Debug)]
+pub struct GateVisual {
+    pub mesh: Handle<Mesh>,
+    /// A texture drawn on the mesh, such as the gate's symbol.
+    pub icon: Option<Handle<Image>>,
+    pub color: Color,
+}
+
+/// Maps gate types to their [`GateVisual`]s.
+///
+/// The default theme draws the basic gates as colored unit quads facing +Z.
+#[derive(Resource, Clone, Debug)]
+pub struct GateVisualTheme {
+    gates: HashMap<TypeId, GateVisual>,
+    /// The visual of gates whose type is not in the theme, if any.
+    pub fallback: Option<GateVisual>,
+    /// The emissive color of a gate with a truthy output.
+    pub on_emissive: LinearRgba,
+    /// The emissive color of a gate whose outputs are all falsy.
+    pub off_emissive: LinearRgba,
+}
+
+impl GateVisualTheme {
+    /// Create an empty theme, drawing gates of unknown type with `fallback`.
+    pub fn new(fallback: Option<GateVisual>) -> Self {
+        Self {
+            gates: HashMap::default(),
+            fallback,
+            on_emissive: LinearRgba::new(0.8, 0.8, 0.8, 1.0),
+            off_emissive: LinearRgba::BLACK,
+        }
+    }
+
+    /// Set the visual of gates of type `T`.
+    pub fn insert<T: Component>(&mut self, visual: GateVisual) -> &mut Self {
+        self.gates.insert(TypeId::of::<T>(), visual);
+        self
+    }
+
+    /// Set the visual of gates of type `T`.
+    pub fn with_gate<T: Component>(mut self, visual: GateVisual) -> Self {
+        self.insert::<T>(visual);
+        self
+    }
+
+    /// Returns the visual of gates of type `T`, if it is in the theme.
+    pub fn get<T: Component>(&self) -> Option<&GateVisual> {
+        self.gates.get(&TypeId::of::<T>())
+    }
+
+    /// Returns the visual of a gate entity, from its gate component that is in the theme,
+    /// or the fallback.
+    pub fn visual_of(&self, entity: EntityRef) -> Option<(TypeId, &GateVisual)> {
+        self.gates
+            .iter()
+            .find(|(type_id, _)| entity.contains_type_id(**type_id))
+            .map(|(type_id, visual)| (*type_id, visual))
+            .or_else(|| self.fallback.as_ref().map(|visual| (TypeId::of::<()>(), visual)))
+    }
+}
+
+impl FromWorld for GateVisualTheme {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world.resource_mut::<Assets<Mesh>>().add(Rectangle::new(1.0, 1.0));
+        let visual = |color: Color| GateVisual {
+            mesh: mesh.clone(),
+            icon: None,
+            color,
+        };
+
+        Self::new(Some(visual(Color::srgb(0.5, 0.5, 0.5))))
+            .with_gate::<Battery>(visual(Color::srgb(0.8, 0.7, 0.2)))
+            .with_gate::<AndGate>(visual(Color::srgb(0.2, 0.4, 0.8)))
+            .with_gate::<OrGate>(visual(Color::srgb(0.2, 0.7, 0.4)))
+            .with_gate::<NotGate>(visual(Color::srgb(0.8, 0.3, 0.3)))
+            .with_gate::<XorGate>(visual(Color::srgb(0.6, 0.3, 0.8)))
+    }
+}
+
+/// The materials a gate switches between, inserted by the [`GateVisualPlugin`].
+#[derive(Component, Clone, Debug)]
+pub struct GateVisualMaterials {
+    pub on: Handle<StandardMaterial>,
+    pub off: Handle<StandardMaterial>,
+}
+
+/// Give each new gate the mesh and materials of its gate type.
+///
+/// Gates of the same type share their materials. They are recreated when the
+/// [`GateVisualTheme`] changes, for gates added afterwards.
+pub fn insert_gate_visuals(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut cache: Local<HashMap<TypeId, GateVisualMaterials>>,
+    theme: Res<GateVisualTheme>,
+    query: Query<(Entity, EntityRef), Added<LogicGateFans>>
+) {
+    if theme.is_changed() {
+        cache.clear();
+    }
+
+    for (entity, entity_ref) in query.iter() {
+        let Some((type_id, visual)) = theme.visual_of(entity_ref) else {
+            continue;
+        };
+        let gate_materials = cache
+            .entry(type_id)
+            .or_insert_with(|| {
+                let mut material = |emissive: LinearRgba| {
+                    materials.add(StandardMaterial {
+                        base_color: visual.color,
+                        base_color_texture: visual.icon.clone(),
+                        emissive,
+                        alpha_mode: AlphaMode::Blend,
+                        ..default()
+                    })
+                };
+                GateVisualMaterials {
+                    on: material(theme.on_emissive),
+                    off: material(theme.off_emissive),
+                }
+            })
+            .clone();
+
+        let mut entity = commands.entity(entity);
+        entity.insert((visual.mesh.clone(), gate_materials.off.clone(), gate_materials));
+        if !entity_ref.contains::<Transform>() {
+            entity.insert(SpatialBundle::default());
+        } else if !entity_ref.contains::<Visibility>() {
+            entity.insert(VisibilityBundle::default());
+        }
+    }
+}
+
+/// Switch each gate between its on and off material when its outputs change.
+pub fn update_gate_visuals(
+    mut query_gates: Query<
+        (&LogicGateFans, Ref<GateVisualMaterials>, &mut Handle<StandardMaterial>)
+    >,
+    query_outputs: Query<Ref<Signal>, With<GateOutput>>
+) {
+    for (fans, gate_materials, mut material) in query_gates.iter_mut() {
+        let outputs = fans.outputs
+            .iter()
+            .flatten()
+            .filter_map(|&output| query_outputs.get(output).ok());
+
+        let mut changed = gate_materials.is_changed();
+        let mut is_active = false;
+        for signal in outputs {
+            changed |= signal.is_changed();
+            is_active |= signal.is_truthy();
+        }
+        if !changed {
+            continue;
+        }
+
+        let target = if is_active { &gate_materials.on } else { &gate_materials.off };
+        if *material != *target {
+            *material = target.clone();
+        }
+    }
+}
@@ -0,0 +1,18 @@
+//! Optional built-in visuals for circuits. Requires the `render` feature for 3D, or the
+//! `render2d` feature for 2D.
+
+#[cfg(feature = "render")]
+pub mod gates;
+pub mod wires;
+#[cfg(feature = "render2d")]
+pub mod sprite;
+
+pub mod prelude {
+    #[cfg(feature = "render")]
+    pub use super::gates::{ GateVisual, GateVisualMaterials, GateVisualPlugin, GateVisualTheme };
+    pub use super::wires::{ WireMesh, WireRenderSettings };
+    #[cfg(feature = "render")]
+    pub use super::wires::{ WireRenderMaterial, WireRenderPlugin };
+    #[cfg(feature = "render2d")]
+    pub use super::sprite::{ GateSpriteBundle, WireRender2dMaterial, WireRender2dPlugin };
+}
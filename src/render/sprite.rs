@@ -0,0 +1,191 @@
+//! 2D gates drawn as sprites, and wires drawn as `Mesh2d` ribbons.
+
+use bevy::{ prelude::*, sprite::Mesh2dHandle };
+
+use crate::{
+    components::{ PrevSignal, Wire },
+    layout::{ FanLayout, GateSize },
+    logic::{ meshgen::gate_size, schedule::LogicTickInterpolation, signal::Signal },
+    routing::{ update_wire_paths, WirePath, WireRoutingPlugin },
+};
+
+use super::wires::{ insert_wire_routes, wire_colors, WireMesh, WireRenderSettings };
+
+/// A plugin that draws every wire as a flat `Mesh2d` ribbon along its [`WirePath`], colored
+/// by the wire's [`Signal`]. It works like the 3D `WireRenderPlugin`, with a
+/// [`ColorMaterial`] instead of a `StandardMaterial`.
+///
+/// By default, wires are drawn at a [`WireRenderSettings::depth`] of `-1.0`, behind gates at
+/// a Z of `0.0`.
+pub struct WireRender2dPlugin {
+    pub settings: WireRenderSettings,
+}
+
+impl Default for WireRender2dPlugin {
+    fn default() -> Self {
+        Self {
+            settings: WireRenderSettings {
+                depth: -1.0,
+                ..default()
+            },
+        }
+    }
+}
+
+impl Plugin for WireRender2dPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<WireRoutingPlugin>() {
+            app.add_plugins(WireRoutingPlugin);
+        }
+
+        app.insert_resource(self.settings.clone()).add_systems(
+            PostUpdate,
+            (
+                insert_wire_routes.before(update_wire_paths),
+                (build_wire_meshes_2d, color_wire_meshes_2d).chain().after(update_wire_paths),
+            )
+        );
+    }
+
+    fn finish(&self, app: &mut App) {
+        app.init_resource::<WireRender2dMaterial>();
+    }
+}
+
+/// The material shared by every 2D wire mesh. Wire colors are stored in the mesh's vertex
+/// colors, which this material multiplies.
+#[derive(Resource, Clone, Debug)]
+pub struct WireRender2dMaterial(pub Handle<ColorMaterial>);
+
+impl FromWorld for WireRender2dMaterial {
+    fn from_world(world: &mut World) -> Self {
+        let material = ColorMaterial::from(Color::WHITE);
+        Self(world.resource_mut::<Assets<ColorMaterial>>().add(material))
+    }
+}
+
+/// A bundle for a 2D gate drawn as a sprite, whose fans are placed by its [`FanLayout`].
+///
+/// Add the [`FanLayoutPlugin`] to place the fans.
+///
+/// ```ignore
+/// commands
+///     .spawn_gate((AndGate::default(), GateSpriteBundle::for_fans(Color::WHITE, 2, 1)))
+///     .with_inputs(2)
+///     .with_outputs(1)
+///     .build();
+/// ```
+///
+/// [`FanLayoutPlugin`]: crate::layout::FanLayoutPlugin
+#[derive(Bundle, Clone, Default)]
+pub struct GateSpriteBundle {
+    pub sprite: SpriteBundle,
+    pub layout: FanLayout,
+    pub size: GateSize,
+}
+
+impl GateSpriteBundle {
+    /// Create a gate of `size`, drawn in a solid `color`.
+    pub fn new(color: Color, size: Vec2) -> Self {
+        Self {
+            sprite: SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(size),
+                    ..default()
+                },
+                ..default()
+            },
+            layout: FanLayout::default(),
+            size: GateSize(size),
+        }
+    }
+
+    /// Create a gate with room for `inputs` and `outputs` fans. See [`gate_size`].
+    pub fn for_fans(color: Color, inputs: usize, outputs: usize) -> Self {
+        Self::new(color, gate_size(inputs, outputs))
+    }
+
+    /// Draw the gate with `texture`, tinted by its color.
+    pub fn with_texture(mut self, texture: Handle<Image>) -> Self {
+        self.sprite.texture = texture;
+        self
+    }
+
+    pub fn with_layout(mut self, layout: FanLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Place the gate at `translation`, keeping its Z.
+    pub fn with_translation(mut self, translation: Vec2) -> Self {
+        let z = self.sprite.transform.translation.z;
+        self.sprite.transform.translation = translation.extend(z);
+        self
+    }
+}
+
+/// A wire's [`WirePath`], and the 2D mesh built from it if it has one.
+type WirePathMesh2d<'a> = (Entity, &'a WirePath, Option<&'a Mesh2dHandle>, Has<Transform>);
+
+/// Build or rebuild the 2D [`WireMesh`] of each wire whose [`WirePath`] changed.
+pub fn build_wire_meshes_2d(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    settings: Res<WireRenderSettings>,
+    material: Res<WireRender2dMaterial>,
+    query: Query<WirePathMesh2d, (With<Wire>, Changed<WirePath>)>
+) {
+    for (entity, path, handle, has_transform) in query.iter() {
+        let (wire_mesh, mesh) = WireMesh::build(path, settings.width);
+
+        let mut entity = commands.entity(entity);
+        match handle {
+            Some(handle) => {
+                meshes.insert(&handle.0, mesh);
+                entity.insert(wire_mesh);
+            }
+            None => {
+                entity.insert((wire_mesh, Mesh2dHandle(meshes.add(mesh)), material.0.clone()));
+                if !has_transform {
+                    let transform = Transform::from_xyz(0.0, 0.0, settings.depth);
+                    entity.insert(SpatialBundle::from_transform(transform));
+                }
+            }
+        }
+    }
+}
+
+/// A wire's 2D mesh, and the signals it is colored with.
+type WireMeshSignals2d<'a> = (
+    &'a Mesh2dHandle,
+    Ref<'a, WireMesh>,
+    Ref<'a, Signal>,
+    Option<&'a PrevSignal>,
+);
+
+/// Write the vertex colors of each 2D [`WireMesh`] from its wire's signal.
+pub fn color_wire_meshes_2d(
+    time: Res<Time>,
+    settings: Res<WireRenderSettings>,
+    interpolation: Option<Res<LogicTickInterpolation>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    query: Query<WireMeshSignals2d, With<Wire>>
+) {
+    let offset = time.elapsed_seconds() * settings.pulse_speed;
+
+    for (handle, wire_mesh, signal, prev_signal) in query.iter() {
+        let colors = wire_colors(
+            &settings,
+            interpolation.as_deref(),
+            offset,
+            wire_mesh,
+            signal,
+            prev_signal
+        );
+        let (Some(colors), Some(mesh)) = (colors, meshes.get_mut(&handle.0)) else {
+            continue;
+        };
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    }
+}
@@ -0,0 +1,301 @@
+//! Mesh-based wire rendering.
+
+use bevy::{
+    prelude::*,
+    render::{ mesh::{ Indices, PrimitiveTopology }, render_asset::RenderAssetUsages },
+};
+
+use crate::{
+    components::{ PrevSignal, Wire },
+    logic::{ schedule::LogicTickInterpolation, signal::Signal },
+    routing::{ WirePath, WireRoute },
+};
+
+/// A plugin that draws every wire as a flat ribbon mesh along its [`WirePath`], colored by
+/// the wire's [`Signal`].
+///
+/// Wires without a [`WireRoute`] are given [`WireRenderSettings::route`]. Meshes are built in
+/// world space, so wires should not be children of transformed entities. If a wire has a
+/// [`PrevSignal`], its color blends between steps with [`LogicTickInterpolation`].
+#[cfg(feature = "render")]
+#[derive(Default)]
+pub struct WireRenderPlugin {
+    pub settings: WireRenderSettings,
+}
+
+#[cfg(feature = "render")]
+impl Plugin for WireRenderPlugin {
+    fn build(&self, app: &mut App) {
+        use crate::routing::{ update_wire_paths, WireRoutingPlugin };
+
+        if !app.is_plugin_added::<WireRoutingPlugin>() {
+            app.add_plugins(WireRoutingPlugin);
+        }
+
+        app.insert_resource(self.settings.clone()).add_systems(
+            PostUpdate,
+            (
+                insert_wire_routes.before(update_wire_paths),
+                (build_wire_meshes, color_wire_meshes).chain().after(update_wire_paths),
+            )
+        );
+    }
+
+    fn finish(&self, app: &mut App) {
+        app.init_resource::<WireRenderMaterial>();
+    }
+}
+
+/// Configures the [`WireRenderPlugin`].
+#[derive(Resource, Clone, Debug)]
+pub struct WireRenderSettings {
+    /// The route given to wires that do not have a [`WireRoute`].
+    pub route: WireRoute,
+    /// The width of each wire ribbon.
+    pub width: f32,
+    /// The color of a wire carrying [`Signal::ON`]. Analog signals blend towards it from
+    /// `off_color`.
+    pub on_color: Color,
+    pub off_color: Color,
+    pub undefined_color: Color,
+    /// How fast pulses travel along wires that are on, in units per second. `0.0` disables
+    /// the animation.
+    pub pulse_speed: f32,
+    /// The distance between two pulses.
+    pub pulse_spacing: f32,
+    /// How much brighter a wire is at the center of a pulse.
+    pub pulse_intensity: f32,
+    /// The Z translation given to wires without a [`Transform`], which moves them in front of
+    /// or behind gates. This is how 2D wires are layered.
+    pub depth: f32,
+}
+
+impl Default for WireRenderSettings {
+    fn default() -> Self {
+        Self {
+            route: WireRoute::Manhattan,
+            width: 0.05,
+            on_color: Color::srgb(0.1, 0.9, 0.2),
+            off_color: Color::srgb(0.1, 0.1, 0.1),
+            undefined_color: Color::srgb(0.6, 0.1, 0.6),
+            pulse_speed: 2.0,
+            pulse_spacing: 0.5,
+            pulse_intensity: 0.6,
+            depth: 0.0,
+        }
+    }
+}
+
+/// The material shared by every wire mesh. Wire colors are stored in the mesh's vertex
+/// colors, which this material multiplies.
+#[cfg(feature = "render")]
+#[derive(Resource, Clone, Debug)]
+pub struct WireRenderMaterial(pub Handle<StandardMaterial>);
+
+#[cfg(feature = "render")]
+impl FromWorld for WireRenderMaterial {
+    fn from_world(world: &mut World) -> Self {
+        let material = StandardMaterial {
+            base_color: Color::WHITE,
+            unlit: true,
+            cull_mode: None,
+            ..default()
+        };
+        Self(world.resource_mut::<Assets<StandardMaterial>>().add(material))
+    }
+}
+
+/// The ribbon mesh of a wire, built by the [`WireRenderPlugin`].
+#[derive(Component, Clone, Debug, Default)]
+pub struct WireMesh {
+    /// The distance along the path of each vertex.
+    distances: Vec<f32>,
+}
+
+impl WireMesh {
+    /// Build a ribbon mesh along `path`, facing +Z.
+    pub fn build(path: &WirePath, width: f32) -> (Self, Mesh) {
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        let mut uvs = Vec::new();
+        let mut indices = Vec::new();
+        let mut distances = Vec::new();
+
+        let length = path.length().max(f32::EPSILON);
+        let mut distance = 0.0;
+        for segment in path.0.windows(2) {
+            let (start, end) = (segment[0], segment[1]);
+            let direction = (end - start).normalize_or_zero();
+            let mut side = direction.cross(Vec3::Z).normalize_or_zero();
+            if side == Vec3::ZERO {
+                side = direction.cross(Vec3::Y).normalize_or_zero();
+            }
+            let side = side * width * 0.5;
+            let end_distance = distance + start.distance(end);
+
+            let first = positions.len() as u32;
+            for (point, along) in [(start, distance), (end, end_distance)] {
+                positions.push((point - side).to_array());
+                positions.push((point + side).to_array());
+                uvs.extend([[along / length, 0.0], [along / length, 1.0]]);
+                distances.extend([along, along]);
+            }
+            normals.extend([[0.0, 0.0, 1.0]; 4]);
+            indices.extend([first, first + 2, first + 1, first + 1, first + 2, first + 3]);
+            distance = end_distance;
+        }
+
+        let colors = vec![[1.0; 4]; positions.len()];
+        let mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+            .with_inserted_indices(Indices::U32(indices));
+
+        (Self { distances }, mesh)
+    }
+
+    /// Returns the vertex colors of the mesh for a wire carrying `signal`, where `blend` is
+    /// how far the color is from `off_color` to `on_color`. If `pulse_offset` is some, pulses
+    /// are drawn that far along the wire.
+    pub fn colors(
+        &self,
+        settings: &WireRenderSettings,
+        signal: Signal,
+        blend: f32,
+        pulse_offset: Option<f32>
+    ) -> Vec<[f32; 4]> {
+        let base = if signal == Signal::Undefined {
+            linear(settings.undefined_color)
+        } else {
+            lerp(linear(settings.off_color), linear(settings.on_color), blend)
+        };
+        let Some(offset) = pulse_offset else {
+            return vec![base; self.distances.len()];
+        };
+
+        self.distances
+            .iter()
+            .map(|&distance| {
+                // Each pulse is brightest at its leading edge and fades towards the next.
+                let phase = ((distance - offset) / settings.pulse_spacing.max(f32::EPSILON))
+                    .rem_euclid(1.0);
+                let glow = 1.0 + settings.pulse_intensity * blend * (1.0 - phase).powi(4);
+                [base[0] * glow, base[1] * glow, base[2] * glow, base[3]]
+            })
+            .collect()
+    }
+}
+
+/// Give wires without a [`WireRoute`] the route from [`WireRenderSettings`].
+pub fn insert_wire_routes(
+    mut commands: Commands,
+    settings: Res<WireRenderSettings>,
+    query: Query<Entity, (Added<Wire>, Without<WireRoute>)>
+) {
+    for entity in query.iter() {
+        commands.entity(entity).insert(settings.route);
+    }
+}
+
+/// A wire's [`WirePath`], and the mesh built from it if it has one.
+#[cfg(feature = "render")]
+type WirePathMesh<'a> = (Entity, &'a WirePath, Option<&'a Handle<Mesh>>, Has<Transform>);
+
+/// Build or rebuild the [`WireMesh`] of each wire whose [`WirePath`] changed.
+#[cfg(feature = "render")]
+pub fn build_wire_meshes(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    settings: Res<WireRenderSettings>,
+    material: Res<WireRenderMaterial>,
+    query: Query<WirePathMesh, (With<Wire>, Changed<WirePath>)>
+) {
+    for (entity, path, handle, has_transform) in query.iter() {
+        let (wire_mesh, mesh) = WireMesh::build(path, settings.width);
+
+        let mut entity = commands.entity(entity);
+        match handle {
+            Some(handle) => {
+                meshes.insert(handle, mesh);
+                entity.insert(wire_mesh);
+            }
+            None => {
+                entity.insert((wire_mesh, meshes.add(mesh), material.0.clone()));
+                if !has_transform {
+                    let transform = Transform::from_xyz(0.0, 0.0, settings.depth);
+                    entity.insert(SpatialBundle::from_transform(transform));
+                }
+            }
+        }
+    }
+}
+
+/// A wire's mesh, and the signals it is colored with.
+#[cfg(feature = "render")]
+type WireMeshSignals<'a> = (
+    &'a Handle<Mesh>,
+    Ref<'a, WireMesh>,
+    Ref<'a, Signal>,
+    Option<&'a PrevSignal>,
+);
+
+/// Write the vertex colors of each [`WireMesh`] from its wire's signal.
+#[cfg(feature = "render")]
+pub fn color_wire_meshes(
+    time: Res<Time>,
+    settings: Res<WireRenderSettings>,
+    interpolation: Option<Res<LogicTickInterpolation>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    query: Query<WireMeshSignals, With<Wire>>
+) {
+    let offset = time.elapsed_seconds() * settings.pulse_speed;
+
+    for (handle, wire_mesh, signal, prev_signal) in query.iter() {
+        let colors = wire_colors(
+            &settings,
+            interpolation.as_deref(),
+            offset,
+            wire_mesh,
+            signal,
+            prev_signal
+        );
+        let (Some(colors), Some(mesh)) = (colors, meshes.get_mut(handle)) else {
+            continue;
+        };
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    }
+}
+
+/// Returns the vertex colors of a wire's mesh, or `None` if they would not change.
+pub(crate) fn wire_colors(
+    settings: &WireRenderSettings,
+    interpolation: Option<&LogicTickInterpolation>,
+    pulse_offset: f32,
+    wire_mesh: Ref<WireMesh>,
+    signal: Ref<Signal>,
+    prev_signal: Option<&PrevSignal>
+) -> Option<Vec<[f32; 4]>> {
+    let blend = match (prev_signal, interpolation) {
+        (Some(prev), Some(interpolation)) => interpolation.lerp_signal(prev.0, *signal),
+        _ => signal.as_f32(),
+    };
+    let blend = blend.clamp(0.0, 1.0);
+    let blending = prev_signal.is_some_and(|prev| prev.0 != *signal);
+    let pulsing = settings.pulse_speed != 0.0 && blend > 0.0;
+    if !(wire_mesh.is_changed() || signal.is_changed() || blending || pulsing) {
+        return None;
+    }
+
+    Some(wire_mesh.colors(settings, *signal, blend, pulsing.then_some(pulse_offset)))
+}
+
+fn linear(color: Color) -> [f32; 4] {
+    let color = color.to_linear();
+    [color.red, color.green, color.blue, color.alpha]
+}
+
+fn lerp(a: [f32; 4], b: [f32; 4], t: f32) -> [f32; 4] {
+    std::array::from_fn(|i| a[i] + (b[i] - a[i]) * t)
+}
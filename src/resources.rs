@@ -1,10 +1,178 @@
-use bevy::prelude::*;
-use petgraph::{ algo::kosaraju_scc, graphmap::DiGraphMap };
+use std::{ cmp::Reverse, ops::Range, sync::{ Arc, Mutex } };
 
-use crate::{ components::Wire, logic::builder::{ GateData, WireData } };
+use bevy::{
+    ecs::entity::EntityHashMap,
+    prelude::*,
+    tasks::AsyncComputeTaskPool,
+    utils::{ Duration, HashMap, Instant },
+};
+use petgraph::{
+    algo::has_path_connecting,
+    graphmap::DiGraphMap,
+    unionfind::UnionFind,
+    visit::{ Bfs, Reversed },
+};
+use uuid::Uuid;
+
+use crate::{
+    components::{ CircuitId, LogicGateFans, Wire, WireFalloff },
+    logic::{ builder::{ GateData, WireData }, registry::GateRegistry, signal::Signal },
+    sim::EvalOrder,
+};
 
 pub mod prelude {
-    pub use super::LogicGraph;
+    pub use super::{
+        LogicGraph,
+        LogicSettings,
+        EvalMode,
+        EvalBudget,
+        PropagationMode,
+        LogicBacklog,
+        PanicPolicy,
+        LogicIslands,
+        LogicIsland,
+        LogicUuidRegistry,
+        SignalFalloff,
+        CircuitBudget,
+        CircuitBudgets,
+    };
+}
+
+/// Configures how the simulation evaluates gates and handles errors.
+///
+/// This is inserted by the [`LogicSimulationPlugin`] from its configuration, and can be
+/// changed at any time.
+///
+/// [`LogicSimulationPlugin`]: crate::LogicSimulationPlugin
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub struct LogicSettings {
+    pub eval_mode: EvalMode,
+    pub eval_budget: EvalBudget,
+    pub panic_policy: PanicPolicy,
+    /// How many more times each feedback loop is evaluated during a step, until none of its
+    /// signals change. `0` evaluates every loop once per step.
+    ///
+    /// A loop that is still changing after the last pass sends a [`SettleFailed`] event.
+    /// Latches built from primitive gates settle within a few passes, while oscillators
+    /// never do, so they should be built with a limit of `0`.
+    ///
+    /// [`SettleFailed`]: crate::components::SettleFailed
+    pub settle_limit: u32,
+    pub propagation: PropagationMode,
+}
+
+/// Determines which gates are evaluated during a logic step.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+pub enum EvalMode {
+    /// Evaluate every gate in the [`LogicGraph`] each step.
+    #[default]
+    Full,
+    /// Only evaluate gates whose [`LogicGateFans`] were just added or changed, or whose input
+    /// signals changed since they were last evaluated. Every gate is evaluated when the graph changes.
+    ///
+    /// This assumes gates are pure functions of their inputs. Gates that change their
+    /// outputs on their own (such as clocks) should not be used in this mode.
+    Dirty,
+    /// Evaluate every gate of each awake island in the [`LogicIslands`] resource, and skip
+    /// sleeping islands entirely.
+    ///
+    /// An island falls asleep after a step in which none of its signals changed, and wakes up
+    /// when one of its gates, or the signal of one of their fans, is changed outside the
+    /// simulation. Like [`EvalMode::Dirty`], this assumes gates are pure functions of their
+    /// inputs.
+    Islands,
+}
+
+/// Determines when gates see the signals written by other gates during the same step.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+pub enum PropagationMode {
+    /// Input signals are written as soon as the gate driving them is evaluated, so a gate
+    /// sees the new outputs of every gate before it in [`LogicGraph::sorted`] order. A signal
+    /// passes through a whole chain of gates in a single step.
+    #[default]
+    Immediate,
+    /// Every gate reads the input signals of the previous step, and input signals are written
+    /// once the whole step completes, like a synchronous circuit where every gate is a
+    /// register. A signal moves one gate per step, and the result does not depend on the order
+    /// gates are evaluated in.
+    ///
+    /// Feedback loops are evaluated once per step in this mode, regardless of
+    /// [`LogicSettings::settle_limit`].
+    Synchronous,
+}
+
+/// Caps the work done by a single run of `step_logic`.
+///
+/// When the budget runs out, the rest of the step continues during the next run, starting
+/// from the next gate in [`LogicGraph::sorted`] order. Signals only settle once the whole step
+/// completes, which the [`LogicBacklog`] resource reports. At least one gate is evaluated per
+/// run, so a step always completes eventually.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+pub enum EvalBudget {
+    /// Every step completes in a single run.
+    #[default]
+    Unlimited,
+    /// Evaluate at most this many gates per run.
+    Gates(usize),
+    /// Stop evaluating gates once this much time has passed during a run.
+    Time(Duration),
+}
+
+impl EvalBudget {
+    /// Returns `true` if a run that started at `run_start` and has evaluated `gates_evaluated`
+    /// gates has used up the budget.
+    pub fn is_exhausted(&self, gates_evaluated: usize, run_start: Instant) -> bool {
+        match self {
+            EvalBudget::Unlimited => false,
+            EvalBudget::Gates(gates) => gates_evaluated >= *gates,
+            EvalBudget::Time(duration) => run_start.elapsed() >= *duration,
+        }
+    }
+}
+
+/// Reports how much of the current logic step is left, when an [`EvalBudget`] splits steps
+/// over several runs of `step_logic`.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Resource)]
+pub struct LogicBacklog {
+    /// The number of gates left to visit before the current step completes. This is `0`
+    /// once the step has completed.
+    pub remaining: usize,
+    /// The number of runs the current step has taken so far, or that the last step took
+    /// if it completed.
+    pub runs: usize,
+}
+
+impl LogicBacklog {
+    /// Returns `true` if the last run completed its step.
+    pub fn is_settled(&self) -> bool {
+        self.remaining == 0
+    }
+}
+
+/// Determines what happens when the simulation finds inconsistent data,
+/// such as a gate in the [`LogicGraph`] that no longer exists.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+pub enum PanicPolicy {
+    /// Panic with a description of the problem.
+    #[default]
+    Panic,
+    /// Log a warning and skip the offending gate or wire.
+    Warn,
+    /// Silently skip the offending gate or wire.
+    Ignore,
+}
+
+impl PanicPolicy {
+    /// Handle an inconsistency according to the policy. `message` is only built
+    /// if it will be used.
+    pub fn handle(&self, message: impl FnOnce() -> String) {
+        match self {
+            PanicPolicy::Panic => panic!("{}", message()),
+            PanicPolicy::Warn => warn!("{}", message()),
+            PanicPolicy::Ignore => {}
+        }
+    }
 }
 
 /// The logic graph resource determines the order
@@ -14,6 +182,21 @@ pub struct LogicGraph {
     #[reflect(ignore)]
     pub graph: DiGraphMap<Entity, Entity>,
     sorted: Vec<Entity>,
+    cycles: Vec<Vec<Entity>>,
+    /// The indices of each feedback loop in `sorted`.
+    #[reflect(ignore)]
+    cycle_spans: Vec<Range<usize>>,
+    #[reflect(ignore)]
+    priorities: EntityHashMap<i32>,
+    longest_path: usize,
+    /// Counts the changes made to the graph through its methods, so a compilation can tell
+    /// whether its copy of the graph is still current.
+    #[reflect(ignore)]
+    revision: u64,
+    /// The compilation started by [`LogicGraph::compile_async`] if it is not ready yet, with
+    /// the revision it started from.
+    #[reflect(ignore)]
+    pending: Option<(u64, PendingOrder)>,
 }
 
 impl LogicGraph {
@@ -31,6 +214,7 @@ impl LogicGraph {
 
     /// Add a gate to the graph.
     pub fn add_gate(&mut self, gate_entity: Entity) -> &mut Self {
+        self.revision += 1;
         self.graph.add_node(gate_entity);
         self
     }
@@ -42,18 +226,21 @@ impl LogicGraph {
         to_gate: Entity,
         wire_entity: Entity
     ) -> &mut Self {
+        self.revision += 1;
         self.graph.add_edge(from_gate, to_gate, wire_entity);
         self
     }
 
     /// Remove a gate from the graph.
     pub fn remove_gate(&mut self, gate_entity: Entity) -> &mut Self {
+        self.revision += 1;
         self.graph.remove_node(gate_entity);
         self
     }
 
     /// Remove a wire from the graph.
     pub fn remove_wire(&mut self, from_gate: Entity, to_gate: Entity) -> &mut Self {
+        self.revision += 1;
         self.graph.remove_edge(from_gate, to_gate);
         self
     }
@@ -79,13 +266,238 @@ impl LogicGraph {
         self.iter_incoming_wires(gate).chain(self.iter_outgoing_wires(gate))
     }
 
+    /// Returns every gate that `gate` drives, directly or through other gates, in breadth-first
+    /// order. `gate` itself is not included, even if it is part of a cycle.
+    pub fn downstream(&self, gate: Entity) -> impl Iterator<Item = Entity> + '_ {
+        let mut bfs = Bfs::new(&self.graph, gate);
+        bfs.next(&self.graph);
+        std::iter::from_fn(move || bfs.next(&self.graph))
+    }
+
+    /// Returns every gate that drives `gate`, directly or through other gates, in breadth-first
+    /// order. `gate` itself is not included, even if it is part of a cycle.
+    pub fn upstream(&self, gate: Entity) -> impl Iterator<Item = Entity> + '_ {
+        let graph = Reversed(&self.graph);
+        let mut bfs = Bfs::new(graph, gate);
+        bfs.next(graph);
+        std::iter::from_fn(move || bfs.next(graph))
+    }
+
+    /// Returns true if a signal from gate `from` can reach gate `to` through the graph's wires.
+    ///
+    /// A gate always has a path to itself.
+    pub fn path_exists(&self, from: Entity, to: Entity) -> bool {
+        has_path_connecting(&self.graph, from, to, None)
+    }
+
+    /// Set the evaluation priority of a gate, which is used by the next
+    /// [`LogicGraph::compile`]. See [`EvalPriority`].
+    ///
+    /// [`EvalPriority`]: crate::components::EvalPriority
+    pub fn set_priority(&mut self, gate: Entity, priority: i32) -> &mut Self {
+        self.revision += 1;
+        if priority == 0 {
+            self.priorities.remove(&gate);
+        } else {
+            self.priorities.insert(gate, priority);
+        }
+        self
+    }
+
+    /// Returns the evaluation priority of a gate, which is `0` unless it was set.
+    pub fn priority(&self, gate: Entity) -> i32 {
+        self.priorities.get(&gate).copied().unwrap_or_default()
+    }
+
+    /// Compute the order gates are evaluated in. See [`LogicGraph::sorted`].
+    ///
+    /// The result of a compilation started by [`LogicGraph::compile_async`] is discarded.
     pub fn compile(&mut self) {
-        self.sorted = kosaraju_scc(&self.graph).into_iter().flatten().rev().collect();
+        self.pending = None;
+        let order = compile_order(&self.graph, &self.priorities);
+        self.apply_order(order);
+    }
+
+    /// Compute the order gates are evaluated in on the [`AsyncComputeTaskPool`], so compiling
+    /// a large graph does not block the frame.
+    ///
+    /// The previous order is used until the new one is ready. It is swapped in by
+    /// [`poll_graph_compilation`], which sends a [`GraphCompiled`] event. The order is computed
+    /// from a copy of the graph. If the graph is changed through its methods in the meantime,
+    /// the result is discarded once it is ready, and the order is computed again. Calling this
+    /// again, or [`LogicGraph::compile`], discards the result of the previous compilation.
+    ///
+    /// [`poll_graph_compilation`]: crate::systems::poll_graph_compilation
+    /// [`GraphCompiled`]: crate::components::GraphCompiled
+    pub fn compile_async(&mut self) {
+        let graph = self.graph.clone();
+        let priorities = self.priorities.clone();
+        let pending = Arc::new(Mutex::new(None));
+        let result = pending.clone();
+        AsyncComputeTaskPool::get()
+            .spawn(async move {
+                let start = Instant::now();
+                let order = compile_order(&graph, &priorities);
+                if let Ok(mut result) = result.lock() {
+                    *result = Some((order, start.elapsed()));
+                }
+            })
+            .detach();
+        self.pending = Some((self.revision, pending));
+    }
+
+    /// Returns `true` if a compilation started by [`LogicGraph::compile_async`] is not ready
+    /// yet.
+    pub fn is_compiling(&self) -> bool {
+        self.pending.is_some()
     }
 
+    /// Take the result of the pending [`LogicGraph::compile_async`] if it is ready, without
+    /// applying it.
+    ///
+    /// If the graph changed since the compilation started, the result is discarded and a new
+    /// compilation is started instead.
+    pub(crate) fn poll_pending(&mut self) -> Option<(GraphOrder, Duration)> {
+        let (revision, pending) = self.pending.as_ref()?;
+        let result = pending.lock().ok()?.take()?;
+        if *revision != self.revision {
+            self.compile_async();
+            return None;
+        }
+        self.pending = None;
+        Some(result)
+    }
+
+    pub(crate) fn apply_order(&mut self, order: GraphOrder) {
+        self.sorted = order.sorted;
+        self.cycles = order.cycles;
+        self.cycle_spans = order.cycle_spans;
+        self.longest_path = order.longest_path;
+    }
+
+    /// Returns the gates in the order they are evaluated, as of the last [`LogicGraph::compile`].
+    ///
+    /// The order is deterministic: it only depends on the gates and wires in the graph, not
+    /// on the order they were added in. Gates come after every gate they depend on, and
+    /// independent gates are ordered by [`Entity`]. Gates in a cycle are evaluated together,
+    /// ordered by [`Entity`].
+    ///
+    /// Gates with a higher [`LogicGraph::priority`] go before independent gates and gates in
+    /// the same cycle, regardless of their [`Entity`].
     pub fn sorted(&self) -> &[Entity] {
         &self.sorted
     }
+
+    /// Returns the feedback loops in the graph, as of the last [`LogicGraph::compile`].
+    ///
+    /// Each loop is a set of gates that all drive each other. Loops, and the gates in them, are
+    /// in the order they are evaluated in. A gate wired to itself is a loop of one.
+    pub fn find_cycles(&self) -> &[Vec<Entity>] {
+        &self.cycles
+    }
+
+    /// Returns the number of gates on the longest path through the graph, as of the last
+    /// [`LogicGraph::compile`].
+    ///
+    /// Wires between gates of the same feedback loop are not followed, so this is the longest
+    /// chain of gates a signal passes through in a single step.
+    pub fn longest_path(&self) -> usize {
+        self.longest_path
+    }
+
+    /// Returns the indices in [`LogicGraph::sorted`] of the feedback loop whose first gate is
+    /// at `index`.
+    pub(crate) fn cycle_starting_at(&self, index: usize) -> Option<Range<usize>> {
+        let i = self.cycle_spans.binary_search_by_key(&index, |span| span.start).ok()?;
+        Some(self.cycle_spans[i].clone())
+    }
+
+    /// Export the graph in the graphviz `dot` format.
+    ///
+    /// Nodes are labeled with their [`Name`] (or [`Entity`] if they do not have one), their
+    /// gate type as registered in the [`GateRegistry`], and their index in the evaluation
+    /// order. Edges are labeled with the output and input fan indices they connect, and the
+    /// current [`Signal`] of the wire.
+    pub fn to_dot(&self, world: &World) -> String {
+        use std::fmt::Write;
+
+        let order = self.sorted
+            .iter()
+            .enumerate()
+            .map(|(index, &gate)| (gate, index))
+            .collect::<EntityHashMap<_>>();
+        let registry = world.get_resource::<GateRegistry>();
+        let mut dot = String::from("digraph LogicGraph {\n");
+
+        for gate in self.graph.nodes() {
+            let name = world
+                .get::<Name>(gate)
+                .map(|name| name.as_str().to_string())
+                .unwrap_or_else(|| format!("{gate}"));
+            let kind = world
+                .get_entity(gate)
+                .zip(registry)
+                .and_then(|(entity, registry)| {
+                    registry.iter().find(|gate| entity.contains_id(gate.component_id))
+                })
+                .map_or("?", |gate| gate.name.as_str());
+            let order = order
+                .get(&gate)
+                .map_or_else(|| "uncompiled".to_string(), |i| format!("#{i}"));
+
+            let _ = writeln!(
+                dot,
+                "    \"{gate}\" [label=\"{}\\n{}\\n{order}\"];",
+                escape_dot(&name),
+                escape_dot(kind)
+            );
+        }
+
+        for (from_gate, to_gate, &wire_entity) in self.graph.all_edges() {
+            let fan_index = |gate: Entity, fan: Entity, outputs: bool| {
+                let fans = world.get::<LogicGateFans>(gate)?;
+                let fans = if outputs { &fans.outputs } else { &fans.inputs };
+                fans.iter().position(|&f| f == Some(fan))
+            };
+            let wire = world.get::<Wire>(wire_entity);
+            let from_index = wire.and_then(|w| fan_index(from_gate, w.from, true));
+            let to_index = wire.and_then(|w| fan_index(to_gate, w.to, false));
+            let signal = world.get::<Signal>(wire_entity).copied().unwrap_or_default();
+
+            let _ = writeln!(
+                dot,
+                "    \"{from_gate}\" -> \"{to_gate}\" [label=\"out.{} -> in.{}\\n{signal}\"];",
+                from_index.map_or_else(|| "?".to_string(), |i| i.to_string()),
+                to_index.map_or_else(|| "?".to_string(), |i| i.to_string())
+            );
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Where [`LogicGraph::compile_async`] writes its result, with the time it took.
+type PendingOrder = Arc<Mutex<Option<(GraphOrder, Duration)>>>;
+
+/// The evaluation order of a [`LogicGraph`], computed by [`LogicGraph::compile`].
+pub(crate) type GraphOrder = EvalOrder<Entity>;
+
+/// Compute the order of `graph`. Gates with a higher priority go first, then gates with a
+/// lower entity.
+fn compile_order(
+    graph: &DiGraphMap<Entity, Entity>,
+    priorities: &EntityHashMap<i32>
+) -> GraphOrder {
+    let edges = graph.all_edges().map(|(from, to, _)| (from, to));
+    EvalOrder::compile(graph.nodes(), edges, |gate| {
+        (Reverse(priorities.get(&gate).copied().unwrap_or_default()), gate)
+    })
+}
+
+/// Escape a string for use inside a quoted `dot` label.
+fn escape_dot(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
 }
 
 pub trait LogicGraphData {
@@ -98,21 +510,21 @@ pub trait LogicGraphData {
 
 impl<I, O> LogicGraphData for GateData<I, O> {
     fn add_to_graph(&self, graph: &mut LogicGraph) {
-        graph.graph.add_node(self.id());
+        graph.add_gate(self.id());
     }
 
     fn remove_from_graph(&self, graph: &mut LogicGraph) {
-        graph.graph.remove_node(self.id());
+        graph.remove_gate(self.id());
     }
 }
 
 impl LogicGraphData for WireData {
     fn add_to_graph(&self, graph: &mut LogicGraph) {
-        graph.graph.add_edge(self.from_gate, self.to_gate, self.id());
+        graph.add_wire(self.from_gate, self.to_gate, self.id());
     }
 
     fn remove_from_graph(&self, graph: &mut LogicGraph) {
-        graph.graph.remove_edge(self.from_gate, self.to_gate);
+        graph.remove_wire(self.from_gate, self.to_gate);
     }
 }
 
@@ -143,3 +555,324 @@ impl<T: LogicGraphData> LogicGraphData for &[T] {
         }
     }
 }
+
+/// The islands of the [`LogicGraph`]: groups of gates connected by wires, regardless of
+/// their direction. Gates in different islands can't affect each other.
+///
+/// In [`EvalMode::Islands`], `step_logic` keeps this in sync with the graph, and uses it to
+/// skip islands that are stable. It can be inspected to see which islands are asleep.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct LogicIslands {
+    islands: Vec<LogicIsland>,
+    island_of: EntityHashMap<usize>,
+}
+
+/// An island in [`LogicIslands`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogicIsland {
+    /// The gates in the island, in the order they are evaluated.
+    pub gates: Vec<Entity>,
+    /// If false, none of the island's signals changed during the last step it was evaluated,
+    /// so it is skipped until something outside the simulation changes it.
+    pub awake: bool,
+}
+
+impl LogicIslands {
+    /// Regroup the gates of `graph` into islands, and wake every island.
+    ///
+    /// Islands are numbered in the order their first gate is evaluated.
+    pub fn rebuild(&mut self, graph: &LogicGraph) {
+        let sorted = graph.sorted();
+        let indices = sorted
+            .iter()
+            .enumerate()
+            .map(|(index, &gate)| (gate, index))
+            .collect::<EntityHashMap<_>>();
+
+        let mut union_find = UnionFind::<usize>::new(sorted.len());
+        for (from, to, _) in graph.graph.all_edges() {
+            if let (Some(&from), Some(&to)) = (indices.get(&from), indices.get(&to)) {
+                union_find.union(from, to);
+            }
+        }
+
+        self.islands.clear();
+        self.island_of.clear();
+        let mut island_of_root = HashMap::<usize, usize>::default();
+        for (index, &gate) in sorted.iter().enumerate() {
+            let island = *island_of_root.entry(union_find.find(index)).or_insert_with(|| {
+                self.islands.push(LogicIsland {
+                    gates: Vec::new(),
+                    awake: true,
+                });
+                self.islands.len() - 1
+            });
+            self.islands[island].gates.push(gate);
+            self.island_of.insert(gate, island);
+        }
+    }
+
+    /// Returns the index of the island containing `gate`.
+    pub fn island_of(&self, gate: Entity) -> Option<usize> {
+        self.island_of.get(&gate).copied()
+    }
+
+    pub fn get(&self, island: usize) -> Option<&LogicIsland> {
+        self.islands.get(island)
+    }
+
+    pub fn get_mut(&mut self, island: usize) -> Option<&mut LogicIsland> {
+        self.islands.get_mut(island)
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &LogicIsland> {
+        self.islands.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.islands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.islands.is_empty()
+    }
+
+    /// Returns the number of gates in all islands.
+    pub fn gate_count(&self) -> usize {
+        self.island_of.len()
+    }
+
+    /// Returns the number of islands that are awake.
+    pub fn awake_count(&self) -> usize {
+        self.islands.iter().filter(|island| island.awake).count()
+    }
+
+    /// Wake every island, so they are all evaluated during the next step.
+    pub fn wake_all(&mut self) {
+        self.islands.iter_mut().for_each(|island| {
+            island.awake = true;
+        });
+    }
+}
+
+/// Maps every [`LogicUuid`] to its entity and back, so saved circuits, network messages, and
+/// blueprints can refer to gates and wires by a stable identifier.
+///
+/// The registry is kept up to date by [`LogicUuid`]'s component hooks, and is added by the
+/// [`LogicSimulationPlugin`]. Entities that got a [`LogicUuid`] before the registry existed
+/// are not tracked.
+///
+/// [`LogicUuid`]: crate::components::LogicUuid
+/// [`LogicSimulationPlugin`]: crate::LogicSimulationPlugin
+#[derive(Resource, Default, Debug)]
+pub struct LogicUuidRegistry {
+    entities: HashMap<Uuid, Entity>,
+    uuids: EntityHashMap<Uuid>,
+}
+
+impl LogicUuidRegistry {
+    /// Returns the entity with `uuid`.
+    pub fn entity(&self, uuid: Uuid) -> Option<Entity> {
+        self.entities.get(&uuid).copied()
+    }
+
+    /// Returns the uuid of `entity`.
+    pub fn uuid(&self, entity: Entity) -> Option<Uuid> {
+        self.uuids.get(&entity).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// Iterate over every `(uuid, entity)` pair.
+    pub fn iter(&self) -> impl Iterator<Item = (Uuid, Entity)> + '_ {
+        self.entities.iter().map(|(&uuid, &entity)| (uuid, entity))
+    }
+
+    pub(crate) fn insert(&mut self, uuid: Uuid, entity: Entity) {
+        if let Some(previous) = self.uuids.insert(entity, uuid) {
+            self.entities.remove(&previous);
+        }
+        if let Some(previous) = self.entities.insert(uuid, entity) {
+            if previous != entity {
+                warn!("LogicUuid {uuid} moved from {previous} to {entity}");
+                self.uuids.remove(&previous);
+            }
+        }
+    }
+
+    pub(crate) fn remove(&mut self, uuid: Uuid, entity: Entity) {
+        if self.entities.get(&uuid) == Some(&entity) {
+            self.entities.remove(&uuid);
+        }
+        if self.uuids.get(&entity) == Some(&uuid) {
+            self.uuids.remove(&entity);
+        }
+    }
+}
+
+/// Makes signals weaker the further they travel, like redstone. Insert this resource to give
+/// every wire a [`WireFalloff`], which is kept up to date by [`update_wire_falloff`]. Remove it
+/// to turn falloff off again.
+///
+/// A digital signal that is on leaves a gate at `max_strength`, and arrives as an analog
+/// strength, reduced by `per_hop` for the wire and by `per_distance` for each unit of its
+/// length. Gates emit at full strength again, so only chains of wires, [`Junction`]s, and
+/// analog pass-through gates lose strength along the way.
+///
+/// [`update_wire_falloff`]: crate::systems::update_wire_falloff
+/// [`Junction`]: crate::logic::gates::Junction
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct SignalFalloff {
+    /// The strength of a digital signal that is on, as it leaves a gate.
+    pub max_strength: f32,
+    /// The strength lost by every wire.
+    pub per_hop: f32,
+    /// The strength lost per world unit of wire length.
+    pub per_distance: f32,
+}
+
+impl Default for SignalFalloff {
+    fn default() -> Self {
+        Self::REDSTONE
+    }
+}
+
+impl SignalFalloff {
+    /// Signals start at 15 and lose 1 per wire.
+    pub const REDSTONE: SignalFalloff = SignalFalloff {
+        max_strength: 15.0,
+        per_hop: 1.0,
+        per_distance: 0.0,
+    };
+
+    /// Returns the [`WireFalloff`] of a wire that is `length` units long.
+    pub fn wire(&self, length: f32) -> WireFalloff {
+        WireFalloff {
+            max_strength: self.max_strength,
+            loss: self.per_hop + self.per_distance * length,
+        }
+    }
+}
+
+/// The most gates and wires a circuit may have. See [`CircuitBudgets`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+pub struct CircuitBudget {
+    /// The most gates in the circuit, or `None` for any number.
+    pub max_gates: Option<usize>,
+    /// The most wires in the circuit, or `None` for any number.
+    pub max_wires: Option<usize>,
+}
+
+impl CircuitBudget {
+    /// No limit on gates or wires.
+    pub const UNLIMITED: CircuitBudget = CircuitBudget {
+        max_gates: None,
+        max_wires: None,
+    };
+
+    /// At most `max_gates` gates, and any number of wires.
+    pub const fn gates(max_gates: usize) -> Self {
+        Self {
+            max_gates: Some(max_gates),
+            max_wires: None,
+        }
+    }
+
+    pub const fn with_max_wires(mut self, max_wires: usize) -> Self {
+        self.max_wires = Some(max_wires);
+        self
+    }
+}
+
+/// A resource that limits how many gates and wires each circuit may have, such as one circuit
+/// per player in a sandbox game.
+///
+/// While it exists, gates and wires that join a circuit with a [`CircuitMember`] past its
+/// budget are despawned before the graph is synced, and a [`BudgetExceeded`] event is sent
+/// for each of them. Gates and wires without a circuit are never limited.
+///
+/// ```ignore
+/// app.insert_resource(CircuitBudgets::new(CircuitBudget::gates(500).with_max_wires(2_000)));
+/// ```
+///
+/// [`CircuitMember`]: crate::components::CircuitMember
+/// [`BudgetExceeded`]: crate::components::BudgetExceeded
+#[derive(Resource, Clone, Debug, Default)]
+pub struct CircuitBudgets {
+    /// The budget of circuits without one of their own.
+    pub default: CircuitBudget,
+    budgets: HashMap<CircuitId, CircuitBudget>,
+}
+
+impl CircuitBudgets {
+    /// Create a resource with the `default` budget for every circuit.
+    pub fn new(default: CircuitBudget) -> Self {
+        Self {
+            default,
+            budgets: HashMap::default(),
+        }
+    }
+
+    /// Set the budget of `circuit`. Gates and wires already in the circuit are kept, even if
+    /// they are past the new budget.
+    pub fn set(&mut self, circuit: CircuitId, budget: CircuitBudget) -> &mut Self {
+        self.budgets.insert(circuit, budget);
+        self
+    }
+
+    /// Remove the budget of `circuit`, so it has the default budget again.
+    pub fn remove(&mut self, circuit: CircuitId) -> Option<CircuitBudget> {
+        self.budgets.remove(&circuit)
+    }
+
+    /// Returns the budget of `circuit`.
+    pub fn get(&self, circuit: CircuitId) -> CircuitBudget {
+        self.budgets.get(&circuit).copied().unwrap_or(self.default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::tasks::TaskPool;
+
+    use super::*;
+    use crate::{ logic::{ builder::LogicExt, gates::AndGate }, LogicSimulationPlugin };
+
+    #[test]
+    fn dot_labels_gates_with_their_type() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_plugins(LogicSimulationPlugin::default());
+        let world = app.world_mut();
+        let gate = world.spawn_gate(AndGate::default()).with_inputs(2).with_outputs(1).build();
+        world.entity_mut(gate.id()).insert(Name::new("and"));
+        world.resource_mut::<LogicGraph>().add_data(gate.clone()).compile();
+
+        let dot = world.resource::<LogicGraph>().to_dot(world);
+        assert!(dot.contains(&format!("\"{}\" [label=\"and\\nAndGate\\n#0\"];", gate.id())));
+    }
+
+    #[test]
+    fn async_order_of_a_changed_graph_is_discarded() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let (a, b) = (Entity::from_raw(1), Entity::from_raw(2));
+        let mut graph = LogicGraph::default();
+        graph.add_gate(a).add_gate(b).compile_async();
+        graph.remove_gate(b);
+
+        let (order, _) = loop {
+            if let Some(result) = graph.poll_pending() {
+                break result;
+            }
+            std::thread::yield_now();
+        };
+        assert_eq!(order.sorted, vec![a]);
+        assert!(!graph.is_compiling());
+    }
+}
@@ -0,0 +1,152 @@
+//! Canonical wire geometry for rendering.
+//!
+//! Wires only know which fans they connect. A [`WirePath`] adds the points a wire passes
+//! through, so gizmos, meshes, and animations along the wire all draw the same route. Add the
+//! [`WireRoutingPlugin`] and a [`WireRoute`] to a wire to keep its path in sync with the
+//! [`GlobalTransform`]s of its fans.
+
+use bevy::{ prelude::*, transform::TransformSystem };
+
+use crate::components::{ GateFan, Wire };
+
+pub mod prelude {
+    pub use super::{ WirePath, WireRoute, WireRoutingPlugin };
+}
+
+/// The points a wire passes through, from its output fan to its input fan.
+#[derive(Component, Clone, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct WirePath(pub Vec<Vec3>);
+
+impl WirePath {
+    /// A straight line from `from` to `to`.
+    pub fn straight(from: Vec3, to: Vec3) -> Self {
+        Self(vec![from, to])
+    }
+
+    /// An orthogonal route on the XY plane: horizontally from `from` to halfway, vertically
+    /// to the height of `to`, then horizontally to `to`.
+    pub fn manhattan(from: Vec3, to: Vec3) -> Self {
+        let middle = from.lerp(to, 0.5);
+        Self(
+            vec![
+                from,
+                Vec3::new(middle.x, from.y, middle.z),
+                Vec3::new(middle.x, to.y, middle.z),
+                to
+            ]
+        )
+    }
+
+    /// A smooth curve from `from` to `to` made of `segments` straight segments. The curve
+    /// leaves and enters horizontally, like the [`manhattan`](Self::manhattan) route.
+    pub fn spline(from: Vec3, to: Vec3, segments: usize) -> Self {
+        let segments = segments.max(1);
+        let handle = Vec3::X * (to.x - from.x).abs().max(0.5) * 0.5;
+        let [p0, p1, p2, p3] = [from, from + handle, to - handle, to];
+
+        // Sample a cubic Bézier curve.
+        let points = (0..=segments).map(|i| {
+            let t = (i as f32) / (segments as f32);
+            let u = 1.0 - t;
+            p0 * u * u * u + p1 * 3.0 * u * u * t + p2 * 3.0 * u * t * t + p3 * t * t * t
+        });
+        Self(points.collect())
+    }
+
+    /// Returns the total length of the path.
+    pub fn length(&self) -> f32 {
+        self.0
+            .windows(2)
+            .map(|segment| segment[0].distance(segment[1]))
+            .sum()
+    }
+
+    /// Returns the point at `fraction` of the path's length, where `0.0` is the output fan
+    /// and `1.0` is the input fan. Returns `None` if the path is empty.
+    pub fn sample(&self, fraction: f32) -> Option<Vec3> {
+        let first = *self.0.first()?;
+        let mut remaining = self.length() * fraction.clamp(0.0, 1.0);
+
+        for segment in self.0.windows(2) {
+            let length = segment[0].distance(segment[1]);
+            if remaining <= length && length > 0.0 {
+                return Some(segment[0].lerp(segment[1], remaining / length));
+            }
+            remaining -= length;
+        }
+
+        self.0.last().copied().or(Some(first))
+    }
+}
+
+/// How the [`WireRoutingPlugin`] routes a wire's [`WirePath`] between its fans.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component)]
+pub enum WireRoute {
+    /// See [`WirePath::straight`].
+    #[default]
+    Straight,
+    /// See [`WirePath::manhattan`].
+    Manhattan,
+    /// See [`WirePath::spline`].
+    Spline {
+        segments: usize,
+    },
+}
+
+impl WireRoute {
+    /// Returns the path between two fan positions.
+    pub fn path(&self, from: Vec3, to: Vec3) -> WirePath {
+        match self {
+            WireRoute::Straight => WirePath::straight(from, to),
+            WireRoute::Manhattan => WirePath::manhattan(from, to),
+            WireRoute::Spline { segments } => WirePath::spline(from, to, *segments),
+        }
+    }
+}
+
+/// A plugin that keeps the [`WirePath`] of every wire with a [`WireRoute`] up to date.
+///
+/// Paths are recomputed in [`PostUpdate`], after transforms are propagated, whenever the
+/// route or the position of either fan changes.
+pub struct WireRoutingPlugin;
+
+impl Plugin for WireRoutingPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<WirePath>()
+            .register_type::<WireRoute>()
+            .add_systems(
+                PostUpdate,
+                update_wire_paths.after(TransformSystem::TransformPropagate)
+            );
+    }
+}
+
+/// A wire with a [`WireRoute`], and its [`WirePath`] if it has one.
+type RoutedWire<'a> = (Entity, Ref<'a, Wire>, Ref<'a, WireRoute>, Option<&'a mut WirePath>);
+
+/// Recompute the [`WirePath`] of each wire with a [`WireRoute`] whose route or fans moved.
+pub fn update_wire_paths(
+    mut commands: Commands,
+    mut query_wires: Query<RoutedWire>,
+    query_fans: Query<Ref<GlobalTransform>, With<GateFan>>
+) {
+    for (entity, wire, route, path) in query_wires.iter_mut() {
+        let (Ok(from), Ok(to)) = (query_fans.get(wire.from), query_fans.get(wire.to)) else {
+            continue;
+        };
+
+        let changed =
+            wire.is_changed() || route.is_changed() || from.is_changed() || to.is_changed();
+        match path {
+            Some(mut path) if changed || path.0.is_empty() => {
+                path.set_if_neq(route.path(from.translation(), to.translation()));
+            }
+            None => {
+                commands.entity(entity).insert(route.path(from.translation(), to.translation()));
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! Source gates that set their signal from the state of the game world.
+
+pub mod state;
+#[cfg(feature = "avian")]
+pub mod physics;
+
+use bevy::prelude::*;
+
+use crate::{
+    components::{ NoEvalOutput, OutputBundle },
+    logic::{ schedule::{ LogicSystemSet, LogicUpdate }, signal::Signal },
+};
+
+pub mod prelude {
+    pub use super::{ ProximitySensor, SensorBundle, SensorPlugin, SensorTarget };
+    pub use super::state::{ AppSensorExt, GameStateSensor, ResourceSensor };
+    #[cfg(feature = "avian")]
+    pub use super::physics::{ CollisionSensor, RaycastSensor };
+}
+
+/// A plugin that updates the signal of every sensor once per logic step, before
+/// [`LogicSystemSet::PropagateNoEval`] sends it through their wires.
+///
+/// Sensors are standalone outputs with a [`NoEvalOutput`], like buttons. Spawn them with a
+/// [`SensorBundle`] and connect them with [`LogicExt::spawn_no_eval_wire`].
+///
+/// The [`RaycastSensor`] and [`CollisionSensor`] require the `avian` feature and avian's
+/// `PhysicsPlugins`.
+///
+/// [`LogicExt::spawn_no_eval_wire`]: crate::logic::builder::LogicExt::spawn_no_eval_wire
+/// [`RaycastSensor`]: physics::RaycastSensor
+/// [`CollisionSensor`]: physics::CollisionSensor
+pub struct SensorPlugin;
+
+impl Plugin for SensorPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<ProximitySensor>()
+            .register_type::<SensorTarget>()
+            .add_systems(
+                LogicUpdate,
+                update_proximity_sensors.before(LogicSystemSet::PropagateNoEval)
+            );
+
+        #[cfg(feature = "avian")]
+        app.register_type::<physics::RaycastSensor>()
+            .register_type::<physics::CollisionSensor>()
+            .add_systems(
+                LogicUpdate,
+                (physics::update_raycast_sensors, physics::update_collision_sensors).before(
+                    LogicSystemSet::PropagateNoEval
+                )
+            );
+    }
+}
+
+/// A bundle for spawning a sensor as a standalone output.
+#[derive(Bundle, Default)]
+pub struct SensorBundle<S: Component> {
+    pub sensor: S,
+    pub output: OutputBundle,
+    pub no_eval: NoEvalOutput,
+}
+
+impl<S: Component> SensorBundle<S> {
+    pub fn new(sensor: S) -> Self {
+        Self {
+            sensor,
+            output: OutputBundle::default(),
+            no_eval: NoEvalOutput,
+        }
+    }
+}
+
+/// Marks an entity that [`ProximitySensor`]s can detect.
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct SensorTarget;
+
+/// A sensor that detects [`SensorTarget`]s within `radius` of its [`GlobalTransform`].
+///
+/// Its signal is [`Signal::ON`] while any target is in range, or the number of targets in
+/// range as [`Signal::Analog`] if `count` is true.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component)]
+pub struct ProximitySensor {
+    pub radius: f32,
+    /// If true, the sensor emits the number of targets in range.
+    pub count: bool,
+}
+
+impl Default for ProximitySensor {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+impl ProximitySensor {
+    pub fn new(radius: f32) -> Self {
+        Self { radius, count: false }
+    }
+
+    pub fn counting(mut self) -> Self {
+        self.count = true;
+        self
+    }
+}
+
+/// Set the signal of each [`ProximitySensor`] from the [`SensorTarget`]s in range.
+pub fn update_proximity_sensors(
+    mut query_sensors: Query<
+        (Entity, &ProximitySensor, &GlobalTransform, &mut Signal),
+        With<NoEvalOutput>
+    >,
+    query_targets: Query<(Entity, &GlobalTransform), With<SensorTarget>>
+) {
+    for (entity, sensor, transform, mut signal) in query_sensors.iter_mut() {
+        let position = transform.translation();
+        let radius_squared = sensor.radius * sensor.radius;
+        let in_range = query_targets
+            .iter()
+            .filter(|&(target, target_transform)| {
+                target != entity &&
+                    target_transform.translation().distance_squared(position) <= radius_squared
+            })
+            .count();
+
+        let new_signal = if sensor.count {
+            Signal::Analog(in_range as f32)
+        } else {
+            Signal::from(in_range > 0)
+        };
+        signal.set_if_neq(new_signal);
+    }
+}
@@ -0,0 +1,148 @@
+//! Sensors backed by `avian3d`. Requires the `avian` feature.
+
+use avian3d::prelude::{ CollisionEnded, CollisionStarted, SpatialQuery, SpatialQueryFilter };
+use bevy::{
+    ecs::{
+        entity::{ EntityHashSet, EntityMapper, MapEntities },
+        reflect::ReflectMapEntities,
+    },
+    prelude::*,
+};
+
+use crate::{ components::NoEvalOutput, logic::signal::Signal };
+
+/// A sensor that casts a ray from its [`GlobalTransform`] every logic step.
+///
+/// Its signal is [`Signal::ON`] while the ray hits a collider, or the distance to the hit
+/// as [`Signal::Analog`] if `distance` is true. The sensor's own entity is never hit.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component)]
+pub struct RaycastSensor {
+    /// The direction of the ray, in the sensor's local space.
+    pub direction: Dir3,
+    /// The length of the ray.
+    pub max_distance: f32,
+    /// If true, the sensor emits the distance to the hit.
+    pub distance: bool,
+}
+
+impl Default for RaycastSensor {
+    fn default() -> Self {
+        Self::new(Dir3::NEG_Z, 10.0)
+    }
+}
+
+impl RaycastSensor {
+    pub fn new(direction: Dir3, max_distance: f32) -> Self {
+        Self {
+            direction,
+            max_distance,
+            distance: false,
+        }
+    }
+
+    pub fn measuring(mut self) -> Self {
+        self.distance = true;
+        self
+    }
+}
+
+/// A sensor that is on while its entity's collider touches another collider, using avian's
+/// [`CollisionStarted`] and [`CollisionEnded`] events.
+///
+/// Add a `Collider` to the sensor entity, usually with `Sensor` so it does not push other
+/// bodies. Its signal is [`Signal::ON`] while anything touches it, or the number of
+/// entities touching it as [`Signal::Analog`] if `count` is true.
+#[derive(Component, Clone, Debug, Default, Reflect)]
+#[reflect(Component, MapEntities)]
+pub struct CollisionSensor {
+    /// If true, the sensor emits the number of entities touching it.
+    pub count: bool,
+    /// The entities touching the sensor.
+    #[reflect(ignore)]
+    touching: EntityHashSet,
+}
+
+impl MapEntities for CollisionSensor {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        self.touching = self.touching
+            .iter()
+            .map(|&entity| entity_mapper.map_entity(entity))
+            .collect();
+    }
+}
+
+impl CollisionSensor {
+    pub fn counting(mut self) -> Self {
+        self.count = true;
+        self
+    }
+
+    /// Returns the entities touching the sensor.
+    pub fn touching(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.touching.iter().copied()
+    }
+}
+
+/// Set the signal of each [`RaycastSensor`] from a ray cast.
+pub fn update_raycast_sensors(
+    spatial_query: SpatialQuery,
+    mut query: Query<
+        (Entity, &RaycastSensor, &GlobalTransform, &mut Signal),
+        With<NoEvalOutput>
+    >
+) {
+    for (entity, sensor, transform, mut signal) in query.iter_mut() {
+        let direction = Dir3::new(transform.affine().transform_vector3(*sensor.direction));
+        let Ok(direction) = direction else {
+            continue;
+        };
+
+        let filter = SpatialQueryFilter::default().with_excluded_entities([entity]);
+        let hit = spatial_query.cast_ray(
+            transform.translation(),
+            direction,
+            sensor.max_distance,
+            true,
+            filter
+        );
+
+        let new_signal = match hit {
+            Some(hit) if sensor.distance => Signal::Analog(hit.time_of_impact),
+            hit => Signal::from(hit.is_some()),
+        };
+        signal.set_if_neq(new_signal);
+    }
+}
+
+/// Track the entities touching each [`CollisionSensor`], and set its signal.
+pub fn update_collision_sensors(
+    mut started: EventReader<CollisionStarted>,
+    mut ended: EventReader<CollisionEnded>,
+    mut query: Query<(&mut CollisionSensor, &mut Signal), With<NoEvalOutput>>
+) {
+    for &CollisionStarted(a, b) in started.read() {
+        for (sensor, other) in [(a, b), (b, a)] {
+            if let Ok((mut sensor, _)) = query.get_mut(sensor) {
+                sensor.touching.insert(other);
+            }
+        }
+    }
+    for &CollisionEnded(a, b) in ended.read() {
+        for (sensor, other) in [(a, b), (b, a)] {
+            if let Ok((mut sensor, _)) = query.get_mut(sensor) {
+                sensor.touching.remove(&other);
+            }
+        }
+    }
+
+    for (sensor, mut signal) in query.iter_mut() {
+        let touching = sensor.touching.len();
+        let new_signal = if sensor.count {
+            Signal::Analog(touching as f32)
+        } else {
+            Signal::from(touching > 0)
+        };
+        signal.set_if_neq(new_signal);
+    }
+}
@@ -0,0 +1,108 @@
+//! Sensors that read a [`Resource`], such as the time of day or the state of a wave.
+
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+
+use crate::{
+    components::NoEvalOutput,
+    logic::{ schedule::{ LogicSystemSet, LogicUpdate }, signal::Signal },
+};
+
+/// A sensor component whose signal is computed from a [`Resource`].
+///
+/// Register it with [`AppSensorExt::add_game_state_sensor`]. The signal is updated when the
+/// resource or the sensor changes, and is [`Signal::Undefined`] while the resource does not
+/// exist.
+///
+/// ```ignore
+/// #[derive(Component)]
+/// struct NightSensor;
+///
+/// impl GameStateSensor for NightSensor {
+///     type State = TimeOfDay;
+///
+///     fn sense(&self, time: &TimeOfDay) -> Signal {
+///         Signal::from(time.hour >= 20 || time.hour < 6)
+///     }
+/// }
+/// ```
+pub trait GameStateSensor: Component {
+    type State: Resource;
+
+    fn sense(&self, state: &Self::State) -> Signal;
+}
+
+/// A sensor whose signal is computed from `R` by the predicate registered with
+/// [`AppSensorExt::add_resource_sensor`].
+#[derive(Component)]
+pub struct ResourceSensor<R: Resource>(PhantomData<fn() -> R>);
+
+impl<R: Resource> Default for ResourceSensor<R> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+/// An [App] extension for registering sensors that read resources.
+pub trait AppSensorExt {
+    /// Update the signal of every `S` sensor from its [`GameStateSensor::State`].
+    fn add_game_state_sensor<S: GameStateSensor>(&mut self) -> &mut Self;
+
+    /// Update the signal of every [`ResourceSensor<R>`] with `predicate`.
+    ///
+    /// Each resource type has a single predicate, so this should only be called once per
+    /// resource type. Implement [`GameStateSensor`] for sensors that need more than one.
+    fn add_resource_sensor<R: Resource, S: Into<Signal>>(
+        &mut self,
+        predicate: impl Fn(&R) -> S + Send + Sync + 'static
+    ) -> &mut Self;
+}
+
+impl AppSensorExt for App {
+    fn add_game_state_sensor<S: GameStateSensor>(&mut self) -> &mut Self {
+        self.add_systems(
+            LogicUpdate,
+            update_game_state_sensors::<S>.before(LogicSystemSet::PropagateNoEval)
+        )
+    }
+
+    fn add_resource_sensor<R: Resource, S: Into<Signal>>(
+        &mut self,
+        predicate: impl Fn(&R) -> S + Send + Sync + 'static
+    ) -> &mut Self {
+        let update = move |
+            state: Option<Res<R>>,
+            mut query: Query<(Ref<ResourceSensor<R>>, &mut Signal), With<NoEvalOutput>>
+        | {
+            let state_changed = state.as_ref().map(DetectChanges::is_changed) != Some(false);
+            for (sensor, mut signal) in query.iter_mut() {
+                if !state_changed && !sensor.is_added() {
+                    continue;
+                }
+                let new_signal = state.as_deref().map_or(Signal::Undefined, |state| {
+                    predicate(state).into()
+                });
+                signal.set_if_neq(new_signal);
+            }
+        };
+        self.add_systems(LogicUpdate, update.before(LogicSystemSet::PropagateNoEval))
+    }
+}
+
+/// Set the signal of each `S` sensor from its state resource.
+pub fn update_game_state_sensors<S: GameStateSensor>(
+    state: Option<Res<S::State>>,
+    mut query: Query<(Ref<S>, &mut Signal), With<NoEvalOutput>>
+) {
+    let state_changed = state.as_ref().map(DetectChanges::is_changed) != Some(false);
+    for (sensor, mut signal) in query.iter_mut() {
+        if !state_changed && !sensor.is_changed() {
+            continue;
+        }
+        let new_signal = state
+            .as_deref()
+            .map_or(Signal::Undefined, |state| sensor.sense(state));
+        signal.set_if_neq(new_signal);
+    }
+}
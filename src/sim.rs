@@ -0,0 +1,548 @@
+//! The simulation core, without the ECS.
+//!
+//! The pieces a circuit is simulated with do not depend on a [`World`]: a [`Signal`], a
+//! [`CircuitGate`], and the [`EvalOrder`] gates are evaluated in. A [`Circuit`] puts them
+//! together with plain indices instead of entities, so a circuit can be simulated on a
+//! server, in a test, or in a tool without an [`App`], schedules, or queries.
+//!
+//! The module only uses `core`, `alloc`, and `hashbrown`, and is generic over the signal
+//! type, so it builds without bevy or `std`. `checks/no_std` builds it as a `#![no_std]`
+//! crate. Every [`LogicGate`] is a [`CircuitGate`] of [`Signal`]s.
+//!
+//! The [`LogicGraph`] computes its order with [`EvalOrder`] too, so a [`Circuit`] evaluates
+//! its gates in the same order as [`step_logic`] with [`PropagationMode::Immediate`].
+//!
+//! Rendering, picking, and the inspector are behind cargo features, and bevy is compiled
+//! without its default features, so a headless build only pulls in the ECS crates:
+//!
+//! ```toml
+//! bevy_logic = { version = "*", default-features = false, features = ["gates-basic"] }
+//! ```
+//!
+//! ```
+//! # use bevy_logic::prelude::*;
+//! let mut circuit = Circuit::new();
+//! let battery = circuit.add_gate(Battery::ON, 0, 1);
+//! let not = circuit.add_gate(NotGate, 1, 1);
+//! circuit.connect((battery, 0), (not, 0));
+//!
+//! circuit.step();
+//! assert_eq!(circuit.output(not, 0), Some(Signal::OFF));
+//! ```
+//!
+//! [`World`]: bevy::prelude::World
+//! [`App`]: bevy::prelude::App
+//! [`Signal`]: crate::logic::signal::Signal
+//! [`LogicGate`]: crate::logic::LogicGate
+//! [`LogicGraph`]: crate::resources::LogicGraph
+//! [`step_logic`]: crate::systems::step_logic
+//! [`PropagationMode::Immediate`]: crate::resources::PropagationMode::Immediate
+
+use alloc::{ boxed::Box, collections::BinaryHeap, vec, vec::Vec };
+use core::{ cmp::Reverse, hash::Hash, ops::Range, time::Duration };
+
+use hashbrown::HashMap;
+
+pub mod prelude {
+    pub use super::{ Circuit, CircuitContext, CircuitGate, EvalOrder, GateId, WireId };
+}
+
+/// The order the gates of a graph are evaluated in, computed by [`EvalOrder::compile`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EvalOrder<N> {
+    pub(crate) sorted: Vec<N>,
+    pub(crate) cycles: Vec<Vec<N>>,
+    pub(crate) cycle_spans: Vec<Range<usize>>,
+    pub(crate) longest_path: usize,
+}
+
+impl<N> Default for EvalOrder<N> {
+    fn default() -> Self {
+        Self {
+            sorted: Vec::new(),
+            cycles: Vec::new(),
+            cycle_spans: Vec::new(),
+            longest_path: 0,
+        }
+    }
+}
+
+impl<N: Copy + Eq + Hash> EvalOrder<N> {
+    /// Compute the evaluation order of a graph of `gates`, where an edge goes from a gate to
+    /// a gate that reads its outputs. Gates only named by an edge are added too.
+    ///
+    /// Gates come after every gate they depend on. Independent gates, and gates in the same
+    /// cycle, are ordered by `key`, from the lowest key. The order only depends on the
+    /// gates, edges, and keys, not on the order they were added in.
+    pub fn compile<K: Ord>(
+        gates: impl IntoIterator<Item = N>,
+        edges: impl IntoIterator<Item = (N, N)>,
+        key: impl Fn(N) -> K
+    ) -> Self {
+        let mut nodes = Vec::new();
+        let mut indices = HashMap::<N, usize>::new();
+        let mut index_of = |gate: N| {
+            *indices.entry(gate).or_insert_with(|| {
+                nodes.push(gate);
+                nodes.len() - 1
+            })
+        };
+        for gate in gates {
+            index_of(gate);
+        }
+        let mut successors = Vec::<Vec<usize>>::new();
+        let mut predecessors = Vec::<Vec<usize>>::new();
+        for (from, to) in edges {
+            let (from, to) = (index_of(from), index_of(to));
+            let len = from.max(to) + 1;
+            if successors.len() < len {
+                successors.resize_with(len, Vec::new);
+                predecessors.resize_with(len, Vec::new);
+            }
+            successors[from].push(to);
+            predecessors[to].push(from);
+        }
+        successors.resize_with(nodes.len(), Vec::new);
+        predecessors.resize_with(nodes.len(), Vec::new);
+
+        let mut components = strongly_connected(&successors);
+        for component in components.iter_mut() {
+            component.sort_unstable_by_key(|&gate| key(nodes[gate]));
+        }
+
+        let mut component_of = vec![0; nodes.len()];
+        for (i, component) in components.iter().enumerate() {
+            for &gate in component.iter() {
+                component_of[gate] = i;
+            }
+        }
+
+        // Count the edges coming into each component from other components.
+        let mut incoming = vec![0usize; components.len()];
+        for (from, successors) in successors.iter().enumerate() {
+            for &to in successors.iter() {
+                if component_of[from] != component_of[to] {
+                    incoming[component_of[to]] += 1;
+                }
+            }
+        }
+
+        // Visit components in topological order, breaking ties by their first gate.
+        let mut ready = components
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| incoming[*i] == 0)
+            .map(|(i, component)| Reverse((key(nodes[component[0]]), i)))
+            .collect::<BinaryHeap<_>>();
+
+        let mut order = Self::default();
+        let mut sorted = Vec::with_capacity(nodes.len());
+        while let Some(Reverse((_, i))) = ready.pop() {
+            let gate = components[i][0];
+            if components[i].len() > 1 || successors[gate].contains(&gate) {
+                let start = sorted.len();
+                order.cycles.push(components[i].iter().map(|&gate| nodes[gate]).collect());
+                order.cycle_spans.push(start..start + components[i].len());
+            }
+            for &gate in components[i].iter() {
+                sorted.push(gate);
+                for &to in successors[gate].iter() {
+                    let j = component_of[to];
+                    if i != j {
+                        incoming[j] -= 1;
+                        if incoming[j] == 0 {
+                            ready.push(Reverse((key(nodes[components[j][0]]), j)));
+                        }
+                    }
+                }
+            }
+        }
+
+        // Gates are sorted after the gates they depend on, outside of their own loop.
+        let mut depths = vec![0usize; nodes.len()];
+        for &gate in sorted.iter() {
+            let depth = predecessors[gate]
+                .iter()
+                .filter(|&&from| component_of[from] != component_of[gate])
+                .map(|&from| depths[from])
+                .max()
+                .unwrap_or_default();
+            depths[gate] = depth + 1;
+        }
+        order.longest_path = depths.into_iter().max().unwrap_or_default();
+        order.sorted = sorted.into_iter().map(|gate| nodes[gate]).collect();
+        order
+    }
+
+    /// Returns the gates in the order they are evaluated.
+    pub fn sorted(&self) -> &[N] {
+        &self.sorted
+    }
+
+    /// Returns the gates of each feedback loop, in the order the loops are evaluated.
+    pub fn cycles(&self) -> &[Vec<N>] {
+        &self.cycles
+    }
+
+    /// Returns the range of [`EvalOrder::sorted`] each feedback loop occupies.
+    pub fn cycle_spans(&self) -> &[Range<usize>] {
+        &self.cycle_spans
+    }
+
+    /// Returns the number of gates on the longest chain of dependencies, counting each
+    /// feedback loop as a single gate.
+    pub fn longest_path(&self) -> usize {
+        self.longest_path
+    }
+}
+
+/// Returns the strongly connected components of a graph, given the successors of each node,
+/// with Tarjan's algorithm. Iterative, so long chains do not overflow the stack.
+fn strongly_connected(successors: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    const UNVISITED: usize = usize::MAX;
+
+    let mut index = vec![UNVISITED; successors.len()];
+    let mut low = vec![0; successors.len()];
+    let mut on_stack = vec![false; successors.len()];
+    let mut stack = Vec::new();
+    let mut calls = Vec::<(usize, usize)>::new();
+    let mut components = Vec::new();
+    let mut next_index = 0;
+
+    for root in 0..successors.len() {
+        if index[root] != UNVISITED {
+            continue;
+        }
+        calls.push((root, 0));
+        index[root] = next_index;
+        low[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some((node, next)) = calls.last_mut() {
+            let node = *node;
+            if let Some(&to) = successors[node].get(*next) {
+                *next += 1;
+                if index[to] == UNVISITED {
+                    index[to] = next_index;
+                    low[to] = next_index;
+                    next_index += 1;
+                    stack.push(to);
+                    on_stack[to] = true;
+                    calls.push((to, 0));
+                } else if on_stack[to] {
+                    low[node] = low[node].min(index[to]);
+                }
+                continue;
+            }
+
+            calls.pop();
+            if let Some(&(parent, _)) = calls.last() {
+                low[parent] = low[parent].min(low[node]);
+            }
+            if low[node] == index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+
+    components
+}
+
+/// A gate added to a [`Circuit`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GateId(usize);
+
+impl GateId {
+    /// Returns the index of the gate, in the order gates were added.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// A wire added to a [`Circuit`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WireId(usize);
+
+impl WireId {
+    /// Returns the index of the wire, in the order wires were added.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// A gate that can be evaluated by a [`Circuit`] with signals of type `S`.
+///
+/// Every [`LogicGate`] is a `CircuitGate<Signal>`, evaluated with
+/// [`LogicGate::evaluate_with_context`]. Its context has a placeholder entity made from the
+/// [`GateId`], and the [`GateEffects`] it queues are discarded, since there is no world to
+/// apply them to.
+///
+/// [`LogicGate`]: crate::logic::LogicGate
+/// [`LogicGate::evaluate_with_context`]: crate::logic::LogicGate::evaluate_with_context
+/// [`GateEffects`]: crate::logic::GateEffects
+pub trait CircuitGate<S> {
+    /// Compute the outputs of the gate from its inputs.
+    fn evaluate_gate(&mut self, context: &CircuitContext<'_, S>, inputs: &[S], outputs: &mut [S]);
+}
+
+/// The step a gate is evaluated in, passed to [`CircuitGate::evaluate_gate`].
+#[derive(Clone, Copy, Debug)]
+pub struct CircuitContext<'a, S> {
+    /// The gate being evaluated.
+    pub gate: GateId,
+    /// The number of the current step, starting from `1`, like the [`LogicTick`].
+    ///
+    /// [`LogicTick`]: crate::logic::schedule::LogicTick
+    pub tick: u64,
+    /// The simulated duration of a step.
+    pub timestep: Duration,
+    /// The input signals of the gate's previous evaluation, which is empty if this is its
+    /// first evaluation.
+    pub previous_inputs: &'a [S],
+}
+
+struct CircuitNode<S> {
+    gate: Box<dyn CircuitGate<S> + Send + Sync>,
+    /// The input signals, followed by the output signals.
+    signals: Range<usize>,
+    inputs: usize,
+    /// The wires driven by the outputs of the gate, in the order they were connected.
+    wires: Vec<usize>,
+    priority: i32,
+    previous_inputs: Vec<S>,
+}
+
+#[derive(Clone, Copy)]
+struct CircuitWire {
+    from: usize,
+    to: usize,
+}
+
+/// A circuit of [`CircuitGate`]s simulated without the ECS.
+///
+/// Each gate has a fixed number of input and output signals. A wire copies an output signal
+/// to an input signal of another gate, and an input without a wire keeps whatever signal
+/// it is given by [`Circuit::set_input`].
+///
+/// Gates are evaluated once per [`Circuit::step`] in [`EvalOrder`], and each output is
+/// written through its wires as soon as its gate is evaluated, so a signal passes through a
+/// whole chain of gates in a single step. Feedback loops are evaluated once per step.
+pub struct Circuit<S> {
+    gates: Vec<CircuitNode<S>>,
+    wires: Vec<CircuitWire>,
+    signals: Vec<S>,
+    /// An edge from each gate to each gate it drives, for every wire.
+    edges: Vec<(GateId, GateId)>,
+    order: Option<EvalOrder<GateId>>,
+    tick: u64,
+    timestep: Duration,
+}
+
+impl<S> Default for Circuit<S> {
+    fn default() -> Self {
+        Self {
+            gates: Vec::new(),
+            wires: Vec::new(),
+            signals: Vec::new(),
+            edges: Vec::new(),
+            order: None,
+            tick: 0,
+            timestep: Duration::from_millis(500),
+        }
+    }
+}
+
+impl<S> core::fmt::Debug for Circuit<S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Circuit")
+            .field("gates", &self.gates.len())
+            .field("wires", &self.wires.len())
+            .field("tick", &self.tick)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S: Copy + PartialEq + Default> Circuit<S> {
+    /// Create an empty circuit.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the simulated duration of a step, passed to gates in their [`CircuitContext`].
+    /// Defaults to half a second, the timestep of the default tick rate.
+    pub fn with_timestep(mut self, timestep: Duration) -> Self {
+        self.timestep = timestep;
+        self
+    }
+
+    /// Add a gate with `inputs` input signals and `outputs` output signals, which all start
+    /// as the default signal, [`Signal::Undefined`].
+    ///
+    /// [`Signal::Undefined`]: crate::logic::signal::Signal::Undefined
+    pub fn add_gate(
+        &mut self,
+        gate: impl CircuitGate<S> + Send + Sync + 'static,
+        inputs: usize,
+        outputs: usize
+    ) -> GateId {
+        let start = self.signals.len();
+        self.signals.resize(start + inputs + outputs, S::default());
+
+        let id = GateId(self.gates.len());
+        self.gates.push(CircuitNode {
+            gate: Box::new(gate),
+            signals: start..self.signals.len(),
+            inputs,
+            wires: Vec::new(),
+            priority: 0,
+            previous_inputs: Vec::new(),
+        });
+        self.order = None;
+        id
+    }
+
+    /// Connect output `from.1` of gate `from.0` to input `to.1` of gate `to.0`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either gate is not part of the circuit, or does not have the fan.
+    pub fn connect(&mut self, from: (GateId, usize), to: (GateId, usize)) -> WireId {
+        let from_signal = self.output_index(from.0, from.1).expect("no such output");
+        let to_signal = self.input_index(to.0, to.1).expect("no such input");
+
+        let id = WireId(self.wires.len());
+        self.wires.push(CircuitWire { from: from_signal, to: to_signal });
+        self.gates[from.0.0].wires.push(id.0);
+        self.edges.push((from.0, to.0));
+        self.order = None;
+        id
+    }
+    /// Set the evaluation priority of a gate, like [`EvalPriority`]. Gates with a higher
+    /// priority go before independent gates and gates in the same feedback loop.
+    ///
+    /// [`EvalPriority`]: crate::components::EvalPriority
+    pub fn set_priority(&mut self, gate: GateId, priority: i32) {
+        if let Some(gate) = self.gates.get_mut(gate.0) {
+            gate.priority = priority;
+            self.order = None;
+        }
+    }
+
+    /// Returns the number of gates.
+    pub fn gate_count(&self) -> usize {
+        self.gates.len()
+    }
+
+    /// Returns the number of wires.
+    pub fn wire_count(&self) -> usize {
+        self.wires.len()
+    }
+
+    /// Returns the current signal of an input, or `None` if the gate does not have it.
+    pub fn input(&self, gate: GateId, input: usize) -> Option<S> {
+        self.input_index(gate, input).map(|index| self.signals[index])
+    }
+
+    /// Returns the current signal of an output, or `None` if the gate does not have it.
+    pub fn output(&self, gate: GateId, output: usize) -> Option<S> {
+        self.output_index(gate, output).map(|index| self.signals[index])
+    }
+
+    /// Set the signal of an input. Returns `false` if the gate does not have it.
+    ///
+    /// The signal is overwritten the next time a wire into the input is written.
+    pub fn set_input(&mut self, gate: GateId, input: usize, signal: S) -> bool {
+        match self.input_index(gate, input) {
+            Some(index) => {
+                self.signals[index] = signal;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the order gates are evaluated in, computing it if the circuit changed.
+    pub fn order(&mut self) -> &EvalOrder<GateId> {
+        let Self { gates, edges, order, .. } = self;
+        order.get_or_insert_with(|| {
+            let ids = (0..gates.len()).map(GateId);
+            EvalOrder::compile(ids, edges.iter().copied(), |gate| {
+                (Reverse(gates[gate.0].priority), gate)
+            })
+        })
+    }
+
+    /// Returns the number of steps evaluated so far, which is also the tick the last step
+    /// was evaluated in, like the [`LogicTick`] after the same number of steps.
+    ///
+    /// [`LogicTick`]: crate::logic::schedule::LogicTick
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Evaluate every gate once, and propagate its outputs through its wires.
+    ///
+    /// Returns the number of output signals that changed value.
+    pub fn step(&mut self) -> usize {
+        self.order();
+        let Self { gates, wires, signals, order, tick, timestep, .. } = self;
+        let Some(order) = order else {
+            return 0;
+        };
+
+        let mut previous = Vec::new();
+        let mut signal_changes = 0;
+        *tick += 1;
+
+        for &id in order.sorted.iter() {
+            let gate = &mut gates[id.0];
+            let (inputs, outputs) = signals[gate.signals.clone()].split_at_mut(gate.inputs);
+            previous.clear();
+            previous.extend_from_slice(outputs);
+
+            let context = CircuitContext {
+                gate: id,
+                tick: *tick,
+                timestep: *timestep,
+                previous_inputs: &gate.previous_inputs,
+            };
+            gate.gate.evaluate_gate(&context, inputs, outputs);
+            gate.previous_inputs.clear();
+            gate.previous_inputs.extend_from_slice(inputs);
+
+            signal_changes += outputs
+                .iter()
+                .zip(previous.iter())
+                .filter(|(output, previous)| output != previous)
+                .count();
+
+            for &wire in gate.wires.iter() {
+                let CircuitWire { from, to } = wires[wire];
+                signals[to] = signals[from];
+            }
+        }
+
+        signal_changes
+    }
+
+    fn input_index(&self, gate: GateId, input: usize) -> Option<usize> {
+        let gate = self.gates.get(gate.0)?;
+        (input < gate.inputs).then_some(gate.signals.start + input)
+    }
+
+    fn output_index(&self, gate: GateId, output: usize) -> Option<usize> {
+        let gate = self.gates.get(gate.0)?;
+        let index = gate.signals.start + gate.inputs + output;
+        (index < gate.signals.end).then_some(index)
+    }
+}
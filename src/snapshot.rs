@@ -0,0 +1,256 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    components::{ GateFan, LogicGateFans, Wire },
+    logic::{
+        schedule::{ LogicSystemSet, LogicTick, LogicUpdate },
+        signal::Signal,
+        LogicGateTypes,
+    },
+};
+
+pub mod prelude {
+    pub use super::{ LogicRollback, LogicSnapshot, RollbackPlugin };
+}
+
+/// A copy of the simulation state of a world: every fan and wire [`Signal`], and the
+/// state of every gate.
+///
+/// Gate state is captured through reflection, for each component registered in
+/// [`LogicGateTypes`] that is also registered with `#[reflect(Component)]`. Fields marked
+/// `#[reflect(ignore)]` are not captured, such as the state of a [`DynamicGate`].
+///
+/// Because evaluation is deterministic, applying a snapshot and stepping with the same
+/// inputs reproduces the same signals. This is what replays, lockstep multiplayer, and
+/// rollback netcode rely on. Compare [`LogicSnapshot::checksum`] between peers to detect
+/// a desync.
+///
+/// [`DynamicGate`]: crate::logic::dynamic::DynamicGate
+#[derive(Debug, Default)]
+pub struct LogicSnapshot {
+    /// The [`LogicTick`] of the step the snapshot was captured after.
+    pub tick: u64,
+    /// The signal of each fan and wire, sorted by [`Entity`].
+    pub signals: Vec<(Entity, Signal)>,
+    /// The reflected gate components of each gate, sorted by [`Entity`].
+    pub gates: Vec<(Entity, Vec<Box<dyn Reflect>>)>,
+}
+
+impl Clone for LogicSnapshot {
+    fn clone(&self) -> Self {
+        Self {
+            tick: self.tick,
+            signals: self.signals.clone(),
+            gates: self.gates
+                .iter()
+                .map(|(gate, components)| {
+                    (*gate, components.iter().map(|component| component.clone_value()).collect())
+                })
+                .collect(),
+        }
+    }
+}
+
+impl LogicSnapshot {
+    /// Capture the signal of every [`GateFan`] and [`Wire`] in `world`, and the
+    /// state of every gate.
+    ///
+    /// Gate state is only captured if `world` has an [`AppTypeRegistry`] and a
+    /// [`LogicGateTypes`] resource.
+    pub fn capture(world: &World) -> Self {
+        let mut signals = world
+            .iter_entities()
+            .filter(|entity| entity.contains::<GateFan>() || entity.contains::<Wire>())
+            .filter_map(|entity| Some((entity.id(), *entity.get::<Signal>()?)))
+            .collect::<Vec<_>>();
+        signals.sort_unstable_by_key(|(entity, _)| *entity);
+
+        let mut gates = Vec::new();
+        let registry = world.get_resource::<AppTypeRegistry>();
+        let types = world.get_resource::<LogicGateTypes>();
+        if let (Some(registry), Some(types)) = (registry, types) {
+            let registry = registry.read();
+            let reflect_components = types
+                .iter()
+                .filter_map(|(id, _)| world.components().get_info(id)?.type_id())
+                .filter_map(|type_id| registry.get_type_data::<ReflectComponent>(type_id))
+                .collect::<Vec<_>>();
+
+            gates = world
+                .iter_entities()
+                .filter(|entity| entity.contains::<LogicGateFans>())
+                .map(|entity| {
+                    let components = reflect_components
+                        .iter()
+                        .filter_map(|reflect_component| reflect_component.reflect(entity))
+                        .map(|component| component.clone_value())
+                        .collect::<Vec<_>>();
+                    (entity.id(), components)
+                })
+                .filter(|(_, components)| !components.is_empty())
+                .collect();
+            gates.sort_unstable_by_key(|(entity, _)| *entity);
+        }
+
+        let tick = world.get_resource::<LogicTick>().map_or(0, |tick| tick.0);
+
+        Self { tick, signals, gates }
+    }
+
+    /// Write the captured tick, signals, and gate state back into `world`.
+    ///
+    /// Signals are only written if they differ, so `Changed<Signal>` only matches the
+    /// signals the snapshot changed. Entities that no longer exist are skipped.
+    pub fn apply(&self, world: &mut World) {
+        if let Some(mut tick) = world.get_resource_mut::<LogicTick>() {
+            tick.0 = self.tick;
+        }
+        for &(entity, signal) in self.signals.iter() {
+            if let Some(mut current) = world.get_mut::<Signal>(entity) {
+                current.set_if_neq(signal);
+            }
+        }
+
+        let Some(registry) = world.get_resource::<AppTypeRegistry>().cloned() else {
+            return;
+        };
+        let registry = registry.read();
+
+        for (gate, components) in self.gates.iter() {
+            let Some(mut entity) = world.get_entity_mut(*gate) else {
+                continue;
+            };
+
+            for component in components.iter() {
+                let reflect_component = component
+                    .get_represented_type_info()
+                    .and_then(|info| registry.get_type_data::<ReflectComponent>(info.type_id()));
+                if let Some(reflect_component) = reflect_component {
+                    reflect_component.apply(&mut entity, component.as_ref());
+                }
+            }
+        }
+    }
+
+    /// Returns a checksum of the captured signals. The tick and gate state are not included.
+    ///
+    /// The checksum is stable across runs and platforms, so it can be sent between peers.
+    pub fn checksum(&self) -> u64 {
+        // 64-bit FNV-1a.
+        let mut hash = 0xcbf29ce484222325u64;
+        let mut write = |bytes: &[u8]| {
+            for &byte in bytes {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+        };
+
+        for (entity, signal) in self.signals.iter() {
+            write(&entity.to_bits().to_le_bytes());
+            match signal {
+                Signal::Undefined => write(&[0]),
+                Signal::Digital(value) => write(&[1, *value as u8]),
+                Signal::Analog(value) => {
+                    write(&[2]);
+                    write(&value.to_bits().to_le_bytes());
+                }
+            }
+        }
+
+        hash
+    }
+}
+
+/// A plugin that records a [`LogicSnapshot`] after every logic step into the
+/// [`LogicRollback`] resource, keeping the last `capacity` steps.
+///
+/// Use [`RewindLogic`] to roll the simulation back.
+///
+/// [`RewindLogic`]: crate::commands::RewindLogic
+pub struct RollbackPlugin {
+    /// The number of steps to keep.
+    pub capacity: usize,
+}
+
+impl Default for RollbackPlugin {
+    fn default() -> Self {
+        Self { capacity: 64 }
+    }
+}
+
+impl Plugin for RollbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LogicRollback::new(self.capacity)).add_systems(
+            LogicUpdate,
+            record_logic_snapshot.in_set(LogicSystemSet::PostStep)
+        );
+    }
+}
+
+/// A ring buffer of the most recent [`LogicSnapshot`]s.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct LogicRollback {
+    snapshots: VecDeque<LogicSnapshot>,
+    capacity: usize,
+}
+
+impl LogicRollback {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            snapshots: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Returns the maximum number of snapshots kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Add a snapshot, dropping the oldest one if the buffer is full.
+    pub fn push(&mut self, snapshot: LogicSnapshot) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(snapshot);
+    }
+
+    /// Returns the snapshot from `steps_ago` steps ago, where `0` is the latest.
+    pub fn get(&self, steps_ago: usize) -> Option<&LogicSnapshot> {
+        self.snapshots.len().checked_sub(steps_ago + 1).and_then(|i| self.snapshots.get(i))
+    }
+
+    /// Drop the `count` most recent snapshots.
+    pub fn truncate_latest(&mut self, count: usize) {
+        let len = self.snapshots.len().saturating_sub(count);
+        self.snapshots.truncate(len);
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+
+    /// Iterate over the snapshots, from oldest to latest.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &LogicSnapshot> {
+        self.snapshots.iter()
+    }
+}
+
+/// Record a [`LogicSnapshot`] into the [`LogicRollback`] resource.
+pub fn record_logic_snapshot(world: &mut World) {
+    let snapshot = LogicSnapshot::capture(world);
+    world.resource_mut::<LogicRollback>().push(snapshot);
+}
@@ -1,95 +1,1120 @@
-use bevy::prelude::*;
+use std::ops::Range;
+
+use bevy::{
+    ecs::{
+        entity::{ EntityHashMap, EntityHashSet },
+        system::{ Deferred, SystemParam },
+        world::EntityRef,
+    },
+    prelude::*,
+    utils::{ HashMap, Instant },
+};
 use bevy_trait_query::One;
 use crate::{
-    components::{ LogicGateFans, Wire, GateFan, GateInput, GateOutput, NoEvalOutput },
-    logic::{ signal::Signal, LogicGate },
-    resources::LogicGraph,
+    components::{
+        LogicGateFans,
+        Wire,
+        WireResistance,
+        WireFalloff,
+        WireChannel,
+        GateFan,
+        GateInput,
+        GateOutput,
+        ConnectedWires,
+        NoEvalOutput,
+        PrevSignal,
+        SignalOverride,
+        SignalForced,
+        SettleFailed,
+        GateArityViolation,
+        BudgetExceeded,
+        BudgetKind,
+        GraphCompiled,
+        CircuitId,
+        CircuitMember,
+        FeedbackLoopMember,
+        EvalPriority,
+        GateDisabled,
+        BidirectionalWire,
+        WireDisabled,
+    },
+    diagnostics::LogicStepMetrics,
+    logic::{
+        schedule::{ ClockDomain, LogicClock },
+        signal::Signal,
+        GateContext,
+        GateEffects,
+        LogicGate,
+        LogicGateTypes,
+    },
+    commands::{ DespawnGateRecursive, DespawnWire },
+    resources::{
+        CircuitBudgets,
+        EvalMode,
+        LogicBacklog,
+        LogicGraph,
+        LogicIslands,
+        LogicSettings,
+        PropagationMode,
+        SignalFalloff,
+    },
+    routing::WirePath,
 };
 
+/// Buffers reused by [`step_logic`] across gates and steps, so evaluating a gate does not
+/// allocate once the buffers have grown to the largest fan count in the graph.
+#[derive(Default)]
+pub struct StepScratch {
+    input_signals: Vec<Signal>,
+    output_entities: Vec<Entity>,
+    output_signals: Vec<Signal>,
+    visited: EntityHashSet,
+    /// The gates to evaluate during this step in dirty mode, even if their inputs did not
+    /// change.
+    pending: EntityHashSet,
+    next_pending: EntityHashSet,
+    /// Whether any signal of each island changed during this step.
+    islands_changed: Vec<bool>,
+    /// The index in [`LogicGraph::sorted`] of the next gate to visit, if the last run did not
+    /// complete its step.
+    cursor: usize,
+    /// If true, every gate is evaluated during this step, even in dirty mode.
+    evaluate_all: bool,
+    /// The input signals of each gate's last evaluation, for [`GateContext::previous_inputs`].
+    previous_inputs: EntityHashMap<Vec<Signal>>,
+    /// The feedback loop being evaluated until it settles.
+    settling: Option<Settling>,
+    /// The input signals to write once the step completes, in [`PropagationMode::Synchronous`].
+    deferred_inputs: Vec<(Entity, Signal)>,
+}
+
+/// A feedback loop that [`step_logic`] evaluates until its signals stop changing.
+struct Settling {
+    /// The indices of the loop in [`LogicGraph::sorted`].
+    span: Range<usize>,
+    /// How many more times the loop has been evaluated.
+    passes: u32,
+    /// Whether any output signal of the loop changed during this pass.
+    changed: bool,
+}
+
+/// The signal of a wire, and the components that change it on its way to the input fan.
+type WireSignal<'a> = (
+    &'a mut Signal,
+    &'a Wire,
+    Option<&'a WireResistance>,
+    Option<&'a WireFalloff>,
+    Option<&'a WireChannel>,
+    Has<WireDisabled>,
+);
+
+/// The signal of an input fan, and the channel it reads wires on.
+type InputSignal<'a> = (&'a mut Signal, Option<&'a WireChannel>);
+
+/// A gate evaluated by [`step_logic`].
+type StepGate<'a> = (
+    Ref<'a, LogicGateFans>,
+    One<&'a mut dyn LogicGate>,
+    Option<&'a ClockDomain>,
+    Option<&'a GateDisabled>,
+);
+
+/// The gates, fans, and wires [`step_logic`] reads and writes.
+#[derive(SystemParam)]
+pub struct StepQueries<'w, 's> {
+    logic_entities: Query<'w, 's, StepGate<'static>>,
+    gate_outputs: Query<'w, 's, &'static GateOutput>,
+    input_fans: Query<'w, 's, (&'static Parent, Option<&'static WireChannel>), With<GateInput>>,
+    gate_fans: Query<'w, 's, &'static mut Signal, With<GateFan>>,
+    wires: Query<'w, 's, WireSignal<'static>, Without<GateFan>>,
+    overrides: Query<'w, 's, &'static SignalOverride, With<GateFan>>,
+}
+
+/// The resources [`step_logic`] is configured by and reports to, if they exist.
+#[derive(SystemParam)]
+pub struct StepResources<'w> {
+    settings: Option<Res<'w, LogicSettings>>,
+    metrics: Option<ResMut<'w, LogicStepMetrics>>,
+    islands: Option<ResMut<'w, LogicIslands>>,
+    backlog: Option<ResMut<'w, LogicBacklog>>,
+}
+
+/// The events sent by [`step_logic`], if their `Events` resource exists.
+#[derive(SystemParam)]
+pub struct StepEvents<'w> {
+    forced: Option<ResMut<'w, Events<SignalForced>>>,
+    settle_failed: Option<ResMut<'w, Events<SettleFailed>>>,
+}
+
 /// A system that evaluates the [`LogicGraph`] resource and updates all entities in a single step.
 ///
-/// This propagates signals through [`Signal`] and [`Wire`] components.
+/// This propagates signals through [`Signal`] and [`Wire`] components. Signals are only written
+/// when their value changes, so `Changed<Signal>` can be used to react to them.
+///
+/// Evaluation is deterministic: gates are evaluated in [`LogicGraph::sorted`] order, and the
+/// wires of each output are written in [`Entity`] order, so the same circuit always produces
+/// the same signals.
+///
+/// Which gates are evaluated, and how missing entities are handled, is configured by the
+/// [`LogicSettings`] resource.
+///
+/// In [`EvalMode::Islands`], the [`LogicIslands`] resource is rebuilt when the graph changes,
+/// and the awake state of each island is updated. Without the resource, every gate is evaluated.
+///
+/// If the [`EvalBudget`] runs out, the step continues from the next gate during the next run,
+/// and the [`LogicBacklog`] resource reports how many gates are left. If the graph changes
+/// before the step completes, the step starts over.
+///
+/// [`EvalBudget`]: crate::resources::EvalBudget
+///
+/// Gates are evaluated with [`LogicGate::evaluate_with_context`], with the tick and timestep
+/// from the [`LogicClock`]. The [`GateEffects`] they queue are applied after the system runs.
+///
+/// Fans with a [`SignalOverride`] are written with the override instead, and a
+/// [`SignalForced`] event is sent if the `Events<SignalForced>` resource exists.
+///
+/// Each feedback loop is evaluated up to [`LogicSettings::settle_limit`] more times, until
+/// none of its output signals change, before moving on to the gates it drives. Loops that do
+/// not settle send a [`SettleFailed`] event.
+///
+/// Gates with a [`ClockDomain`] are only evaluated on the ticks of their domain.
+///
+/// In [`PropagationMode::Synchronous`], input signals are written once the step completes
+/// instead, so every gate reads the signals of the previous step.
+///
+/// If the [`LogicStepMetrics`] resource exists, it is updated with measurements from this step.
 pub fn step_logic(
     logic_graph: Res<LogicGraph>,
-    mut logic_entities: Query<(&LogicGateFans, One<&mut dyn LogicGate>)>,
-    gate_outputs: Query<&GateOutput>,
-    mut gate_fans: Query<&mut Signal, With<GateFan>>,
-    mut wires: Query<(&mut Signal, &Wire), Without<GateFan>>
+    resources: StepResources,
+    queries: StepQueries,
+    mut events: StepEvents,
+    mut scratch: Local<StepScratch>,
+    clock: LogicClock,
+    effects: Deferred<GateEffects>
 ) {
+    let StepResources { settings, mut metrics, islands, mut backlog } = resources;
+    let StepQueries {
+        mut logic_entities,
+        gate_outputs,
+        input_fans,
+        mut gate_fans,
+        mut wires,
+        overrides,
+    } = queries;
+    let settings = settings.map(|settings| *settings).unwrap_or_default();
     let sorted = logic_graph.sorted();
+    let run_start = Instant::now();
+    let has_overrides = !overrides.is_empty();
+
+    let StepScratch {
+        input_signals,
+        output_entities,
+        output_signals,
+        visited,
+        pending,
+        next_pending,
+        islands_changed,
+        cursor,
+        evaluate_all,
+        previous_inputs,
+        settling,
+        deferred_inputs,
+    } = &mut *scratch;
+    let synchronous = settings.propagation == PropagationMode::Synchronous;
+    let (tick, timestep) = (clock.tick(), clock.timestep());
+
+    // In islands mode, whole islands are skipped while they are asleep.
+    let mut islands = islands.filter(|_| settings.eval_mode == EvalMode::Islands);
+    let rebuild_islands = islands
+        .as_ref()
+        .is_some_and(|islands| {
+            logic_graph.is_changed() || islands.gate_count() != sorted.len()
+        });
+
+    // Continue the step the last run did not complete, unless the graph changed since.
+    if logic_graph.is_changed() || rebuild_islands || *cursor >= sorted.len() {
+        *cursor = 0;
+        *settling = None;
+        deferred_inputs.clear();
+    }
+    let step_runs = backlog.as_ref().map_or(0, |backlog| backlog.runs);
+    if *cursor == 0 {
+        // In dirty mode, gates are skipped unless something could have changed their outputs.
+        *evaluate_all = settings.eval_mode != EvalMode::Dirty || logic_graph.is_changed();
+        visited.clear();
+        next_pending.clear();
+        if logic_graph.is_changed() {
+            previous_inputs.retain(|gate, _| logic_graph.graph.contains_node(*gate));
+        }
+
+        if let Some(islands) = islands.as_mut() {
+            if rebuild_islands {
+                islands.rebuild(&logic_graph);
+            }
+            islands_changed.clear();
+            islands_changed.resize(islands.len(), false);
+        }
+    }
+    let step_runs = if *cursor == 0 { 1 } else { step_runs + 1 };
+    let mut gates_evaluated = 0;
+
+    let step_start = metrics.as_mut().map(|metrics| {
+        metrics.clear();
+        Instant::now()
+    });
+    let record_gate_types = metrics.as_ref().is_some_and(|metrics| metrics.record_gate_types);
+
+    loop {
+        // Evaluate the feedback loop that was just evaluated again, until it settles.
+        if let Some(settle) = settling.as_mut().filter(|settle| *cursor == settle.span.end) {
+            if settle.changed && settle.passes < settings.settle_limit {
+                settle.passes += 1;
+                settle.changed = false;
+                *cursor = settle.span.start;
+            } else {
+                if settle.changed {
+                    if let Some(events) = events.settle_failed.as_mut() {
+                        events.send(SettleFailed {
+                            tick,
+                            gates: sorted[settle.span.clone()].to_vec(),
+                        });
+                    }
+                }
+                *settling = None;
+            }
+        }
+
+        let index = *cursor;
+        let Some(&entity) = sorted.get(index) else {
+            break;
+        };
+        if gates_evaluated > 0 && settings.eval_budget.is_exhausted(gates_evaluated, run_start) {
+            break;
+        }
+        *cursor = index + 1;
+        visited.insert(entity);
+
+        if settling.is_none() && settings.settle_limit > 0 && !synchronous {
+            *settling = logic_graph.cycle_starting_at(index).map(|span| Settling {
+                span,
+                passes: 0,
+                changed: false,
+            });
+        }
 
-    for &entity in sorted.iter() {
         // Get the GATE.
-        let (fans, mut gate) = logic_entities
-            .get_mut(entity)
-            .expect("Entity does not exist or does not have a LogicGateFans or dyn LogicGate");
+        let Ok((fans, mut gate, domain, disabled)) = logic_entities.get_mut(entity) else {
+            settings.panic_policy.handle(||
+                format!("Entity {entity} does not exist or does not have a LogicGateFans or dyn LogicGate")
+            );
+            continue;
+        };
+
+        let island = islands.as_ref().and_then(|islands| islands.island_of(entity));
+
+        // Gates in a clock domain that does not step this tick hold their outputs. Keep them
+        // from being skipped when it does.
+        if !clock.domain_steps(domain.copied().unwrap_or_default()) {
+            if let Some(changed) = island.and_then(|island| islands_changed.get_mut(island)) {
+                *changed = true;
+            }
+            if settings.eval_mode == EvalMode::Dirty {
+                next_pending.insert(entity);
+            }
+            continue;
+        }
+
+        // Disabled gates that hold their outputs are not evaluated at all.
+        if disabled == Some(&GateDisabled::Hold) {
+            continue;
+        }
+
+        if let Some(island) = island.and_then(|island| islands.as_mut()?.get_mut(island)) {
+            if !island.awake {
+                let woken =
+                    fans.is_changed() ||
+                    gate.is_changed() ||
+                    fans.inputs
+                        .iter()
+                        .chain(fans.outputs.iter())
+                        .flatten()
+                        .any(|&fan| gate_fans.get_mut(fan).is_ok_and(|signal| signal.is_changed()));
+
+                if !woken {
+                    continue;
+                }
+                island.awake = true;
+            }
+        } else if !*evaluate_all && !fans.is_changed() && !pending.contains(&entity) {
+            let inputs_changed = fans.inputs
+                .iter()
+                .flatten()
+                .any(|&input| gate_fans.get_mut(input).is_ok_and(|signal| signal.is_changed()));
+
+            if !inputs_changed {
+                continue;
+            }
+        }
+        gates_evaluated += 1;
 
         // Collect its fan input signals.
-        let input_signals = fans.inputs
-            .iter()
-            .filter_map(|&input| {
-                let input = input?;
-                let signal = gate_fans.get(input).ok().copied();
-                signal
-            })
-            .collect::<Vec<_>>();
+        input_signals.clear();
+        input_signals.extend(
+            fans.inputs
+                .iter()
+                .filter_map(|&input| gate_fans.get(input?).ok().copied())
+        );
 
-        // Collect its fan outputs entities, and create an empty signals vec matching the number of outputs.
-        let (output_entities, mut output_signals): (Vec<_>, Vec<_>) = fans.outputs
-            .iter()
-            .filter_map(|&output| {
-                let output = output?;
-                let signal = gate_fans.get(output).ok().copied()?;
-                Some((output, signal))
-            })
-            .unzip();
+        // Collect its fan output entities, and their current signals.
+        output_entities.clear();
+        output_signals.clear();
+        for output in fans.outputs.iter().flatten() {
+            if let Ok(signal) = gate_fans.get(*output) {
+                output_entities.push(*output);
+                output_signals.push(*signal);
+            }
+        }
+
+        // Evaluate the gate, or turn its outputs off if it is disabled.
+        let context = GateContext {
+            entity,
+            tick,
+            timestep,
+            previous_inputs: previous_inputs.get(&entity).map_or(&[], Vec::as_slice),
+            effects: &effects,
+        };
+        if disabled.is_some() {
+            output_signals.fill(Signal::OFF);
+        } else if record_gate_types {
+            let gate_start = Instant::now();
+            gate.evaluate_with_context(&context, input_signals, output_signals);
+            let elapsed = gate_start.elapsed();
 
-        // Evaluate the gate.
-        gate.evaluate(&input_signals, &mut output_signals);
+            if let Some(metrics) = metrics.as_mut() {
+                *metrics.gate_type_times.entry(gate.gate_type_name()).or_default() += elapsed;
+            }
+        } else {
+            gate.evaluate_with_context(&context, input_signals, output_signals);
+        }
+
+        let previous = previous_inputs.entry(entity).or_default();
+        previous.clear();
+        previous.extend_from_slice(input_signals);
+
+        if has_overrides {
+            for (&output, signal) in output_entities.iter().zip(output_signals.iter_mut()) {
+                if let Ok(forced) = overrides.get(output) {
+                    force_signal(output, signal, forced.0, &mut events.forced);
+                }
+            }
+        }
 
         // Update the output signals.
-        for (entity, signal) in output_entities.iter().zip(output_signals) {
+        for (entity, &signal) in output_entities.iter().zip(output_signals.iter()) {
             if let Ok(mut output_signal) = gate_fans.get_mut(*entity) {
-                *output_signal = signal;
+                if output_signal.set_if_neq(signal) {
+                    if let Some(island) = island {
+                        islands_changed[island] = true;
+                    }
+                    if let Some(settle) = settling.as_mut() {
+                        settle.changed = true;
+                    }
+                    if let Some(metrics) = metrics.as_mut() {
+                        metrics.signal_changes += 1;
+                    }
+                }
             }
 
             // Grab the out-going wires from this output.
-            let out_going_wires = &gate_outputs
-                .get(*entity)
-                .expect("GateOutput does not exist").wires;
+            let Ok(output) = gate_outputs.get(*entity) else {
+                settings.panic_policy.handle(|| format!("GateOutput {entity} does not exist"));
+                continue;
+            };
 
-            // Update the wire signals.
-            for entity in out_going_wires.iter() {
-                let (mut wire_signal, wire) = wires.get_mut(*entity).expect("Wire does not exist");
-                *wire_signal = signal;
+            // Update the wire signals in entity order, so an input with several wires
+            // resolves to the same signal every run.
+            for entity in output.wires.iter() {
+                let Ok((mut wire_signal, wire, resistance, falloff, channel, disabled)) =
+                    wires.get_mut(*entity) else {
+                    settings.panic_policy.handle(|| format!("Wire {entity} does not exist"));
+                    continue;
+                };
+                let signal = if disabled { Signal::Undefined } else { signal };
+                let signal = resistance.map_or(signal, |resistance| resistance.apply(signal));
+                let mut signal = falloff.map_or(signal, |falloff| falloff.apply(signal));
+                wire_signal.set_if_neq(signal);
+                if let Some(metrics) = metrics.as_mut() {
+                    metrics.wires_propagated += 1;
+                }
 
-                if let Ok(mut input_signal) = gate_fans.get_mut(wire.to) {
-                    *input_signal = signal;
+                let input = input_fans.get(wire.to).ok();
+                if !WireChannel::connects(channel, input.and_then(|(_, channel)| channel)) {
+                    continue;
+                }
+
+                if has_overrides {
+                    if let Ok(forced) = overrides.get(wire.to) {
+                        force_signal(wire.to, &mut signal, forced.0, &mut events.forced);
+                    }
+                }
+                if synchronous {
+                    deferred_inputs.push((wire.to, signal));
+                    continue;
+                }
+                let Ok(mut input_signal) = gate_fans.get_mut(wire.to) else {
+                    continue;
+                };
+                if !input_signal.set_if_neq(signal) {
+                    continue;
+                }
+                if let Some(island) = island {
+                    islands_changed[island] = true;
+                }
+
+                // A gate that was already visited this step will not see the new signal
+                // until the next step, so remember to evaluate it then. Other gates are
+                // evaluated later in this step, even if it continues in a later run.
+                if settings.eval_mode == EvalMode::Dirty {
+                    if let Some((parent, _)) = input {
+                        if visited.contains(&parent.get()) {
+                            next_pending.insert(parent.get());
+                        } else {
+                            pending.insert(parent.get());
+                        }
+                    }
                 }
             }
         }
     }
+
+    let remaining = sorted.len() - *cursor;
+    if let Some(backlog) = backlog.as_mut() {
+        backlog.remaining = remaining;
+        backlog.runs = step_runs;
+    }
+
+    if remaining == 0 {
+        *cursor = 0;
+
+        // Gates read the signals of this step during the next one.
+        for (input, signal) in deferred_inputs.drain(..) {
+            let Ok(mut input_signal) = gate_fans.get_mut(input) else {
+                continue;
+            };
+            if !input_signal.set_if_neq(signal) {
+                continue;
+            }
+            let Ok((parent, _)) = input_fans.get(input) else {
+                continue;
+            };
+            let island = islands.as_ref().and_then(|islands| islands.island_of(parent.get()));
+            if let Some(changed) = island.and_then(|island| islands_changed.get_mut(island)) {
+                *changed = true;
+            }
+            if settings.eval_mode == EvalMode::Dirty {
+                next_pending.insert(parent.get());
+            }
+        }
+
+        std::mem::swap(pending, next_pending);
+
+        // Islands whose signals did not change would produce the same signals next step.
+        if let Some(islands) = islands.as_mut() {
+            for (index, &changed) in islands_changed.iter().enumerate() {
+                if let Some(island) = islands.get_mut(index) {
+                    island.awake = changed;
+                }
+            }
+        }
+    }
+
+    if let (Some(metrics), Some(step_start)) = (metrics.as_mut(), step_start) {
+        metrics.gates_evaluated = gates_evaluated;
+        metrics.evaluation_time = step_start.elapsed();
+    }
 }
 
+/// Replace `signal` with `forced`, and send a [`SignalForced`] event if they differ.
+fn force_signal(
+    fan: Entity,
+    signal: &mut Signal,
+    forced: Signal,
+    events: &mut Option<ResMut<Events<SignalForced>>>
+) {
+    if *signal == forced {
+        return;
+    }
+    if let Some(events) = events.as_mut() {
+        events.send(SignalForced {
+            fan,
+            signal: *signal,
+            forced,
+        });
+    }
+    *signal = forced;
+}
+
+/// Write each [`SignalOverride`] to its fan, so it applies before the next step even if no
+/// gate or wire writes the fan.
+pub fn apply_signal_overrides(
+    mut query: Query<(&SignalOverride, &mut Signal), With<GateFan>>
+) {
+    for (forced, mut signal) in query.iter_mut() {
+        signal.set_if_neq(forced.0);
+    }
+}
+
+/// A [`BidirectionalWire`], and the signal written to it.
+type BusWire<'a> = (&'a Wire, Option<&'a WireChannel>, Has<WireDisabled>, &'a mut Signal);
+
+/// A filter for [`BidirectionalWire`]s, which are not fans.
+type BusWireFilter = (With<BidirectionalWire>, Without<GateInput>, Without<GateOutput>);
+
+/// A filter for input fans, which are not wires.
+type BusInputFilter = (With<GateInput>, Without<GateOutput>, Without<Wire>);
+
+/// Resolve each bus of fans connected by [`BidirectionalWire`]s on the same [`WireChannel`] to
+/// the strongest signal of its output fans, as in [`Signal::max_abs`], and write it to every
+/// input fan and wire of the bus.
+///
+/// A bus without outputs, or whose outputs are all undefined, is [`Signal::Undefined`]. An
+/// input fan on several buses receives the strongest of their signals, from the buses on its
+/// own channel if it has one.
+pub fn resolve_buses(
+    mut query_wires: Query<BusWire, BusWireFilter>,
+    query_outputs: Query<&Signal, (With<GateOutput>, Without<Wire>)>,
+    mut query_inputs: Query<InputSignal, BusInputFilter>
+) {
+    // Each fan is on a separate bus for every channel it has wires on.
+    // Fans only connected by disabled wires are on a bus of their own.
+    let mut neighbors = HashMap::<(Entity, WireChannel), Vec<Entity>>::default();
+    for (wire, channel, disabled, _) in query_wires.iter() {
+        let channel = channel.copied().unwrap_or_default();
+        let from = neighbors.entry((wire.from, channel)).or_default();
+        if !disabled {
+            from.push(wire.to);
+        }
+        let to = neighbors.entry((wire.to, channel)).or_default();
+        if !disabled {
+            to.push(wire.from);
+        }
+    }
+    if neighbors.is_empty() {
+        return;
+    }
+
+    // Fans are visited in order, so ties between outputs resolve the same way each step.
+    let mut nodes = neighbors.keys().copied().collect::<Vec<_>>();
+    nodes.sort_unstable();
+
+    let mut buses = HashMap::<(Entity, WireChannel), Signal>::default();
+    let mut bus = Vec::new();
+    for &(fan, channel) in nodes.iter() {
+        if buses.contains_key(&(fan, channel)) {
+            continue;
+        }
+        bus.clear();
+        bus.push(fan);
+        buses.insert((fan, channel), Signal::Undefined);
+        let mut index = 0;
+        while let Some(&next) = bus.get(index) {
+            for &neighbor in neighbors[&(next, channel)].iter() {
+                if !buses.contains_key(&(neighbor, channel)) {
+                    buses.insert((neighbor, channel), Signal::Undefined);
+                    bus.push(neighbor);
+                }
+            }
+            index += 1;
+        }
+        bus.sort_unstable();
+
+        let signal = bus
+            .iter()
+            .filter_map(|&fan| query_outputs.get(fan).ok())
+            .fold(Signal::Undefined, |acc, &signal| acc.max_abs(signal));
+        for &fan in bus.iter() {
+            buses.insert((fan, channel), signal);
+        }
+    }
+
+    let mut inputs = EntityHashMap::<Signal>::default();
+    for &(fan, channel) in nodes.iter() {
+        let Ok((_, input_channel)) = query_inputs.get(fan) else {
+            continue;
+        };
+        if WireChannel::connects(Some(&channel), input_channel) {
+            let signal = inputs.entry(fan).or_insert(Signal::Undefined);
+            *signal = signal.max_abs(buses[&(fan, channel)]);
+        }
+    }
+    for (fan, signal) in inputs {
+        if let Ok((mut input, _)) = query_inputs.get_mut(fan) {
+            input.set_if_neq(signal);
+        }
+    }
+
+    for (wire, channel, disabled, mut signal) in query_wires.iter_mut() {
+        if disabled {
+            signal.set_if_neq(Signal::Undefined);
+            continue;
+        }
+        let channel = channel.copied().unwrap_or_default();
+        if let Some(&bus_signal) = buses.get(&(wire.from, channel)) {
+            signal.set_if_neq(bus_signal);
+        }
+    }
+}
+
+/// A filter for fans without a [`SignalOverride`].
+type ReleasedFan = (With<GateFan>, Without<SignalOverride>);
+
+/// Restore the signals of fans whose [`SignalOverride`] was removed.
+///
+/// The gate of an output fan, and the gates driving an input fan, are marked as changed so
+/// [`step_logic`] evaluates them again. An input fan without wires becomes
+/// [`Signal::Undefined`].
+pub fn release_signal_overrides(
+    mut removed: RemovedComponents<SignalOverride>,
+    query_fans: Query<(&Parent, Option<&ConnectedWires>), ReleasedFan>,
+    query_wires: Query<&Wire>,
+    query_parents: Query<&Parent, With<GateOutput>>,
+    mut query_gates: Query<&mut LogicGateFans>,
+    mut query_signals: Query<&mut Signal, With<GateInput>>
+) {
+    for fan in removed.read() {
+        let Ok((parent, connected_wires)) = query_fans.get(fan) else {
+            continue;
+        };
+        let Some(connected_wires) = connected_wires else {
+            if let Ok(mut gate) = query_gates.get_mut(parent.get()) {
+                gate.set_changed();
+            }
+            continue;
+        };
+
+        if connected_wires.wires.is_empty() {
+            if let Ok(mut signal) = query_signals.get_mut(fan) {
+                signal.set_if_neq(Signal::Undefined);
+            }
+        }
+        for wire in connected_wires.wires.iter() {
+            let Ok(wire) = query_wires.get(*wire) else {
+                continue;
+            };
+            if let Ok(mut gate) = query_parents.get(wire.from).and_then(|parent| {
+                query_gates.get_mut(parent.get())
+            }) {
+                gate.set_changed();
+            }
+        }
+    }
+}
+
+/// A filter for entities whose [`PrevSignal`] may differ from their [`Signal`].
+type PrevSignalStale = Or<(Changed<Signal>, Added<PrevSignal>)>;
+
+/// Copy the current [`Signal`] of every entity with a [`PrevSignal`] into it, before the
+/// next step changes any signals.
+pub fn record_prev_signals(mut query: Query<(&Signal, &mut PrevSignal), PrevSignalStale>) {
+    for (signal, mut prev_signal) in query.iter_mut() {
+        prev_signal.set_if_neq(PrevSignal(*signal));
+    }
+}
+
+/// A filter for output fans with a [`NoEvalOutput`] whose [`Signal`] changed.
+type NoEvalOutputChanged = (Changed<Signal>, With<NoEvalOutput>, Without<GateInput>);
+
 /// Immediately propagate signals through wires for all [`GateOutput`]s with a [`Signal`] and [`NoEvalOutput`].
 pub fn no_eval_output(
-    query_outputs: Query<
-        (&GateOutput, &Signal),
-        (Changed<Signal>, With<NoEvalOutput>, Without<GateInput>)
-    >,
-    mut query_wires: Query<(&mut Signal, &Wire), (Without<GateInput>, Without<GateOutput>)>,
-    mut query_inputs: Query<&mut Signal, (With<GateInput>, Without<GateOutput>)>
+    query_outputs: Query<(&GateOutput, &Signal), NoEvalOutputChanged>,
+    mut query_wires: Query<WireSignal, (Without<GateInput>, Without<GateOutput>)>,
+    mut query_inputs: Query<InputSignal, (With<GateInput>, Without<GateOutput>)>,
+    settings: Option<Res<LogicSettings>>
 ) {
+    let panic_policy = settings.map(|settings| settings.panic_policy).unwrap_or_default();
+
     for (outputs, &signal) in query_outputs.iter() {
         outputs.wires.iter().for_each(|&wire_entity| {
-            let (mut wire_signal, wire) = query_wires
-                .get_mut(wire_entity)
-                .expect("GateOutput stored an entity without a WireBundle");
+            let Ok((mut wire_signal, wire, resistance, falloff, channel, disabled)) =
+                query_wires.get_mut(wire_entity) else {
+                panic_policy.handle(||
+                    format!("GateOutput stored an entity without a WireBundle: {wire_entity}")
+                );
+                return;
+            };
+            let signal = if disabled { Signal::Undefined } else { signal };
+            let signal = resistance.map_or(signal, |resistance| resistance.apply(signal));
+            let signal = falloff.map_or(signal, |falloff| falloff.apply(signal));
             wire_signal.replace(signal);
 
-            if let Ok(mut input_signal) = query_inputs.get_mut(wire.to) {
-                input_signal.replace(signal);
+            if let Ok((mut input_signal, input_channel)) = query_inputs.get_mut(wire.to) {
+                if WireChannel::connects(channel, input_channel) {
+                    input_signal.replace(signal);
+                }
             }
         });
     }
 }
+
+/// A wire, what its length is measured from, and its [`WireFalloff`] if it has one.
+type FalloffWire<'a> = (Entity, Ref<'a, Wire>, Option<Ref<'a, WirePath>>, Option<&'a WireFalloff>);
+
+/// Give every wire the [`WireFalloff`] of its length while the [`SignalFalloff`] resource
+/// exists, and remove them once it is removed.
+///
+/// A wire's length is the length of its [`WirePath`], or the distance between its fans if it
+/// has none. It is measured again when the resource, the wire, or its path changes, or when
+/// either fan moves.
+pub fn update_wire_falloff(
+    mut commands: Commands,
+    falloff: Option<Res<SignalFalloff>>,
+    query_wires: Query<FalloffWire, Without<BidirectionalWire>>,
+    query_fans: Query<Ref<GlobalTransform>, With<GateFan>>
+) {
+    let Some(falloff) = falloff else {
+        for (entity, _, _, wire_falloff) in query_wires.iter() {
+            if wire_falloff.is_some() {
+                commands.entity(entity).remove::<WireFalloff>();
+            }
+        }
+        return;
+    };
+
+    for (entity, wire, path, wire_falloff) in query_wires.iter() {
+        let from = query_fans.get(wire.from).ok();
+        let to = query_fans.get(wire.to).ok();
+        let moved =
+            from.as_ref().is_some_and(DetectChanges::is_changed) ||
+            to.as_ref().is_some_and(DetectChanges::is_changed) ||
+            path.as_ref().is_some_and(DetectChanges::is_changed);
+        if !falloff.is_changed() && !wire.is_changed() && !moved && wire_falloff.is_some() {
+            continue;
+        }
+
+        let length = match (path, from, to) {
+            (Some(path), _, _) => path.length(),
+            (None, Some(from), Some(to)) => from.translation().distance(to.translation()),
+            _ => 0.0,
+        };
+        let new_falloff = falloff.wire(length);
+        if wire_falloff != Some(&new_falloff) {
+            commands.entity(entity).insert(new_falloff);
+        }
+    }
+}
+
+/// A filter for new wires that are added to the [`LogicGraph`].
+type AddedGraphWire = (Added<Wire>, Without<BidirectionalWire>);
+
+/// The gates and wires that were added to or removed from the world, for [`sync_logic_graph`].
+#[derive(SystemParam)]
+pub struct LogicGraphChanges<'w, 's> {
+    added_gates: Query<'w, 's, Entity, Added<LogicGateFans>>,
+    added_wires: Query<'w, 's, (Entity, &'static Wire), AddedGraphWire>,
+    removed_gates: RemovedComponents<'w, 's, LogicGateFans>,
+    removed_wires: RemovedComponents<'w, 's, Wire>,
+}
+
+/// The wire sets of a fan.
+type FanWireSets<'a> = (Option<&'a mut GateOutput>, Option<&'a mut ConnectedWires>);
+
+/// Keep the [`LogicGraph`] in sync with the world, without explicit calls to
+/// [`LogicGraph::add_data`] or [`LogicGraph::compile`].
+///
+/// Gates are added when they receive [`LogicGateFans`], and wires are added when they receive a
+/// [`Wire`], unless they are a [`BidirectionalWire`]. Gates and wires are removed when those
+/// components are removed or despawned. All changes made during a frame are batched into a
+/// single recompile.
+///
+/// This system is added by the [`LogicSimulationPlugin`] when `auto_sync_graph` is enabled.
+///
+/// [`LogicSimulationPlugin`]: crate::LogicSimulationPlugin
+pub fn sync_logic_graph(
+    mut logic_graph: ResMut<LogicGraph>,
+    changes: LogicGraphChanges,
+    query_fans: Query<&LogicGateFans>,
+    query_wires: Query<&Wire>,
+    query_parents: Query<&Parent, With<GateFan>>,
+    mut query_fan_wires: Query<FanWireSets, With<GateFan>>
+) {
+    let LogicGraphChanges { added_gates, added_wires, mut removed_gates, mut removed_wires } =
+        changes;
+    let removed_gates = removed_gates.read().collect::<EntityHashSet>();
+    let removed_wires = removed_wires.read().collect::<EntityHashSet>();
+
+    if
+        added_gates.is_empty() &&
+        added_wires.is_empty() &&
+        removed_gates.is_empty() &&
+        removed_wires.is_empty()
+    {
+        return;
+    }
+
+    // Update the wire sets of a fan, without triggering change detection if nothing changed.
+    let mut set_fan_wire = |fan: Entity, wire_entity: Entity, connected: bool| {
+        let Ok((output, input)) = query_fan_wires.get_mut(fan) else {
+            return;
+        };
+        let wires = output
+            .map(|output| output.map_unchanged(|output| &mut output.wires))
+            .or_else(|| input.map(|input| input.map_unchanged(|input| &mut input.wires)));
+
+        if let Some(mut wires) = wires {
+            if wires.contains(&wire_entity) != connected {
+                if connected {
+                    wires.insert(wire_entity);
+                } else {
+                    wires.remove(&wire_entity);
+                }
+            }
+        }
+    };
+
+    // Removed wires no longer have a `Wire` component, so find them by their edge in the graph.
+    let removed_edges = logic_graph.graph
+        .all_edges()
+        .filter(|(_, _, wire_entity)| removed_wires.contains(*wire_entity))
+        .map(|(from_gate, to_gate, &wire_entity)| (from_gate, to_gate, wire_entity))
+        .collect::<Vec<_>>();
+
+    for (from_gate, to_gate, wire_entity) in removed_edges {
+        logic_graph.remove_wire(from_gate, to_gate);
+
+        let from_fans = query_fans.get(from_gate).map(|fans| fans.some_outputs());
+        let to_fans = query_fans.get(to_gate).map(|fans| fans.some_inputs());
+        for fan in from_fans.into_iter().chain(to_fans).flatten() {
+            set_fan_wire(fan, wire_entity, false);
+        }
+    }
+
+    for &gate in removed_gates.iter() {
+        let connected_wires = logic_graph
+            .iter_all_wires(gate)
+            .map(|(wire_entity, _)| wire_entity)
+            .collect::<Vec<_>>();
+
+        // Wires connected to the removed gate may outlive it, so stop propagating through them.
+        for wire_entity in connected_wires {
+            if let Ok(wire) = query_wires.get(wire_entity) {
+                set_fan_wire(wire.from, wire_entity, false);
+                set_fan_wire(wire.to, wire_entity, false);
+            }
+        }
+
+        logic_graph.remove_gate(gate);
+    }
+
+    for gate in added_gates.iter() {
+        logic_graph.add_gate(gate);
+    }
+
+    for (wire_entity, wire) in added_wires.iter() {
+        let (Ok(from_gate), Ok(to_gate)) = (
+            query_parents.get(wire.from),
+            query_parents.get(wire.to),
+        ) else {
+            continue;
+        };
+
+        set_fan_wire(wire.from, wire_entity, true);
+        set_fan_wire(wire.to, wire_entity, true);
+
+        logic_graph.add_wire(from_gate.get(), to_gate.get(), wire_entity);
+    }
+
+    logic_graph.compile();
+}
+
+/// Rebuild the [`LogicGraph`] with [`RebuildLogicGraph`] when a scene instance is ready, so
+/// circuits spawned from scenes are simulated.
+///
+/// [`RebuildLogicGraph`]: crate::commands::RebuildLogicGraph
+#[cfg(feature = "scene")]
+pub fn rebuild_graph_on_scene_ready(
+    mut commands: Commands,
+    mut events: EventReader<bevy::scene::SceneInstanceReady>
+) {
+    if events.read().count() > 0 {
+        commands.add(crate::commands::RebuildLogicGraph);
+    }
+}
+
+/// Swap the order computed by [`LogicGraph::compile_async`] into the graph once it is ready,
+/// and send a [`GraphCompiled`] event.
+///
+/// The graph is only marked as changed when the new order is swapped in. An order computed
+/// from a graph that changed since is discarded, and computed again.
+pub fn poll_graph_compilation(
+    mut logic_graph: ResMut<LogicGraph>,
+    mut events: EventWriter<GraphCompiled>
+) {
+    if !logic_graph.is_compiling() {
+        return;
+    }
+    let Some((order, duration)) = logic_graph.bypass_change_detection().poll_pending() else {
+        return;
+    };
+
+    logic_graph.apply_order(order);
+    events.send(GraphCompiled {
+        gates: logic_graph.sorted().len(),
+        duration,
+    });
+}
+
+/// Copy inserted, changed, and removed [`EvalPriority`] components to the [`LogicGraph`], and
+/// recompile it if any priority changed.
+pub fn sync_eval_priorities(
+    mut logic_graph: ResMut<LogicGraph>,
+    query_priorities: Query<(Entity, &EvalPriority), Changed<EvalPriority>>,
+    mut removed: RemovedComponents<EvalPriority>
+) {
+    let mut changed = false;
+    for entity in removed.read() {
+        if logic_graph.priority(entity) != 0 && !query_priorities.contains(entity) {
+            logic_graph.set_priority(entity, 0);
+            changed = true;
+        }
+    }
+    for (entity, priority) in query_priorities.iter() {
+        if logic_graph.priority(entity) != priority.0 {
+            logic_graph.set_priority(entity, priority.0);
+            changed = true;
+        }
+    }
+
+    if changed {
+        logic_graph.compile();
+    }
+}
+
+/// Insert a [`FeedbackLoopMember`] on every gate in a feedback loop of the [`LogicGraph`],
+/// and remove it from gates that no longer are, whenever the graph changes.
+pub fn tag_feedback_loops(
+    mut commands: Commands,
+    logic_graph: Res<LogicGraph>,
+    query_members: Query<(Entity, &FeedbackLoopMember)>
+) {
+    if !logic_graph.is_changed() {
+        return;
+    }
+
+    let loop_of = logic_graph
+        .find_cycles()
+        .iter()
+        .enumerate()
+        .flat_map(|(i, cycle)| cycle.iter().map(move |&gate| (gate, i)))
+        .collect::<EntityHashMap<_>>();
+
+    for (entity, member) in query_members.iter() {
+        if !loop_of.contains_key(&entity) {
+            commands.entity(entity).remove::<FeedbackLoopMember>();
+        } else if loop_of[&entity] != member.0 {
+            commands.entity(entity).insert(FeedbackLoopMember(loop_of[&entity]));
+        }
+    }
+    for (&gate, &i) in loop_of.iter() {
+        if !query_members.contains(gate) {
+            if let Some(mut gate) = commands.get_entity(gate) {
+                gate.insert(FeedbackLoopMember(i));
+            }
+        }
+    }
+}
+
+/// Warn about each gate whose fans changed to a number its declared [`GateArity`] does not
+/// accept, and send a [`GateArityViolation`] event for it.
+///
+/// Gates without a declared arity, or without the [`LogicGateTypes`] resource, are not checked.
+///
+/// [`GateArity`]: crate::logic::GateArity
+pub fn validate_gate_arity(
+    mut commands: Commands,
+    gate_types: Option<Res<LogicGateTypes>>,
+    query_gates: Query<(EntityRef, &LogicGateFans), Changed<LogicGateFans>>
+) {
+    let Some(gate_types) = gate_types else {
+        return;
+    };
+
+    for (entity, fans) in query_gates.iter() {
+        let Some((gate_type, arity)) = gate_types.arity_of(entity) else {
+            continue;
+        };
+        let (inputs, outputs) = (fans.inputs.len(), fans.outputs.len());
+        if arity.accepts(inputs, outputs) {
+            continue;
+        }
+
+        warn!(
+            "{gate_type} {:?} has {inputs} inputs and {outputs} outputs, but expects {arity}",
+            entity.id()
+        );
+        // `EntityRef` reads every resource, so events are sent once the system is done.
+        let violation = GateArityViolation {
+            gate: entity.id(),
+            gate_type: gate_type.to_string(),
+            inputs,
+            outputs,
+            arity,
+        };
+        commands.add(move |world: &mut World| {
+            world.send_event(violation);
+        });
+    }
+}
+
+/// Despawn the gates and wires that joined a circuit past its budget in the
+/// [`CircuitBudgets`] resource, and send a [`BudgetExceeded`] event for each of them.
+///
+/// New members are admitted in [`Entity`] order, so the newest ones are rejected. Gates are
+/// despawned with [`DespawnGateRecursive`], and wires with [`DespawnWire`].
+pub fn enforce_circuit_budgets(
+    mut commands: Commands,
+    budgets: Res<CircuitBudgets>,
+    mut events: EventWriter<BudgetExceeded>,
+    query_added: Query<(), Added<CircuitMember>>,
+    query_gates: Query<(Entity, Ref<CircuitMember>), With<LogicGateFans>>,
+    query_wires: Query<(Entity, Ref<CircuitMember>), With<Wire>>
+) {
+    if query_added.is_empty() {
+        return;
+    }
+
+    let members = [
+        (BudgetKind::Gates, query_gates.iter().collect::<Vec<_>>()),
+        (BudgetKind::Wires, query_wires.iter().collect::<Vec<_>>()),
+    ];
+    for (kind, members) in members {
+        let mut counts = HashMap::<CircuitId, usize>::default();
+        let mut added = Vec::new();
+        for (entity, member) in members {
+            if member.is_added() {
+                added.push((member.0, entity));
+            } else {
+                *counts.entry(member.0).or_default() += 1;
+            }
+        }
+        added.sort_unstable();
+
+        for (circuit, entity) in added {
+            let budget = budgets.get(circuit);
+            let limit = match kind {
+                BudgetKind::Gates => budget.max_gates,
+                BudgetKind::Wires => budget.max_wires,
+            };
+            let count = counts.entry(circuit).or_default();
+            let Some(limit) = limit.filter(|&limit| *count >= limit) else {
+                *count += 1;
+                continue;
+            };
+
+            match kind {
+                BudgetKind::Gates => commands.add(DespawnGateRecursive(entity)),
+                BudgetKind::Wires => commands.add(DespawnWire(entity)),
+            }
+            events.send(BudgetExceeded { circuit, entity, kind, limit });
+        }
+    }
+}